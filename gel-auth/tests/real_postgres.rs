@@ -1,6 +1,8 @@
 #![cfg(unix)]
 
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::time::Duration;
 
 use gel_auth::postgres::client::{
     ConnectionDrive, ConnectionState, ConnectionStateSend, ConnectionStateType,
@@ -8,6 +10,7 @@ use gel_auth::postgres::client::{
 };
 use gel_auth::postgres::{ConnectionSslRequirement, Credentials};
 use gel_auth::*;
+use gel_dsn::postgres::{ConnectionParameters, Host, HostType, SessionState, TargetSessionAttrs};
 use gel_pg_captive::*;
 use gel_pg_protocol::errors::PgServerError;
 use gel_pg_protocol::prelude::StructBuffer;
@@ -391,3 +394,57 @@ async fn test_bad_database(
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_target_session_attrs() -> Result<(), Box<dyn std::error::Error>> {
+    let Some(cluster) = create_cluster(AuthType::Trust, NonZeroUsize::new(2).unwrap())? else {
+        return Ok(());
+    };
+    let primary = cluster.primary().tcp_address;
+    let standby = cluster.standbys()[0].tcp_address;
+
+    let mut params = ConnectionParameters {
+        hosts: [standby, primary]
+            .iter()
+            .map(|addr| Host(HostType::IP(addr.ip(), None), addr.port()))
+            .collect(),
+        ..Default::default()
+    };
+
+    for (target_session_attrs, expected) in [
+        (TargetSessionAttrs::Any, standby),
+        (TargetSessionAttrs::ReadWrite, primary),
+        (TargetSessionAttrs::ReadOnly, standby),
+        (TargetSessionAttrs::Primary, primary),
+        (TargetSessionAttrs::Standby, standby),
+        (TargetSessionAttrs::PreferStandby, standby),
+    ] {
+        params.target_session_attrs = target_session_attrs;
+        let (host, _socket) = params
+            .connect_candidates(|host| {
+                let target = host.target_name().map(Target::new);
+                async move {
+                    let credentials = Credentials {
+                        username: DEFAULT_USERNAME.to_string(),
+                        password: DEFAULT_PASSWORD.to_string(),
+                        database: DEFAULT_DATABASE.to_string(),
+                        server_settings: Default::default(),
+                    };
+                    let (socket, params) =
+                        connect_raw_ssl(credentials, ConnectionSslRequirement::Optional, target?)
+                            .await?;
+                    let state = SessionState::from_server_parameters(
+                        params.params.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+                    );
+                    Ok::<_, ConnectError>((socket, state))
+                }
+            })
+            .await?;
+        assert_eq!(host.1, expected.port(), "{target_session_attrs}");
+    }
+
+    cluster
+        .shutdown_timeout(Duration::from_secs(10), ShutdownSignal::Smart)
+        .unwrap();
+    Ok(())
+}
@@ -321,19 +321,6 @@ async fn bind(
     info!("Listening on {local_addr:?}");
 
     let task = match addr.transport() {
-        Transport::Tcp => tokio::task::spawn(async move {
-            defer!({
-                warn!("Closing TCP listener");
-            });
-            while let Some(res) = acceptor.next().await {
-                let Ok((preview, stream)) = res else {
-                    continue;
-                };
-                (callback.lock().unwrap())(ListenerStream::new_tcp(stream, preview));
-            }
-            #[allow(unreachable_code)]
-            Ok::<_, std::io::Error>(())
-        }),
         Transport::Unix => {
             let local_addr = local_addr.clone();
 
@@ -360,6 +347,19 @@ async fn bind(
                 Ok::<_, std::io::Error>(())
             })
         }
+        Transport::Tcp | Transport::Memory => tokio::task::spawn(async move {
+            defer!({
+                warn!("Closing TCP listener");
+            });
+            while let Some(res) = acceptor.next().await {
+                let Ok((preview, stream)) = res else {
+                    continue;
+                };
+                (callback.lock().unwrap())(ListenerStream::new_tcp(stream, preview));
+            }
+            #[allow(unreachable_code)]
+            Ok::<_, std::io::Error>(())
+        }),
     };
 
     Ok((local_addr, task))
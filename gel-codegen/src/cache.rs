@@ -0,0 +1,128 @@
+//! Recorded query descriptors for offline builds.
+//!
+//! The cache is a JSON file mapping query paths to the descriptors returned
+//! by the server. Each entry records a hash of the query text, so that a
+//! changed query is never generated from a stale description.
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use bytes::Bytes;
+use gel_protocol::common::{Capabilities, Cardinality, RawTypedesc};
+use gel_protocol::features::ProtocolVersion;
+use gel_protocol::model::Uuid;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use crate::Error;
+use crate::describe::QueryDescription;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DescriptorCache {
+    queries: BTreeMap<String, CachedQuery>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedQuery {
+    hash: String,
+    protocol: (u16, u16),
+    cardinality: u8,
+    capabilities: u64,
+    input: CachedTypedesc,
+    output: CachedTypedesc,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedTypedesc {
+    id: String,
+    data: String,
+}
+
+impl DescriptorCache {
+    /// Load the cache from `path`, returning an empty cache if the file does
+    /// not exist.
+    pub fn load(path: &Path) -> Result<DescriptorCache, Error> {
+        match fs::read(path) {
+            Ok(data) => Ok(serde_json::from_slice(&data)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(DescriptorCache::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let mut data = serde_json::to_vec_pretty(self)?;
+        data.push(b'\n');
+        fs::write(path, data)?;
+        Ok(())
+    }
+
+    /// Return the recorded description of `query` stored under `path`, if
+    /// the query text is unchanged since it was recorded.
+    pub fn get(&self, path: &str, query: &str) -> Result<Option<QueryDescription>, Error> {
+        let Some(entry) = self.queries.get(path) else {
+            return Ok(None);
+        };
+        if entry.hash != hash(query) {
+            return Ok(None);
+        }
+        let invalid = |message: String| Error::InvalidCache {
+            query: path.to_string(),
+            message,
+        };
+        let (major, minor) = entry.protocol;
+        let proto = ProtocolVersion::new(major, minor);
+        Ok(Some(QueryDescription {
+            cardinality: Cardinality::try_from(entry.cardinality)
+                .map_err(|_| invalid(format!("unknown cardinality {}", entry.cardinality)))?,
+            capabilities: Capabilities::from_bits(entry.capabilities)
+                .ok_or_else(|| invalid(format!("unknown capabilities {}", entry.capabilities)))?,
+            input: entry.input.decode(&proto).map_err(invalid)?,
+            output: entry.output.decode(&proto).map_err(invalid)?,
+        }))
+    }
+
+    pub fn insert(&mut self, path: &str, query: &str, desc: &QueryDescription) {
+        let (major, minor) = desc.output.proto.version_tuple();
+        self.queries.insert(
+            path.to_string(),
+            CachedQuery {
+                hash: hash(query),
+                protocol: (major, minor),
+                cardinality: desc.cardinality as u8,
+                capabilities: desc.capabilities.bits(),
+                input: CachedTypedesc::encode(&desc.input),
+                output: CachedTypedesc::encode(&desc.output),
+            },
+        );
+    }
+
+    /// Drop entries for queries that are not in `paths`.
+    pub fn retain<'a>(&mut self, paths: impl IntoIterator<Item = &'a str>) {
+        let keep = paths.into_iter().collect::<Vec<_>>();
+        self.queries.retain(|path, _| keep.contains(&path.as_str()));
+    }
+}
+
+impl CachedTypedesc {
+    fn encode(desc: &RawTypedesc) -> CachedTypedesc {
+        CachedTypedesc {
+            id: desc.id.to_string(),
+            data: base16ct::lower::encode_string(&desc.data),
+        }
+    }
+
+    fn decode(&self, proto: &ProtocolVersion) -> Result<RawTypedesc, String> {
+        let id = self.id.parse::<Uuid>().map_err(|e| e.to_string())?;
+        let data = base16ct::lower::decode_vec(&self.data).map_err(|e| e.to_string())?;
+        Ok(RawTypedesc {
+            proto: proto.clone(),
+            id,
+            data: Bytes::from(data),
+        })
+    }
+}
+
+fn hash(query: &str) -> String {
+    base16ct::lower::encode_string(&Sha1::digest(query.as_bytes()))
+}
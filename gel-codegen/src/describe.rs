@@ -0,0 +1,92 @@
+//! Describing queries against a running database.
+use std::path::Path;
+use std::sync::Arc;
+
+use gel_dsn::gel::{Builder, Config};
+use gel_protocol::common::{Capabilities, Cardinality, CompilationOptions};
+use gel_protocol::common::{InputLanguage, IoFormat, RawTypedesc};
+use gel_protocol::descriptors::Typedesc;
+use gel_protocol::encoding::Annotations;
+use gel_protocol::server_message::CommandDataDescription1;
+use gel_tokio::raw::{Connection, PoolState};
+
+use crate::Error;
+
+/// Input and output types of a query as reported by the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryDescription {
+    pub cardinality: Cardinality,
+    pub capabilities: Capabilities,
+    pub input: RawTypedesc,
+    pub output: RawTypedesc,
+}
+
+impl QueryDescription {
+    pub fn input(&self) -> Result<Typedesc, Error> {
+        Ok(self.input.decode()?)
+    }
+    pub fn output(&self) -> Result<Typedesc, Error> {
+        Ok(self.output.decode()?)
+    }
+}
+
+impl From<CommandDataDescription1> for QueryDescription {
+    fn from(desc: CommandDataDescription1) -> QueryDescription {
+        QueryDescription {
+            cardinality: desc.result_cardinality,
+            capabilities: desc.capabilities,
+            input: desc.input,
+            output: desc.output,
+        }
+    }
+}
+
+/// A database connection used to describe queries.
+pub struct Describer {
+    conn: Connection,
+    state: Arc<PoolState>,
+    annotations: Arc<Annotations>,
+}
+
+impl Describer {
+    /// Connect to the database of the project in `project_dir`.
+    ///
+    /// Environment variables override the project's instance, as with any
+    /// other client.
+    pub async fn connect_project(project_dir: &Path) -> Result<Describer, Error> {
+        let config = Builder::default()
+            .without_system()
+            .with_env()
+            .with_fs()
+            .with_explicit_project(project_dir)
+            .build()?;
+        Describer::connect(&config).await
+    }
+
+    pub async fn connect(config: &Config) -> Result<Describer, Error> {
+        Ok(Describer {
+            conn: Connection::connect(config).await?,
+            state: Default::default(),
+            annotations: Default::default(),
+        })
+    }
+
+    /// Parse the query and return its description without executing it.
+    pub async fn describe(&mut self, query: &str) -> Result<QueryDescription, Error> {
+        let flags = CompilationOptions {
+            implicit_limit: None,
+            implicit_typenames: false,
+            implicit_typeids: false,
+            explicit_objectids: true,
+            allow_capabilities: Capabilities::ALL,
+            input_language: InputLanguage::EdgeQL,
+            io_format: IoFormat::Binary,
+            expected_cardinality: Cardinality::Many,
+        };
+        let desc = self
+            .conn
+            .parse(&flags, query, &self.state, &self.annotations)
+            .await?;
+        Ok(desc.into())
+    }
+}
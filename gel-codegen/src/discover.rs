@@ -0,0 +1,78 @@
+//! Finding the project and its query files.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use gel_dsn::gel::{ProjectDir, ProjectSearchResult};
+
+use crate::Error;
+
+/// Directories that never contain application queries.
+const SKIP_DIRS: &[&str] = &["dbschema", "target", "node_modules"];
+
+/// A query file found in the project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryFile {
+    /// Path relative to the project root, with `/` separators.
+    pub path: String,
+    /// Name of the generated module and function.
+    pub name: String,
+    /// The query text.
+    pub text: String,
+}
+
+/// Find the root directory of the project containing `start`.
+pub fn find_project_root(start: &Path) -> Result<PathBuf, Error> {
+    let found = ProjectSearchResult::find(ProjectDir::Search(start.to_path_buf()))?;
+    found
+        .and_then(|result| result.project_path.parent().map(Path::to_path_buf))
+        .ok_or_else(|| Error::NoProject {
+            path: start.to_path_buf(),
+        })
+}
+
+/// Find all `*.edgeql` files below `root`, sorted by path.
+///
+/// Hidden directories, `dbschema` (migrations) and build output directories
+/// are skipped.
+pub fn find_queries(root: &Path) -> Result<Vec<QueryFile>, Error> {
+    let mut paths = Vec::new();
+    walk(root, &mut paths)?;
+    paths.sort();
+    let mut queries = Vec::with_capacity(paths.len());
+    for path in paths {
+        let text = fs::read_to_string(&path)?;
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        let path = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let stem = relative
+            .file_stem()
+            .map(|s| s.to_string_lossy())
+            .unwrap_or_default();
+        queries.push(QueryFile {
+            name: crate::generate::snake_ident(&stem),
+            path,
+            text,
+        });
+    }
+    Ok(queries)
+}
+
+fn walk(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), Error> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            if !name.starts_with('.') && !SKIP_DIRS.contains(&name.as_ref()) {
+                walk(&path, paths)?;
+            }
+        } else if path.extension().is_some_and(|ext| ext == "edgeql") {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
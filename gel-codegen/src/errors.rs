@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+
+/// Errors produced while generating code.
+#[derive(Debug, derive_more::Display, derive_more::Error, derive_more::From)]
+#[non_exhaustive]
+pub enum Error {
+    #[display("I/O error: {_0}")]
+    #[from]
+    Io(std::io::Error),
+    #[display("no gel.toml or edgedb.toml found in {} or its parents", path.display())]
+    NoProject {
+        #[error(not(source))]
+        path: PathBuf,
+    },
+    #[display("{_0}")]
+    #[from]
+    Gel(gel_errors::Error),
+    #[display("cannot decode type descriptor: {_0}")]
+    #[from]
+    Decode(gel_protocol::errors::DecodeError),
+    #[display("invalid descriptor cache: {_0}")]
+    #[from]
+    Json(serde_json::Error),
+    #[display("invalid descriptor cache entry for {query}: {message}")]
+    InvalidCache {
+        #[error(not(source))]
+        query: String,
+        #[error(not(source))]
+        message: String,
+    },
+    #[display("{query} is not in the descriptor cache or has changed since it was recorded")]
+    NotCached {
+        #[error(not(source))]
+        query: String,
+    },
+    #[display("queries {first} and {second} both map to the module `{module}`")]
+    DuplicateModule {
+        #[error(not(source))]
+        module: String,
+        #[error(not(source))]
+        first: String,
+        #[error(not(source))]
+        second: String,
+    },
+    #[display("{query}: {message}")]
+    Unsupported {
+        #[error(not(source))]
+        query: String,
+        #[error(not(source))]
+        message: String,
+    },
+}
@@ -0,0 +1,500 @@
+//! Rendering query descriptions as Rust source code.
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use gel_protocol::codec;
+use gel_protocol::common::Cardinality;
+use gel_protocol::descriptors::{Descriptor, ShapeElement, TypePos, Typedesc};
+use gel_protocol::model::Uuid;
+
+use crate::Error;
+use crate::describe::QueryDescription;
+
+const HEADER: &str = "\
+// This file is generated by gel-codegen. Do not edit.
+#![allow(clippy::all, dead_code, unused_imports)]
+";
+
+/// A query to generate a module for.
+#[derive(Debug, Clone, Copy)]
+pub struct Query<'a> {
+    /// Path of the query file, used in error messages and docs.
+    pub path: &'a str,
+    /// Name of the module and function.
+    pub name: &'a str,
+    pub text: &'a str,
+    pub description: &'a QueryDescription,
+}
+
+/// Scalar types: type id, Rust type and the [`Value`] variant for arguments.
+///
+/// [`Value`]: gel_protocol::value::Value
+const SCALARS: &[(Uuid, &str, &str)] = &[
+    (codec::STD_UUID, "::gel_protocol::model::Uuid", "Uuid"),
+    (codec::STD_STR, "String", "Str"),
+    (codec::STD_BYTES, "::bytes::Bytes", "Bytes"),
+    (codec::STD_INT16, "i16", "Int16"),
+    (codec::STD_INT32, "i32", "Int32"),
+    (codec::STD_INT64, "i64", "Int64"),
+    (codec::STD_FLOAT32, "f32", "Float32"),
+    (codec::STD_FLOAT64, "f64", "Float64"),
+    (
+        codec::STD_DECIMAL,
+        "::gel_protocol::model::Decimal",
+        "Decimal",
+    ),
+    (codec::STD_BOOL, "bool", "Bool"),
+    (
+        codec::STD_DATETIME,
+        "::gel_protocol::model::Datetime",
+        "Datetime",
+    ),
+    (
+        codec::CAL_LOCAL_DATETIME,
+        "::gel_protocol::model::LocalDatetime",
+        "LocalDatetime",
+    ),
+    (
+        codec::CAL_LOCAL_DATE,
+        "::gel_protocol::model::LocalDate",
+        "LocalDate",
+    ),
+    (
+        codec::CAL_LOCAL_TIME,
+        "::gel_protocol::model::LocalTime",
+        "LocalTime",
+    ),
+    (
+        codec::STD_DURATION,
+        "::gel_protocol::model::Duration",
+        "Duration",
+    ),
+    (
+        codec::CAL_RELATIVE_DURATION,
+        "::gel_protocol::model::RelativeDuration",
+        "RelativeDuration",
+    ),
+    (
+        codec::CAL_DATE_DURATION,
+        "::gel_protocol::model::DateDuration",
+        "DateDuration",
+    ),
+    (codec::STD_JSON, "::gel_protocol::model::Json", "Json"),
+    (codec::STD_BIGINT, "::gel_protocol::model::BigInt", "BigInt"),
+    (
+        codec::CFG_MEMORY,
+        "::gel_protocol::model::ConfigMemory",
+        "ConfigMemory",
+    ),
+    (
+        codec::PGVECTOR_VECTOR,
+        "::gel_protocol::model::Vector",
+        "Vector",
+    ),
+];
+
+/// Generate a Rust source file with one module per query.
+pub fn generate(queries: &[Query]) -> Result<String, Error> {
+    let mut modules: HashMap<&str, &str> = HashMap::new();
+    let mut out = String::from(HEADER);
+    for query in queries {
+        if let Some(first) = modules.insert(query.name, query.path) {
+            return Err(Error::DuplicateModule {
+                module: query.name.to_string(),
+                first: first.to_string(),
+                second: query.path.to_string(),
+            });
+        }
+        out.push('\n');
+        out.push_str(&generate_module(query)?);
+    }
+    Ok(out)
+}
+
+/// Generate the module for a single query.
+pub fn generate_module(query: &Query) -> Result<String, Error> {
+    let input = query.description.input()?;
+    let output = query.description.output()?;
+    let mut module = Module {
+        query,
+        items: Vec::new(),
+        names: HashMap::new(),
+    };
+    let prefix = camel_ident(query.name);
+    let args = module.args(&input, &format!("{prefix}Args"))?;
+    let result = match (query.description.cardinality, output.root_pos()) {
+        (Cardinality::NoResult, _) | (_, None) => None,
+        (_, Some(pos)) => Some(module.output_type(&output, pos, &format!("{prefix}Result"))?),
+    };
+
+    let mut out = String::new();
+    writeln!(out, "/// Generated from `{}`.", query.path).unwrap();
+    writeln!(out, "pub mod {} {{", query.name).unwrap();
+    let hashes = "#".repeat(raw_string_hashes(query.text));
+    writeln!(
+        out,
+        "    pub const QUERY: &str = r{hashes}\"{}\"{hashes};",
+        query.text
+    )
+    .unwrap();
+    for item in &module.items {
+        out.push('\n');
+        for line in item.lines() {
+            if line.is_empty() {
+                out.push('\n');
+            } else {
+                writeln!(out, "    {line}").unwrap();
+            }
+        }
+    }
+
+    let (args_param, args_expr) = match &args {
+        Some(name) => (format!("\n        args: &{name},"), "args"),
+        None => (String::new(), "&()"),
+    };
+    let (method, ret) = match (&result, query.description.cardinality) {
+        (None, _) => ("execute", "()".to_string()),
+        (Some(ty), Cardinality::One) => ("query_required_single", ty.clone()),
+        (Some(ty), Cardinality::AtMostOne) => ("query_single", format!("Option<{ty}>")),
+        (Some(ty), _) => ("query", format!("Vec<{ty}>")),
+    };
+    out.push('\n');
+    writeln!(
+        out,
+        "    pub async fn {}(\n        executor: impl ::gel_tokio::QueryExecutor,{args_param}\n    ) -> Result<{ret}, ::gel_tokio::Error> {{",
+        query.name
+    )
+    .unwrap();
+    writeln!(out, "        executor.{method}(QUERY, {args_expr}).await").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+    Ok(out)
+}
+
+/// Type of a query argument.
+enum ArgType {
+    Scalar(&'static str, &'static str),
+    Enum,
+    Array(Box<ArgType>),
+}
+
+impl ArgType {
+    fn rust_type(&self) -> String {
+        match self {
+            ArgType::Scalar(ty, _) => ty.to_string(),
+            ArgType::Enum => "String".into(),
+            ArgType::Array(inner) => format!("Vec<{}>", inner.rust_type()),
+        }
+    }
+
+    /// Expression converting a reference `expr` to a `Value`.
+    fn to_value(&self, expr: &str) -> String {
+        match self {
+            ArgType::Scalar(_, "Vector") => format!("Value::Vector({expr}.0.clone())"),
+            ArgType::Scalar(_, variant) => format!("Value::{variant}({expr}.clone())"),
+            ArgType::Enum => format!("Value::Enum({expr}.as_str().into())"),
+            ArgType::Array(inner) => format!(
+                "Value::Array({expr}.iter().map(|v| {}).collect())",
+                inner.to_value("v")
+            ),
+        }
+    }
+}
+
+struct Module<'a> {
+    query: &'a Query<'a>,
+    items: Vec<String>,
+    /// Generated type names, to avoid emitting the same enum twice.
+    names: HashMap<String, Uuid>,
+}
+
+impl Module<'_> {
+    fn unsupported(&self, message: impl Into<String>) -> Error {
+        Error::Unsupported {
+            query: self.query.path.to_string(),
+            message: message.into(),
+        }
+    }
+
+    fn get<'d>(&self, desc: &'d Typedesc, pos: TypePos) -> Result<&'d Descriptor, Error> {
+        desc.get(pos)
+            .map_err(|e| self.unsupported(format!("invalid type descriptor: {e}")))
+    }
+
+    fn args(&mut self, input: &Typedesc, name: &str) -> Result<Option<String>, Error> {
+        let Some(pos) = input.root_pos() else {
+            return Ok(None);
+        };
+        if input.is_empty_tuple() {
+            return Ok(None);
+        }
+        let elements = match self.get(input, pos)? {
+            Descriptor::ObjectShape(shape) => &shape.elements,
+            other => {
+                return Err(self.unsupported(format!("unsupported arguments type {}", kind(other))));
+            }
+        };
+        if elements.is_empty() {
+            return Ok(None);
+        }
+        let mut fields = String::new();
+        let mut inserts = String::new();
+        for element in elements {
+            let ty = self.arg_type(input, element.type_pos)?;
+            let ident = field_ident(&element.name);
+            let optional = element.cardinality == Some(Cardinality::AtMostOne);
+            let (rust_type, value) = if optional {
+                (
+                    format!("Option<{}>", ty.rust_type()),
+                    format!("self.{ident}.as_ref().map(|v| {})", ty.to_value("v")),
+                )
+            } else {
+                (ty.rust_type(), ty.to_value(&format!("self.{ident}")))
+            };
+            writeln!(fields, "    pub {ident}: {rust_type},").unwrap();
+            writeln!(
+                inserts,
+                "        args.insert({:?}, ValueOpt::from({value}));",
+                element.name
+            )
+            .unwrap();
+        }
+        self.items.push(format!(
+            "#[derive(Debug, Clone)]
+pub struct {name} {{
+{fields}}}
+
+impl ::gel_protocol::query_arg::QueryArgs for {name} {{
+    fn encode(
+        &self,
+        encoder: &mut ::gel_protocol::query_arg::Encoder,
+    ) -> Result<(), ::gel_tokio::Error> {{
+        use ::gel_protocol::value::Value;
+        use ::gel_protocol::value_opt::ValueOpt;
+        let mut args = ::std::collections::HashMap::<&str, ValueOpt>::new();
+{inserts}        ::gel_protocol::query_arg::QueryArgs::encode(&args, encoder)
+    }}
+}}
+"
+        ));
+        Ok(Some(name.to_string()))
+    }
+
+    fn arg_type(&self, input: &Typedesc, pos: TypePos) -> Result<ArgType, Error> {
+        match self.get(input, pos)? {
+            Descriptor::BaseScalar(base) => self.scalar(*base.id).map(scalar_arg),
+            Descriptor::Scalar(scalar) => match scalar.base_type_pos {
+                Some(base) => self.arg_type(input, base),
+                None => self.scalar(*scalar.id).map(scalar_arg),
+            },
+            Descriptor::Enumeration(_) => Ok(ArgType::Enum),
+            Descriptor::Array(array) => Ok(ArgType::Array(Box::new(
+                self.arg_type(input, array.type_pos)?,
+            ))),
+            other => Err(self.unsupported(format!("unsupported argument type {}", kind(other)))),
+        }
+    }
+
+    fn scalar(&self, id: Uuid) -> Result<(&'static str, &'static str), Error> {
+        SCALARS
+            .iter()
+            .find(|(scalar_id, _, _)| *scalar_id == id)
+            .map(|(_, ty, variant)| (*ty, *variant))
+            .ok_or_else(|| self.unsupported(format!("unsupported scalar type {id}")))
+    }
+
+    fn output_type(
+        &mut self,
+        output: &Typedesc,
+        pos: TypePos,
+        hint: &str,
+    ) -> Result<String, Error> {
+        match self.get(output, pos)? {
+            Descriptor::BaseScalar(base) => Ok(self.scalar(*base.id)?.0.to_string()),
+            Descriptor::Scalar(scalar) => match scalar.base_type_pos {
+                Some(base) => self.output_type(output, base, hint),
+                None => Ok(self.scalar(*scalar.id)?.0.to_string()),
+            },
+            Descriptor::Set(set) => Ok(format!(
+                "Vec<{}>",
+                self.output_type(output, set.type_pos, hint)?
+            )),
+            Descriptor::Array(array) => Ok(format!(
+                "Vec<{}>",
+                self.output_type(output, array.type_pos, hint)?
+            )),
+            Descriptor::Tuple(tuple) if !tuple.element_types.is_empty() => {
+                let mut elements = Vec::with_capacity(tuple.element_types.len());
+                for (idx, &pos) in tuple.element_types.iter().enumerate() {
+                    elements.push(self.output_type(output, pos, &format!("{hint}{idx}"))?);
+                }
+                if elements.len() == 1 {
+                    Ok(format!("({},)", elements[0]))
+                } else {
+                    Ok(format!("({})", elements.join(", ")))
+                }
+            }
+            Descriptor::Enumeration(enumeration) => {
+                let name = match &enumeration.name {
+                    Some(name) => camel_ident(name.rsplit("::").next().unwrap_or(name)),
+                    None => hint.to_string(),
+                };
+                self.enumeration(&name, *enumeration.id, &enumeration.members)
+            }
+            Descriptor::ObjectShape(shape) => self.object(output, hint, &shape.elements),
+            other => Err(self.unsupported(format!("unsupported result type {}", kind(other)))),
+        }
+    }
+
+    fn enumeration(&mut self, name: &str, id: Uuid, members: &[String]) -> Result<String, Error> {
+        match self.names.get(name) {
+            Some(existing) if *existing == id => return Ok(name.to_string()),
+            Some(_) => {
+                return Err(self.unsupported(format!("conflicting types named {name}")));
+            }
+            None => {}
+        }
+        self.names.insert(name.to_string(), id);
+        let mut variants = String::new();
+        let mut seen = Vec::with_capacity(members.len());
+        for (idx, member) in members.iter().enumerate() {
+            let mut variant = camel_ident(member);
+            if seen.contains(&variant) {
+                variant = format!("Variant{idx}");
+            }
+            writeln!(variants, "    #[gel(rename = {member:?})]").unwrap();
+            writeln!(variants, "    {variant},").unwrap();
+            seen.push(variant);
+        }
+        self.items.push(format!(
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, ::gel_tokio::Queryable)]
+pub enum {name} {{
+{variants}}}
+"
+        ));
+        Ok(name.to_string())
+    }
+
+    fn object(
+        &mut self,
+        output: &Typedesc,
+        name: &str,
+        elements: &[ShapeElement],
+    ) -> Result<String, Error> {
+        if self.names.contains_key(name) {
+            return Err(self.unsupported(format!("conflicting types named {name}")));
+        }
+        self.names.insert(name.to_string(), Uuid::nil());
+        let mut fields = String::new();
+        for element in elements {
+            let ident = field_ident(&element.name);
+            let hint = format!("{name}{}", camel_ident(&element.name));
+            let mut ty = self.output_type(output, element.type_pos, &hint)?;
+            if element.cardinality == Some(Cardinality::AtMostOne) {
+                ty = format!("Option<{ty}>");
+            }
+            if ident != element.name {
+                writeln!(fields, "    #[gel(rename = {:?})]", element.name).unwrap();
+            }
+            writeln!(fields, "    pub {ident}: {ty},").unwrap();
+        }
+        self.items.push(format!(
+            "#[derive(Debug, Clone, ::gel_tokio::Queryable)]
+pub struct {name} {{
+{fields}}}
+"
+        ));
+        Ok(name.to_string())
+    }
+}
+
+fn scalar_arg((ty, variant): (&'static str, &'static str)) -> ArgType {
+    ArgType::Scalar(ty, variant)
+}
+
+fn kind(desc: &Descriptor) -> &'static str {
+    match desc {
+        Descriptor::Set(_) => "set",
+        Descriptor::ObjectShape(_) => "object",
+        Descriptor::BaseScalar(_) | Descriptor::Scalar(_) => "scalar",
+        Descriptor::Tuple(_) => "tuple",
+        Descriptor::NamedTuple(_) => "named tuple",
+        Descriptor::Array(_) => "array",
+        Descriptor::Enumeration(_) => "enum",
+        Descriptor::InputShape(_) => "input shape",
+        Descriptor::Range(_) => "range",
+        Descriptor::MultiRange(_) => "multirange",
+        _ => "descriptor",
+    }
+}
+
+/// Smallest number of `#` that make `text` a valid raw string literal.
+fn raw_string_hashes(text: &str) -> usize {
+    (0..)
+        .find(|&n| !text.contains(&format!("\"{}", "#".repeat(n))))
+        .unwrap()
+        .max(1)
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "gen", "macro", "override", "priv", "try",
+    "typeof", "unsized", "virtual", "yield",
+];
+
+/// Convert a name to a `snake_case` Rust identifier.
+pub fn snake_ident(name: &str) -> String {
+    let mut ident = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            ident.push(c.to_ascii_lowercase());
+        } else if !ident.ends_with('_') {
+            ident.push('_');
+        }
+    }
+    let ident = ident.trim_matches('_');
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("q_{ident}")
+    } else if KEYWORDS.contains(&ident) {
+        format!("r#{ident}")
+    } else if matches!(ident, "self" | "super" | "crate") {
+        format!("{ident}_")
+    } else {
+        ident.to_string()
+    }
+}
+
+/// Identifier for a struct field named after a shape element.
+fn field_ident(name: &str) -> String {
+    if name.chars().all(|c| c.is_ascii_digit()) {
+        return format!("arg{name}");
+    }
+    snake_ident(name)
+}
+
+/// Convert a name to a `CamelCase` Rust identifier.
+pub fn camel_ident(name: &str) -> String {
+    let mut ident = String::with_capacity(name.len());
+    let mut upper = true;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            if upper {
+                ident.push(c.to_ascii_uppercase());
+            } else {
+                ident.push(c);
+            }
+            upper = false;
+        } else {
+            upper = true;
+        }
+    }
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, 'T');
+    }
+    if ident == "Self" {
+        ident.push('_');
+    }
+    ident
+}
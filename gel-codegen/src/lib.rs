@@ -0,0 +1,88 @@
+/*!
+Generates typed Rust functions from the `*.edgeql` files of a Gel project.
+
+Each query file is described by the server (or read from a
+[`DescriptorCache`](cache::DescriptorCache) for offline builds) and rendered
+as a module containing the query text, an arguments struct implementing
+[`QueryArgs`](gel_protocol::query_arg::QueryArgs), result types deriving
+[`Queryable`](gel_protocol::queryable::Queryable) and an async function
+taking a [`QueryExecutor`](gel_tokio::QueryExecutor).
+
+```rust,no_run
+# async fn run() -> Result<(), gel_codegen::Error> {
+use gel_codegen::Options;
+
+let root = gel_codegen::discover::find_project_root(".".as_ref())?;
+let code = gel_codegen::run(&root, &Options::default()).await?;
+std::fs::write(root.join("src/queries.rs"), code)?;
+# Ok(())
+# }
+```
+*/
+use std::path::{Path, PathBuf};
+
+pub mod cache;
+pub mod describe;
+pub mod discover;
+mod errors;
+pub mod generate;
+
+pub use describe::{Describer, QueryDescription};
+pub use errors::Error;
+
+use cache::DescriptorCache;
+use generate::Query;
+
+/// Options for [`run`].
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Descriptor cache file. When set, descriptions from the database are
+    /// recorded there.
+    pub cache: Option<PathBuf>,
+    /// Use only the descriptor cache and never connect to the database.
+    pub offline: bool,
+}
+
+/// Describe all queries of the project at `root` and generate the code.
+pub async fn run(root: &Path, options: &Options) -> Result<String, Error> {
+    let files = discover::find_queries(root)?;
+    let mut cache = match &options.cache {
+        Some(path) => DescriptorCache::load(path)?,
+        None => DescriptorCache::default(),
+    };
+    let mut describer = None;
+    let mut descriptions = Vec::with_capacity(files.len());
+    for file in &files {
+        if let Some(desc) = cache.get(&file.path, &file.text)? {
+            descriptions.push(desc);
+            continue;
+        }
+        if options.offline {
+            return Err(Error::NotCached {
+                query: file.path.clone(),
+            });
+        }
+        let describer = match &mut describer {
+            Some(describer) => describer,
+            None => describer.insert(Describer::connect_project(root).await?),
+        };
+        let desc = describer.describe(&file.text).await?;
+        cache.insert(&file.path, &file.text, &desc);
+        descriptions.push(desc);
+    }
+    if let (Some(path), false) = (&options.cache, options.offline) {
+        cache.retain(files.iter().map(|f| f.path.as_str()));
+        cache.save(path)?;
+    }
+    let queries = files
+        .iter()
+        .zip(&descriptions)
+        .map(|(file, description)| Query {
+            path: &file.path,
+            name: &file.name,
+            text: &file.text,
+            description,
+        })
+        .collect::<Vec<_>>();
+    generate::generate(&queries)
+}
@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use gel_codegen::Options;
+
+const USAGE: &str = "\
+Usage: gel-codegen [OPTIONS]
+
+Generates Rust functions from the *.edgeql files of a Gel project.
+
+Options:
+    --project-dir <DIR>  Project directory (default: searched from the current directory)
+    --output <FILE>      Output file (default: src/queries.rs in the project)
+    --cache <FILE>       Descriptor cache to read and update
+    --offline            Only use the descriptor cache, do not connect
+    -h, --help           Print this help
+";
+
+struct Args {
+    project_dir: Option<PathBuf>,
+    output: Option<PathBuf>,
+    options: Options,
+}
+
+fn parse_args() -> Result<Option<Args>, String> {
+    let mut project_dir = None;
+    let mut output = None;
+    let mut options = Options::default();
+    let mut args = std::env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .map(PathBuf::from)
+                .ok_or_else(|| format!("{name} requires a value"))
+        };
+        match arg.to_str() {
+            Some("--project-dir") => project_dir = Some(value("--project-dir")?),
+            Some("--output") => output = Some(value("--output")?),
+            Some("--cache") => options.cache = Some(value("--cache")?),
+            Some("--offline") => options.offline = true,
+            Some("-h" | "--help") => return Ok(None),
+            _ => return Err(format!("unexpected argument {arg:?}")),
+        }
+    }
+    if options.offline && options.cache.is_none() {
+        return Err("--offline requires --cache".into());
+    }
+    Ok(Some(Args {
+        project_dir,
+        output,
+        options,
+    }))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let Args {
+        project_dir,
+        output,
+        options,
+    } = match parse_args() {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("gel-codegen: {e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let result = async {
+        let start = match project_dir {
+            Some(dir) => dir,
+            None => std::env::current_dir()?,
+        };
+        let root = gel_codegen::discover::find_project_root(&start)?;
+        let code = gel_codegen::run(&root, &options).await?;
+        let output = output.unwrap_or_else(|| root.join("src").join("queries.rs"));
+        std::fs::write(&output, code)?;
+        Ok::<_, gel_codegen::Error>(output)
+    };
+    match result.await {
+        Ok(output) => {
+            eprintln!("gel-codegen: wrote {}", output.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("gel-codegen: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use gel_codegen::QueryDescription;
+use gel_codegen::generate::{Query, generate, generate_module};
+use gel_protocol::common::{Capabilities, Cardinality, RawTypedesc};
+use gel_protocol::features::ProtocolVersion;
+use gel_protocol::model::Uuid;
+use gel_protocol::query_arg::{Encoder, QueryArgs};
+
+#[rustfmt::skip]
+#[path = "golden/queries.rs"]
+mod queries;
+
+const IMPLICIT: u32 = 0b001;
+
+/// Writes type descriptors in the protocol 1.0 format.
+#[derive(Default)]
+struct Descriptors(BytesMut);
+
+impl Descriptors {
+    fn header(&mut self, tag: u8, id: u128) -> &mut Self {
+        self.0.put_u8(tag);
+        self.0.put_u128(id);
+        self
+    }
+    fn string(&mut self, s: &str) {
+        self.0.put_u32(s.len() as u32);
+        self.0.put_slice(s.as_bytes());
+    }
+    fn base_scalar(&mut self, id: u128) -> &mut Self {
+        self.header(2, id)
+    }
+    fn array(&mut self, id: u128, type_pos: u16) -> &mut Self {
+        self.header(6, id);
+        self.0.put_u16(type_pos);
+        self.0.put_u16(1);
+        self.0.put_i32(-1);
+        self
+    }
+    fn enumeration(&mut self, id: u128, members: &[&str]) -> &mut Self {
+        self.header(7, id);
+        self.0.put_u16(members.len() as u16);
+        for member in members {
+            self.string(member);
+        }
+        self
+    }
+    fn range(&mut self, id: u128, type_pos: u16) -> &mut Self {
+        self.header(9, id);
+        self.0.put_u16(type_pos);
+        self
+    }
+    fn shape(&mut self, id: u128, elements: &[(u32, Cardinality, &str, u16)]) -> &mut Self {
+        self.header(1, id);
+        self.0.put_u16(elements.len() as u16);
+        for &(flags, cardinality, name, type_pos) in elements {
+            self.0.put_u32(flags);
+            self.0.put_u8(cardinality as u8);
+            self.string(name);
+            self.0.put_u16(type_pos);
+        }
+        self
+    }
+    fn build(&mut self, root: u128) -> RawTypedesc {
+        RawTypedesc {
+            proto: ProtocolVersion::new(1, 0),
+            id: Uuid::from_u128(root),
+            data: self.0.split().freeze(),
+        }
+    }
+}
+
+fn no_args() -> RawTypedesc {
+    RawTypedesc {
+        proto: ProtocolVersion::new(1, 0),
+        id: Uuid::from_u128(0xFF),
+        data: Bytes::from_static(b"\x04\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\xff\0\0"),
+    }
+}
+
+fn no_result() -> RawTypedesc {
+    RawTypedesc {
+        proto: ProtocolVersion::new(1, 0),
+        id: Uuid::from_u128(0),
+        data: Bytes::new(),
+    }
+}
+
+fn users_query() -> QueryDescription {
+    let input = Descriptors::default()
+        .base_scalar(0x101)
+        .base_scalar(0x105)
+        .shape(
+            0xA0,
+            &[
+                (0, Cardinality::One, "name", 0),
+                (0, Cardinality::AtMostOne, "limit", 1),
+            ],
+        )
+        .build(0xA0);
+    let output = Descriptors::default()
+        .base_scalar(0x100)
+        .base_scalar(0x101)
+        .base_scalar(0x104)
+        .array(0xA1, 1)
+        .enumeration(0xA2, &["red", "dark green"])
+        .shape(
+            0xA3,
+            &[
+                (IMPLICIT, Cardinality::One, "id", 0),
+                (0, Cardinality::One, "name", 1),
+                (0, Cardinality::AtMostOne, "age", 2),
+                (0, Cardinality::One, "tags", 3),
+                (0, Cardinality::AtMostOne, "type", 4),
+            ],
+        )
+        .build(0xA3);
+    QueryDescription {
+        cardinality: Cardinality::Many,
+        capabilities: Capabilities::empty(),
+        input,
+        output,
+    }
+}
+
+#[test]
+fn golden() -> Result<(), gel_codegen::Error> {
+    let users = users_query();
+    let count = QueryDescription {
+        cardinality: Cardinality::One,
+        capabilities: Capabilities::empty(),
+        input: no_args(),
+        output: Descriptors::default().base_scalar(0x105).build(0x105),
+    };
+    let cleanup = QueryDescription {
+        cardinality: Cardinality::NoResult,
+        capabilities: Capabilities::MODIFICATIONS,
+        input: no_args(),
+        output: no_result(),
+    };
+    let code = generate(&[
+        Query {
+            path: "queries/users.edgeql",
+            name: "users",
+            text: "select User { name, age, tags, type }\nfilter .name = <str>$name\nlimit <optional int64>$limit\n",
+            description: &users,
+        },
+        Query {
+            path: "queries/count.edgeql",
+            name: "count",
+            text: "select count(User)",
+            description: &count,
+        },
+        Query {
+            path: "queries/cleanup.edgeql",
+            name: "cleanup",
+            text: r##"delete User filter .name = "#""##,
+            description: &cleanup,
+        },
+    ])?;
+    assert_eq!(code, include_str!("golden/queries.rs"));
+    Ok(())
+}
+
+#[test]
+fn duplicate_module() {
+    let count = QueryDescription {
+        cardinality: Cardinality::One,
+        capabilities: Capabilities::empty(),
+        input: no_args(),
+        output: Descriptors::default().base_scalar(0x105).build(0x105),
+    };
+    let query = Query {
+        path: "a/count.edgeql",
+        name: "count",
+        text: "select 1",
+        description: &count,
+    };
+    let err = generate(&[
+        query,
+        Query {
+            path: "b/count.edgeql",
+            ..query
+        },
+    ])
+    .unwrap_err();
+    assert!(matches!(err, gel_codegen::Error::DuplicateModule { .. }));
+}
+
+#[test]
+fn unsupported_type() {
+    let desc = QueryDescription {
+        cardinality: Cardinality::Many,
+        capabilities: Capabilities::empty(),
+        input: no_args(),
+        output: Descriptors::default()
+            .base_scalar(0x105)
+            .range(0xA4, 0)
+            .build(0xA4),
+    };
+    let err = generate_module(&Query {
+        path: "range.edgeql",
+        name: "range",
+        text: "select range(1, 2)",
+        description: &desc,
+    })
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "range.edgeql: unsupported result type range"
+    );
+}
+
+#[test]
+fn generated_args() -> Result<(), Box<dyn std::error::Error>> {
+    let input = users_query().input()?;
+    let ctx = input.as_query_arg_context();
+    let args = queries::users::UsersArgs {
+        name: "alice".into(),
+        limit: None,
+    };
+    let mut buf = BytesMut::new();
+    args.encode(&mut Encoder::new(&ctx, &mut buf))?;
+
+    let expected = gel_protocol::named_args! {
+        "name" => "alice",
+        "limit" => None::<i64>,
+    };
+    let mut expected_buf = BytesMut::new();
+    expected.encode(&mut Encoder::new(&ctx, &mut expected_buf))?;
+    assert_eq!(buf, expected_buf);
+    Ok(())
+}
@@ -0,0 +1,77 @@
+// This file is generated by gel-codegen. Do not edit.
+#![allow(clippy::all, dead_code, unused_imports)]
+
+/// Generated from `queries/users.edgeql`.
+pub mod users {
+    pub const QUERY: &str = r#"select User { name, age, tags, type }
+filter .name = <str>$name
+limit <optional int64>$limit
+"#;
+
+    #[derive(Debug, Clone)]
+    pub struct UsersArgs {
+        pub name: String,
+        pub limit: Option<i64>,
+    }
+
+    impl ::gel_protocol::query_arg::QueryArgs for UsersArgs {
+        fn encode(
+            &self,
+            encoder: &mut ::gel_protocol::query_arg::Encoder,
+        ) -> Result<(), ::gel_tokio::Error> {
+            use ::gel_protocol::value::Value;
+            use ::gel_protocol::value_opt::ValueOpt;
+            let mut args = ::std::collections::HashMap::<&str, ValueOpt>::new();
+            args.insert("name", ValueOpt::from(Value::Str(self.name.clone())));
+            args.insert("limit", ValueOpt::from(self.limit.as_ref().map(|v| Value::Int64(v.clone()))));
+            ::gel_protocol::query_arg::QueryArgs::encode(&args, encoder)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, ::gel_tokio::Queryable)]
+    pub enum UsersResultType {
+        #[gel(rename = "red")]
+        Red,
+        #[gel(rename = "dark green")]
+        DarkGreen,
+    }
+
+    #[derive(Debug, Clone, ::gel_tokio::Queryable)]
+    pub struct UsersResult {
+        pub id: ::gel_protocol::model::Uuid,
+        pub name: String,
+        pub age: Option<i32>,
+        pub tags: Vec<String>,
+        #[gel(rename = "type")]
+        pub r#type: Option<UsersResultType>,
+    }
+
+    pub async fn users(
+        executor: impl ::gel_tokio::QueryExecutor,
+        args: &UsersArgs,
+    ) -> Result<Vec<UsersResult>, ::gel_tokio::Error> {
+        executor.query(QUERY, args).await
+    }
+}
+
+/// Generated from `queries/count.edgeql`.
+pub mod count {
+    pub const QUERY: &str = r#"select count(User)"#;
+
+    pub async fn count(
+        executor: impl ::gel_tokio::QueryExecutor,
+    ) -> Result<i64, ::gel_tokio::Error> {
+        executor.query_required_single(QUERY, &()).await
+    }
+}
+
+/// Generated from `queries/cleanup.edgeql`.
+pub mod cleanup {
+    pub const QUERY: &str = r##"delete User filter .name = "#""##;
+
+    pub async fn cleanup(
+        executor: impl ::gel_tokio::QueryExecutor,
+    ) -> Result<(), ::gel_tokio::Error> {
+        executor.execute(QUERY, &()).await
+    }
+}
@@ -0,0 +1,71 @@
+use std::fs;
+
+use bytes::Bytes;
+use gel_codegen::QueryDescription;
+use gel_codegen::cache::DescriptorCache;
+use gel_codegen::discover::{find_project_root, find_queries};
+use gel_protocol::common::{Capabilities, Cardinality, RawTypedesc};
+use gel_protocol::features::ProtocolVersion;
+use gel_protocol::model::Uuid;
+
+#[test]
+fn discover_queries() -> Result<(), gel_codegen::Error> {
+    let dir = tempfile::tempdir()?;
+    let root = dir.path();
+    fs::write(
+        root.join("gel.toml"),
+        "[instance]\nserver-version = \"6\"\n",
+    )?;
+    fs::create_dir_all(root.join("src/queries"))?;
+    fs::create_dir_all(root.join("dbschema/migrations"))?;
+    fs::create_dir_all(root.join(".git"))?;
+    fs::write(root.join("src/queries/get-user.edgeql"), "select User")?;
+    fs::write(root.join("src/queries/notes.txt"), "")?;
+    fs::write(root.join("top.edgeql"), "select 1")?;
+    fs::write(root.join("dbschema/migrations/00001.edgeql"), "")?;
+    fs::write(root.join(".git/x.edgeql"), "")?;
+
+    let found = find_project_root(&root.join("src/queries"))?;
+    assert_eq!(found.canonicalize()?, root.canonicalize()?);
+
+    let queries = find_queries(root)?;
+    let paths = queries.iter().map(|q| q.path.as_str()).collect::<Vec<_>>();
+    assert_eq!(paths, ["src/queries/get-user.edgeql", "top.edgeql"]);
+    assert_eq!(queries[0].name, "get_user");
+    assert_eq!(queries[0].text, "select User");
+    Ok(())
+}
+
+#[test]
+fn cache_roundtrip() -> Result<(), gel_codegen::Error> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("queries.json");
+    let proto = ProtocolVersion::new(1, 0);
+    let desc = QueryDescription {
+        cardinality: Cardinality::AtMostOne,
+        capabilities: Capabilities::MODIFICATIONS,
+        input: RawTypedesc {
+            proto: proto.clone(),
+            id: Uuid::from_u128(0xFF),
+            data: Bytes::from_static(b"\x04\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\xff\0\0"),
+        },
+        output: RawTypedesc {
+            proto,
+            id: Uuid::from_u128(0x105),
+            data: Bytes::from_static(b"\x02\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\x05"),
+        },
+    };
+
+    let mut cache = DescriptorCache::load(&path)?;
+    assert_eq!(cache.get("a.edgeql", "select 1")?, None);
+    cache.insert("a.edgeql", "select 1", &desc);
+    cache.insert("b.edgeql", "select 2", &desc);
+    cache.retain(["a.edgeql"]);
+    cache.save(&path)?;
+
+    let cache = DescriptorCache::load(&path)?;
+    assert_eq!(cache.get("a.edgeql", "select 1")?, Some(desc));
+    assert_eq!(cache.get("a.edgeql", "select 2")?, None);
+    assert_eq!(cache.get("b.edgeql", "select 2")?, None);
+    Ok(())
+}
@@ -2,5 +2,6 @@ pub mod codecs;
 pub mod descriptors;
 pub mod errors;
 pub mod protocol;
+pub mod values;
 
 pub use gel_protogen::prelude;
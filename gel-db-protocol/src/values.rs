@@ -0,0 +1,1509 @@
+//! Descriptor-driven value codecs.
+//!
+//! A [`Codec`] is built once from [`ParsedDescriptors`] and can then decode
+//! data elements either into borrowed [`Value`]s or into a stream of
+//! [`Visitor`] callbacks, and encode [`Value`]s back into the wire format.
+//!
+//! ```rust
+//! use gel_db_protocol::values::{Codec, ScalarType, Value};
+//!
+//! let codec = Codec::Tuple(vec![
+//!     Codec::Scalar(ScalarType::Int64),
+//!     Codec::Scalar(ScalarType::Str),
+//! ]);
+//! let value = Value::Tuple(vec![Value::Int64(42), Value::Str("answer")]);
+//! let mut buf = Vec::new();
+//! codec.encode(&value, &mut buf).unwrap();
+//! assert_eq!(codec.decode(&buf).unwrap(), value);
+//! ```
+use gel_protogen::prelude::*;
+
+use crate::codecs::*;
+use crate::descriptors::{ParsedDescriptor, ParsedDescriptors};
+use crate::protocol::Cardinality;
+
+pub const STD_UUID: Uuid = Uuid::from_u128(0x100);
+pub const STD_STR: Uuid = Uuid::from_u128(0x101);
+pub const STD_BYTES: Uuid = Uuid::from_u128(0x102);
+pub const STD_INT16: Uuid = Uuid::from_u128(0x103);
+pub const STD_INT32: Uuid = Uuid::from_u128(0x104);
+pub const STD_INT64: Uuid = Uuid::from_u128(0x105);
+pub const STD_FLOAT32: Uuid = Uuid::from_u128(0x106);
+pub const STD_FLOAT64: Uuid = Uuid::from_u128(0x107);
+pub const STD_DECIMAL: Uuid = Uuid::from_u128(0x108);
+pub const STD_BOOL: Uuid = Uuid::from_u128(0x109);
+pub const STD_DATETIME: Uuid = Uuid::from_u128(0x10a);
+pub const CAL_LOCAL_DATETIME: Uuid = Uuid::from_u128(0x10b);
+pub const CAL_LOCAL_DATE: Uuid = Uuid::from_u128(0x10c);
+pub const CAL_LOCAL_TIME: Uuid = Uuid::from_u128(0x10d);
+pub const STD_DURATION: Uuid = Uuid::from_u128(0x10e);
+pub const STD_JSON: Uuid = Uuid::from_u128(0x10f);
+pub const STD_BIGINT: Uuid = Uuid::from_u128(0x110);
+pub const CAL_RELATIVE_DURATION: Uuid = Uuid::from_u128(0x111);
+pub const CAL_DATE_DURATION: Uuid = Uuid::from_u128(0x112);
+pub const CFG_MEMORY: Uuid = Uuid::from_u128(0x130);
+pub const PGVECTOR_VECTOR: Uuid = Uuid::from_u128(0x9565dd88_04f5_11ee_a691_0b6ebe179825);
+pub const STD_PG_JSON: Uuid = Uuid::from_u128(0x1000001);
+pub const STD_PG_TIMESTAMPTZ: Uuid = Uuid::from_u128(0x1000002);
+pub const STD_PG_TIMESTAMP: Uuid = Uuid::from_u128(0x1000003);
+pub const STD_PG_DATE: Uuid = Uuid::from_u128(0x1000004);
+pub const STD_PG_INTERVAL: Uuid = Uuid::from_u128(0x1000005);
+pub const POSTGIS_GEOMETRY: Uuid = Uuid::from_u128(0x44c901c0_d922_4894_83c8_061bd05e4840);
+pub const POSTGIS_GEOGRAPHY: Uuid = Uuid::from_u128(0x4d738878_3a5f_4821_ab76_9d8e7d6b32c4);
+pub const POSTGIS_BOX_2D: Uuid = Uuid::from_u128(0x7fae5536_6311_4f60_8eb9_096a5d972f48);
+pub const POSTGIS_BOX_3D: Uuid = Uuid::from_u128(0xc1a50ff8_fded_48b0_85c2_4905a8481433);
+
+#[derive(derive_more::Error, derive_more::Display, Debug, Clone, PartialEq, Eq)]
+pub enum CodecError {
+    #[display("Undefined base scalar type {_0}")]
+    UndefinedBaseScalar(#[error(not(source))] Uuid),
+    #[display("Buffer is too short")]
+    Underflow,
+    #[display("Extra data after the value")]
+    ExtraData,
+    #[display("Invalid {_0} data")]
+    InvalidData(#[error(not(source))] &'static str),
+    #[display("Expected {expected} elements, got {actual}")]
+    CountMismatch { expected: usize, actual: usize },
+    #[display("Element {_0} is out of range")]
+    InvalidIndex(#[error(not(source))] usize),
+    #[display("Required element is missing")]
+    MissingRequiredElement,
+    #[display("Enum has no member {_0:?}")]
+    UnknownEnumMember(#[error(not(source))] String),
+    #[display("Cannot encode {found} value with {expected} codec")]
+    ValueMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    #[display("Element is too long")]
+    ElementTooLong,
+    #[display("Invalid UTF-8: {_0}")]
+    Utf8(std::str::Utf8Error),
+    #[display("{_0}")]
+    Parse(ParseError),
+}
+
+impl From<std::str::Utf8Error> for CodecError {
+    fn from(error: std::str::Utf8Error) -> Self {
+        CodecError::Utf8(error)
+    }
+}
+
+impl From<ParseError> for CodecError {
+    fn from(error: ParseError) -> Self {
+        CodecError::Parse(error)
+    }
+}
+
+/// Base scalar types with a known wire format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScalarType {
+    Uuid,
+    Str,
+    Bytes,
+    Int16,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    Decimal,
+    Bool,
+    Datetime,
+    LocalDatetime,
+    LocalDate,
+    LocalTime,
+    Duration,
+    RelativeDuration,
+    DateDuration,
+    Json,
+    /// `std::pg::json`, which is sent as text without a format byte.
+    PgJson,
+    BigInt,
+    Memory,
+    Vector,
+    PostGisGeometry,
+    PostGisGeography,
+    PostGisBox2d,
+    PostGisBox3d,
+}
+
+impl ScalarType {
+    /// Look up the wire format of a base scalar type by its id.
+    pub fn from_id(id: Uuid) -> Option<ScalarType> {
+        use ScalarType::*;
+        Some(match id {
+            STD_UUID => Uuid,
+            STD_STR => Str,
+            STD_BYTES => Bytes,
+            STD_INT16 => Int16,
+            STD_INT32 => Int32,
+            STD_INT64 => Int64,
+            STD_FLOAT32 => Float32,
+            STD_FLOAT64 => Float64,
+            STD_DECIMAL => Decimal,
+            STD_BOOL => Bool,
+            STD_DATETIME | STD_PG_TIMESTAMPTZ => Datetime,
+            CAL_LOCAL_DATETIME | STD_PG_TIMESTAMP => LocalDatetime,
+            CAL_LOCAL_DATE | STD_PG_DATE => LocalDate,
+            CAL_LOCAL_TIME => LocalTime,
+            STD_DURATION => Duration,
+            CAL_RELATIVE_DURATION | STD_PG_INTERVAL => RelativeDuration,
+            CAL_DATE_DURATION => DateDuration,
+            STD_JSON => Json,
+            STD_PG_JSON => PgJson,
+            STD_BIGINT => BigInt,
+            CFG_MEMORY => Memory,
+            PGVECTOR_VECTOR => Vector,
+            POSTGIS_GEOMETRY => PostGisGeometry,
+            POSTGIS_GEOGRAPHY => PostGisGeography,
+            POSTGIS_BOX_2D => PostGisBox2d,
+            POSTGIS_BOX_3D => PostGisBox3d,
+            _ => return None,
+        })
+    }
+}
+
+/// A named element of an object, input shape, named tuple or SQL row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    /// Shape element flags (implicit, link property, link). Always zero
+    /// for named tuples and SQL rows.
+    pub flags: u32,
+    pub cardinality: Option<Cardinality>,
+    pub codec: Codec,
+}
+
+/// A codec tree built from type descriptors.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Codec {
+    /// No data, e.g. a statement without a result.
+    Nothing,
+    Scalar(ScalarType),
+    Enum(Vec<String>),
+    Object(Vec<Field>),
+    /// Input shapes, where any subset of fields may be present.
+    Input(Vec<Field>),
+    Set(Box<Codec>),
+    Tuple(Vec<Codec>),
+    NamedTuple(Vec<Field>),
+    Array(Box<Codec>),
+    Range(Box<Codec>),
+    /// A multirange; the element codec is always a [`Codec::Range`].
+    MultiRange(Box<Codec>),
+    SqlRow(Vec<Field>),
+}
+
+/// Range flags as sent on the wire.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RangeFlags(pub u8);
+
+impl RangeFlags {
+    pub const EMPTY: u8 = 0x01;
+    pub const LB_INC: u8 = 0x02;
+    pub const UB_INC: u8 = 0x04;
+    pub const LB_INF: u8 = 0x08;
+    pub const UB_INF: u8 = 0x10;
+
+    pub fn empty(&self) -> bool {
+        self.0 & Self::EMPTY != 0
+    }
+    pub fn inc_lower(&self) -> bool {
+        self.0 & Self::LB_INC != 0
+    }
+    pub fn inc_upper(&self) -> bool {
+        self.0 & Self::UB_INC != 0
+    }
+    /// Whether a lower bound follows the flags.
+    pub fn has_lower(&self) -> bool {
+        self.0 & (Self::EMPTY | Self::LB_INF) == 0
+    }
+    /// Whether an upper bound follows the flags.
+    pub fn has_upper(&self) -> bool {
+        self.0 & (Self::EMPTY | Self::UB_INF) == 0
+    }
+}
+
+/// Arbitrary-precision number in base 10000 digits, as used by
+/// `std::decimal` and `std::bigint`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Numeric {
+    pub negative: bool,
+    pub weight: i16,
+    /// Number of decimal digits after the point, always zero for bigint.
+    pub decimal_digits: u16,
+    pub digits: Vec<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range<'a> {
+    pub lower: Option<Box<Value<'a>>>,
+    pub upper: Option<Box<Value<'a>>>,
+    pub inc_lower: bool,
+    pub inc_upper: bool,
+    pub empty: bool,
+}
+
+impl Range<'_> {
+    fn flags(&self) -> RangeFlags {
+        if self.empty {
+            return RangeFlags(RangeFlags::EMPTY);
+        }
+        let mut flags = 0;
+        if self.inc_lower {
+            flags |= RangeFlags::LB_INC;
+        }
+        if self.inc_upper {
+            flags |= RangeFlags::UB_INC;
+        }
+        if self.lower.is_none() {
+            flags |= RangeFlags::LB_INF;
+        }
+        if self.upper.is_none() {
+            flags |= RangeFlags::UB_INF;
+        }
+        RangeFlags(flags)
+    }
+}
+
+/// A decoded value. Strings and byte payloads borrow from the input buffer.
+///
+/// Temporal values are kept in their wire representation: microseconds
+/// since 2000-01-01 for `Datetime` and `LocalDatetime`, days since
+/// 2000-01-01 for `LocalDate` and microseconds since midnight for
+/// `LocalTime`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<'a> {
+    Nothing,
+    Uuid(Uuid),
+    Str(&'a str),
+    Bytes(&'a [u8]),
+    Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    Float32(f32),
+    Float64(f64),
+    Decimal(Numeric),
+    Bool(bool),
+    Datetime(i64),
+    LocalDatetime(i64),
+    LocalDate(i32),
+    LocalTime(i64),
+    Duration(i64),
+    RelativeDuration {
+        micros: i64,
+        days: i32,
+        months: i32,
+    },
+    DateDuration {
+        days: i32,
+        months: i32,
+    },
+    Json(&'a str),
+    BigInt(Numeric),
+    Memory(i64),
+    Vector(Vec<f32>),
+    PostGisGeometry(&'a [u8]),
+    PostGisGeography(&'a [u8]),
+    PostGisBox2d(&'a [u8]),
+    PostGisBox3d(&'a [u8]),
+    Enum(&'a str),
+    /// Object fields in shape order, `None` for empty fields.
+    Object(Vec<Option<Value<'a>>>),
+    /// Input shape fields in shape order: `None` if the field is not
+    /// given, `Some(None)` if it is explicitly empty.
+    SparseObject(Vec<Option<Option<Value<'a>>>>),
+    Set(Vec<Value<'a>>),
+    Tuple(Vec<Value<'a>>),
+    NamedTuple(Vec<Value<'a>>),
+    Array(Vec<Value<'a>>),
+    Range(Range<'a>),
+    MultiRange(Vec<Range<'a>>),
+    SqlRow(Vec<Option<Value<'a>>>),
+}
+
+impl Value<'_> {
+    /// A short name of the kind of the value, used in error messages.
+    pub fn kind(&self) -> &'static str {
+        use Value::*;
+        match self {
+            Nothing => "nothing",
+            Uuid(_) => "uuid",
+            Str(_) => "str",
+            Bytes(_) => "bytes",
+            Int16(_) => "int16",
+            Int32(_) => "int32",
+            Int64(_) => "int64",
+            Float32(_) => "float32",
+            Float64(_) => "float64",
+            Decimal(_) => "decimal",
+            Bool(_) => "bool",
+            Datetime(_) => "datetime",
+            LocalDatetime(_) => "local_datetime",
+            LocalDate(_) => "local_date",
+            LocalTime(_) => "local_time",
+            Duration(_) => "duration",
+            RelativeDuration { .. } => "relative_duration",
+            DateDuration { .. } => "date_duration",
+            Json(_) => "json",
+            BigInt(_) => "bigint",
+            Memory(_) => "memory",
+            Vector(_) => "vector",
+            PostGisGeometry(_) => "geometry",
+            PostGisGeography(_) => "geography",
+            PostGisBox2d(_) => "box2d",
+            PostGisBox3d(_) => "box3d",
+            Enum(_) => "enum",
+            Object(_) => "object",
+            SparseObject(_) => "sparse object",
+            Set(_) => "set",
+            Tuple(_) => "tuple",
+            NamedTuple(_) => "named tuple",
+            Array(_) => "array",
+            Range(_) => "range",
+            MultiRange(_) => "multirange",
+            SqlRow(_) => "SQL row",
+        }
+    }
+}
+
+/// The kind of composite value reported to [`Visitor::start`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Composite {
+    Object,
+    Input,
+    Set,
+    Tuple,
+    NamedTuple,
+    Array,
+    Range(RangeFlags),
+    MultiRange,
+    SqlRow,
+}
+
+/// Receives a decoded value as a stream of callbacks.
+///
+/// Composite values are reported as `start`, their elements and `end`.
+/// Elements of objects, input shapes, named tuples and SQL rows are
+/// preceded by a `field` call. Range elements are the bounds that are
+/// present according to the range flags.
+pub trait Visitor<'a> {
+    /// A scalar, enum or vector value.
+    fn scalar(&mut self, value: Value<'a>) -> Result<(), CodecError>;
+    /// An empty object field or SQL row column, or an explicitly empty
+    /// input field.
+    fn null(&mut self) -> Result<(), CodecError>;
+    /// The start of a composite value with `len` elements (for objects and
+    /// input shapes, the number of fields in the shape).
+    fn start(&mut self, composite: Composite, len: usize) -> Result<(), CodecError>;
+    /// The next element is the field at `index` of the shape.
+    fn field(&mut self, index: usize, name: &str) -> Result<(), CodecError> {
+        let _ = (index, name);
+        Ok(())
+    }
+    /// The end of the innermost composite value.
+    fn end(&mut self) -> Result<(), CodecError>;
+}
+
+impl Codec {
+    /// Build a codec for the root type of the descriptors.
+    pub fn build(descriptors: &ParsedDescriptors<'_>) -> Result<Codec, CodecError> {
+        Codec::from_descriptor(descriptors.root())
+    }
+
+    fn from_descriptor(descriptor: ParsedDescriptor<'_, '_>) -> Result<Codec, CodecError> {
+        use ParsedDescriptor as D;
+        Ok(match descriptor {
+            D::Set(d) => Codec::Set(Box::new(Codec::from_descriptor(d.set_type())?)),
+            D::ObjectShape(d) => Codec::Object(
+                d.elements()
+                    .map(|e| {
+                        Ok(Field {
+                            name: e.name().to_string_lossy().into_owned(),
+                            flags: e.flags(),
+                            cardinality: Some(e.cardinality()),
+                            codec: Codec::from_descriptor(e.element_type())?,
+                        })
+                    })
+                    .collect::<Result<_, CodecError>>()?,
+            ),
+            D::BaseScalarType(d) => scalar(d.id())?,
+            // Derived scalars list their base type last in the ancestors.
+            D::ScalarType(d) => match d.ancestors().last() {
+                Some(base) => Codec::from_descriptor(base)?,
+                None => scalar(d.id())?,
+            },
+            D::TupleType(d) => Codec::Tuple(
+                d.elements()
+                    .map(Codec::from_descriptor)
+                    .collect::<Result<_, _>>()?,
+            ),
+            D::NamedTupleType(d) => Codec::NamedTuple(named_fields(d.elements())?),
+            D::ArrayType(d) => Codec::Array(Box::new(Codec::from_descriptor(d.element_type())?)),
+            D::EnumerationType(d) => Codec::Enum(d.members().map(String::from).collect()),
+            D::InputShape(d) => Codec::Input(
+                d.elements()
+                    .map(|e| {
+                        Ok(Field {
+                            name: e.name().to_string_lossy().into_owned(),
+                            flags: e.flags(),
+                            cardinality: Some(e.cardinality()),
+                            codec: Codec::from_descriptor(e.element_type())?,
+                        })
+                    })
+                    .collect::<Result<_, CodecError>>()?,
+            ),
+            D::RangeType(d) => Codec::Range(Box::new(Codec::from_descriptor(d.element_type())?)),
+            D::MultiRangeType(d) => Codec::MultiRange(Box::new(Codec::Range(Box::new(
+                Codec::from_descriptor(d.range_type())?,
+            )))),
+            D::SQLRecord(d) => Codec::SqlRow(named_fields(d.elements())?),
+            // Object and compound types only describe the source types of
+            // shape elements and carry no data of their own.
+            D::ObjectType(_) | D::CompoundType(_) => Codec::Nothing,
+        })
+    }
+
+    /// A short name of the kind of the codec, used in error messages.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Codec::Nothing => "nothing",
+            Codec::Scalar(_) => "scalar",
+            Codec::Enum(_) => "enum",
+            Codec::Object(_) => "object",
+            Codec::Input(_) => "input",
+            Codec::Set(_) => "set",
+            Codec::Tuple(_) => "tuple",
+            Codec::NamedTuple(_) => "named tuple",
+            Codec::Array(_) => "array",
+            Codec::Range(_) => "range",
+            Codec::MultiRange(_) => "multirange",
+            Codec::SqlRow(_) => "SQL row",
+        }
+    }
+
+    /// Decode a value, borrowing strings and bytes from `buf`.
+    pub fn decode<'a>(&self, buf: &'a [u8]) -> Result<Value<'a>, CodecError> {
+        let mut builder = ValueBuilder::default();
+        self.visit(buf, &mut builder)?;
+        builder.result.ok_or(CodecError::Underflow)
+    }
+
+    /// Decode a value as a stream of visitor callbacks.
+    pub fn visit<'a>(
+        &self,
+        buf: &'a [u8],
+        visitor: &mut impl Visitor<'a>,
+    ) -> Result<(), CodecError> {
+        match self {
+            Codec::Nothing => visitor.scalar(Value::Nothing),
+            Codec::Scalar(scalar) => visitor.scalar(decode_scalar(*scalar, buf)?),
+            Codec::Enum(members) => {
+                let member = EnumValue::new(buf)?.value().to_str()?;
+                if !members.iter().any(|m| m == member) {
+                    return Err(CodecError::UnknownEnumMember(member.to_string()));
+                }
+                visitor.scalar(Value::Enum(member))
+            }
+            Codec::Object(fields) => visit_fields(Composite::Object, fields, buf, visitor),
+            Codec::SqlRow(fields) => visit_fields(Composite::SqlRow, fields, buf, visitor),
+            Codec::NamedTuple(fields) => visit_fields(Composite::NamedTuple, fields, buf, visitor),
+            Codec::Input(fields) => {
+                let mut reader = Reader(buf);
+                let count = reader.u32()? as usize;
+                visitor.start(Composite::Input, fields.len())?;
+                for _ in 0..count {
+                    let index = reader.u32()? as usize;
+                    let field = fields.get(index).ok_or(CodecError::InvalidIndex(index))?;
+                    visitor.field(index, &field.name)?;
+                    match reader.element()? {
+                        Some(element) => field.codec.visit(element, visitor)?,
+                        None => visitor.null()?,
+                    }
+                }
+                reader.finish()?;
+                visitor.end()
+            }
+            Codec::Tuple(elements) => {
+                let mut reader = Reader(buf);
+                let count = reader.u32()? as usize;
+                check_count(elements.len(), count)?;
+                visitor.start(Composite::Tuple, count)?;
+                for codec in elements {
+                    reader.u32()?; // reserved
+                    let element = reader
+                        .element()?
+                        .ok_or(CodecError::MissingRequiredElement)?;
+                    codec.visit(element, visitor)?;
+                }
+                reader.finish()?;
+                visitor.end()
+            }
+            Codec::Set(element) => {
+                let mut reader = Reader(buf);
+                let count = reader.array_header()?;
+                visitor.start(Composite::Set, count)?;
+                for _ in 0..count {
+                    let item = reader
+                        .element()?
+                        .ok_or(CodecError::MissingRequiredElement)?;
+                    if let Codec::Array(_) = **element {
+                        // Arrays in sets are wrapped in a single-element envelope
+                        let mut envelope = Reader(item);
+                        if envelope.u32()? != 1 {
+                            return Err(CodecError::InvalidData("array envelope"));
+                        }
+                        envelope.u32()?; // reserved
+                        let array = envelope
+                            .element()?
+                            .ok_or(CodecError::MissingRequiredElement)?;
+                        envelope.finish()?;
+                        element.visit(array, visitor)?;
+                    } else {
+                        element.visit(item, visitor)?;
+                    }
+                }
+                reader.finish()?;
+                visitor.end()
+            }
+            Codec::Array(element) => {
+                let mut reader = Reader(buf);
+                let count = reader.array_header()?;
+                visitor.start(Composite::Array, count)?;
+                for _ in 0..count {
+                    let item = reader
+                        .element()?
+                        .ok_or(CodecError::MissingRequiredElement)?;
+                    element.visit(item, visitor)?;
+                }
+                reader.finish()?;
+                visitor.end()
+            }
+            Codec::Range(element) => {
+                let mut reader = Reader(buf);
+                let flags = RangeFlags(reader.u8()?);
+                let bounds = flags.has_lower() as usize + flags.has_upper() as usize;
+                visitor.start(Composite::Range(flags), bounds)?;
+                for _ in 0..bounds {
+                    let bound = reader
+                        .element()?
+                        .ok_or(CodecError::MissingRequiredElement)?;
+                    element.visit(bound, visitor)?;
+                }
+                reader.finish()?;
+                visitor.end()
+            }
+            Codec::MultiRange(range) => {
+                let mut reader = Reader(buf);
+                let count = reader.u32()? as usize;
+                visitor.start(Composite::MultiRange, count)?;
+                for _ in 0..count {
+                    let item = reader
+                        .element()?
+                        .ok_or(CodecError::MissingRequiredElement)?;
+                    range.visit(item, visitor)?;
+                }
+                reader.finish()?;
+                visitor.end()
+            }
+        }
+    }
+
+    /// Encode a value, appending it to `buf`.
+    pub fn encode(&self, value: &Value<'_>, buf: &mut Vec<u8>) -> Result<(), CodecError> {
+        let mismatch = || CodecError::ValueMismatch {
+            expected: self.kind(),
+            found: value.kind(),
+        };
+        match (self, value) {
+            (Codec::Nothing, Value::Nothing) => {}
+            (Codec::Scalar(scalar), value) => encode_scalar(*scalar, value, buf)?,
+            (Codec::Enum(members), Value::Enum(member) | Value::Str(member)) => {
+                if !members.iter().any(|m| m == member) {
+                    return Err(CodecError::UnknownEnumMember(member.to_string()));
+                }
+                buf.extend_from_slice(member.as_bytes());
+            }
+            (Codec::Object(fields), Value::Object(values))
+            | (Codec::SqlRow(fields), Value::SqlRow(values)) => {
+                check_count(fields.len(), values.len())?;
+                put_u32(buf, fields.len())?;
+                for (field, value) in fields.iter().zip(values) {
+                    put_u32(buf, 0)?; // reserved
+                    match value {
+                        Some(value) => element(buf, |buf| field.codec.encode(value, buf))?,
+                        None => buf.extend_from_slice(&(-1i32).to_be_bytes()),
+                    }
+                }
+            }
+            (Codec::Input(fields), Value::SparseObject(values)) => {
+                check_count(fields.len(), values.len())?;
+                put_u32(buf, values.iter().filter(|v| v.is_some()).count())?;
+                for (index, (field, value)) in fields.iter().zip(values).enumerate() {
+                    match value {
+                        Some(Some(value)) => {
+                            put_u32(buf, index)?;
+                            element(buf, |buf| field.codec.encode(value, buf))?;
+                        }
+                        Some(None) => {
+                            put_u32(buf, index)?;
+                            buf.extend_from_slice(&(-1i32).to_be_bytes());
+                        }
+                        None => {}
+                    }
+                }
+            }
+            (Codec::Tuple(codecs), Value::Tuple(values)) => {
+                check_count(codecs.len(), values.len())?;
+                put_u32(buf, codecs.len())?;
+                for (codec, value) in codecs.iter().zip(values) {
+                    put_u32(buf, 0)?; // reserved
+                    element(buf, |buf| codec.encode(value, buf))?;
+                }
+            }
+            (Codec::NamedTuple(fields), Value::NamedTuple(values)) => {
+                check_count(fields.len(), values.len())?;
+                put_u32(buf, fields.len())?;
+                for (field, value) in fields.iter().zip(values) {
+                    put_u32(buf, 0)?; // reserved
+                    element(buf, |buf| field.codec.encode(value, buf))?;
+                }
+            }
+            (Codec::Set(codec), Value::Set(values)) => {
+                if values.is_empty() {
+                    buf.extend_from_slice(&[0; 12]);
+                    return Ok(());
+                }
+                array_header(buf, values.len())?;
+                for value in values {
+                    if let Codec::Array(_) = **codec {
+                        element(buf, |buf| {
+                            put_u32(buf, 1)?;
+                            put_u32(buf, 0)?; // reserved
+                            element(buf, |buf| codec.encode(value, buf))
+                        })?;
+                    } else {
+                        element(buf, |buf| codec.encode(value, buf))?;
+                    }
+                }
+            }
+            (Codec::Array(codec), Value::Array(values)) => {
+                // The short form of an empty array is rejected by older servers
+                array_header(buf, values.len())?;
+                for value in values {
+                    element(buf, |buf| codec.encode(value, buf))?;
+                }
+            }
+            (Codec::Range(codec), Value::Range(range)) => encode_range(codec, range, buf)?,
+            (Codec::MultiRange(range), Value::MultiRange(ranges)) => {
+                let Codec::Range(codec) = &**range else {
+                    return Err(CodecError::InvalidData("multirange codec"));
+                };
+                put_u32(buf, ranges.len())?;
+                for range in ranges {
+                    element(buf, |buf| encode_range(codec, range, buf))?;
+                }
+            }
+            _ => return Err(mismatch()),
+        }
+        Ok(())
+    }
+}
+
+fn scalar(id: Uuid) -> Result<Codec, CodecError> {
+    ScalarType::from_id(id)
+        .map(Codec::Scalar)
+        .ok_or(CodecError::UndefinedBaseScalar(id))
+}
+
+fn named_fields<'a: 'b, 'b>(
+    elements: impl Iterator<Item = (&'a str, ParsedDescriptor<'a, 'b>)>,
+) -> Result<Vec<Field>, CodecError> {
+    elements
+        .map(|(name, descriptor)| {
+            Ok(Field {
+                name: name.to_string(),
+                flags: 0,
+                cardinality: None,
+                codec: Codec::from_descriptor(descriptor)?,
+            })
+        })
+        .collect()
+}
+
+fn visit_fields<'a>(
+    composite: Composite,
+    fields: &[Field],
+    buf: &'a [u8],
+    visitor: &mut impl Visitor<'a>,
+) -> Result<(), CodecError> {
+    let mut reader = Reader(buf);
+    let count = reader.u32()? as usize;
+    check_count(fields.len(), count)?;
+    visitor.start(composite, count)?;
+    for (index, field) in fields.iter().enumerate() {
+        reader.u32()?; // reserved
+        visitor.field(index, &field.name)?;
+        match reader.element()? {
+            Some(element) => field.codec.visit(element, visitor)?,
+            None if composite == Composite::NamedTuple => {
+                return Err(CodecError::MissingRequiredElement)
+            }
+            None => visitor.null()?,
+        }
+    }
+    reader.finish()?;
+    visitor.end()
+}
+
+fn check_count(expected: usize, actual: usize) -> Result<(), CodecError> {
+    if expected != actual {
+        return Err(CodecError::CountMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+fn decode_scalar(scalar: ScalarType, buf: &[u8]) -> Result<Value<'_>, CodecError> {
+    use ScalarType as S;
+    Ok(match scalar {
+        S::Uuid => Value::Uuid(Uuid::from_bytes(UuidValue::new(buf)?.value())),
+        S::Str => Value::Str(StringValue::new(buf)?.value().to_str()?),
+        S::Bytes => Value::Bytes(BytesValue::new(buf)?.value().into_slice()),
+        S::Int16 => Value::Int16(Int16Value::new(buf)?.value()),
+        S::Int32 => Value::Int32(Int32Value::new(buf)?.value()),
+        S::Int64 => Value::Int64(Int64Value::new(buf)?.value()),
+        S::Float32 => Value::Float32(Float32Value::new(buf)?.value()),
+        S::Float64 => Value::Float64(Float64Value::new(buf)?.value()),
+        S::Bool => match BoolValue::new(buf)?.value() {
+            0 => Value::Bool(false),
+            1 => Value::Bool(true),
+            _ => return Err(CodecError::InvalidData("bool")),
+        },
+        S::Datetime => Value::Datetime(DatetimeValue::new(buf)?.micros()),
+        S::LocalDatetime => Value::LocalDatetime(LocalDatetimeValue::new(buf)?.micros()),
+        S::LocalDate => Value::LocalDate(LocalDateValue::new(buf)?.days()),
+        S::LocalTime => Value::LocalTime(LocalTimeValue::new(buf)?.micros()),
+        S::Duration => Value::Duration(DurationValue::new(buf)?.micros()),
+        S::RelativeDuration => {
+            let value = RelativeDurationValue::new(buf)?;
+            Value::RelativeDuration {
+                micros: value.micros(),
+                days: value.days(),
+                months: value.months(),
+            }
+        }
+        S::DateDuration => {
+            let value = DateDurationValue::new(buf)?;
+            Value::DateDuration {
+                days: value.days(),
+                months: value.months(),
+            }
+        }
+        S::Json => {
+            let value = JsonValue::new(buf)?;
+            if value.format() != 1 {
+                return Err(CodecError::InvalidData("json"));
+            }
+            Value::Json(value.value().to_str()?)
+        }
+        S::PgJson => Value::Json(StringValue::new(buf)?.value().to_str()?),
+        S::Memory => Value::Memory(Int64Value::new(buf)?.value()),
+        S::Decimal => {
+            let value = DecimalValue::new(buf)?;
+            Value::Decimal(numeric(
+                value.ndigits(),
+                value.weight(),
+                value.sign(),
+                value.decimal_digits(),
+                value.digits(),
+            )?)
+        }
+        S::BigInt => {
+            let value = BigIntValue::new(buf)?;
+            Value::BigInt(numeric(
+                value.ndigits(),
+                value.weight(),
+                value.sign(),
+                0,
+                value.digits(),
+            )?)
+        }
+        S::Vector => {
+            let value = VectorValue::new(buf)?;
+            let values = value.values();
+            check_count(value.length() as usize, values.len())?;
+            Value::Vector(values.into_iter().collect())
+        }
+        S::PostGisGeometry => {
+            Value::PostGisGeometry(PostGisGeometryValue::new(buf)?.value().into_slice())
+        }
+        S::PostGisGeography => {
+            Value::PostGisGeography(PostGisGeographyValue::new(buf)?.value().into_slice())
+        }
+        S::PostGisBox2d => Value::PostGisBox2d(PostGisBox2dValue::new(buf)?.value().into_slice()),
+        S::PostGisBox3d => Value::PostGisBox3d(PostGisBox3dValue::new(buf)?.value().into_slice()),
+    })
+}
+
+fn numeric(
+    ndigits: u16,
+    weight: i16,
+    sign: u16,
+    decimal_digits: u16,
+    digits: RestArray<'_, u16>,
+) -> Result<Numeric, CodecError> {
+    check_count(ndigits as usize, digits.len())?;
+    let negative = match sign {
+        0x0000 => false,
+        0x4000 => true,
+        _ => return Err(CodecError::InvalidData("numeric sign")),
+    };
+    Ok(Numeric {
+        negative,
+        weight,
+        decimal_digits,
+        digits: digits.into_iter().collect(),
+    })
+}
+
+fn encode_scalar(
+    scalar: ScalarType,
+    value: &Value<'_>,
+    buf: &mut Vec<u8>,
+) -> Result<(), CodecError> {
+    use ScalarType as S;
+    match (scalar, value) {
+        (S::Uuid, Value::Uuid(v)) => buf.extend_from_slice(v.as_bytes()),
+        (S::Str, Value::Str(v)) => buf.extend_from_slice(v.as_bytes()),
+        (S::Bytes, Value::Bytes(v)) => buf.extend_from_slice(v),
+        (S::Int16, Value::Int16(v)) => buf.extend_from_slice(&v.to_be_bytes()),
+        (S::Int32, Value::Int32(v)) => buf.extend_from_slice(&v.to_be_bytes()),
+        (S::Int64, Value::Int64(v)) | (S::Memory, Value::Memory(v)) => {
+            buf.extend_from_slice(&v.to_be_bytes())
+        }
+        (S::Float32, Value::Float32(v)) => buf.extend_from_slice(&v.to_be_bytes()),
+        (S::Float64, Value::Float64(v)) => buf.extend_from_slice(&v.to_be_bytes()),
+        (S::Bool, Value::Bool(v)) => buf.push(*v as u8),
+        (S::Datetime, Value::Datetime(v))
+        | (S::LocalDatetime, Value::LocalDatetime(v))
+        | (S::LocalTime, Value::LocalTime(v)) => buf.extend_from_slice(&v.to_be_bytes()),
+        (S::LocalDate, Value::LocalDate(v)) => buf.extend_from_slice(&v.to_be_bytes()),
+        (S::Duration, Value::Duration(v)) => {
+            buf.extend_from_slice(&v.to_be_bytes());
+            buf.extend_from_slice(&[0; 8]);
+        }
+        (
+            S::RelativeDuration,
+            Value::RelativeDuration {
+                micros,
+                days,
+                months,
+            },
+        ) => {
+            buf.extend_from_slice(&micros.to_be_bytes());
+            buf.extend_from_slice(&days.to_be_bytes());
+            buf.extend_from_slice(&months.to_be_bytes());
+        }
+        (S::DateDuration, Value::DateDuration { days, months }) => {
+            buf.extend_from_slice(&[0; 8]);
+            buf.extend_from_slice(&days.to_be_bytes());
+            buf.extend_from_slice(&months.to_be_bytes());
+        }
+        (S::Json, Value::Json(v)) => {
+            buf.push(1);
+            buf.extend_from_slice(v.as_bytes());
+        }
+        (S::PgJson, Value::Json(v)) => buf.extend_from_slice(v.as_bytes()),
+        (S::Decimal, Value::Decimal(v)) => encode_numeric(v, v.decimal_digits, buf)?,
+        (S::BigInt, Value::BigInt(v)) => encode_numeric(v, 0, buf)?,
+        (S::Vector, Value::Vector(v)) => {
+            let len = u16::try_from(v.len()).map_err(|_| CodecError::ElementTooLong)?;
+            buf.extend_from_slice(&len.to_be_bytes());
+            buf.extend_from_slice(&[0; 2]);
+            for item in v {
+                buf.extend_from_slice(&item.to_be_bytes());
+            }
+        }
+        (S::PostGisGeometry, Value::PostGisGeometry(v))
+        | (S::PostGisGeography, Value::PostGisGeography(v))
+        | (S::PostGisBox2d, Value::PostGisBox2d(v))
+        | (S::PostGisBox3d, Value::PostGisBox3d(v)) => buf.extend_from_slice(v),
+        (_, value) => {
+            return Err(CodecError::ValueMismatch {
+                expected: "scalar",
+                found: value.kind(),
+            })
+        }
+    }
+    Ok(())
+}
+
+fn encode_numeric(value: &Numeric, last: u16, buf: &mut Vec<u8>) -> Result<(), CodecError> {
+    let ndigits = u16::try_from(value.digits.len()).map_err(|_| CodecError::ElementTooLong)?;
+    buf.extend_from_slice(&ndigits.to_be_bytes());
+    buf.extend_from_slice(&value.weight.to_be_bytes());
+    let sign: u16 = if value.negative { 0x4000 } else { 0 };
+    buf.extend_from_slice(&sign.to_be_bytes());
+    buf.extend_from_slice(&last.to_be_bytes());
+    for digit in &value.digits {
+        buf.extend_from_slice(&digit.to_be_bytes());
+    }
+    Ok(())
+}
+
+fn encode_range(codec: &Codec, range: &Range<'_>, buf: &mut Vec<u8>) -> Result<(), CodecError> {
+    let flags = range.flags();
+    buf.push(flags.0);
+    if flags.has_lower() {
+        if let Some(lower) = &range.lower {
+            element(buf, |buf| codec.encode(lower, buf))?;
+        }
+    }
+    if flags.has_upper() {
+        if let Some(upper) = &range.upper {
+            element(buf, |buf| codec.encode(upper, buf))?;
+        }
+    }
+    Ok(())
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: usize) -> Result<(), CodecError> {
+    let value = u32::try_from(value).map_err(|_| CodecError::ElementTooLong)?;
+    buf.extend_from_slice(&value.to_be_bytes());
+    Ok(())
+}
+
+fn array_header(buf: &mut Vec<u8>, len: usize) -> Result<(), CodecError> {
+    put_u32(buf, 1)?; // ndims
+    put_u32(buf, 0)?; // reserved
+    put_u32(buf, 0)?; // reserved
+    put_u32(buf, len)?;
+    put_u32(buf, 1) // lower bound
+}
+
+/// Write a length-prefixed element.
+fn element(
+    buf: &mut Vec<u8>,
+    f: impl FnOnce(&mut Vec<u8>) -> Result<(), CodecError>,
+) -> Result<(), CodecError> {
+    let pos = buf.len();
+    buf.extend_from_slice(&[0; 4]); // replaced after serializing a value
+    f(buf)?;
+    let len = i32::try_from(buf.len() - pos - 4).map_err(|_| CodecError::ElementTooLong)?;
+    buf[pos..pos + 4].copy_from_slice(&len.to_be_bytes());
+    Ok(())
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CodecError> {
+        if self.0.len() < len {
+            return Err(CodecError::Underflow);
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, CodecError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, CodecError> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    /// A length-prefixed element, `None` for a length of -1.
+    fn element(&mut self) -> Result<Option<&'a [u8]>, CodecError> {
+        match self.u32()? as i32 {
+            -1 => Ok(None),
+            len if len < 0 => Err(CodecError::InvalidData("element length")),
+            len => self.take(len as usize).map(Some),
+        }
+    }
+
+    /// The header of a set or array, returning the number of elements.
+    fn array_header(&mut self) -> Result<usize, CodecError> {
+        let ndims = self.u32()?;
+        self.u32()?; // reserved
+        self.u32()?; // reserved
+        match ndims {
+            0 => Ok(0),
+            1 => {
+                let len = self.u32()? as usize;
+                if self.u32()? != 1 {
+                    return Err(CodecError::InvalidData("array lower bound"));
+                }
+                Ok(len)
+            }
+            _ => Err(CodecError::InvalidData("array dimensions")),
+        }
+    }
+
+    fn finish(self) -> Result<(), CodecError> {
+        if !self.0.is_empty() {
+            return Err(CodecError::ExtraData);
+        }
+        Ok(())
+    }
+}
+
+/// Builds a [`Value`] from visitor callbacks.
+#[derive(Default)]
+struct ValueBuilder<'a> {
+    stack: Vec<Frame<'a>>,
+    result: Option<Value<'a>>,
+}
+
+struct Frame<'a> {
+    composite: Composite,
+    len: usize,
+    next: usize,
+    items: Vec<Option<Option<Value<'a>>>>,
+}
+
+impl<'a> ValueBuilder<'a> {
+    fn push(&mut self, item: Option<Value<'a>>) -> Result<(), CodecError> {
+        let Some(frame) = self.stack.last_mut() else {
+            self.result = Some(item.unwrap_or(Value::Nothing));
+            return Ok(());
+        };
+        let index = frame.next;
+        if index >= frame.items.len() {
+            frame.items.resize(index + 1, None);
+        }
+        frame.items[index] = Some(item);
+        frame.next += 1;
+        Ok(())
+    }
+}
+
+impl<'a> Visitor<'a> for ValueBuilder<'a> {
+    fn scalar(&mut self, value: Value<'a>) -> Result<(), CodecError> {
+        self.push(Some(value))
+    }
+
+    fn null(&mut self) -> Result<(), CodecError> {
+        self.push(None)
+    }
+
+    fn start(&mut self, composite: Composite, len: usize) -> Result<(), CodecError> {
+        // `len` comes from the data for sets and arrays, so items are only
+        // allocated as they arrive.
+        self.stack.push(Frame {
+            composite,
+            len,
+            next: 0,
+            items: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn field(&mut self, index: usize, _name: &str) -> Result<(), CodecError> {
+        if let Some(frame) = self.stack.last_mut() {
+            frame.next = index;
+        }
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result<(), CodecError> {
+        let Frame {
+            composite,
+            len,
+            mut items,
+            ..
+        } = self.stack.pop().ok_or(CodecError::Underflow)?;
+        let values = |items: Vec<_>| items.into_iter().map(Option::flatten);
+        let required = |items: Vec<_>| {
+            values(items)
+                .map(|value| value.ok_or(CodecError::MissingRequiredElement))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let value = match composite {
+            Composite::Object => Value::Object(values(items).collect()),
+            Composite::SqlRow => Value::SqlRow(values(items).collect()),
+            Composite::Input => {
+                // Trailing fields of the shape may not have been sent.
+                if items.len() < len {
+                    items.resize(len, None);
+                }
+                Value::SparseObject(items)
+            }
+            Composite::Set => Value::Set(required(items)?),
+            Composite::Tuple => Value::Tuple(required(items)?),
+            Composite::NamedTuple => Value::NamedTuple(required(items)?),
+            Composite::Array => Value::Array(required(items)?),
+            Composite::MultiRange => Value::MultiRange(
+                required(items)?
+                    .into_iter()
+                    .map(|value| match value {
+                        Value::Range(range) => Ok(range),
+                        _ => Err(CodecError::InvalidData("multirange")),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Composite::Range(flags) => {
+                let mut bounds = required(items)?.into_iter();
+                let lower = flags.has_lower().then(|| bounds.next()).flatten();
+                let upper = flags.has_upper().then(|| bounds.next()).flatten();
+                Value::Range(Range {
+                    lower: lower.map(Box::new),
+                    upper: upper.map(Box::new),
+                    inc_lower: flags.inc_lower(),
+                    inc_upper: flags.inc_upper(),
+                    empty: flags.empty(),
+                })
+            }
+        };
+        self.push(Some(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::descriptors::parse_descriptor;
+
+    fn concat_bytes(bytes: &[&[u8]]) -> Vec<u8> {
+        let mut result = Vec::new();
+        for b in bytes {
+            result.extend_from_slice(b);
+        }
+        result
+    }
+
+    fn roundtrip(codec: &Codec, value: Value<'_>) -> Vec<u8> {
+        let mut buf = Vec::new();
+        codec.encode(&value, &mut buf).unwrap();
+        assert_eq!(codec.decode(&buf).unwrap(), value);
+        buf
+    }
+
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl<'a> Visitor<'a> for Recorder {
+        fn scalar(&mut self, value: Value<'a>) -> Result<(), CodecError> {
+            self.0.push(format!("{value:?}"));
+            Ok(())
+        }
+        fn null(&mut self) -> Result<(), CodecError> {
+            self.0.push("null".into());
+            Ok(())
+        }
+        fn start(&mut self, composite: Composite, len: usize) -> Result<(), CodecError> {
+            self.0.push(format!("start {composite:?} {len}"));
+            Ok(())
+        }
+        fn field(&mut self, index: usize, name: &str) -> Result<(), CodecError> {
+            self.0.push(format!("field {index} {name}"));
+            Ok(())
+        }
+        fn end(&mut self) -> Result<(), CodecError> {
+            self.0.push("end".into());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_build_scalar() {
+        let buf = concat_bytes(&[
+            b"\0\0\0 \x03\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\x01\0\0\0\x08std::str\x01\0\0",
+        ]);
+        let root = Uuid::parse_str("00000000-0000-0000-0000-000000000101").unwrap();
+        let codec = Codec::build(&parse_descriptor(root, &buf).unwrap()).unwrap();
+        assert_eq!(codec, Codec::Scalar(ScalarType::Str));
+        assert_eq!(roundtrip(&codec, Value::Str("hello")), b"hello");
+        assert!(matches!(codec.decode(b"\xff"), Err(CodecError::Utf8(_))));
+    }
+
+    #[test]
+    fn test_array_tuple() {
+        // <array<tuple<int16, str>>>
+        let buf = concat_bytes(&[
+            b"\0\0\0\"\x03\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\x03\0\0\0\nstd::int16\x01\0\0\0\0\0 \x03\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\x01\0\0\0\x08",
+            b"std::str\x01\0\0\0\0\0\x37\x04\xee\x8d\xb7.\x13\xb0Z\xf1\xaa\x96T\xf6>\x96q\xe8\0\0\0\x19",
+            b"tuple<std|int16, std|str>\0\0\0\0\x02\0\0\0\x01\0\0\0B\x06\x17\x83\xb0(F\xd0X\x98\xb7\x0c\x1cu\xcd\xa5\x1b\xef\0\0\0\"",
+            b"array<tuple<std||int16, std||str>>\0\0\0\0\x02\0\x01\xff\xff\xff\xff",
+        ]);
+        let root = Uuid::parse_str("1783b028-46d0-5898-b70c-1c75cda51bef").unwrap();
+        let codec = Codec::build(&parse_descriptor(root, &buf).unwrap()).unwrap();
+        assert_eq!(
+            codec,
+            Codec::Array(Box::new(Codec::Tuple(vec![
+                Codec::Scalar(ScalarType::Int16),
+                Codec::Scalar(ScalarType::Str),
+            ])))
+        );
+
+        let value = Value::Array(vec![Value::Tuple(vec![Value::Int16(1), Value::Str("a")])]);
+        let data = roundtrip(&codec, value);
+        assert_eq!(
+            data,
+            concat_bytes(&[
+                b"\0\0\0\x01\0\0\0\0\0\0\0\0\0\0\0\x01\0\0\0\x01",
+                b"\0\0\0\x17\0\0\0\x02",
+                b"\0\0\0\0\0\0\0\x02\0\x01",
+                b"\0\0\0\0\0\0\0\x01a",
+            ])
+        );
+
+        let mut recorder = Recorder::default();
+        codec.visit(&data, &mut recorder).unwrap();
+        assert_eq!(
+            recorder.0,
+            [
+                "start Array 1",
+                "start Tuple 2",
+                "Int16(1)",
+                "Str(\"a\")",
+                "end",
+                "end"
+            ]
+        );
+
+        // Empty arrays still carry a single dimension
+        assert_eq!(
+            roundtrip(&codec, Value::Array(vec![])),
+            b"\0\0\0\x01\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01"
+        );
+    }
+
+    #[test]
+    fn test_free_object() {
+        let buf = concat_bytes(&[
+            b"\0\0\0 \x03\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\x01\0\0\0\x08std::str\x01\0\0\0\0\0,\x01\xb5",
+            b"\0\x7f\x19mPR\xcc\xa1\0\xcaN,B\xa4\x7f\x01\0\0\0\x01\0\0\0\0A\0\0\0\tmy_string\0\0\0\0",
+        ]);
+        let root = Uuid::parse_str("b5007f19-6d50-52cc-a100-ca4e2c42a47f").unwrap();
+        let codec = Codec::build(&parse_descriptor(root, &buf).unwrap()).unwrap();
+        let Codec::Object(fields) = &codec else {
+            panic!("expected object codec: {codec:?}");
+        };
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].name, "my_string");
+        assert_eq!(fields[0].codec, Codec::Scalar(ScalarType::Str));
+
+        let data = roundtrip(&codec, Value::Object(vec![Some(Value::Str("x"))]));
+        assert_eq!(data, b"\0\0\0\x01\0\0\0\0\0\0\0\x01x");
+        let data = roundtrip(&codec, Value::Object(vec![None]));
+        assert_eq!(data, b"\0\0\0\x01\0\0\0\0\xff\xff\xff\xff");
+
+        let mut recorder = Recorder::default();
+        codec.visit(&data, &mut recorder).unwrap();
+        assert_eq!(
+            recorder.0,
+            ["start Object 1", "field 0 my_string", "null", "end"]
+        );
+
+        assert_eq!(
+            codec.decode(b"\0\0\0\x02\0\0\0\0\xff\xff\xff\xff"),
+            Err(CodecError::CountMismatch {
+                expected: 1,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            codec.decode(b"\0\0\0\x01\0\0\0\0\xff\xff\xff\xff\0"),
+            Err(CodecError::ExtraData)
+        );
+    }
+
+    #[test]
+    fn test_multirange() {
+        let buf = concat_bytes(&[
+            b"\0\0\0\"\x03\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\x05\0\0\0\nstd::int64\x01\0\0\0\0\0/\x0c\x8bM1S",
+            b"\xae\xe2\\\xc4\xae\xc1\xf1^ 3\x99B\0\0\0\x15multirange<std|int64>\x01\0\0\0\0",
+        ]);
+        let root = Uuid::parse_str("8b4d3153-aee2-5cc4-aec1-f15e20339942").unwrap();
+        let codec = Codec::build(&parse_descriptor(root, &buf).unwrap()).unwrap();
+        assert_eq!(
+            codec,
+            Codec::MultiRange(Box::new(Codec::Range(Box::new(Codec::Scalar(
+                ScalarType::Int64
+            )))))
+        );
+
+        let value = Value::MultiRange(vec![
+            Range {
+                lower: Some(Box::new(Value::Int64(1))),
+                upper: Some(Box::new(Value::Int64(5))),
+                inc_lower: true,
+                inc_upper: false,
+                empty: false,
+            },
+            Range {
+                lower: Some(Box::new(Value::Int64(10))),
+                upper: None,
+                inc_lower: true,
+                inc_upper: false,
+                empty: false,
+            },
+        ]);
+        let data = roundtrip(&codec, value);
+        assert_eq!(
+            data,
+            concat_bytes(&[
+                b"\0\0\0\x02",
+                b"\0\0\0\x19\x02",
+                b"\0\0\0\x08\0\0\0\0\0\0\0\x01",
+                b"\0\0\0\x08\0\0\0\0\0\0\0\x05",
+                b"\0\0\0\x0d\x12",
+                b"\0\0\0\x08\0\0\0\0\0\0\0\x0a",
+            ])
+        );
+
+        let empty = Value::MultiRange(vec![Range {
+            lower: None,
+            upper: None,
+            inc_lower: false,
+            inc_upper: false,
+            empty: true,
+        }]);
+        assert_eq!(roundtrip(&codec, empty), b"\0\0\0\x01\0\0\0\x01\x01");
+    }
+
+    #[test]
+    fn test_input_shape() {
+        let buf = concat_bytes(&[
+            b"\0\0\0\"\x03\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\x03\0\0\0\nstd::int16\x01\0\0\0\0\0,\x04\x8b\xbb",
+            b"<\xa0Z\x91X?\x92\xc9\x86\x1f#\xfcX\xff\0\0\0\x10tuple<std|int16>\0\0\0\0\x01\0\0\0\0\08\x06\x8e",
+            b"\x10Vcy\xd8Ts\x94$\x0c\x0c\x13\x07R\"\0\0\0\x18array<tuple<std||int16>>\0\0\0\0\x01\0\x01\xff\xff\xff\xff",
+            b"\0\0\0$\x01g+\xd2\xbf\x05#W*\x9fz8\xf6\xe4:\x07\xa6\x01\0\0\0\x01\0\0\0\0A\0\0\0\x010\0\x02\0\0",
+        ]);
+        let root = Uuid::parse_str("672bd2bf-0523-572a-9f7a-38f6e43a07a6").unwrap();
+        let codec = Codec::build(&parse_descriptor(root, &buf).unwrap()).unwrap();
+        assert!(matches!(&codec, Codec::Object(fields) if fields[0].name == "0"));
+
+        let input = Codec::Input(vec![
+            Field {
+                name: "a".into(),
+                flags: 0,
+                cardinality: Some(Cardinality::AtMostOne),
+                codec: Codec::Scalar(ScalarType::Int32),
+            },
+            Field {
+                name: "b".into(),
+                flags: 0,
+                cardinality: Some(Cardinality::AtMostOne),
+                codec: Codec::Scalar(ScalarType::Bool),
+            },
+        ]);
+        let data = roundtrip(&input, Value::SparseObject(vec![None, Some(None)]));
+        assert_eq!(data, b"\0\0\0\x01\0\0\0\x01\xff\xff\xff\xff");
+        let data = roundtrip(
+            &input,
+            Value::SparseObject(vec![Some(Some(Value::Int32(7))), None]),
+        );
+        assert_eq!(data, b"\0\0\0\x01\0\0\0\0\0\0\0\x04\0\0\0\x07");
+        assert_eq!(
+            input.decode(b"\0\0\0\x01\0\0\0\x02\xff\xff\xff\xff"),
+            Err(CodecError::InvalidIndex(2))
+        );
+    }
+
+    #[test]
+    fn test_set_of_arrays() {
+        let codec = Codec::Set(Box::new(Codec::Array(Box::new(Codec::Scalar(
+            ScalarType::Int16,
+        )))));
+        assert_eq!(roundtrip(&codec, Value::Set(vec![])), [0; 12]);
+        let data = roundtrip(
+            &codec,
+            Value::Set(vec![Value::Array(vec![Value::Int16(3)])]),
+        );
+        assert_eq!(
+            data,
+            concat_bytes(&[
+                b"\0\0\0\x01\0\0\0\0\0\0\0\0\0\0\0\x01\0\0\0\x01",
+                b"\0\0\0\x26\0\0\0\x01\0\0\0\0\0\0\0\x1a",
+                b"\0\0\0\x01\0\0\0\0\0\0\0\0\0\0\0\x01\0\0\0\x01",
+                b"\0\0\0\x02\0\x03",
+            ])
+        );
+    }
+
+    #[test]
+    fn test_array_length_from_data() {
+        // An array header claiming u32::MAX elements without any data
+        let codec = Codec::Array(Box::new(Codec::Scalar(ScalarType::Int16)));
+        let data = b"\0\0\0\x01\0\0\0\0\0\0\0\0\xff\xff\xff\xff\0\0\0\x01";
+        assert!(codec.decode(data).is_err());
+    }
+
+    #[test]
+    fn test_scalars() {
+        let decimal = Numeric {
+            negative: true,
+            weight: 0,
+            decimal_digits: 2,
+            digits: vec![12, 3400],
+        };
+        assert_eq!(
+            roundtrip(&Codec::Scalar(ScalarType::Decimal), Value::Decimal(decimal)),
+            b"\0\x02\0\0\x40\0\0\x02\0\x0c\x0d\x48"
+        );
+        assert_eq!(
+            roundtrip(&Codec::Scalar(ScalarType::Json), Value::Json("{}")),
+            b"\x01{}"
+        );
+        assert_eq!(
+            roundtrip(&Codec::Scalar(ScalarType::PgJson), Value::Json("[]")),
+            b"[]"
+        );
+        assert_eq!(
+            roundtrip(
+                &Codec::Scalar(ScalarType::Vector),
+                Value::Vector(vec![1.0, 2.0])
+            ),
+            b"\0\x02\0\0\x3f\x80\0\0\x40\0\0\0"
+        );
+        assert_eq!(
+            roundtrip(&Codec::Scalar(ScalarType::Duration), Value::Duration(1)),
+            b"\0\0\0\0\0\0\0\x01\0\0\0\0\0\0\0\0"
+        );
+        assert!(Codec::Scalar(ScalarType::Bool).decode(b"\x02").is_err());
+        assert_eq!(
+            Codec::Scalar(ScalarType::Int32).encode(&Value::Int64(1), &mut Vec::new()),
+            Err(CodecError::ValueMismatch {
+                expected: "scalar",
+                found: "int64"
+            })
+        );
+    }
+
+    #[test]
+    fn test_enum() {
+        let codec = Codec::Enum(vec!["Red".into(), "Green".into()]);
+        assert_eq!(roundtrip(&codec, Value::Enum("Green")), b"Green");
+        assert_eq!(
+            codec.decode(b"Blue"),
+            Err(CodecError::UnknownEnumMember("Blue".into()))
+        );
+    }
+
+    #[test]
+    fn test_sql_row() {
+        let codec = Codec::SqlRow(vec![
+            Field {
+                name: "id".into(),
+                flags: 0,
+                cardinality: None,
+                codec: Codec::Scalar(ScalarType::Int64),
+            },
+            Field {
+                name: "name".into(),
+                flags: 0,
+                cardinality: None,
+                codec: Codec::Scalar(ScalarType::Str),
+            },
+        ]);
+        let data = roundtrip(&codec, Value::SqlRow(vec![Some(Value::Int64(1)), None]));
+        assert_eq!(
+            data,
+            b"\0\0\0\x02\0\0\0\0\0\0\0\x08\0\0\0\0\0\0\0\x01\0\0\0\0\xff\xff\xff\xff"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::attrib::{ContainerAttrs, FieldAttrs};
+
+pub fn derive(item: &syn::Item) -> syn::Result<TokenStream> {
+    match item {
+        syn::Item::Struct(s) => derive_newtype(s),
+        syn::Item::Enum(e) => derive_enum(e),
+        _ => Err(syn::Error::new_spanned(
+            item,
+            "can only derive QueryArg for structs and enums",
+        )),
+    }
+}
+
+/// A single-field struct is encoded exactly as the wrapped type.
+fn derive_newtype(s: &syn::ItemStruct) -> syn::Result<TokenStream> {
+    let attrs = ContainerAttrs::from_syn(&s.attrs)?;
+    let gel_protocol = attrs.gel_protocol_path();
+    let name = &s.ident;
+    let (impl_generics, ty_generics, where_clause) = s.generics.split_for_impl();
+    let (field, inner) = match &s.fields {
+        syn::Fields::Unnamed(f) if f.unnamed.len() == 1 => (quote!(0), f.unnamed[0].ty.clone()),
+        syn::Fields::Named(f) if f.named.len() == 1 => {
+            let ident = &f.named[0].ident;
+            (quote!(#ident), f.named[0].ty.clone())
+        }
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &s.fields,
+                "QueryArg can only be derived for structs with a single field",
+            ));
+        }
+    };
+    let expanded = quote! {
+        impl #impl_generics #gel_protocol::query_arg::ScalarArg
+            for #name #ty_generics #where_clause {
+            fn encode(&self, encoder: &mut #gel_protocol::query_arg::Encoder)
+                -> ::std::result::Result<(), #gel_protocol::gel_errors::Error>
+            {
+                #gel_protocol::query_arg::ScalarArg::encode(&self.#field, encoder)
+            }
+            fn check_descriptor(
+                ctx: &#gel_protocol::query_arg::DescriptorContext,
+                pos: #gel_protocol::descriptors::TypePos,
+            ) -> ::std::result::Result<(), #gel_protocol::gel_errors::Error> {
+                <#inner as #gel_protocol::query_arg::ScalarArg>::check_descriptor(ctx, pos)
+            }
+            fn check_value(
+                &self,
+                ctx: &#gel_protocol::query_arg::DescriptorContext,
+                pos: #gel_protocol::descriptors::TypePos,
+            ) -> ::std::result::Result<(), #gel_protocol::gel_errors::Error> {
+                #gel_protocol::query_arg::ScalarArg::check_value(&self.#field, ctx, pos)
+            }
+            fn to_value(&self)
+                -> ::std::result::Result<#gel_protocol::value::Value, #gel_protocol::gel_errors::Error>
+            {
+                #gel_protocol::query_arg::ScalarArg::to_value(&self.#field)
+            }
+        }
+    };
+    Ok(expanded)
+}
+
+/// Enums with unit variants are encoded as the enum label.
+fn derive_enum(e: &syn::ItemEnum) -> syn::Result<TokenStream> {
+    let attrs = ContainerAttrs::from_syn(&e.attrs)?;
+    let gel_protocol = attrs.gel_protocol_path();
+    let type_name = &e.ident;
+    let (impl_generics, ty_generics, where_clause) = e.generics.split_for_impl();
+    let branches = e
+        .variants
+        .iter()
+        .map(|v| match v.fields {
+            syn::Fields::Unit => {
+                let attrs = FieldAttrs::from_syn(&v.attrs)?;
+                let name = &v.ident;
+                let label = attrs
+                    .rename
+                    .unwrap_or_else(|| syn::LitStr::new(&name.to_string(), name.span()));
+                Ok(quote!(#type_name::#name => #label))
+            }
+            _ => Err(syn::Error::new_spanned(
+                &v.fields,
+                "fields are not allowed in enum variants",
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let label = quote! {
+        let label: &str = match self {
+            #(#branches,)*
+        };
+    };
+    let expanded = quote! {
+        impl #impl_generics #gel_protocol::query_arg::ScalarArg
+            for #type_name #ty_generics #where_clause {
+            fn encode(&self, encoder: &mut #gel_protocol::query_arg::Encoder)
+                -> ::std::result::Result<(), #gel_protocol::gel_errors::Error>
+            {
+                #label
+                #gel_protocol::query_arg::ScalarArg::encode(&label, encoder)
+            }
+            fn check_descriptor(
+                ctx: &#gel_protocol::query_arg::DescriptorContext,
+                pos: #gel_protocol::descriptors::TypePos,
+            ) -> ::std::result::Result<(), #gel_protocol::gel_errors::Error> {
+                use #gel_protocol::descriptors::Descriptor::Enumeration;
+                let desc = ctx.get(pos)?;
+                match desc {
+                    Enumeration(_) => ::std::result::Result::Ok(()),
+                    _ => ::std::result::Result::Err(ctx.wrong_type(desc, "enum")),
+                }
+            }
+            fn check_value(
+                &self,
+                ctx: &#gel_protocol::query_arg::DescriptorContext,
+                pos: #gel_protocol::descriptors::TypePos,
+            ) -> ::std::result::Result<(), #gel_protocol::gel_errors::Error> {
+                #label
+                ctx.check_enum_member(pos, label)
+            }
+            fn to_value(&self)
+                -> ::std::result::Result<#gel_protocol::value::Value, #gel_protocol::gel_errors::Error>
+            {
+                #label
+                ::std::result::Result::Ok(#gel_protocol::value::Value::Enum(label.into()))
+            }
+        }
+    };
+    Ok(expanded)
+}
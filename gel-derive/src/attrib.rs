@@ -3,12 +3,16 @@ use syn::punctuated::Punctuated;
 
 enum FieldAttr {
     Json,
+    Link,
     Rename(syn::LitStr),
+    Cast(syn::LitStr),
 }
 
 enum ContainerAttr {
     Json,
+    Shape,
     CratePath(syn::Path),
+    TypeName(syn::LitStr),
 }
 
 struct FieldAttrList(pub Punctuated<FieldAttr, syn::Token![,]>);
@@ -16,12 +20,16 @@ struct ContainerAttrList(pub Punctuated<ContainerAttr, syn::Token![,]>);
 
 pub struct FieldAttrs {
     pub json: bool,
+    pub link: bool,
     pub rename: Option<syn::LitStr>,
+    pub cast: Option<syn::LitStr>,
 }
 
 pub struct ContainerAttrs {
     pub json: bool,
+    pub shape: bool,
     pub crate_path: Option<syn::Path>,
+    pub type_name: Option<syn::LitStr>,
 }
 
 impl ContainerAttrs {
@@ -34,8 +42,12 @@ impl ContainerAttrs {
 
 mod kw {
     syn::custom_keyword!(json);
+    syn::custom_keyword!(link);
+    syn::custom_keyword!(shape);
     syn::custom_keyword!(crate_path);
     syn::custom_keyword!(rename);
+    syn::custom_keyword!(cast);
+    syn::custom_keyword!(type_name);
 }
 
 impl Parse for FieldAttr {
@@ -44,10 +56,17 @@ impl Parse for FieldAttr {
         if lookahead.peek(kw::json) {
             let _ident: syn::Ident = input.parse()?;
             Ok(FieldAttr::Json)
+        } else if lookahead.peek(kw::link) {
+            input.parse::<kw::link>()?;
+            Ok(FieldAttr::Link)
         } else if lookahead.peek(kw::rename) {
             input.parse::<kw::rename>()?;
             input.parse::<syn::Token![=]>()?;
             Ok(FieldAttr::Rename(input.parse()?))
+        } else if lookahead.peek(kw::cast) {
+            input.parse::<kw::cast>()?;
+            input.parse::<syn::Token![=]>()?;
+            Ok(FieldAttr::Cast(input.parse()?))
         } else {
             Err(lookahead.error())
         }
@@ -60,10 +79,17 @@ impl Parse for ContainerAttr {
         if lookahead.peek(kw::json) {
             let _ident: syn::Ident = input.parse()?;
             Ok(ContainerAttr::Json)
+        } else if lookahead.peek(kw::shape) {
+            input.parse::<kw::shape>()?;
+            Ok(ContainerAttr::Shape)
         } else if lookahead.peek(kw::crate_path) {
             input.parse::<kw::crate_path>()?;
             input.parse::<syn::Token![=]>()?;
             Ok(ContainerAttr::CratePath(input.parse()?))
+        } else if lookahead.peek(kw::type_name) {
+            input.parse::<kw::type_name>()?;
+            input.parse::<syn::Token![=]>()?;
+            Ok(ContainerAttr::TypeName(input.parse()?))
         } else {
             Err(lookahead.error())
         }
@@ -86,7 +112,9 @@ impl FieldAttrs {
     fn default() -> FieldAttrs {
         FieldAttrs {
             json: false,
+            link: false,
             rename: None,
+            cast: None,
         }
     }
     pub fn from_syn(attrs: &[syn::Attribute]) -> syn::Result<FieldAttrs> {
@@ -97,6 +125,16 @@ impl FieldAttrs {
                 for item in chunk.0 {
                     match item {
                         FieldAttr::Json => res.json = true,
+                        FieldAttr::Link => res.link = true,
+                        FieldAttr::Cast(cast) => {
+                            if res.cast.is_some() {
+                                return Err(syn::Error::new_spanned(
+                                    cast,
+                                    "duplicate gel attribute `cast`",
+                                ));
+                            }
+                            res.cast = Some(cast)
+                        }
                         FieldAttr::Rename(name) => {
                             if res.rename.is_some() {
                                 return Err(syn::Error::new_spanned(
@@ -118,7 +156,9 @@ impl ContainerAttrs {
     fn default() -> ContainerAttrs {
         ContainerAttrs {
             json: false,
+            shape: false,
             crate_path: None,
+            type_name: None,
         }
     }
     pub fn from_syn(attrs: &[syn::Attribute]) -> syn::Result<ContainerAttrs> {
@@ -129,6 +169,16 @@ impl ContainerAttrs {
                 for item in chunk.0 {
                     match item {
                         ContainerAttr::Json => res.json = true,
+                        ContainerAttr::Shape => res.shape = true,
+                        ContainerAttr::TypeName(name) => {
+                            if res.type_name.is_some() {
+                                return Err(syn::Error::new_spanned(
+                                    name,
+                                    "duplicate gel attribute `type_name`",
+                                ));
+                            }
+                            res.type_name = Some(name)
+                        }
                         ContainerAttr::CratePath(path) => {
                             if res.crate_path.is_some() {
                                 return Err(syn::Error::new_spanned(
@@ -0,0 +1,127 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::attrib::ContainerAttrs;
+use crate::shape::named_fields;
+
+pub fn derive(s: &syn::ItemStruct) -> syn::Result<TokenStream> {
+    let attrs = ContainerAttrs::from_syn(&s.attrs)?;
+    let gel_protocol = attrs.gel_protocol_path();
+    let name = &s.ident;
+    let (impl_generics, ty_generics, where_clause) = s.generics.split_for_impl();
+    let type_name = attrs
+        .type_name
+        .as_ref()
+        .map(|n| n.value())
+        .unwrap_or_else(|| name.to_string());
+    let fields = named_fields(s)?;
+
+    let mut elements = Vec::with_capacity(fields.len());
+    let mut args = Vec::with_capacity(fields.len());
+    for field in &fields {
+        if field.attrs.link || field.attrs.json {
+            return Err(syn::Error::new_spanned(
+                &field.name,
+                "links and JSON fields are not supported by Insertable",
+            ));
+        }
+        let cast = match &field.attrs.cast {
+            Some(cast) => cast.value(),
+            None => edgeql_cast(&field.ty).ok_or_else(|| {
+                syn::Error::new_spanned(
+                    &field.ty,
+                    "cannot infer EdgeQL type, use #[gel(cast = \"...\")]",
+                )
+            })?,
+        };
+        let str_name = field.str_name.value();
+        elements.push(format!("{str_name} := <{cast}>${str_name}"));
+        let ident = &field.name;
+        args.push(quote! {
+            (#str_name, #gel_protocol::shape::named_arg(&self.#ident)?)
+        });
+    }
+    let insert = if elements.is_empty() {
+        format!("insert {type_name}")
+    } else {
+        format!("insert {type_name} {{ {} }}", elements.join(", "))
+    };
+
+    let expanded = quote! {
+        impl #impl_generics #gel_protocol::shape::Insertable
+            for #name #ty_generics #where_clause {
+            const INSERT: &'static str = #insert;
+
+            fn insert_args(&self) -> ::std::result::Result<
+                ::std::collections::HashMap<&'static str, #gel_protocol::value_opt::ValueOpt>,
+                #gel_protocol::gel_errors::Error,
+            > {
+                ::std::result::Result::Ok(::std::collections::HashMap::from([
+                    #(#args),*
+                ]))
+            }
+        }
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The `insert` statement for this type.
+            pub const INSERT: &'static str =
+                <Self as #gel_protocol::shape::Insertable>::INSERT;
+        }
+    };
+    Ok(expanded)
+}
+
+/// EdgeQL type of the argument for well-known Rust types.
+fn edgeql_cast(ty: &syn::Type) -> Option<String> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    let last = path.path.segments.last()?;
+    let inner = || match &last.arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first()? {
+            syn::GenericArgument::Type(ty) => edgeql_cast(ty),
+            _ => None,
+        },
+        _ => None,
+    };
+    let name = match last.ident.to_string().as_str() {
+        "Option" => {
+            let inner = inner()?;
+            if inner.starts_with("optional ") {
+                return None;
+            }
+            return Some(format!("optional {inner}"));
+        }
+        "Vec" => {
+            let inner = inner()?;
+            if inner.starts_with("optional ") {
+                return None;
+            }
+            return Some(format!("array<{inner}>"));
+        }
+        "String" => "str",
+        "bool" => "bool",
+        "i16" => "int16",
+        "i32" => "int32",
+        "i64" => "int64",
+        "f32" => "float32",
+        "f64" => "float64",
+        "Uuid" => "uuid",
+        "Bytes" => "bytes",
+        "Json" => "json",
+        "BigInt" => "bigint",
+        "Decimal" | "BigDecimal" => "decimal",
+        "Datetime" | "SystemTime" | "DateTime" => "datetime",
+        "Duration" => "duration",
+        "LocalDatetime" | "NaiveDateTime" => "cal::local_datetime",
+        "LocalDate" | "NaiveDate" => "cal::local_date",
+        "LocalTime" | "NaiveTime" => "cal::local_time",
+        "RelativeDuration" => "cal::relative_duration",
+        "DateDuration" => "cal::date_duration",
+        "ConfigMemory" => "cfg::memory",
+        "Vector" => "ext::pgvector::vector",
+        "HalfVector" => "ext::pgvector::halfvec",
+        "SparseVector" => "ext::pgvector::sparsevec",
+        _ => return None,
+    };
+    Some(name.into())
+}
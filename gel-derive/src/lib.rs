@@ -48,8 +48,72 @@ struct User {
 }
 ```
 
+## Links
+
+The `#[gel(link)]` attribute marks a field holding another object (or an
+`Option` or `Vec` of them). It is only used for the shape, see below.
+
+# Newtypes
+
+A tuple struct with a single field is decoded the same way as the wrapped
+type:
+
+```rust
+# use gel_derive::Queryable;
+# use gel_protocol::model::Uuid;
+#[derive(Queryable)]
+struct UserId(Uuid);
+```
+
+# Query arguments
+
+The `QueryArg` derive allows the same newtypes and enums to be passed as
+query arguments. A single-field struct is encoded as its field, and an enum
+with unit variants is encoded as the label of the variant (which may be
+changed with `#[gel(rename = "...")]`):
+
+```rust
+# use gel_derive::QueryArg;
+# use gel_protocol::model::Uuid;
+#[derive(QueryArg)]
+struct UserId(Uuid);
+
+#[derive(QueryArg)]
+enum Status {
+    Open,
+    #[gel(rename = "closed")]
+    Closed,
+}
+```
+
 # Container attributes
 
+## Shape
+
+The `#[gel(shape)]` attribute adds a `SHAPE` constant with the shape that
+matches the fields of the structure, so it doesn't need to be kept in sync
+by hand. Shapes of `#[gel(link)]` fields are nested, which requires the
+linked type to have the attribute too.
+
+```rust
+# use gel_derive::Queryable;
+#[derive(Queryable)]
+#[gel(shape)]
+struct Friend {
+    name: String,
+}
+
+#[derive(Queryable)]
+#[gel(shape)]
+struct User {
+    first_name: String,
+    #[gel(link)]
+    friends: Vec<Friend>,
+}
+
+assert_eq!(User::SHAPE, "{ first_name, friends: { name } }");
+```
+
 ## JSON
 
 The `#[gel(json)]` attribute can be used to unpack the structure from
@@ -74,14 +138,53 @@ let query = "select <json>JsonData { field1, field2 };";
 let query_res: Vec<JsonData> = client.query(query, &()).await?;
 ```
 
+# Insert statements
+
+The `Insertable` derive adds an `INSERT` constant with an `insert` statement
+taking a named argument for each field, and an `insert_args` method
+returning the arguments. The type of the object defaults to the name of
+the structure and can be changed with `#[gel(type_name = "...")]`. Types of
+arguments are inferred for common scalars, other types need a
+`#[gel(cast = "...")]` attribute:
+
+```rust
+# use gel_derive::{Insertable, QueryArg};
+#[derive(QueryArg)]
+enum Status {
+    Active,
+    Banned,
+}
+
+#[derive(Insertable)]
+#[gel(type_name = "default::User")]
+struct NewUser {
+    first_name: String,
+    age: Option<i32>,
+    #[gel(cast = "default::Status")]
+    status: Status,
+}
+
+assert_eq!(
+    NewUser::INSERT,
+    "insert default::User { first_name := <str>$first_name, \
+     age := <optional int32>$age, status := <default::Status>$status }",
+);
+```
+
+```rust,ignore
+client.execute(NewUser::INSERT, &user.insert_args()?).await?;
+```
+
 */
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use syn::parse_macro_input;
 
+mod args;
 mod attrib;
 mod enums;
+mod insert;
 mod json;
 mod shape;
 mod variables;
@@ -122,6 +225,24 @@ fn derive(item: &syn::Item) -> syn::Result<proc_macro2::TokenStream> {
     }
 }
 
+#[proc_macro_derive(QueryArg, attributes(gel))]
+pub fn query_arg(input: TokenStream) -> TokenStream {
+    let s = parse_macro_input!(input as syn::Item);
+    match args::derive(&s) {
+        Ok(stream) => stream.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+#[proc_macro_derive(Insertable, attributes(gel))]
+pub fn insertable(input: TokenStream) -> TokenStream {
+    let s = parse_macro_input!(input as syn::ItemStruct);
+    match insert::derive(&s) {
+        Ok(stream) => stream.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
 #[proc_macro_derive(GlobalsDelta, attributes(gel))]
 pub fn globals_delta(input: TokenStream) -> TokenStream {
     let s = parse_macro_input!(input as syn::ItemStruct);
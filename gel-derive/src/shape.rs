@@ -3,27 +3,15 @@ use quote::quote;
 
 use crate::attrib::{ContainerAttrs, FieldAttrs};
 
-struct Field {
-    name: syn::Ident,
-    str_name: syn::LitStr,
-    ty: syn::Type,
-    attrs: FieldAttrs,
+pub struct Field {
+    pub name: syn::Ident,
+    pub str_name: syn::LitStr,
+    pub ty: syn::Type,
+    pub attrs: FieldAttrs,
 }
 
-pub fn derive_struct(
-    s: &syn::ItemStruct,
-    container_attrs: &ContainerAttrs,
-) -> syn::Result<TokenStream> {
-    let gel_protocol = container_attrs.gel_protocol_path();
-    let name = &s.ident;
-    let decoder = syn::Ident::new("decoder", Span::mixed_site());
-    let buf = syn::Ident::new("buf", Span::mixed_site());
-    let nfields = syn::Ident::new("nfields", Span::mixed_site());
-    let elements = syn::Ident::new("elements", Span::mixed_site());
-    let order = syn::Ident::new("order", Span::mixed_site());
-    let sub_args = syn::Ident::new("sub_args", Span::mixed_site());
-    let (impl_generics, ty_generics, _) = s.generics.split_for_impl();
-    let fields = match &s.fields {
+pub fn named_fields(s: &syn::ItemStruct) -> syn::Result<Vec<Field>> {
+    match &s.fields {
         syn::Fields::Named(named) => {
             let mut fields = Vec::with_capacity(named.named.len());
             for field in &named.named {
@@ -41,15 +29,34 @@ pub fn derive_struct(
                     attrs,
                 });
             }
-            fields
+            Ok(fields)
         }
-        _ => {
-            return Err(syn::Error::new_spanned(
-                &s.fields,
-                "only named fields are supported",
-            ));
+        _ => Err(syn::Error::new_spanned(
+            &s.fields,
+            "only named fields and single-field tuple structs are supported",
+        )),
+    }
+}
+
+pub fn derive_struct(
+    s: &syn::ItemStruct,
+    container_attrs: &ContainerAttrs,
+) -> syn::Result<TokenStream> {
+    let gel_protocol = container_attrs.gel_protocol_path();
+    let name = &s.ident;
+    let decoder = syn::Ident::new("decoder", Span::mixed_site());
+    let buf = syn::Ident::new("buf", Span::mixed_site());
+    let nfields = syn::Ident::new("nfields", Span::mixed_site());
+    let elements = syn::Ident::new("elements", Span::mixed_site());
+    let order = syn::Ident::new("order", Span::mixed_site());
+    let sub_args = syn::Ident::new("sub_args", Span::mixed_site());
+    let (impl_generics, ty_generics, _) = s.generics.split_for_impl();
+    if let syn::Fields::Unnamed(unnamed) = &s.fields {
+        if unnamed.unnamed.len() == 1 {
+            return derive_newtype(s, &unnamed.unnamed[0].ty, container_attrs);
         }
-    };
+    }
+    let fields = named_fields(s)?;
     let fieldname = fields.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
     let base_fields = fields.len();
     let type_id_block = Some(quote! {
@@ -178,6 +185,11 @@ pub fn derive_struct(
         .collect::<TokenStream>();
 
     let field_count = fields.len();
+    let shape = if container_attrs.shape {
+        Some(derive_shape(s, &fields, container_attrs))
+    } else {
+        None
+    };
 
     let expanded = quote! {
         impl #impl_generics #gel_protocol::queryable::Queryable
@@ -243,6 +255,108 @@ pub fn derive_struct(
                 ::std::result::Result::Ok((order, (#construct_sub_args)))
             }
         }
+        #shape
+    };
+    Ok(expanded)
+}
+
+/// The `Shape` implementation and the `SHAPE` constant for `#[gel(shape)]`.
+///
+/// Shapes of links are taken from the `Shape` implementation of the field
+/// type, so they have to be concatenated at compile time.
+fn derive_shape(
+    s: &syn::ItemStruct,
+    fields: &[Field],
+    container_attrs: &ContainerAttrs,
+) -> TokenStream {
+    let gel_protocol = container_attrs.gel_protocol_path();
+    let name = &s.ident;
+    let (impl_generics, ty_generics, where_clause) = s.generics.split_for_impl();
+
+    let mut parts = Vec::new();
+    let mut text = String::from("{");
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            text.push(',');
+        }
+        text.push(' ');
+        text.push_str(&field.str_name.value());
+        if field.attrs.link {
+            text.push_str(": ");
+            parts.push(quote!(#text));
+            text.clear();
+            let ty = &field.ty;
+            parts.push(quote!(<#ty as #gel_protocol::shape::Shape>::SHAPE));
+        }
+    }
+    text.push_str(" }");
+    let value = if parts.is_empty() {
+        quote!(#text)
+    } else {
+        parts.push(quote!(#text));
+        quote! {{
+            const PARTS: &[&str] = &[#(#parts),*];
+            const BYTES: [u8; #gel_protocol::shape::concat_len(PARTS)] =
+                #gel_protocol::shape::concat(PARTS);
+            match ::std::str::from_utf8(&BYTES) {
+                ::std::result::Result::Ok(shape) => shape,
+                ::std::result::Result::Err(_) => ::std::panic!("shape is not valid UTF-8"),
+            }
+        }}
+    };
+    quote! {
+        impl #impl_generics #gel_protocol::shape::Shape
+            for #name #ty_generics #where_clause {
+            const SHAPE: &'static str = #value;
+        }
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Shape of the object, suitable for `select Type { ... }`.
+            pub const SHAPE: &'static str =
+                <Self as #gel_protocol::shape::Shape>::SHAPE;
+        }
+    }
+}
+
+/// Newtypes are decoded exactly as the wrapped type.
+fn derive_newtype(
+    s: &syn::ItemStruct,
+    inner: &syn::Type,
+    container_attrs: &ContainerAttrs,
+) -> syn::Result<TokenStream> {
+    let gel_protocol = container_attrs.gel_protocol_path();
+    let name = &s.ident;
+    let (impl_generics, ty_generics, where_clause) = s.generics.split_for_impl();
+    let expanded = quote! {
+        impl #impl_generics #gel_protocol::queryable::Queryable
+            for #name #ty_generics #where_clause {
+            type Args = <#inner as #gel_protocol::queryable::Queryable>::Args;
+
+            fn decode(
+                decoder: &#gel_protocol::queryable::Decoder,
+                args: &Self::Args,
+                buf: &[u8],
+            ) -> ::std::result::Result<Self, #gel_protocol::errors::DecodeError> {
+                <#inner as #gel_protocol::queryable::Queryable>::decode(decoder, args, buf)
+                    .map(#name)
+            }
+            fn decode_optional(
+                decoder: &#gel_protocol::queryable::Decoder,
+                args: &Self::Args,
+                buf: ::std::option::Option<&[u8]>,
+            ) -> ::std::result::Result<Self, #gel_protocol::errors::DecodeError> {
+                <#inner as #gel_protocol::queryable::Queryable>::decode_optional(
+                    decoder, args, buf,
+                )
+                .map(#name)
+            }
+            fn check_descriptor(
+                ctx: &#gel_protocol::queryable::DescriptorContext,
+                type_pos: #gel_protocol::descriptors::TypePos
+            ) -> ::std::result::Result<Self::Args, #gel_protocol::queryable::DescriptorMismatch>
+            {
+                <#inner as #gel_protocol::queryable::Queryable>::check_descriptor(ctx, type_pos)
+            }
+        }
     };
     Ok(expanded)
 }
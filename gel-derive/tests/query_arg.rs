@@ -0,0 +1,52 @@
+use gel_derive::{QueryArg, Queryable};
+use gel_protocol::model::Uuid;
+use gel_protocol::query_arg::ScalarArg;
+use gel_protocol::queryable::{Decoder, Queryable};
+use gel_protocol::value::Value;
+
+#[derive(QueryArg, Queryable, Debug, PartialEq)]
+struct UserId(Uuid);
+
+#[derive(QueryArg)]
+struct Email {
+    address: String,
+}
+
+#[derive(QueryArg, Debug, PartialEq)]
+enum Status {
+    Open,
+    #[gel(rename = "closed")]
+    Closed,
+}
+
+#[test]
+fn newtype_value() {
+    let id = Uuid::from_u128(0x1234);
+    assert_eq!(UserId(id).to_value().unwrap(), Value::Uuid(id));
+    let email = Email {
+        address: "user@example.com".into(),
+    };
+    assert_eq!(
+        email.to_value().unwrap(),
+        Value::Str("user@example.com".into())
+    );
+}
+
+#[test]
+fn newtype_decode() {
+    let dec = Decoder::default();
+    let id = Uuid::from_u128(0x1234);
+    assert_eq!(
+        UserId::decode(&dec, &(), id.as_bytes()).unwrap(),
+        UserId(id)
+    );
+}
+
+#[test]
+fn enum_value() {
+    assert_eq!(Status::Open.to_value().unwrap(), Value::Enum("Open".into()));
+    assert_eq!(
+        Status::Closed.to_value().unwrap(),
+        Value::Enum("closed".into())
+    );
+}
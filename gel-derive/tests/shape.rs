@@ -0,0 +1,75 @@
+use gel_derive::{Insertable, QueryArg, Queryable};
+use gel_protocol::model::Uuid;
+use gel_protocol::shape::{Insertable, Shape};
+use gel_protocol::value::Value;
+use gel_protocol::value_opt::ValueOpt;
+
+#[derive(Queryable)]
+#[gel(shape)]
+#[allow(dead_code)]
+struct Friend {
+    name: String,
+}
+
+#[derive(Queryable)]
+#[gel(shape)]
+#[allow(dead_code)]
+struct User {
+    id: Uuid,
+    #[gel(rename = "firstName")]
+    first_name: String,
+    #[gel(link)]
+    best_friend: Option<Friend>,
+    #[gel(link)]
+    friends: Vec<Friend>,
+}
+
+#[derive(QueryArg)]
+enum Status {
+    Active,
+}
+
+#[derive(Insertable)]
+#[gel(type_name = "default::Person")]
+struct NewUser {
+    first_name: String,
+    age: Option<i32>,
+    tags: Vec<String>,
+    #[gel(cast = "default::Status")]
+    status: Status,
+}
+
+#[test]
+fn shape() {
+    assert_eq!(Friend::SHAPE, "{ name }");
+    assert_eq!(
+        User::SHAPE,
+        "{ id, firstName, best_friend: { name }, friends: { name } }"
+    );
+    assert_eq!(<User as Shape>::SHAPE, User::SHAPE);
+}
+
+#[test]
+fn insert() {
+    assert_eq!(
+        NewUser::INSERT,
+        "insert default::Person { first_name := <str>$first_name, \
+         age := <optional int32>$age, tags := <array<str>>$tags, \
+         status := <default::Status>$status }"
+    );
+    let user = NewUser {
+        first_name: "John".into(),
+        age: None,
+        tags: vec!["admin".into()],
+        status: Status::Active,
+    };
+    let args = user.insert_args().unwrap();
+    assert_eq!(args.len(), 4);
+    assert_eq!(args["first_name"], ValueOpt::from("John"));
+    assert_eq!(args["age"], ValueOpt::from(None::<Value>));
+    assert_eq!(
+        args["tags"],
+        ValueOpt::from(Value::Array(vec![Value::Str("admin".into())]))
+    );
+    assert_eq!(args["status"], ValueOpt::from(Value::Enum("Active".into())));
+}
@@ -1,9 +1,17 @@
-use gel_dsn::{postgres::*, SystemEnvVars};
+use gel_dsn::{postgres::*, SystemEnvVars, SystemFileAccess, SystemUserProfile};
 
 fn main() {
     let dsn = std::env::args().nth(1).expect("No DSN provided");
 
-    let mut params = parse_postgres_dsn_env(&dsn, SystemEnvVars).unwrap();
+    let mut raw = parse_postgres_dsn(&dsn).unwrap();
+    for warning in raw
+        .apply_service(SystemEnvVars, SystemFileAccess, SystemUserProfile)
+        .unwrap()
+    {
+        eprintln!("Warning: {warning}");
+    }
+    raw.apply_env(SystemEnvVars).unwrap();
+    let mut params: ConnectionParameters = raw.try_into().unwrap();
     #[allow(deprecated)]
     let home = std::env::home_dir().unwrap();
     eprintln!("DSN: {dsn}\n----\n{params:#?}\n");
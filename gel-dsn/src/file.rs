@@ -91,6 +91,47 @@ pub trait FileAccess {
     }
 }
 
+impl<T> FileAccess for &T
+where
+    T: FileAccess,
+{
+    fn read(&self, path: &Path) -> Result<String, std::io::Error> {
+        (*self).read(path)
+    }
+
+    fn cwd(&self) -> Option<PathBuf> {
+        (*self).cwd()
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, std::io::Error> {
+        (*self).exists(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> Result<PathBuf, std::io::Error> {
+        (*self).canonicalize(path)
+    }
+
+    fn all_files(&self) -> Option<Vec<PathBuf>> {
+        (*self).all_files()
+    }
+
+    fn exists_dir(&self, path: &Path) -> Result<bool, std::io::Error> {
+        (*self).exists_dir(path)
+    }
+
+    fn list_dir(&self, path: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+        (*self).list_dir(path)
+    }
+
+    fn write(&self, path: &Path, content: &str) -> Result<(), std::io::Error> {
+        (*self).write(path, content)
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), std::io::Error> {
+        (*self).delete(path)
+    }
+}
+
 impl FileAccess for &[(&Path, &str)] {
     fn read(&self, path: &Path) -> Result<String, std::io::Error> {
         self.iter()
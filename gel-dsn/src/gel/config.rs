@@ -103,11 +103,18 @@ pub struct Config {
 
     pub tls_ca: Option<Vec<CertificateDer<'static>>>,
     pub tls_server_name: Option<String>,
+    /// Pinned SHA-256 fingerprints of the server certificate. When set, the
+    /// certificate is accepted only if it matches one of these, and the CA
+    /// and hostname are not verified.
+    pub tls_cert_fingerprint: Option<TlsCertFingerprint>,
     pub wait_until_available: Duration,
 
     pub connect_timeout: Duration,
     pub max_concurrency: Option<usize>,
     pub tcp_keepalive: TcpKeepalive,
+    pub transport: Transport,
+    /// The proxy to tunnel TCP connections through.
+    pub proxy: Option<gel_stream::Proxy>,
 
     pub cloud_certs: Option<CloudCerts>,
 
@@ -126,10 +133,13 @@ impl Default for Config {
             tls_security: TlsSecurity::Strict,
             tls_ca: None,
             tls_server_name: None,
+            tls_cert_fingerprint: None,
             wait_until_available: DEFAULT_WAIT,
             connect_timeout: DEFAULT_CONNECT_TIMEOUT,
             max_concurrency: None,
             tcp_keepalive: TcpKeepalive::Default,
+            transport: Transport::Binary,
+            proxy: None,
             cloud_certs: None,
             server_settings: HashMap::new(),
         }
@@ -140,6 +150,8 @@ impl Default for Config {
 pub enum CredentialsError {
     #[display("no TCP address")]
     NoTcpAddress,
+    #[display("{_0} cannot be represented as environment variables")]
+    NotExportable(#[error(not(source))] &'static str),
 }
 
 fn to_pem(certs: &[CertificateDer<'static>]) -> String {
@@ -273,6 +285,11 @@ impl Config {
                 .append_pair("tls_server_name", tls_server_name);
         }
 
+        if let Some(tls_cert_fingerprint) = &self.tls_cert_fingerprint {
+            url.query_pairs_mut()
+                .append_pair("tls_cert_fingerprint", &tls_cert_fingerprint.to_string());
+        }
+
         if self.wait_until_available != DEFAULT_WAIT {
             url.query_pairs_mut().append_pair(
                 "wait_until_available",
@@ -408,10 +425,168 @@ impl Config {
             tls_ca: self.tls_ca_pem(),
             tls_security: self.tls_security,
             tls_server_name: self.tls_server_name.clone(),
+            password_ref: None,
+            secret_key_ref: None,
             warnings: vec![],
         })
     }
 
+    /// Export the config as the minimal set of `GEL_*` environment variables
+    /// that builds an equal [`Config`], so that a resolved connection can be
+    /// passed to a child process.
+    ///
+    /// Secrets and the TLS CA are exported as values (`GEL_PASSWORD`,
+    /// `GEL_SECRET_KEY`, `GEL_TLS_CA`) so that no files are required. The name
+    /// of a local instance is not exported, as it refers to stored
+    /// credentials.
+    ///
+    /// An ambiguous database/branch or server settings can only be set by a
+    /// DSN, so `GEL_DSN` is used for the address in that case.
+    ///
+    /// Returns [`CredentialsError::NotExportable`] if an option without an
+    /// environment variable (the connect timeout, maximum concurrency or TCP
+    /// keepalive) is not the default.
+    pub fn to_env_vars(&self) -> Result<Vec<(&'static str, String)>, CredentialsError> {
+        if self.connect_timeout != DEFAULT_CONNECT_TIMEOUT {
+            return Err(CredentialsError::NotExportable("connect timeout"));
+        }
+        if self.max_concurrency.is_some() {
+            return Err(CredentialsError::NotExportable("maximum concurrency"));
+        }
+        if self.tcp_keepalive != TcpKeepalive::Default {
+            return Err(CredentialsError::NotExportable("TCP keepalive"));
+        }
+
+        let mut vars = vec![];
+
+        let cloud_instance = match &self.instance_name {
+            Some(instance @ InstanceName::Cloud(..)) => Some(instance),
+            _ => None,
+        };
+        let use_dsn =
+            matches!(self.db, DatabaseBranch::Ambiguous(_)) || !self.server_settings.is_empty();
+
+        let target = self
+            .host
+            .target_name()
+            .map_err(|_| CredentialsError::NoTcpAddress)?;
+        let (host, port) = target.tcp().ok_or(CredentialsError::NoTcpAddress)?;
+
+        if let Some(instance) = cloud_instance {
+            // The host is derived from the instance name and the secret key,
+            // and the instance can't be combined with an address.
+            if use_dsn || port != DEFAULT_PORT {
+                return Err(CredentialsError::NotExportable("cloud instance address"));
+            }
+            vars.push(("GEL_INSTANCE", instance.to_string()));
+        } else if use_dsn {
+            vars.push(("GEL_DSN", self.env_dsn(&host, port)?));
+        } else {
+            vars.push(("GEL_HOST", host.to_string()));
+            if port != DEFAULT_PORT {
+                vars.push(("GEL_PORT", port.to_string()));
+            }
+        }
+
+        match &self.db {
+            DatabaseBranch::Database(database) => vars.push(("GEL_DATABASE", database.clone())),
+            DatabaseBranch::Branch(branch) => vars.push(("GEL_BRANCH", branch.clone())),
+            DatabaseBranch::Ambiguous(_) | DatabaseBranch::Default => {}
+        }
+
+        if self.user != DEFAULT_USER {
+            vars.push(("GEL_USER", self.user.clone()));
+        }
+        match &self.authentication {
+            Authentication::Password(password) => vars.push(("GEL_PASSWORD", password.clone())),
+            Authentication::SecretKey(secret_key) => {
+                vars.push(("GEL_SECRET_KEY", secret_key.clone()))
+            }
+            Authentication::None => {}
+        }
+
+        let client_security = match self.client_security {
+            ClientSecurity::Default => None,
+            ClientSecurity::Strict => Some("strict"),
+            ClientSecurity::InsecureDevMode => Some("insecure_dev_mode"),
+        };
+        if let Some(client_security) = client_security {
+            vars.push(("GEL_CLIENT_SECURITY", client_security.to_string()));
+        }
+        // Only export the TLS security if it differs from what the client
+        // security and CA imply.
+        let implied_tls_security = match self.client_security {
+            ClientSecurity::Strict => TlsSecurity::Strict,
+            ClientSecurity::InsecureDevMode => TlsSecurity::Insecure,
+            ClientSecurity::Default if self.tls_ca.is_some() => TlsSecurity::NoHostVerification,
+            ClientSecurity::Default => TlsSecurity::Strict,
+        };
+        if self.tls_security != implied_tls_security {
+            vars.push(("GEL_CLIENT_TLS_SECURITY", self.tls_security.to_string()));
+        }
+        if let Some(tls_ca) = self.tls_ca_pem() {
+            vars.push(("GEL_TLS_CA", tls_ca));
+        }
+        if let Some(tls_server_name) = &self.tls_server_name {
+            vars.push(("GEL_TLS_SERVER_NAME", tls_server_name.clone()));
+        }
+        if let Some(tls_cert_fingerprint) = &self.tls_cert_fingerprint {
+            vars.push(("GEL_TLS_CERT_FINGERPRINT", tls_cert_fingerprint.to_string()));
+        }
+
+        if self.wait_until_available != DEFAULT_WAIT {
+            vars.push((
+                "GEL_WAIT_UNTIL_AVAILABLE",
+                format_duration(&self.wait_until_available),
+            ));
+        }
+        if self.transport != Transport::Binary {
+            vars.push(("GEL_CLIENT_TRANSPORT", self.transport.to_string()));
+        }
+        if let Some(proxy) = &self.proxy {
+            vars.push(("GEL_CLIENT_PROXY", proxy.to_url()));
+        }
+        if let Some(cloud_certs) = self.cloud_certs {
+            let cloud_certs = match cloud_certs {
+                CloudCerts::Staging => "staging",
+                CloudCerts::Local => "local",
+            };
+            vars.push(("_GEL_CLOUD_CERTS", cloud_certs.to_string()));
+        }
+
+        Ok(vars)
+    }
+
+    /// The DSN used by [`Config::to_env_vars`], which only contains the
+    /// address, the ambiguous database/branch and the server settings.
+    fn env_dsn(&self, host: &str, port: u16) -> Result<String, CredentialsError> {
+        let mut url = Url::parse("gel://").unwrap();
+        if let DatabaseBranch::Ambiguous(name) = &self.db {
+            // DSN paths are not percent-decoded
+            url.set_path(name);
+            if url.path().strip_prefix('/') != Some(name) {
+                return Err(CredentialsError::NotExportable("database/branch name"));
+            }
+        }
+
+        // The host is passed as a query parameter to support any host,
+        // including IPv6 addresses with a scope.
+        url.query_pairs_mut()
+            .append_pair("host", host)
+            .append_pair("port", &port.to_string());
+
+        let mut server_settings = self.server_settings.iter().collect::<Vec<_>>();
+        server_settings.sort();
+        for (key, value) in server_settings {
+            // These suffixes redirect to an environment variable or file
+            if key.ends_with("_env") || key.ends_with("_file") {
+                return Err(CredentialsError::NotExportable("server setting"));
+            }
+            url.query_pairs_mut().append_pair(key, value);
+        }
+        Ok(url.to_string())
+    }
+
     #[allow(clippy::field_reassign_with_default)]
     pub fn to_tls(&self) -> gel_stream::TlsParameters {
         use gel_stream::{TlsAlpn, TlsCert, TlsParameters, TlsServerCertVerify};
@@ -430,10 +605,12 @@ impl Config {
                 }
             }
         }
-        tls.server_cert_verify = match self.tls_security {
-            TlsSecurity::Insecure => TlsServerCertVerify::Insecure,
-            TlsSecurity::NoHostVerification => TlsServerCertVerify::IgnoreHostname,
-            TlsSecurity::Strict | TlsSecurity::Default => TlsServerCertVerify::VerifyFull,
+        tls.server_cert_verify = match (&self.tls_cert_fingerprint, self.tls_security) {
+            // A pinned certificate replaces CA and hostname verification.
+            (Some(fingerprints), _) => TlsServerCertVerify::Pinned(fingerprints.0.clone()),
+            (None, TlsSecurity::Insecure) => TlsServerCertVerify::Insecure,
+            (None, TlsSecurity::NoHostVerification) => TlsServerCertVerify::IgnoreHostname,
+            (None, TlsSecurity::Strict | TlsSecurity::Default) => TlsServerCertVerify::VerifyFull,
         };
         tls.alpn = TlsAlpn::new_str(&["edgedb-binary", "gel-binary"]);
         tls.sni_override = match &self.tls_server_name {
@@ -738,6 +915,67 @@ impl FromStr for TcpKeepalive {
     }
 }
 
+/// The transport used to send queries to the server.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    /// A persistent connection speaking the binary protocol.
+    #[default]
+    Binary,
+    /// Stateless HTTP(S) requests, one per query. Useful in environments
+    /// where connections cannot be kept open between requests.
+    Http,
+}
+
+impl FromStr for Transport {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "binary" => Ok(Transport::Binary),
+            "http" => Ok(Transport::Http),
+            _ => Err(ParseError::InvalidTransport),
+        }
+    }
+}
+
+impl fmt::Display for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transport::Binary => write!(f, "binary"),
+            Transport::Http => write!(f, "http"),
+        }
+    }
+}
+
+/// One or more SHA-256 fingerprints of the server certificate, written as
+/// hex with optional colons and separated by commas. Each fingerprint may be
+/// of the whole certificate or of its public key (SPKI).
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct TlsCertFingerprint(pub Vec<[u8; 32]>);
+
+impl FromStr for TlsCertFingerprint {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fingerprints = s
+            .split(',')
+            .map(|fingerprint| gel_stream::parse_fingerprint(fingerprint.trim()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ParseError::InvalidCertificateFingerprint)?;
+        Ok(TlsCertFingerprint(fingerprints))
+    }
+}
+
+impl fmt::Display for TlsCertFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, fingerprint) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            f.write_str(&gel_stream::format_fingerprint(fingerprint))?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(derive_more::Debug, Clone, PartialEq, Eq)]
 enum UnixPathInner {
     /// The selected port will be appended to the path.
@@ -960,4 +1198,99 @@ mod tests {
         let url = config.dsn_url().unwrap();
         assert_eq!(url, "gel:///main?user=user&password=%25%5B%5D%7B%7D");
     }
+
+    fn build_from_env(vars: Vec<(&'static str, String)>) -> Config {
+        let env = vars.into_iter().collect::<HashMap<_, _>>();
+        crate::gel::Builder::default()
+            .without_system()
+            .with_env_impl(env)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_to_env_vars() {
+        let config = Config::default();
+        let vars = config.to_env_vars().unwrap();
+        assert_eq!(vars, vec![("GEL_HOST", "localhost".to_string())]);
+        assert_eq!(build_from_env(vars), config);
+
+        let config = Config::default()
+            .with_host("::1", 1234)
+            .unwrap()
+            .with_branch("main")
+            .with_user("user")
+            .with_password("secret")
+            .with_tls_ca(CloudCerts::Local.certificates());
+        let vars = config.to_env_vars().unwrap();
+        assert_eq!(
+            vars.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            vec![
+                "GEL_HOST",
+                "GEL_PORT",
+                "GEL_BRANCH",
+                "GEL_USER",
+                "GEL_PASSWORD",
+                "GEL_CLIENT_TLS_SECURITY",
+                "GEL_TLS_CA"
+            ]
+        );
+        assert_eq!(build_from_env(vars), config);
+    }
+
+    #[test]
+    fn test_to_env_vars_dsn() {
+        let config = crate::gel::Builder::default()
+            .dsn("gel://example.com/db?tls_security=insecure&setting=value")
+            .without_system()
+            .build()
+            .unwrap();
+        assert_eq!(config.db, DatabaseBranch::Ambiguous("db".to_string()));
+        let vars = config.to_env_vars().unwrap();
+        assert_eq!(
+            vars,
+            vec![
+                (
+                    "GEL_DSN",
+                    "gel:///db?host=example.com&port=5656&setting=value".to_string()
+                ),
+                ("GEL_CLIENT_TLS_SECURITY", "insecure".to_string()),
+            ]
+        );
+        assert_eq!(build_from_env(vars), config);
+
+        let config = config.with_db(DatabaseBranch::Ambiguous("my db".to_string()));
+        assert_eq!(
+            config.to_env_vars(),
+            Err(CredentialsError::NotExportable("database/branch name"))
+        );
+    }
+
+    #[test]
+    fn test_to_env_vars_not_exportable() {
+        let config = Config {
+            connect_timeout: Duration::from_secs(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.to_env_vars(),
+            Err(CredentialsError::NotExportable("connect timeout"))
+        );
+        let config = Config {
+            max_concurrency: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.to_env_vars(),
+            Err(CredentialsError::NotExportable("maximum concurrency"))
+        );
+        let config = Config {
+            tcp_keepalive: TcpKeepalive::Disabled,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.to_env_vars(),
+            Err(CredentialsError::NotExportable("TCP keepalive"))
+        );
+    }
 }
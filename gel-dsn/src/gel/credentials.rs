@@ -3,8 +3,8 @@ use std::{num::NonZeroU16, str::FromStr};
 use serde::{Deserialize, Serialize};
 
 use super::{
-    error::*, Param, Params, TlsSecurity, DEFAULT_BRANCH_NAME_CONNECT, DEFAULT_DATABASE_NAME,
-    DEFAULT_HOST, DEFAULT_PORT,
+    context_trace, error::*, BuildContext, Param, Params, TlsSecurity, DEFAULT_BRANCH_NAME_CONNECT,
+    DEFAULT_DATABASE_NAME, DEFAULT_HOST, DEFAULT_PORT,
 };
 
 /// An opaque type representing a credentials file.
@@ -12,6 +12,7 @@ use super::{
 /// Use [`std::str::FromStr`] to parse a credentials file from a string.
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
+#[non_exhaustive]
 pub struct CredentialsFile {
     pub user: Option<String>,
     pub host: Option<String>,
@@ -24,6 +25,14 @@ pub struct CredentialsFile {
     #[serde(default)]
     pub tls_security: TlsSecurity,
     pub tls_server_name: Option<String>,
+    /// The key of the password in the [`super::SecretStore`], if it is not
+    /// stored in this file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_ref: Option<String>,
+    /// The key of the secret key in the [`super::SecretStore`], if it is not
+    /// stored in this file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_key_ref: Option<String>,
 
     #[serde(skip)]
     pub(crate) warnings: Vec<Warning>,
@@ -68,6 +77,33 @@ impl CredentialsFile {
     pub fn warnings(&self) -> &[Warning] {
         &self.warnings
     }
+
+    /// Replace `password_ref` and `secret_key_ref` with the secrets they
+    /// reference in the [`super::SecretStore`] of the context.
+    pub(crate) fn resolve_secrets(
+        &mut self,
+        context: &impl BuildContext,
+    ) -> Result<(), ParseError> {
+        if let Some(key) = self.password_ref.take() {
+            self.password = Some(read_secret(context, key)?);
+        }
+        if let Some(key) = self.secret_key_ref.take() {
+            self.secret_key = Some(read_secret(context, key)?);
+        }
+        Ok(())
+    }
+}
+
+fn read_secret(context: &impl BuildContext, key: String) -> Result<String, ParseError> {
+    let Some(store) = context.secret_store() else {
+        context_trace!(context, "No secret store to read {key:?} from");
+        return Err(InvalidCredentialsFileError::SecretNotFound(key).into());
+    };
+    match store.get(&key) {
+        Ok(Some(secret)) => Ok(secret),
+        Ok(None) => Err(InvalidCredentialsFileError::SecretNotFound(key).into()),
+        Err(e) => Err(InvalidCredentialsFileError::SecretStoreError(e.to_string()).into()),
+    }
 }
 
 impl FromStr for CredentialsFile {
@@ -135,6 +171,10 @@ struct CredentialsFileCompat {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     tls_verify_hostname: Option<bool>, // deprecated
     tls_security: Option<TlsSecurity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    password_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secret_key_ref: Option<String>,
 }
 
 impl CredentialsFileCompat {
@@ -244,6 +284,8 @@ impl TryInto<CredentialsFile> for CredentialsFileCompat {
                     Some(true) => TlsSecurity::Strict,
                     Some(false) => TlsSecurity::NoHostVerification,
                 }),
+                password_ref: self.password_ref,
+                secret_key_ref: self.secret_key_ref,
                 warnings,
             })
         }
@@ -1,8 +1,9 @@
 use super::{
     error::*, BuildContext, ClientSecurity, CloudCerts, FromParamStr, InstanceName, ParamSource,
-    TlsSecurity,
+    TlsCertFingerprint, TlsSecurity, Transport,
 };
 use crate::host::HostType;
+use gel_stream::Proxy;
 use std::{borrow::Cow, fmt::Debug, num::NonZeroU16, path::PathBuf, time::Duration};
 
 define_env!(
@@ -80,6 +81,18 @@ define_env!(
     /// How long to wait for server to become available.
     #[env(GEL_WAIT_UNTIL_AVAILABLE, EDGEDB_WAIT_UNTIL_AVAILABLE)]
     wait_until_available: Duration,
+
+    /// The transport used to send queries.
+    #[env(GEL_CLIENT_TRANSPORT)]
+    client_transport: Transport,
+
+    /// The proxy to tunnel TCP connections through.
+    #[env(GEL_CLIENT_PROXY)]
+    client_proxy: Proxy,
+
+    /// The pinned SHA-256 fingerprints of the server certificate.
+    #[env(GEL_TLS_CERT_FINGERPRINT)]
+    tls_cert_fingerprint: TlsCertFingerprint,
 );
 
 fn ignore_docker_tcp_port(
@@ -147,6 +160,17 @@ macro_rules! __UNEXPORTED_define_env {
                     Ok(Some(value))
                 }
             )*
+
+            /// The name of the environment variable that `field` is read
+            /// from, if any of its variables are set.
+            pub fn var_name(field: &str, context: &impl $crate::gel::BuildContext) -> ::std::option::Option<&'static str> {
+                $(
+                    if field == stringify!($name) {
+                        return $crate::gel::env::find_env(&[$(stringify!($env_name)),+], context);
+                    }
+                )*
+                None
+            }
         }
     };
 }
@@ -163,6 +187,14 @@ where
     }
 }
 
+#[doc(hidden)]
+pub fn find_env(names: &[&'static str], context: &impl BuildContext) -> Option<&'static str> {
+    names
+        .iter()
+        .find(|name| context.read_env(name).is_ok())
+        .copied()
+}
+
 #[inline(never)]
 #[doc(hidden)]
 pub fn get_envs(
@@ -220,6 +252,8 @@ mod tests {
         let warnings = Warnings::default();
         context.logging.warning = Some(warnings.clone().warn_fn());
         assert_eq!(Env::host(&context).unwrap(), Some("localhost".to_string()));
+        assert_eq!(Env::var_name("host", &context), Some("GEL_HOST"));
+        assert_eq!(Env::var_name("port", &context), None);
         assert_eq!(
             warnings.into_vec(),
             vec![Warning::MultipleEnvironmentVariables(vec![
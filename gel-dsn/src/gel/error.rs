@@ -44,6 +44,10 @@ pub enum InvalidCredentialsFileError {
     #[display("{}={}, {}={}", _0.0, _0.1, _1.0, _1.1)]
     ConflictingSettings((String, String), (String, String)),
     SerializationError(String),
+    #[display("secret {_0:?} not found")]
+    SecretNotFound(String),
+    #[display("secret store error: {_0}")]
+    SecretStoreError(String),
 }
 
 #[derive(
@@ -116,8 +120,14 @@ pub enum ParseError {
     InvalidUser,
     #[display("Invalid certificate")]
     InvalidCertificate,
+    #[display("Invalid certificate fingerprint")]
+    InvalidCertificateFingerprint,
     #[display("Invalid duration")]
     InvalidDuration,
+    #[display("Invalid transport")]
+    InvalidTransport,
+    #[display("Invalid proxy: {_0}")]
+    InvalidProxy(gel_stream::InvalidProxyUrl),
     #[display("Multiple compound options were specified while parsing {_0}: {_1:#?}")]
     MultipleCompound(BuildPhase, #[error(not(source))] Vec<CompoundSource>),
     #[display("No connection options specified, and no project manifest file found ({MANIFEST_FILE_DISPLAY_NAME})")]
@@ -148,7 +158,10 @@ impl ParseError {
             Self::InvalidTlsSecurity(_) => "invalid_tls_security",
             Self::InvalidUser => "invalid_user",
             Self::InvalidCertificate => "invalid_certificate",
+            Self::InvalidCertificateFingerprint => "invalid_certificate_fingerprint",
             Self::InvalidDuration => "invalid_duration",
+            Self::InvalidTransport => "invalid_transport",
+            Self::InvalidProxy(_) => "invalid_proxy",
             Self::MultipleCompound(BuildPhase::Environment, _) => "multiple_compound_env",
             Self::MultipleCompound(BuildPhase::Options, _) => "multiple_compound_opts",
             Self::MultipleCompound(BuildPhase::Project, _) => "multiple_compound_project",
@@ -177,7 +190,10 @@ impl ParseError {
             | Self::InvalidTlsSecurity(_)
             | Self::InvalidUser
             | Self::InvalidCertificate
+            | Self::InvalidCertificateFingerprint
             | Self::InvalidDuration
+            | Self::InvalidTransport
+            | Self::InvalidProxy(_)
             | Self::UnixSocketUnsupported => {
                 // The argument is invalid
                 gel_errors::InvalidArgumentError::with_source(self)
@@ -222,6 +238,24 @@ impl From<Infallible> for ParseError {
     }
 }
 
+/// Errors reading a project manifest (`gel.toml` or `edgedb.toml`).
+#[derive(
+    Debug, Clone, PartialEq, Eq, derive_more::Display, derive_more::Error, PartialOrd, Ord,
+)]
+#[error(ignore)]
+pub enum ManifestError {
+    #[display("Failed to read project manifest {_0}")]
+    Read(String),
+    #[display("Invalid project manifest: {_0}")]
+    Syntax(String),
+    #[display("Invalid server-version {_0:?}: expected a version such as \"6\", \"=6.1\" or \"6.0-rc.1\", or one of \"*\", \"nightly\" or \"testing\"")]
+    InvalidServerVersion(String),
+    #[display("[{_0}] and [{_1}] cannot both be present in the project manifest")]
+    ConflictingSections(String, String),
+    #[display("{_0} must not be empty in the project manifest")]
+    EmptyValue(String),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, derive_more::Display, PartialOrd, Ord)]
 pub enum Warning {
     #[display("Deprecated credential property: {_0}")]
@@ -236,6 +270,12 @@ pub enum Warning {
     DefaultDatabaseAndBranch,
     #[display("Updated out-of-date credentials file")]
     UpdatedOutdatedCredentials,
+    #[display("Unknown key in project manifest: {_0}")]
+    UnknownManifestKey(String),
+    #[display("Deprecated project manifest section [{_0}], use [{_1}] instead")]
+    DeprecatedManifestSection(String, String),
+    #[display("Ignoring unsupported proxy in environment variable {_0}")]
+    UnsupportedProxy(String),
 }
 
 #[derive(Debug, Default)]
@@ -0,0 +1,634 @@
+//! The project manifest (`gel.toml` or `edgedb.toml`).
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::Deserialize;
+
+use super::error::{ManifestError, Warning};
+use crate::FileAccess;
+
+/// The default schema directory, relative to the project directory.
+pub const DEFAULT_SCHEMA_DIR: &str = "dbschema";
+
+/// A parsed project manifest.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Manifest {
+    pub instance: ManifestInstance,
+    pub project: ManifestProject,
+    pub hooks: Hooks,
+    pub watch: Vec<WatchScript>,
+
+    pub(crate) warnings: Vec<Warning>,
+}
+
+/// The `[instance]` section (`[edgedb]` in older manifests).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ManifestInstance {
+    /// The `server-version` the project requires.
+    pub server_version: Option<VersionSpec>,
+    /// The `[instance.config]` table, in the format accepted by
+    /// `gel_config::parse_toml` under the `instance.config` key.
+    pub config: Option<toml::Table>,
+}
+
+/// The `[project]` section.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestProject {
+    /// The `schema-dir`, relative to the project directory.
+    pub schema_dir: Option<PathBuf>,
+}
+
+/// The `[hooks]` section. Each hook is a shell script that runs before or
+/// after the named action.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hooks {
+    pub project_init_before: Option<String>,
+    pub project_init_after: Option<String>,
+    pub branch_switch_before: Option<String>,
+    pub branch_switch_after: Option<String>,
+    pub branch_wipe_before: Option<String>,
+    pub branch_wipe_after: Option<String>,
+    pub migration_apply_before: Option<String>,
+    pub migration_apply_after: Option<String>,
+    pub schema_update_before: Option<String>,
+    pub schema_update_after: Option<String>,
+}
+
+/// A `[[watch]]` entry: a script to run when any of the files change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchScript {
+    /// Glob patterns, relative to the project directory.
+    pub files: Vec<String>,
+    pub script: String,
+}
+
+impl Manifest {
+    /// Read and parse the manifest at `path`.
+    pub fn read(files: impl FileAccess, path: &Path) -> Result<Self, ManifestError> {
+        let content = files
+            .read(path)
+            .map_err(|e| ManifestError::Read(format!("{}: {e}", path.display())))?;
+        content.parse()
+    }
+
+    /// Warnings for unknown keys and deprecated sections.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// The schema directory, resolved against the project directory.
+    pub fn schema_dir(&self, project_dir: &Path) -> PathBuf {
+        project_dir.join(
+            self.project
+                .schema_dir
+                .as_deref()
+                .unwrap_or(Path::new(DEFAULT_SCHEMA_DIR)),
+        )
+    }
+
+    /// The configuration to apply, in the format accepted by
+    /// `gel_config::parse_toml`, or `None` if the manifest has no
+    /// `[instance.config]` section.
+    pub fn config_toml(&self) -> Option<toml::Table> {
+        let config = self.instance.config.clone()?;
+        let mut instance = toml::Table::new();
+        instance.insert("config".to_string(), config.into());
+        let mut table = toml::Table::new();
+        table.insert("instance".to_string(), instance.into());
+        Some(table)
+    }
+}
+
+impl FromStr for Manifest {
+    type Err = ManifestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let table: toml::Table = s
+            .parse()
+            .map_err(|e: toml::de::Error| ManifestError::Syntax(e.message().to_string()))?;
+
+        let mut warnings = Vec::new();
+        if table.contains_key("edgedb") {
+            if table.contains_key("instance") {
+                return Err(ManifestError::ConflictingSections(
+                    "edgedb".to_string(),
+                    "instance".to_string(),
+                ));
+            }
+            warnings.push(Warning::DeprecatedManifestSection(
+                "edgedb".to_string(),
+                "instance".to_string(),
+            ));
+        }
+
+        let raw: RawManifest = toml::Value::Table(table)
+            .try_into()
+            .map_err(|e| ManifestError::Syntax(e.message().to_string()))?;
+        raw.unknown_keys(&mut warnings);
+
+        let mut manifest = Manifest {
+            warnings,
+            ..Default::default()
+        };
+        if let Some(instance) = raw.instance {
+            manifest.instance = ManifestInstance {
+                server_version: instance
+                    .server_version
+                    .map(|version| version.parse())
+                    .transpose()?,
+                config: instance.config,
+            };
+        }
+        if let Some(project) = raw.project {
+            if let Some(schema_dir) = project.schema_dir {
+                if schema_dir.is_empty() {
+                    return Err(ManifestError::EmptyValue("project.schema-dir".to_string()));
+                }
+                manifest.project.schema_dir = Some(schema_dir.into());
+            }
+        }
+        if let Some(hooks) = raw.hooks {
+            manifest.hooks = hooks.into_hooks(&mut manifest.warnings)?;
+        }
+        for (index, watch) in raw.watch.into_iter().enumerate() {
+            if watch.files.is_empty() {
+                return Err(ManifestError::EmptyValue(format!("watch[{index}].files")));
+            }
+            if watch.script.trim().is_empty() {
+                return Err(ManifestError::EmptyValue(format!("watch[{index}].script")));
+            }
+            manifest.watch.push(WatchScript {
+                files: watch.files,
+                script: watch.script,
+            });
+        }
+        Ok(manifest)
+    }
+}
+
+/// The `server-version` requirement of a project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionSpec {
+    /// The latest stable release: `*` or `latest`.
+    Latest,
+    /// The latest nightly build: `nightly`.
+    Nightly,
+    /// The latest testing release: `testing`.
+    Testing,
+    /// A release such as `6`, `6.1`, `=6.1` or `6.0-rc.1`. Without `=`, any
+    /// later minor release of the same major version also matches.
+    Version {
+        exact: bool,
+        major: u16,
+        minor: Option<u16>,
+        /// A pre-release such as `alpha.1`, `beta.2`, `rc.1` or `dev.8000`.
+        pre: Option<String>,
+    },
+}
+
+impl VersionSpec {
+    /// Whether the release `major.minor` satisfies this requirement. Channels
+    /// and pre-releases are resolved by the server installer, so this only
+    /// checks stable version numbers.
+    pub fn matches(&self, major: u16, minor: u16) -> bool {
+        match self {
+            VersionSpec::Latest => true,
+            VersionSpec::Nightly | VersionSpec::Testing => false,
+            VersionSpec::Version { pre: Some(_), .. } => false,
+            VersionSpec::Version {
+                exact,
+                major: spec_major,
+                minor: spec_minor,
+                pre: None,
+            } => {
+                if major != *spec_major {
+                    return false;
+                }
+                match (spec_minor, exact) {
+                    (None, _) => true,
+                    (Some(spec_minor), true) => minor == *spec_minor,
+                    (Some(spec_minor), false) => minor >= *spec_minor,
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for VersionSpec {
+    type Err = ManifestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ManifestError::InvalidServerVersion(s.to_string());
+        match s.trim() {
+            "*" | "latest" => return Ok(VersionSpec::Latest),
+            "nightly" => return Ok(VersionSpec::Nightly),
+            "testing" => return Ok(VersionSpec::Testing),
+            _ => {}
+        }
+
+        let (exact, version) = match s.trim().strip_prefix('=') {
+            Some(version) => (true, version.trim_start()),
+            None => (false, s.trim()),
+        };
+        let (version, pre) = match version.split_once('-') {
+            Some((version, pre)) => (version, Some(pre)),
+            None => (version, None),
+        };
+        let (major, minor) = match version.split_once('.') {
+            Some((major, minor)) => (major, Some(minor)),
+            None => (version, None),
+        };
+        let number = |n: &str| {
+            if n.is_empty() || !n.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            n.parse::<u16>().map_err(|_| invalid())
+        };
+        let major = number(major)?;
+        let minor = minor.map(number).transpose()?;
+
+        if let Some(pre) = pre {
+            // Pre-releases only exist for a specific minor version
+            let valid = minor.is_some()
+                && pre.split_once('.').is_some_and(|(kind, n)| {
+                    matches!(kind, "alpha" | "beta" | "rc" | "dev") && number(n).is_ok()
+                });
+            if !valid {
+                return Err(invalid());
+            }
+        }
+
+        Ok(VersionSpec::Version {
+            exact,
+            major,
+            minor,
+            pre: pre.map(str::to_string),
+        })
+    }
+}
+
+impl fmt::Display for VersionSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionSpec::Latest => write!(f, "*"),
+            VersionSpec::Nightly => write!(f, "nightly"),
+            VersionSpec::Testing => write!(f, "testing"),
+            VersionSpec::Version {
+                exact,
+                major,
+                minor,
+                pre,
+            } => {
+                if *exact {
+                    write!(f, "=")?;
+                }
+                write!(f, "{major}")?;
+                if let Some(minor) = minor {
+                    write!(f, ".{minor}")?;
+                }
+                if let Some(pre) = pre {
+                    write!(f, "-{pre}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The manifest as written. Unknown keys are collected rather than rejected,
+/// so that newer manifests can be read with a warning.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawManifest {
+    #[serde(default, alias = "edgedb")]
+    instance: Option<RawInstance>,
+    #[serde(default)]
+    project: Option<RawProject>,
+    #[serde(default)]
+    hooks: Option<RawHooks>,
+    #[serde(default)]
+    watch: Vec<RawWatch>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawInstance {
+    server_version: Option<String>,
+    config: Option<toml::Table>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawProject {
+    schema_dir: Option<String>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+/// `[hooks]` is nested as `hooks.<group>.<action>.<before|after>`.
+#[derive(Deserialize)]
+struct RawHooks(BTreeMap<String, BTreeMap<String, RawHookActions>>);
+
+#[derive(Deserialize)]
+struct RawHookActions {
+    before: Option<String>,
+    after: Option<String>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Deserialize)]
+struct RawWatch {
+    files: Vec<String>,
+    script: String,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+fn warn_unknown(
+    warnings: &mut Vec<Warning>,
+    prefix: &str,
+    unknown: &BTreeMap<String, toml::Value>,
+) {
+    for key in unknown.keys() {
+        warnings.push(Warning::UnknownManifestKey(format!("{prefix}{key}")));
+    }
+}
+
+impl RawManifest {
+    fn unknown_keys(&self, warnings: &mut Vec<Warning>) {
+        warn_unknown(warnings, "", &self.unknown);
+        if let Some(instance) = &self.instance {
+            warn_unknown(warnings, "instance.", &instance.unknown);
+        }
+        if let Some(project) = &self.project {
+            warn_unknown(warnings, "project.", &project.unknown);
+        }
+        for (index, watch) in self.watch.iter().enumerate() {
+            warn_unknown(warnings, &format!("watch[{index}]."), &watch.unknown);
+        }
+    }
+}
+
+impl RawHooks {
+    fn into_hooks(self, warnings: &mut Vec<Warning>) -> Result<Hooks, ManifestError> {
+        let mut hooks = Hooks::default();
+        for (group, actions) in self.0 {
+            for (action, scripts) in actions {
+                let prefix = format!("hooks.{group}.{action}.");
+                let (before, after) = match (group.as_str(), action.as_str()) {
+                    ("project", "init") => (
+                        &mut hooks.project_init_before,
+                        &mut hooks.project_init_after,
+                    ),
+                    ("branch", "switch") => (
+                        &mut hooks.branch_switch_before,
+                        &mut hooks.branch_switch_after,
+                    ),
+                    ("branch", "wipe") => {
+                        (&mut hooks.branch_wipe_before, &mut hooks.branch_wipe_after)
+                    }
+                    ("migration", "apply") => (
+                        &mut hooks.migration_apply_before,
+                        &mut hooks.migration_apply_after,
+                    ),
+                    ("schema", "update") => (
+                        &mut hooks.schema_update_before,
+                        &mut hooks.schema_update_after,
+                    ),
+                    _ => {
+                        warnings.push(Warning::UnknownManifestKey(format!(
+                            "hooks.{group}.{action}"
+                        )));
+                        continue;
+                    }
+                };
+                for (name, script, slot) in [
+                    ("before", scripts.before, before),
+                    ("after", scripts.after, after),
+                ] {
+                    if let Some(script) = script {
+                        if script.trim().is_empty() {
+                            return Err(ManifestError::EmptyValue(format!("{prefix}{name}")));
+                        }
+                        *slot = Some(script);
+                    }
+                }
+                warn_unknown(warnings, &prefix, &scripts.unknown);
+            }
+        }
+        Ok(hooks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest() {
+        let manifest: Manifest = r#"
+            [instance]
+            server-version = "6.1"
+
+            [instance.config]
+            allow_user_specified_id = true
+
+            [project]
+            schema-dir = "db/schema"
+
+            [hooks]
+            project.init.after = "npx @gel/generate edgeql-js"
+            migration.apply.after = "npx @gel/generate queries"
+
+            [[watch]]
+            files = ["queries/*.edgeql"]
+            script = "npx @gel/generate queries"
+        "#
+        .parse()
+        .unwrap();
+
+        assert_eq!(manifest.warnings(), &[]);
+        assert_eq!(
+            manifest.instance.server_version,
+            Some(VersionSpec::Version {
+                exact: false,
+                major: 6,
+                minor: Some(1),
+                pre: None,
+            })
+        );
+        assert_eq!(
+            manifest.schema_dir(Path::new("/project")),
+            PathBuf::from("/project/db/schema")
+        );
+        assert_eq!(
+            manifest.hooks,
+            Hooks {
+                project_init_after: Some("npx @gel/generate edgeql-js".to_string()),
+                migration_apply_after: Some("npx @gel/generate queries".to_string()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            manifest.watch,
+            vec![WatchScript {
+                files: vec!["queries/*.edgeql".to_string()],
+                script: "npx @gel/generate queries".to_string(),
+            }]
+        );
+        assert_eq!(
+            manifest.config_toml().unwrap().to_string(),
+            "[instance.config]\nallow_user_specified_id = true\n"
+        );
+    }
+
+    #[test]
+    fn test_manifest_defaults() {
+        let manifest: Manifest = "".parse().unwrap();
+        assert_eq!(manifest, Manifest::default());
+        assert_eq!(
+            manifest.schema_dir(Path::new("/project")),
+            PathBuf::from("/project/dbschema")
+        );
+        assert_eq!(manifest.config_toml(), None);
+    }
+
+    #[test]
+    fn test_manifest_warnings() {
+        let manifest: Manifest = r#"
+            future = 1
+
+            [edgedb]
+            server-version = "nightly"
+            server-flavour = "large"
+
+            [hooks]
+            branch.switch.during = "echo"
+            branch.merge.after = "echo"
+
+            [[watch]]
+            files = ["*.edgeql"]
+            script = "echo"
+            debounce = 100
+        "#
+        .parse()
+        .unwrap();
+
+        assert_eq!(manifest.instance.server_version, Some(VersionSpec::Nightly));
+        assert_eq!(
+            manifest.warnings(),
+            &[
+                Warning::DeprecatedManifestSection("edgedb".to_string(), "instance".to_string()),
+                Warning::UnknownManifestKey("future".to_string()),
+                Warning::UnknownManifestKey("instance.server-flavour".to_string()),
+                Warning::UnknownManifestKey("watch[0].debounce".to_string()),
+                Warning::UnknownManifestKey("hooks.branch.merge".to_string()),
+                Warning::UnknownManifestKey("hooks.branch.switch.during".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_manifest_errors() {
+        let err = |s: &str| s.parse::<Manifest>().unwrap_err();
+        assert!(matches!(err("[instance"), ManifestError::Syntax(_)));
+        assert!(matches!(
+            err("[instance]\nserver-version = 6"),
+            ManifestError::Syntax(_)
+        ));
+        assert_eq!(
+            err("[instance]\nserver-version = \"six\""),
+            ManifestError::InvalidServerVersion("six".to_string())
+        );
+        assert_eq!(
+            err("[instance]\n[edgedb]"),
+            ManifestError::ConflictingSections("edgedb".to_string(), "instance".to_string())
+        );
+        assert_eq!(
+            err("[project]\nschema-dir = \"\""),
+            ManifestError::EmptyValue("project.schema-dir".to_string())
+        );
+        assert_eq!(
+            err("[[watch]]\nfiles = []\nscript = \"echo\""),
+            ManifestError::EmptyValue("watch[0].files".to_string())
+        );
+        assert_eq!(
+            err("[hooks]\nschema.update.before = \" \""),
+            ManifestError::EmptyValue("hooks.schema.update.before".to_string())
+        );
+    }
+
+    #[test]
+    fn test_version_spec() {
+        for (s, spec) in [
+            ("*", VersionSpec::Latest),
+            ("nightly", VersionSpec::Nightly),
+            ("testing", VersionSpec::Testing),
+            (
+                "6",
+                VersionSpec::Version {
+                    exact: false,
+                    major: 6,
+                    minor: None,
+                    pre: None,
+                },
+            ),
+            (
+                "=6.1",
+                VersionSpec::Version {
+                    exact: true,
+                    major: 6,
+                    minor: Some(1),
+                    pre: None,
+                },
+            ),
+            (
+                "6.0-rc.1",
+                VersionSpec::Version {
+                    exact: false,
+                    major: 6,
+                    minor: Some(0),
+                    pre: Some("rc.1".to_string()),
+                },
+            ),
+        ] {
+            assert_eq!(s.parse::<VersionSpec>().unwrap(), spec, "{s}");
+            assert_eq!(spec.to_string(), s);
+        }
+        assert_eq!(
+            "latest".parse::<VersionSpec>().unwrap(),
+            VersionSpec::Latest
+        );
+
+        for s in [
+            "",
+            "6.",
+            "6.x",
+            "-1",
+            "6-rc.1",
+            "6.0-gamma.1",
+            "6.0-rc",
+            "99999",
+        ] {
+            assert!(s.parse::<VersionSpec>().is_err(), "{s}");
+        }
+
+        let spec: VersionSpec = "6.1".parse().unwrap();
+        assert!(spec.matches(6, 1));
+        assert!(spec.matches(6, 2));
+        assert!(!spec.matches(6, 0));
+        assert!(!spec.matches(7, 1));
+        let spec: VersionSpec = "=6.1".parse().unwrap();
+        assert!(!spec.matches(6, 2));
+        assert!(VersionSpec::Latest.matches(5, 0));
+    }
+}
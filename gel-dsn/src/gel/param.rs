@@ -8,7 +8,8 @@ use url::Url;
 
 use super::{
     duration, error::*, BuildContext, ClientSecurity, CloudCerts, CloudCredentialsFile,
-    CredentialsFile, InstanceName, TcpKeepalive, TlsSecurity, UnixPath,
+    CredentialsFile, InstanceName, TcpKeepalive, TlsCertFingerprint, TlsSecurity, Transport,
+    UnixPath,
 };
 use crate::{gel::context_trace, host::HostType, FileAccess};
 
@@ -44,6 +45,8 @@ impl_from_param_str!(
     CloudCredentialsFile,
     CloudCerts,
     TcpKeepalive,
+    TlsCertFingerprint,
+    Transport,
     UnixPath
 );
 
@@ -56,6 +59,13 @@ impl FromParamStr for std::time::Duration {
     }
 }
 
+impl FromParamStr for gel_stream::Proxy {
+    type Err = ParseError;
+    fn from_param_str(s: &str, _context: &impl BuildContext) -> Result<Self, Self::Err> {
+        s.parse().map_err(ParseError::InvalidProxy)
+    }
+}
+
 impl FromParamStr for Url {
     type Err = ParseError;
     fn from_param_str(s: &str, context: &impl BuildContext) -> Result<Self, Self::Err> {
@@ -6,7 +6,8 @@ use std::{
 };
 
 use crate::{
-    gel::{context_trace, DatabaseBranch},
+    file::SystemFileAccess,
+    gel::{context_trace, error::ManifestError, DatabaseBranch, Manifest},
     FileAccess,
 };
 
@@ -33,6 +34,21 @@ impl ProjectSearchResult {
         let project = find_project_file(&context, dir)?;
         Ok(project)
     }
+
+    /// The directory containing the project file.
+    pub fn project_dir(&self) -> &Path {
+        self.project_path.parent().unwrap_or(&self.project_path)
+    }
+
+    /// Read and parse the project file.
+    pub fn manifest(&self) -> Result<Manifest, ManifestError> {
+        self.manifest_with(SystemFileAccess)
+    }
+
+    /// Read and parse the project file, using the given file access.
+    pub fn manifest_with(&self, files: impl FileAccess) -> Result<Manifest, ManifestError> {
+        Manifest::read(files, &self.project_path)
+    }
 }
 
 pub enum ProjectDir {
@@ -294,6 +310,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_project_manifest() {
+        let files = HashMap::from_iter([
+            (Path::new("/home/edgedb/test/gel.toml"),
+            "[instance]\nserver-version = \"6\"\n[project]\nschema-dir = \"schema\"\n"),
+            (Path::new("/home/edgedb/.config/edgedb/projects/test-cf3c86df8fc33fbb73a47671ac5762eda8219158/instance-name"),
+            "instance-name"),
+        ]);
+
+        let mut context = BuildContextImpl::new_with((), &files);
+        context.paths.config_dirs = vec![PathBuf::from("/home/edgedb/.config/edgedb")];
+        let res = find_project_file(
+            &context,
+            ProjectDir::Search(PathBuf::from("/home/edgedb/test/src")),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(res.project_dir(), Path::new("/home/edgedb/test"));
+        let manifest = res.manifest_with(&files).unwrap();
+        assert_eq!(
+            manifest
+                .instance
+                .server_version
+                .as_ref()
+                .map(|v| v.to_string()),
+            Some("6".to_string())
+        );
+        assert_eq!(
+            manifest.schema_dir(res.project_dir()),
+            PathBuf::from("/home/edgedb/test/schema")
+        );
+    }
+
     #[test]
     fn test_project_file_priority() {
         use std::fs;
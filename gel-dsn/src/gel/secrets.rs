@@ -0,0 +1,330 @@
+//! Storage for secret credential fields (passwords and secret keys) outside
+//! of the credentials files.
+//!
+//! A [`super::CredentialsFile`] written with a [`SecretStore`] configured
+//! references its secrets by key (`password_ref` and `secret_key_ref`), and
+//! the secrets are resolved from the store when the credentials are read.
+
+/// A backend that stores secrets by key.
+pub trait SecretStore {
+    /// Read the secret for `key`, or `None` if it doesn't exist.
+    fn get(&self, key: &str) -> std::io::Result<Option<String>>;
+    /// Store the secret for `key`, replacing any existing secret.
+    fn set(&self, key: &str, secret: &str) -> std::io::Result<()>;
+    /// Delete the secret for `key`. If the secret doesn't exist, this is a
+    /// no-op.
+    fn delete(&self, key: &str) -> std::io::Result<()>;
+}
+
+impl<T: SecretStore + ?Sized> SecretStore for std::sync::Arc<T> {
+    fn get(&self, key: &str) -> std::io::Result<Option<String>> {
+        (**self).get(key)
+    }
+
+    fn set(&self, key: &str, secret: &str) -> std::io::Result<()> {
+        (**self).set(key, secret)
+    }
+
+    fn delete(&self, key: &str) -> std::io::Result<()> {
+        (**self).delete(key)
+    }
+}
+
+impl SecretStore for std::sync::Mutex<std::collections::HashMap<String, String>> {
+    fn get(&self, key: &str) -> std::io::Result<Option<String>> {
+        Ok(self.lock().unwrap().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, secret: &str) -> std::io::Result<()> {
+        self.lock()
+            .unwrap()
+            .insert(key.to_string(), secret.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> std::io::Result<()> {
+        self.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "encrypted-secrets")]
+pub use encrypted::{EncryptedFileStore, EncryptionKey, SECRETS_KEY_ENV};
+
+#[cfg(feature = "encrypted-secrets")]
+mod encrypted {
+    use super::SecretStore;
+    use crate::{file::SystemFileAccess, EnvVar, FileAccess};
+    use base64::{prelude::BASE64_STANDARD, Engine};
+    use ring::{
+        aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+        rand::{SecureRandom, SystemRandom},
+    };
+    use serde::{Deserialize, Serialize};
+    use std::{
+        collections::BTreeMap,
+        io::{Error, ErrorKind},
+        path::{Path, PathBuf},
+    };
+
+    /// The environment variable holding the base64-encoded key for
+    /// [`EncryptedFileStore`].
+    pub const SECRETS_KEY_ENV: &str = "GEL_SECRETS_KEY";
+
+    const KEY_LEN: usize = 32;
+    const VERSION: u32 = 1;
+
+    /// A 256-bit key for [`EncryptedFileStore`], encoded as base64 in
+    /// environment variables and key files.
+    #[derive(Clone)]
+    pub struct EncryptionKey([u8; KEY_LEN]);
+
+    impl std::fmt::Debug for EncryptionKey {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("EncryptionKey(..)")
+        }
+    }
+
+    impl EncryptionKey {
+        /// Generate a new random key.
+        pub fn generate() -> std::io::Result<Self> {
+            let mut key = [0; KEY_LEN];
+            SystemRandom::new()
+                .fill(&mut key)
+                .map_err(|_| Error::other("failed to generate a key"))?;
+            Ok(Self(key))
+        }
+
+        /// Read the key from the environment variable `name`, usually
+        /// [`SECRETS_KEY_ENV`].
+        pub fn from_env(env: impl EnvVar, name: &str) -> std::io::Result<Self> {
+            let value = env.read(name).map_err(|_| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!("environment variable {name} is not set"),
+                )
+            })?;
+            Self::from_base64(&value)
+        }
+
+        /// Read the key from a key file.
+        pub fn from_file(files: impl FileAccess, path: impl AsRef<Path>) -> std::io::Result<Self> {
+            Self::from_base64(&files.read(path.as_ref())?)
+        }
+
+        /// Decode the key from base64. Surrounding whitespace is ignored.
+        pub fn from_base64(s: &str) -> std::io::Result<Self> {
+            let key = BASE64_STANDARD
+                .decode(s.trim())
+                .ok()
+                .and_then(|key| <[u8; KEY_LEN]>::try_from(key).ok())
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        "the key must be 32 bytes, encoded as base64",
+                    )
+                })?;
+            Ok(Self(key))
+        }
+
+        /// Encode the key as base64, for storing in a key file or
+        /// environment variable.
+        pub fn to_base64(&self) -> String {
+            BASE64_STANDARD.encode(self.0)
+        }
+    }
+
+    #[derive(Default, Serialize, Deserialize)]
+    struct SecretsFile {
+        version: u32,
+        secrets: BTreeMap<String, String>,
+    }
+
+    /// A [`SecretStore`] that keeps secrets in a single file, encrypted with
+    /// AES-256-GCM.
+    ///
+    /// Each secret is encrypted with a random nonce and authenticated with its
+    /// key, so secrets can't be swapped between keys. The file is rewritten on
+    /// every change, and concurrent writers are not coordinated.
+    pub struct EncryptedFileStore<F: FileAccess = SystemFileAccess> {
+        files: F,
+        path: PathBuf,
+        key: LessSafeKey,
+    }
+
+    impl EncryptedFileStore<SystemFileAccess> {
+        /// Create a store for the file at `path`. The file is created when
+        /// the first secret is stored.
+        pub fn new(path: impl Into<PathBuf>, key: &EncryptionKey) -> Self {
+            Self::new_with(SystemFileAccess, path, key)
+        }
+    }
+
+    impl<F: FileAccess> EncryptedFileStore<F> {
+        /// Create a store for the file at `path`, using the given file access.
+        pub fn new_with(files: F, path: impl Into<PathBuf>, key: &EncryptionKey) -> Self {
+            let key = UnboundKey::new(&AES_256_GCM, &key.0).expect("key length is correct");
+            Self {
+                files,
+                path: path.into(),
+                key: LessSafeKey::new(key),
+            }
+        }
+
+        fn read_file(&self) -> std::io::Result<SecretsFile> {
+            let contents = match self.files.read(&self.path) {
+                Ok(contents) => contents,
+                Err(e) if e.kind() == ErrorKind::NotFound => return Ok(SecretsFile::default()),
+                Err(e) => return Err(e),
+            };
+            let file: SecretsFile = serde_json::from_str(&contents)?;
+            if file.version != VERSION {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("unsupported secrets file version {}", file.version),
+                ));
+            }
+            Ok(file)
+        }
+
+        fn write_file(&self, file: &SecretsFile) -> std::io::Result<()> {
+            self.files.write(&self.path, &serde_json::to_string(file)?)
+        }
+
+        fn encrypt(&self, key: &str, secret: &str) -> std::io::Result<String> {
+            let mut nonce = [0; NONCE_LEN];
+            SystemRandom::new()
+                .fill(&mut nonce)
+                .map_err(|_| Error::other("failed to generate a nonce"))?;
+            let mut data = secret.as_bytes().to_vec();
+            self.key
+                .seal_in_place_append_tag(
+                    Nonce::assume_unique_for_key(nonce),
+                    Aad::from(key.as_bytes()),
+                    &mut data,
+                )
+                .map_err(|_| Error::other("failed to encrypt secret"))?;
+            let mut sealed = nonce.to_vec();
+            sealed.extend_from_slice(&data);
+            Ok(BASE64_STANDARD.encode(sealed))
+        }
+
+        fn decrypt(&self, key: &str, sealed: &str) -> std::io::Result<String> {
+            let invalid = || {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("failed to decrypt secret {key:?}"),
+                )
+            };
+            let mut sealed = BASE64_STANDARD.decode(sealed).map_err(|_| invalid())?;
+            if sealed.len() < NONCE_LEN {
+                return Err(invalid());
+            }
+            let mut data = sealed.split_off(NONCE_LEN);
+            let nonce = Nonce::try_assume_unique_for_key(&sealed).map_err(|_| invalid())?;
+            let secret = self
+                .key
+                .open_in_place(nonce, Aad::from(key.as_bytes()), &mut data)
+                .map_err(|_| invalid())?;
+            String::from_utf8(secret.to_vec()).map_err(|_| invalid())
+        }
+    }
+
+    impl<F: FileAccess> SecretStore for EncryptedFileStore<F> {
+        fn get(&self, key: &str) -> std::io::Result<Option<String>> {
+            let file = self.read_file()?;
+            file.secrets
+                .get(key)
+                .map(|sealed| self.decrypt(key, sealed))
+                .transpose()
+        }
+
+        fn set(&self, key: &str, secret: &str) -> std::io::Result<()> {
+            let mut file = self.read_file()?;
+            file.version = VERSION;
+            file.secrets
+                .insert(key.to_string(), self.encrypt(key, secret)?);
+            self.write_file(&file)
+        }
+
+        fn delete(&self, key: &str) -> std::io::Result<()> {
+            let mut file = self.read_file()?;
+            if file.secrets.remove(key).is_some() {
+                self.write_file(&file)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::{collections::HashMap, sync::Mutex};
+
+        #[test]
+        fn test_encrypted_file_store() {
+            let key = EncryptionKey::generate().unwrap();
+            let files = Mutex::new(HashMap::<PathBuf, String>::new());
+            let path = Path::new("/home/edgedb/.config/edgedb/secrets.json");
+            let store = EncryptedFileStore::new_with(&files, path, &key);
+
+            assert_eq!(store.get("password").unwrap(), None);
+            store.set("password", "hunter2").unwrap();
+            store.set("secret_key", "nbwt1_abc").unwrap();
+            assert_eq!(store.get("password").unwrap().as_deref(), Some("hunter2"));
+
+            // Secrets are not stored in plain text
+            let contents = files.lock().unwrap().get(path).unwrap().clone();
+            assert!(!contents.contains("hunter2"), "{contents}");
+
+            store.delete("password").unwrap();
+            assert_eq!(store.get("password").unwrap(), None);
+            assert_eq!(
+                store.get("secret_key").unwrap().as_deref(),
+                Some("nbwt1_abc")
+            );
+
+            // The wrong key can't decrypt the secrets
+            let other =
+                EncryptedFileStore::new_with(&files, path, &EncryptionKey::generate().unwrap());
+            assert_eq!(
+                other.get("secret_key").unwrap_err().kind(),
+                ErrorKind::InvalidData
+            );
+        }
+
+        #[test]
+        fn test_swapped_secrets() {
+            let key = EncryptionKey::generate().unwrap();
+            let files = Mutex::new(HashMap::<PathBuf, String>::new());
+            let path = Path::new("secrets.json");
+            let store = EncryptedFileStore::new_with(&files, path, &key);
+            store.set("a", "secret a").unwrap();
+
+            let mut file = store.read_file().unwrap();
+            let sealed = file.secrets.remove("a").unwrap();
+            file.secrets.insert("b".to_string(), sealed);
+            store.write_file(&file).unwrap();
+            assert_eq!(store.get("b").unwrap_err().kind(), ErrorKind::InvalidData);
+        }
+
+        #[test]
+        fn test_encryption_key() {
+            let key = EncryptionKey::generate().unwrap();
+            let env = HashMap::from([(SECRETS_KEY_ENV, key.to_base64())]);
+            let from_env = EncryptionKey::from_env(&env, SECRETS_KEY_ENV).unwrap();
+            assert_eq!(from_env.0, key.0);
+
+            let files =
+                HashMap::from([(PathBuf::from("/keyfile"), format!("{}\n", key.to_base64()))]);
+            let from_file = EncryptionKey::from_file(&files, "/keyfile").unwrap();
+            assert_eq!(from_file.0, key.0);
+
+            assert_eq!(
+                EncryptionKey::from_base64("c2hvcnQ=").unwrap_err().kind(),
+                ErrorKind::InvalidInput
+            );
+            assert_eq!(format!("{key:?}"), "EncryptionKey(..)");
+        }
+    }
+}
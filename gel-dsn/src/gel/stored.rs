@@ -221,10 +221,16 @@ impl<CT: BuildContext, C: std::ops::Deref<Target = CT>> StoredCredentials<CT, C>
                 }
             }
         }
-        Ok(content)
+        let Some(mut content) = content else {
+            return Ok(None);
+        };
+        content.resolve_secrets(&*self.context)?;
+        Ok(Some(content))
     }
 
-    /// Write the credentials for the given instance.
+    /// Write the credentials for the given instance. If a secret store is
+    /// configured, the password and secret key are written to the store and
+    /// referenced from the credentials file.
     pub fn write(
         &self,
         instance: InstanceName,
@@ -238,25 +244,62 @@ impl<CT: BuildContext, C: std::ops::Deref<Target = CT>> StoredCredentials<CT, C>
             content.database = None;
             content.branch = None;
         }
+        if let Some(store) = self.context.secret_store() {
+            if let Some(password) = content.password.take() {
+                let key = secret_key(&instance, "password");
+                store.set(&key, &password)?;
+                content.password_ref = Some(key);
+            }
+            if let Some(secret_key_value) = content.secret_key.take() {
+                let key = secret_key(&instance, "secret_key");
+                store.set(&key, &secret_key_value)?;
+                content.secret_key_ref = Some(key);
+            }
+        }
         let path = Path::new("credentials").join(format!("{instance}.json"));
         self.context
             .write_config_file(path, &serde_json::to_string(&content)?)
     }
 
-    /// Delete the credentials for the given instance. If the credentials
-    /// do not exist, this is a no-op.
+    /// Delete the credentials for the given instance, along with any secrets
+    /// they reference in the secret store. If the credentials do not exist,
+    /// this is a no-op.
     pub fn delete(&self, instance: InstanceName) -> Result<(), std::io::Error> {
         let path = Path::new("credentials").join(format!("{instance}.json"));
-        self.context.delete_config_file(&path)
+        // Secrets are cleaned up on a best-effort basis: an unreadable
+        // credentials file shouldn't prevent its deletion.
+        let refs = self
+            .context
+            .read_config_file::<CredentialsFile>(&path)
+            .ok()
+            .flatten()
+            .map(|content| [content.password_ref, content.secret_key_ref])
+            .unwrap_or_default();
+        self.context.delete_config_file(&path)?;
+        if let Some(store) = self.context.secret_store() {
+            for key in refs.into_iter().flatten() {
+                store.delete(&key)?;
+            }
+        }
+        Ok(())
     }
 }
 
+/// The secret store key for a secret field of an instance's credentials.
+fn secret_key(instance: &InstanceName, field: &str) -> String {
+    format!("credentials/{instance}/{field}")
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::gel::error::{InvalidCredentialsFileError, ParseError};
     use crate::gel::{Builder, CredentialsFile, InstanceName};
     use crate::{FileAccess, UserProfile};
     use std::path::PathBuf;
-    use std::{collections::HashMap, sync::Mutex};
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
 
     #[test]
     fn test_list() {
@@ -319,6 +362,104 @@ mod tests {
         assert!(content.is_some());
     }
 
+    #[test]
+    fn test_secret_store() {
+        let files = Mutex::new(HashMap::<PathBuf, String>::new());
+        let secrets = Arc::new(Mutex::new(HashMap::<String, String>::new()));
+        let user = "edgedb";
+        let instance = InstanceName::Local("local".to_string());
+        let stored = Builder::default()
+            .without_system()
+            .with_env_impl(())
+            .with_fs_impl(&files)
+            .with_user_impl(user)
+            .with_secret_store(secrets.clone())
+            .stored_info();
+
+        let credentials = stored.credentials();
+        let content = CredentialsFile {
+            password: Some("hunter2".to_string()),
+            secret_key: Some("nbwt1_abc".to_string()),
+            ..Default::default()
+        };
+        credentials.write(instance.clone(), &content).unwrap();
+
+        // The secrets are only referenced from the credentials file
+        let file = user
+            .config_dirs()
+            .first()
+            .unwrap()
+            .join("credentials")
+            .join("local.json");
+        let raw = files.lock().unwrap().get(&file).unwrap().clone();
+        assert!(!raw.contains("hunter2"), "{raw}");
+        assert!(raw.contains("\"password_ref\":\"credentials/local/password\""));
+        assert_eq!(
+            secrets.lock().unwrap().get("credentials/local/password"),
+            Some(&"hunter2".to_string())
+        );
+
+        let read = credentials.read(instance.clone()).unwrap().unwrap();
+        assert_eq!(read.password.as_deref(), Some("hunter2"));
+        assert_eq!(read.secret_key.as_deref(), Some("nbwt1_abc"));
+        assert_eq!(read.password_ref, None);
+
+        // The secrets are resolved when connecting to the instance
+        let config = Builder::default()
+            .instance(instance.clone())
+            .without_system()
+            .with_env_impl(())
+            .with_fs_impl(&files)
+            .with_user_impl(user)
+            .with_secret_store(secrets.clone())
+            .build()
+            .unwrap();
+        assert_eq!(config.authentication.password(), Some("hunter2"));
+
+        // An explicit credentials file resolves its secrets the same way
+        let explicit = |secrets: Option<Arc<_>>| {
+            let builder = Builder::default()
+                .credentials_file(&file)
+                .without_system()
+                .with_env_impl(())
+                .with_fs_impl(&files)
+                .with_user_impl(user);
+            match secrets {
+                Some(secrets) => builder.with_secret_store(secrets).build_parse_error(),
+                None => builder.build_parse_error(),
+            }
+        };
+        let config = explicit(Some(secrets.clone())).unwrap();
+        assert_eq!(config.authentication.password(), Some("hunter2"));
+        assert_eq!(
+            explicit(None).unwrap_err(),
+            ParseError::InvalidCredentialsFile(InvalidCredentialsFileError::SecretNotFound(
+                "credentials/local/password".to_string()
+            ))
+        );
+
+        // Without the store, the secrets can't be found
+        let err = Builder::default()
+            .without_system()
+            .with_env_impl(())
+            .with_fs_impl(&files)
+            .with_user_impl(user)
+            .stored_info()
+            .credentials()
+            .read(instance.clone())
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::InvalidCredentialsFile(InvalidCredentialsFileError::SecretNotFound(
+                "credentials/local/password".to_string()
+            ))
+        );
+
+        credentials.delete(instance.clone()).unwrap();
+        assert!(credentials.read(instance).unwrap().is_none());
+        assert!(secrets.lock().unwrap().is_empty());
+    }
+
     /// Ensure that read/write works with the real filesystem, starting with
     /// empty config dirs.
     #[test]
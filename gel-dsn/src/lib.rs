@@ -8,6 +8,6 @@ pub mod postgres;
 mod user;
 
 pub use env::{EnvVar, SystemEnvVars};
-pub use file::FileAccess;
+pub use file::{FileAccess, SystemFileAccess};
 pub use host::{Host, HostType};
-pub use user::UserProfile;
+pub use user::{SystemUserProfile, UserProfile};
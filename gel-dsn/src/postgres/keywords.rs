@@ -0,0 +1,165 @@
+//! libpq `keyword=value` connection strings.
+//!
+//! See <https://www.postgresql.org/docs/current/libpq-connect.html#LIBPQ-CONNSTRING-KEYWORD-VALUE>.
+use super::{ParseError, RawConnectionParameters};
+use std::borrow::Cow;
+
+/// Parse a libpq `keyword=value` connection string, ie:
+/// `host=localhost port=5432 dbname='my db'`.
+///
+/// Values may be single-quoted, and a backslash escapes the next character
+/// in both quoted and unquoted values.
+pub fn parse_postgres_keywords(
+    conninfo: &str,
+) -> Result<RawConnectionParameters<'static>, ParseError> {
+    let mut raw_params = RawConnectionParameters::<'static>::default();
+    let mut chars = conninfo.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|c| *c != '=' && !c.is_whitespace()) {
+            name.push(c);
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next() != Some('=') {
+            return Err(ParseError::MissingEquals(name));
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let mut value = String::new();
+        if chars.next_if_eq(&'\'').is_some() {
+            loop {
+                match chars.next() {
+                    None => return Err(ParseError::UnterminatedQuotedString),
+                    Some('\'') => break,
+                    Some('\\') => {
+                        if let Some(c) = chars.next() {
+                            value.push(c);
+                        }
+                    }
+                    Some(c) => value.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                if c == '\\' {
+                    if let Some(c) = chars.next() {
+                        value.push(c);
+                    }
+                } else {
+                    value.push(c);
+                }
+            }
+        }
+
+        // Intentional difference: database is an alias for dbname
+        if name == "database" {
+            name = "dbname".to_string();
+        }
+
+        raw_params.set_by_name(&name, Cow::Owned(value))?;
+    }
+
+    Ok(raw_params)
+}
+
+pub(crate) fn params_to_keywords(params: &RawConnectionParameters) -> String {
+    let mut pairs = vec![];
+    for name in RawConnectionParameters::field_names() {
+        if let Some(value) = params.get_by_name(name) {
+            pairs.push((Cow::Borrowed(name), value));
+        }
+    }
+    if let Some(settings) = &params.server_settings {
+        let mut settings = settings.iter().collect::<Vec<_>>();
+        settings.sort();
+        for (name, value) in settings {
+            pairs.push((Cow::Borrowed(name.as_ref()), Cow::Borrowed(value.as_ref())));
+        }
+    }
+
+    let mut conninfo = String::new();
+    for (name, value) in pairs {
+        if !conninfo.is_empty() {
+            conninfo.push(' ');
+        }
+        conninfo.push_str(&name);
+        conninfo.push('=');
+        if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '\'' || c == '\\')
+        {
+            conninfo.push('\'');
+            for c in value.chars() {
+                if c == '\'' || c == '\\' {
+                    conninfo.push('\\');
+                }
+                conninfo.push(c);
+            }
+            conninfo.push('\'');
+        } else {
+            conninfo.push_str(&value);
+        }
+    }
+    conninfo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::{HostType, SslMode};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_keywords() {
+        let params =
+            parse_postgres_keywords("host=foo port = 5432\tdbname=bar sslmode=require").unwrap();
+        assert_eq!(
+            params,
+            RawConnectionParameters {
+                host: Some(vec![Some(HostType::Hostname("foo".to_string()))]),
+                port: Some(vec![Some(5432)]),
+                dbname: Some("bar".into()),
+                sslmode: Some(SslMode::Require),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_quoting() {
+        let params = parse_postgres_keywords(
+            r"user=my\ user password='it\'s a \\secret' dbname='' application_name=app",
+        )
+        .unwrap();
+        assert_eq!(params.user.as_deref(), Some("my user"));
+        assert_eq!(params.password.as_deref(), Some(r"it's a \secret"));
+        assert_eq!(params.dbname.as_deref(), Some(""));
+        assert_eq!(
+            params.get_by_name("application_name").as_deref(),
+            Some("app")
+        );
+
+        let conninfo = params.to_keywords();
+        assert_eq!(
+            conninfo,
+            r"dbname='' user='my user' password='it\'s a \\secret' application_name=app"
+        );
+        assert_eq!(parse_postgres_keywords(&conninfo).unwrap(), params);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_postgres_keywords("host"),
+            Err(ParseError::MissingEquals("host".to_string()))
+        );
+        assert_eq!(
+            parse_postgres_keywords("host='foo"),
+            Err(ParseError::UnterminatedQuotedString)
+        );
+    }
+}
@@ -1,23 +1,38 @@
 //! Parses DSNs for PostgreSQL database connections.
 //!
+//! Both `postgres://` URLs and libpq `keyword=value` connection strings are
+//! accepted by [`parse_postgres_dsn`].
+//!
 //! There are some small differences with how `libpq` works:
 //!
 //!  - Unrecognized options are supported and collected in a map.
 //!  - `database` is recognized as an alias for `dbname`
 //!  - `[host1,host2]` is considered valid for psql
+//!
+//! Connection service files (`pg_service.conf`) are applied with
+//! [`RawConnectionParameters::apply_service`], and multiple hosts are tried
+//! according to `target_session_attrs` and `load_balance_hosts` with
+//! [`ConnectionParameters::connect_candidates`].
 use gel_stream::SslVersionParseError;
+use std::path::PathBuf;
 
 mod host;
+mod keywords;
 mod params;
 mod passfile;
 mod raw_params;
+mod service;
+mod session;
 mod url;
 
 pub use host::{Host, HostType, ToAddrsSyncVec};
+pub use keywords::parse_postgres_keywords;
 pub use params::{ConnectionParameters, Ssl, SslParameters};
 pub use passfile::{Password, PasswordWarning};
-pub use raw_params::{RawConnectionParameters, SslMode};
-pub use url::{parse_postgres_dsn, parse_postgres_dsn_env};
+pub use raw_params::{LoadBalanceHosts, RawConnectionParameters, SslMode, TargetSessionAttrs};
+pub use service::ServiceWarning;
+pub use session::{NoSuitableHost, SessionState};
+pub use url::{parse_postgres_dsn, parse_postgres_dsn_env, parse_postgres_dsn_env_with_files};
 
 #[derive(Debug, PartialEq, Eq, derive_more::Display, derive_more::From, derive_more::Error)]
 #[allow(clippy::enum_variant_names)]
@@ -53,6 +68,21 @@ pub enum ParseError {
     #[display("Could not determine the connection {_0}")]
     MissingRequiredParameter(#[error(not(source))] String),
 
+    #[display("Missing \"=\" after \"{_0}\" in connection info string")]
+    MissingEquals(#[error(not(source))] String),
+
+    #[display("Unterminated quoted string in connection info string")]
+    UnterminatedQuotedString,
+
+    #[display("Syntax error in service file {_0:?}, line {_1}")]
+    InvalidServiceFile(PathBuf, usize),
+
+    #[display("Nested service specifications are not supported in service file {_0:?}, line {_1}")]
+    NestedService(PathBuf, usize),
+
+    #[display("Definition of service \"{_0}\" not found")]
+    ServiceNotFound(#[error(not(source))] String),
+
     #[display("URL parse error: {_0}")]
     #[from]
     UrlParseError(::url::ParseError),
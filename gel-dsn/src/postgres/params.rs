@@ -1,7 +1,8 @@
 use crate::env::EnvVar;
 
 use super::{
-    parse_postgres_dsn, Host, HostType, ParseError, Password, RawConnectionParameters, SslMode,
+    parse_postgres_dsn, Host, HostType, LoadBalanceHosts, ParseError, Password,
+    RawConnectionParameters, SslMode, TargetSessionAttrs,
 };
 use gel_stream::SslVersion;
 use std::collections::HashMap;
@@ -55,6 +56,8 @@ pub struct ConnectionParameters {
     pub user: String,
     pub password: Password,
     pub connect_timeout: Option<Duration>,
+    pub target_session_attrs: TargetSessionAttrs,
+    pub load_balance_hosts: LoadBalanceHosts,
     pub server_settings: HashMap<String, String>,
     pub ssl: Ssl,
 }
@@ -88,6 +91,13 @@ impl From<ConnectionParameters> for RawConnectionParameters<'static> {
             raw_params.connect_timeout = Some(timeout.as_secs() as isize);
         }
 
+        if val.target_session_attrs != TargetSessionAttrs::default() {
+            raw_params.target_session_attrs = Some(val.target_session_attrs);
+        }
+        if val.load_balance_hosts != LoadBalanceHosts::default() {
+            raw_params.load_balance_hosts = Some(val.load_balance_hosts);
+        }
+
         match val.ssl {
             Ssl::Disable => {
                 raw_params.sslmode = Some(SslMode::Disable);
@@ -230,6 +240,8 @@ impl TryFrom<RawConnectionParameters<'_>> for ConnectionParameters {
             user: user.into_owned(),
             password,
             connect_timeout,
+            target_session_attrs: raw_params.target_session_attrs.unwrap_or_default(),
+            load_balance_hosts: raw_params.load_balance_hosts.unwrap_or_default(),
             server_settings: raw_params
                 .server_settings
                 .unwrap_or_default()
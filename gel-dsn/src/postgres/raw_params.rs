@@ -34,6 +34,8 @@ from_env_impl!(Cow<'_, Path>: |e: Cow<str>| Ok(PathBuf::from(e.into_owned()).int
 from_env_impl!(isize: |e: Cow<str>| parse_connect_timeout(e));
 from_env_impl!(bool: |e: Cow<str>| Ok(e == "1" || e == "true" || e == "on" || e == "yes"));
 from_env_impl!(SslMode: |e: Cow<str>| SslMode::try_from(e.as_ref()));
+from_env_impl!(TargetSessionAttrs: |e: Cow<str>| TargetSessionAttrs::try_from(e.as_ref()));
+from_env_impl!(LoadBalanceHosts: |e: Cow<str>| LoadBalanceHosts::try_from(e.as_ref()));
 from_env_impl!(SslVersion: |e: Cow<str>| e.try_into().map_err(|e: SslVersionParseError| e.into()));
 
 trait ToEnv {
@@ -66,6 +68,8 @@ to_env_impl!(Vec<Option<u16>>: |e| {
 to_env_impl!(isize: |e| Cow::Owned(e.to_string()));
 to_env_impl!(bool: |e| Cow::Owned(if *e { "1" } else { "0" }.to_string()));
 to_env_impl!(SslMode: |e| Cow::Owned(e.to_string()));
+to_env_impl!(TargetSessionAttrs: |e| Cow::Owned(e.to_string()));
+to_env_impl!(LoadBalanceHosts: |e| Cow::Owned(e.to_string()));
 to_env_impl!(SslVersion: |e| Cow::Owned(e.to_string()));
 
 trait RawToOwned {
@@ -99,6 +103,8 @@ trivial_raw_to_owned!(Vec<T>);
 trivial_raw_to_owned!(isize);
 trivial_raw_to_owned!(bool);
 trivial_raw_to_owned!(SslMode);
+trivial_raw_to_owned!(TargetSessionAttrs);
+trivial_raw_to_owned!(LoadBalanceHosts);
 trivial_raw_to_owned!(SslVersion);
 
 macro_rules! define_params {
@@ -289,8 +295,14 @@ define_params!('a,
     /// The password to use when connecting.
     password: Cow<'a, str>, env = "PGPASSWORD";
 
+    /// The name of the connection service to read parameters from.
+    service: Cow<'a, str>, env = "PGSERVICE", query_only = query_only;
     /// The path to the passfile.
     passfile: Cow<'a, Path>, env = "PGPASSFILE", query_only = query_only;
+    /// The properties a server session must have to be accepted.
+    target_session_attrs: TargetSessionAttrs, env = "PGTARGETSESSIONATTRS", query_only = query_only;
+    /// Whether to try hosts in random order.
+    load_balance_hosts: LoadBalanceHosts, env = "PGLOADBALANCEHOSTS", query_only = query_only;
     /// The timeout for the connection to be established.
     connect_timeout: isize, env = "PGCONNECT_TIMEOUT", query_only = query_only;
     /// The SSL mode to use.
@@ -318,6 +330,11 @@ impl RawConnectionParameters<'_> {
     pub fn to_url(&self) -> String {
         super::url::params_to_url(self)
     }
+
+    /// Format the parameters as a libpq `keyword=value` connection string.
+    pub fn to_keywords(&self) -> String {
+        super::keywords::params_to_keywords(self)
+    }
 }
 
 /// SSL mode for PostgreSQL connections.
@@ -379,6 +396,108 @@ impl std::fmt::Display for SslMode {
     }
 }
 
+/// The properties a server session must have to be accepted when trying
+/// multiple hosts.
+///
+/// For more information, see the [PostgreSQL documentation](https://www.postgresql.org/docs/current/libpq-connect.html#LIBPQ-CONNECT-TARGET-SESSION-ATTRS).
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TargetSessionAttrs {
+    /// Any successful connection is acceptable.
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "any"))]
+    Any,
+    /// The session must accept read-write transactions by default.
+    #[cfg_attr(feature = "serde", serde(rename = "read-write"))]
+    ReadWrite,
+    /// The session must not accept read-write transactions by default.
+    #[cfg_attr(feature = "serde", serde(rename = "read-only"))]
+    ReadOnly,
+    /// The server must not be in hot standby mode.
+    #[cfg_attr(feature = "serde", serde(rename = "primary"))]
+    Primary,
+    /// The server must be in hot standby mode.
+    #[cfg_attr(feature = "serde", serde(rename = "standby"))]
+    Standby,
+    /// Prefer a server in hot standby mode, but accept any server if none is
+    /// found.
+    #[cfg_attr(feature = "serde", serde(rename = "prefer-standby"))]
+    PreferStandby,
+}
+
+impl TryFrom<&str> for TargetSessionAttrs {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "any" => Ok(TargetSessionAttrs::Any),
+            "read-write" => Ok(TargetSessionAttrs::ReadWrite),
+            "read-only" => Ok(TargetSessionAttrs::ReadOnly),
+            "primary" => Ok(TargetSessionAttrs::Primary),
+            "standby" => Ok(TargetSessionAttrs::Standby),
+            "prefer-standby" => Ok(TargetSessionAttrs::PreferStandby),
+            _ => Err(ParseError::InvalidParameter(
+                "target_session_attrs".to_string(),
+                s.to_string(),
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for TargetSessionAttrs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TargetSessionAttrs::Any => "any",
+            TargetSessionAttrs::ReadWrite => "read-write",
+            TargetSessionAttrs::ReadOnly => "read-only",
+            TargetSessionAttrs::Primary => "primary",
+            TargetSessionAttrs::Standby => "standby",
+            TargetSessionAttrs::PreferStandby => "prefer-standby",
+        };
+        f.write_str(s)
+    }
+}
+
+/// The order in which multiple hosts are tried.
+///
+/// For more information, see the [PostgreSQL documentation](https://www.postgresql.org/docs/current/libpq-connect.html#LIBPQ-CONNECT-LOAD-BALANCE-HOSTS).
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LoadBalanceHosts {
+    /// Hosts are tried in the order they are specified.
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "disable"))]
+    Disable,
+    /// Hosts are tried in random order.
+    #[cfg_attr(feature = "serde", serde(rename = "random"))]
+    Random,
+}
+
+impl TryFrom<&str> for LoadBalanceHosts {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "disable" => Ok(LoadBalanceHosts::Disable),
+            "random" => Ok(LoadBalanceHosts::Random),
+            _ => Err(ParseError::InvalidParameter(
+                "load_balance_hosts".to_string(),
+                s.to_string(),
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for LoadBalanceHosts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            LoadBalanceHosts::Disable => "disable",
+            LoadBalanceHosts::Random => "random",
+        };
+        f.write_str(s)
+    }
+}
+
 fn parse_host_param(value: &str) -> Result<Vec<Option<HostType>>, ParseError> {
     value
         .split(',')
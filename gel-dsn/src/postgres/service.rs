@@ -0,0 +1,293 @@
+//! libpq connection service files (`pg_service.conf`).
+//!
+//! See <https://www.postgresql.org/docs/current/libpq-pgservice.html>.
+use super::{ParseError, RawConnectionParameters};
+use crate::{EnvVar, FileAccess, UserProfile};
+use std::borrow::Cow;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+const USER_SERVICE_FILE: &str = ".pg_service.conf";
+const SYSTEM_SERVICE_FILE: &str = "pg_service.conf";
+
+/// Parameters that are not part of [`RawConnectionParameters`] but are
+/// recognized by libpq, and passed through to the server.
+const SERVER_SETTINGS: &[&str] = &["application_name", "fallback_application_name", "options"];
+
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServiceWarning {
+    /// The service file exists but could not be read.
+    NotAccessible(PathBuf),
+    /// The service definition contains a key that is not a connection
+    /// parameter. The key is ignored.
+    UnknownKey(PathBuf, String),
+}
+
+impl std::fmt::Display for ServiceWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServiceWarning::NotAccessible(path) => {
+                write!(f, "Service file {path:?} is not accessible")
+            }
+            ServiceWarning::UnknownKey(path, key) => {
+                write!(f, "Service file {path:?} contains unknown key \"{key}\"")
+            }
+        }
+    }
+}
+
+/// Find the `[service]` section in the contents of a service file, returning
+/// its `key=value` pairs in order, or `None` if the service is not defined.
+fn read_service_file<'s>(
+    path: &Path,
+    contents: &'s str,
+    service: &str,
+) -> Result<Option<Vec<(&'s str, &'s str)>>, ParseError> {
+    let mut entries = None;
+    for (line_number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(section) = line.strip_prefix('[') {
+            if entries.is_some() {
+                break;
+            }
+            let Some(section) = section.strip_suffix(']') else {
+                return Err(ParseError::InvalidServiceFile(
+                    path.to_path_buf(),
+                    line_number + 1,
+                ));
+            };
+            if section.trim() == service {
+                entries = Some(vec![]);
+            }
+            continue;
+        }
+
+        // Lines outside of the requested service are not validated, as libpq
+        // does.
+        let Some(entries) = &mut entries else {
+            continue;
+        };
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(ParseError::InvalidServiceFile(
+                path.to_path_buf(),
+                line_number + 1,
+            ));
+        };
+        let key = key.trim_end();
+        if key == "service" {
+            return Err(ParseError::NestedService(
+                path.to_path_buf(),
+                line_number + 1,
+            ));
+        }
+        entries.push((key, value.trim_start()));
+    }
+
+    Ok(entries)
+}
+
+impl RawConnectionParameters<'_> {
+    /// Apply the connection service named by the `service` parameter or the
+    /// `PGSERVICE` environment variable.
+    ///
+    /// The service is looked up in the file named by `PGSERVICEFILE` (or
+    /// `~/.pg_service.conf`), and then in `PGSYSCONFDIR/pg_service.conf`. As
+    /// with libpq, a service setting overrides the environment but never a
+    /// parameter that was set explicitly, so this must be called before
+    /// [`RawConnectionParameters::apply_env`].
+    pub fn apply_service(
+        &mut self,
+        env: impl EnvVar,
+        files: impl FileAccess,
+        user: impl UserProfile,
+    ) -> Result<Vec<ServiceWarning>, ParseError> {
+        let service = match &self.service {
+            Some(service) => service.to_string(),
+            None => match env.read("PGSERVICE") {
+                Ok(service) => service.into_owned(),
+                Err(_) => return Ok(vec![]),
+            },
+        };
+
+        let mut paths = vec![];
+        if let Ok(path) = env.read("PGSERVICEFILE") {
+            paths.push(PathBuf::from(path.as_ref()));
+        } else if let Some(home) = user.homedir() {
+            paths.push(home.join(USER_SERVICE_FILE));
+        }
+        if let Ok(dir) = env.read("PGSYSCONFDIR") {
+            paths.push(Path::new(dir.as_ref()).join(SYSTEM_SERVICE_FILE));
+        }
+
+        let mut warnings = vec![];
+        for path in paths {
+            let contents = match files.read(&path) {
+                Ok(contents) => contents,
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(_) => {
+                    warnings.push(ServiceWarning::NotAccessible(path));
+                    continue;
+                }
+            };
+            let Some(entries) = read_service_file(&path, &contents, &service)? else {
+                continue;
+            };
+
+            let field_names = Self::field_names();
+            for (mut key, value) in entries {
+                // Intentional difference: database is an alias for dbname
+                if key == "database" {
+                    key = "dbname";
+                }
+                if !field_names.contains(&key) && !SERVER_SETTINGS.contains(&key) {
+                    warnings.push(ServiceWarning::UnknownKey(path.clone(), key.to_string()));
+                    continue;
+                }
+                // The first occurrence of a key wins
+                if self.get_by_name(key).is_none() {
+                    self.set_by_name(key, Cow::Owned(value.to_string()))?;
+                }
+            }
+            return Ok(warnings);
+        }
+
+        Err(ParseError::ServiceNotFound(service))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::{HostType, SslMode};
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    const SERVICE_FILE: &str = r#"
+# A comment
+[prod]
+host=db.example.com
+port = 5433
+dbname=app
+sslmode=verify-full
+application_name=ops
+hots=typo
+
+[dev]
+host=localhost
+dbname=dev
+"#;
+
+    #[test]
+    fn test_read_service_file() {
+        let path = Path::new("pg_service.conf");
+        assert_eq!(
+            read_service_file(path, SERVICE_FILE, "dev").unwrap(),
+            Some(vec![("host", "localhost"), ("dbname", "dev")])
+        );
+        assert_eq!(read_service_file(path, SERVICE_FILE, "test").unwrap(), None);
+        assert_eq!(
+            read_service_file(path, "[dev]\nhost\n", "dev"),
+            Err(ParseError::InvalidServiceFile(path.into(), 2))
+        );
+        assert_eq!(
+            read_service_file(path, "[dev]\nservice=prod\n", "dev"),
+            Err(ParseError::NestedService(path.into(), 2))
+        );
+        // Errors in other services are ignored
+        assert_eq!(
+            read_service_file(path, "[prod]\nhost\n[dev]\nhost=localhost", "dev").unwrap(),
+            Some(vec![("host", "localhost")])
+        );
+    }
+
+    #[test]
+    fn test_apply_service() {
+        let files = HashMap::from([(PathBuf::from("/home/edgedb/.pg_service.conf"), SERVICE_FILE)]);
+        let env = HashMap::from([("PGHOST", "envhost"), ("PGUSER", "envuser")]);
+
+        let mut params = RawConnectionParameters {
+            service: Some("prod".into()),
+            dbname: Some("explicit".into()),
+            ..Default::default()
+        };
+        let warnings = params.apply_service(&env, files, "edgedb").unwrap();
+        params.apply_env(&env).unwrap();
+
+        assert_eq!(
+            warnings,
+            vec![ServiceWarning::UnknownKey(
+                PathBuf::from("/home/edgedb/.pg_service.conf"),
+                "hots".to_string()
+            )]
+        );
+        assert_eq!(
+            params.host,
+            Some(vec![Some(HostType::Hostname("db.example.com".to_string()))])
+        );
+        assert_eq!(params.port, Some(vec![Some(5433)]));
+        assert_eq!(params.dbname.as_deref(), Some("explicit"));
+        assert_eq!(params.user.as_deref(), Some("envuser"));
+        assert_eq!(params.sslmode, Some(SslMode::VerifyFull));
+        assert_eq!(
+            params.get_by_name("application_name").as_deref(),
+            Some("ops")
+        );
+    }
+
+    #[test]
+    fn test_service_file_precedence() {
+        let files = HashMap::from([
+            (PathBuf::from("/custom/services.conf"), "[dev]\nhost=custom"),
+            (
+                PathBuf::from("/home/edgedb/.pg_service.conf"),
+                "[dev]\nhost=user",
+            ),
+            (
+                PathBuf::from("/etc/pg_service.conf"),
+                "[dev]\nhost=system\n[test]\nhost=test",
+            ),
+        ]);
+
+        let host = |env: &[(&str, &str)]| {
+            let mut params = RawConnectionParameters::default();
+            params
+                .apply_service(env, files.clone(), "edgedb")
+                .map(|_| params.host)
+        };
+        let hostname = |name: &str| Ok(Some(vec![Some(HostType::Hostname(name.to_string()))]));
+
+        assert_eq!(host(&[]), Ok(None));
+        assert_eq!(host(&[("PGSERVICE", "dev")]), hostname("user"));
+        assert_eq!(
+            host(&[
+                ("PGSERVICE", "dev"),
+                ("PGSERVICEFILE", "/custom/services.conf")
+            ]),
+            hostname("custom")
+        );
+        assert_eq!(
+            host(&[
+                ("PGSERVICE", "dev"),
+                ("PGSERVICEFILE", "/missing.conf"),
+                ("PGSYSCONFDIR", "/etc")
+            ]),
+            hostname("system")
+        );
+        assert_eq!(
+            host(&[("PGSERVICE", "test"), ("PGSYSCONFDIR", "/etc")]),
+            hostname("test")
+        );
+        assert_eq!(
+            host(&[("PGSERVICE", "test")]),
+            Err(ParseError::ServiceNotFound("test".to_string()))
+        );
+    }
+}
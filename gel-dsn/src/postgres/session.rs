@@ -0,0 +1,278 @@
+//! Choosing a host among multiple candidates, using `target_session_attrs`
+//! and `load_balance_hosts`.
+use super::{ConnectionParameters, Host, LoadBalanceHosts, TargetSessionAttrs};
+use rand::seq::SliceRandom;
+use std::future::Future;
+
+/// The state of a server session, as reported by the server after
+/// connecting.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SessionState {
+    /// The server is in hot standby mode (`in_hot_standby`).
+    pub in_hot_standby: bool,
+    /// Transactions are read-only by default
+    /// (`default_transaction_read_only`).
+    pub default_transaction_read_only: bool,
+}
+
+impl SessionState {
+    /// Build the session state from the `ParameterStatus` values reported by
+    /// the server during startup (PostgreSQL 14 and later).
+    pub fn from_server_parameters<'a>(
+        parameters: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        let mut state = SessionState::default();
+        for (name, value) in parameters {
+            match name {
+                "in_hot_standby" => state.in_hot_standby = value == "on",
+                "default_transaction_read_only" => {
+                    state.default_transaction_read_only = value == "on"
+                }
+                _ => {}
+            }
+        }
+        state
+    }
+
+    /// Whether the session accepts read-write transactions by default.
+    pub fn is_read_only(&self) -> bool {
+        self.in_hot_standby || self.default_transaction_read_only
+    }
+}
+
+impl TargetSessionAttrs {
+    /// Whether a session with the given state is acceptable. For
+    /// [`TargetSessionAttrs::PreferStandby`], this only accepts standby
+    /// servers.
+    pub fn matches(&self, state: &SessionState) -> bool {
+        match self {
+            TargetSessionAttrs::Any => true,
+            TargetSessionAttrs::ReadWrite => !state.is_read_only(),
+            TargetSessionAttrs::ReadOnly => state.is_read_only(),
+            TargetSessionAttrs::Primary => !state.in_hot_standby,
+            TargetSessionAttrs::Standby | TargetSessionAttrs::PreferStandby => state.in_hot_standby,
+        }
+    }
+}
+
+/// None of the candidate hosts could be used.
+#[derive(Debug)]
+pub struct NoSuitableHost<E> {
+    pub target_session_attrs: TargetSessionAttrs,
+    /// Hosts that failed to connect, with their errors.
+    pub errors: Vec<(Host, E)>,
+    /// Hosts that connected, but did not match the session attributes.
+    pub mismatched: Vec<Host>,
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for NoSuitableHost<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Could not connect to a server with target_session_attrs={}",
+            self.target_session_attrs
+        )?;
+        for (host, error) in &self.errors {
+            write!(f, "; {}:{}: {error}", host.0, host.1)?;
+        }
+        for host in &self.mismatched {
+            write!(
+                f,
+                "; {}:{}: session attributes do not match",
+                host.0, host.1
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: std::fmt::Debug + std::fmt::Display> std::error::Error for NoSuitableHost<E> {}
+
+impl ConnectionParameters {
+    /// The hosts to try, in order. Hosts are shuffled if `load_balance_hosts`
+    /// is `random`.
+    pub fn candidate_hosts(&self) -> Vec<Host> {
+        let mut hosts = self.hosts.clone();
+        if self.load_balance_hosts == LoadBalanceHosts::Random {
+            hosts.shuffle(&mut rand::rng());
+        }
+        hosts
+    }
+
+    /// Try each candidate host in turn until `connect` returns a session
+    /// matching `target_session_attrs`, and return that host and connection.
+    ///
+    /// `connect` establishes a connection to a single host and returns it with
+    /// the state of the session. Connections that don't match are dropped,
+    /// except that the first one is kept as a fallback for `prefer-standby`.
+    pub async fn connect_candidates<T, E, F, Fut>(
+        &self,
+        mut connect: F,
+    ) -> Result<(Host, T), NoSuitableHost<E>>
+    where
+        F: FnMut(&Host) -> Fut,
+        Fut: Future<Output = Result<(T, SessionState), E>>,
+    {
+        let target_session_attrs = self.target_session_attrs;
+        let mut errors = vec![];
+        let mut mismatched = vec![];
+        let mut fallback = None;
+
+        for host in self.candidate_hosts() {
+            match connect(&host).await {
+                Ok((connection, state)) => {
+                    if target_session_attrs.matches(&state) {
+                        return Ok((host, connection));
+                    }
+                    if target_session_attrs == TargetSessionAttrs::PreferStandby
+                        && fallback.is_none()
+                    {
+                        fallback = Some((host, connection));
+                    } else {
+                        mismatched.push(host);
+                    }
+                }
+                Err(error) => errors.push((host, error)),
+            }
+        }
+
+        if let Some(fallback) = fallback {
+            return Ok(fallback);
+        }
+        Err(NoSuitableHost {
+            target_session_attrs,
+            errors,
+            mismatched,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::postgres::HostType;
+    use std::collections::HashMap;
+
+    const PRIMARY: SessionState = SessionState {
+        in_hot_standby: false,
+        default_transaction_read_only: false,
+    };
+    const READ_ONLY_PRIMARY: SessionState = SessionState {
+        in_hot_standby: false,
+        default_transaction_read_only: true,
+    };
+    const STANDBY: SessionState = SessionState {
+        in_hot_standby: true,
+        default_transaction_read_only: false,
+    };
+
+    fn host(name: &str) -> Host {
+        Host(HostType::Hostname(name.to_string()), 5432)
+    }
+
+    /// Connect to the hosts `primary`, `ro` (a read-only primary), `standby`,
+    /// and `down` (which fails).
+    async fn connect(
+        hosts: &[&str],
+        target_session_attrs: TargetSessionAttrs,
+    ) -> Result<String, NoSuitableHost<String>> {
+        let params = ConnectionParameters {
+            hosts: hosts.iter().map(|name| host(name)).collect(),
+            target_session_attrs,
+            ..Default::default()
+        };
+        let servers = HashMap::from([
+            ("primary", PRIMARY),
+            ("ro", READ_ONLY_PRIMARY),
+            ("standby", STANDBY),
+        ]);
+        let (host, connection) = params
+            .connect_candidates(|host| {
+                let HostType::Hostname(name) = &host.0 else {
+                    unreachable!();
+                };
+                let result = match servers.get(name.as_str()) {
+                    Some(state) => Ok((name.clone(), *state)),
+                    None => Err("connection refused".to_string()),
+                };
+                async move { result }
+            })
+            .await?;
+        assert_eq!(host.0, HostType::Hostname(connection.clone()));
+        Ok(connection)
+    }
+
+    #[tokio::test]
+    async fn test_target_session_attrs() {
+        use TargetSessionAttrs::*;
+
+        let hosts = ["down", "standby", "ro", "primary"];
+        for (target_session_attrs, expected) in [
+            (Any, "standby"),
+            (ReadWrite, "primary"),
+            (ReadOnly, "standby"),
+            (Primary, "ro"),
+            (Standby, "standby"),
+            (PreferStandby, "standby"),
+        ] {
+            assert_eq!(
+                connect(&hosts, target_session_attrs).await.unwrap(),
+                expected,
+                "{target_session_attrs}"
+            );
+        }
+
+        assert_eq!(
+            connect(&["primary", "ro"], PreferStandby).await.unwrap(),
+            "primary"
+        );
+
+        let error = connect(&["down", "primary"], Standby).await.unwrap_err();
+        assert_eq!(
+            error.errors,
+            vec![(host("down"), "connection refused".to_string())]
+        );
+        assert_eq!(error.mismatched, vec![host("primary")]);
+        assert_eq!(
+            error.to_string(),
+            "Could not connect to a server with target_session_attrs=standby; \
+            down:5432: connection refused; \
+            primary:5432: session attributes do not match"
+        );
+    }
+
+    #[test]
+    fn test_session_state() {
+        assert_eq!(
+            SessionState::from_server_parameters([
+                ("server_version", "17.0"),
+                ("in_hot_standby", "on"),
+                ("default_transaction_read_only", "off"),
+            ]),
+            STANDBY
+        );
+        assert!(STANDBY.is_read_only());
+        assert!(READ_ONLY_PRIMARY.is_read_only());
+        assert!(!PRIMARY.is_read_only());
+    }
+
+    #[test]
+    fn test_candidate_hosts() {
+        let hosts = (0..32)
+            .map(|i| host(&format!("host{i}")))
+            .collect::<Vec<_>>();
+        let mut params = ConnectionParameters {
+            hosts: hosts.clone(),
+            ..Default::default()
+        };
+        assert_eq!(params.candidate_hosts(), hosts);
+
+        params.load_balance_hosts = LoadBalanceHosts::Random;
+        let mut shuffled = params.candidate_hosts();
+        assert_ne!(shuffled, hosts);
+        shuffled.sort();
+        let mut sorted = hosts;
+        sorted.sort();
+        assert_eq!(shuffled, sorted);
+    }
+}
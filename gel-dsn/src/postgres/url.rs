@@ -1,4 +1,5 @@
 use crate::env::EnvVar;
+use crate::{FileAccess, UserProfile};
 
 use super::*;
 use ::url::Url;
@@ -24,7 +25,17 @@ fn maybe_decode(str: Cow<str>) -> Cow<str> {
     }
 }
 
-pub fn parse_postgres_dsn(url_str: &str) -> Result<RawConnectionParameters, ParseError> {
+/// Parse a Postgres DSN, either a `postgres://` URL or a libpq
+/// `keyword=value` connection string.
+pub fn parse_postgres_dsn(dsn: &str) -> Result<RawConnectionParameters, ParseError> {
+    if dsn.starts_with("postgres://") || dsn.starts_with("postgresql://") {
+        parse_postgres_url(dsn)
+    } else {
+        parse_postgres_keywords(dsn)
+    }
+}
+
+fn parse_postgres_url(url_str: &str) -> Result<RawConnectionParameters, ParseError> {
     let url_str = if let Some(url) = url_str.strip_prefix("postgres://") {
         url
     } else if let Some(url) = url_str.strip_prefix("postgresql://") {
@@ -159,6 +170,10 @@ pub fn parse_postgres_dsn(url_str: &str) -> Result<RawConnectionParameters, Pars
     Ok(raw_params)
 }
 
+/// Parse a Postgres DSN, then apply the `PG*` variables from `env`.
+///
+/// Connection services are not resolved. Use
+/// [`parse_postgres_dsn_env_with_files`] to apply them as well.
 pub fn parse_postgres_dsn_env(
     url_str: &str,
     env: impl EnvVar,
@@ -168,6 +183,28 @@ pub fn parse_postgres_dsn_env(
     raw_params.try_into()
 }
 
+/// Parse a Postgres DSN, then apply its connection service and the `PG*`
+/// variables from `env`.
+///
+/// Service files are read through `files`, as named by the `PGSERVICEFILE`
+/// and `PGSYSCONFDIR` variables in `env` or found in the home directory of
+/// `user`.
+pub fn parse_postgres_dsn_env_with_files(
+    url_str: &str,
+    env: impl EnvVar,
+    files: impl FileAccess,
+    user: impl UserProfile,
+) -> Result<ConnectionParameters, ParseError> {
+    let mut raw_params = parse_postgres_dsn(url_str)?;
+    let _warnings = raw_params.apply_service(&env, files, user)?;
+    #[cfg(feature = "log")]
+    for warning in _warnings {
+        log::warn!("{warning}");
+    }
+    raw_params.apply_env(env)?;
+    raw_params.try_into()
+}
+
 pub(crate) fn params_to_url(params: &RawConnectionParameters) -> String {
     let mut url = String::from("postgresql://");
     let mut params_vec: Vec<(Cow<'_, str>, Cow<'_, str>)> = Vec::new();
@@ -403,6 +440,8 @@ mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
     use rstest::rstest;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
     use std::time::Duration;
 
     #[rstest]
@@ -455,6 +494,41 @@ mod tests {
         assert_eq!(expected_host_types, result);
     }
 
+    #[test]
+    fn test_parse_dsn_service() {
+        let files = HashMap::from([(
+            PathBuf::from("/home/edgedb/.pg_service.conf"),
+            "[dev]\ndbname=servicedb\nuser=serviceuser\n",
+        )]);
+        let parse = |url: &str, env: &[(&str, &str)]| {
+            parse_postgres_dsn_env_with_files(url, env, files.clone(), "edgedb")
+        };
+
+        let params = parse(
+            "postgres://?service=dev&dbname=explicit",
+            &[("PGUSER", "envuser")],
+        )
+        .unwrap();
+        assert_eq!(params.database, "explicit");
+        assert_eq!(params.user, "serviceuser");
+
+        let params = parse("postgres://", &[("PGSERVICE", "dev")]).unwrap();
+        assert_eq!(params.database, "servicedb");
+
+        assert_eq!(
+            parse("postgres://?service=missing", &[]),
+            Err(ParseError::ServiceNotFound("missing".to_string()))
+        );
+
+        // Services are only resolved when files are provided.
+        let params = parse_postgres_dsn_env(
+            "postgres://",
+            [("PGSERVICE", "dev"), ("PGUSER", "envuser")].as_slice(),
+        )
+        .unwrap();
+        assert_eq!(params.database, "envuser");
+    }
+
     #[test]
     fn test_parse_dsn() {
         assert_eq!(
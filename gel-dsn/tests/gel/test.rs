@@ -7,7 +7,10 @@ use std::{
 };
 
 use gel_dsn::{
-    gel::{Builder, ConnectionOptions, Params, Traces, Warnings},
+    gel::{
+        Builder, Config, ConnectionOptions, CredentialsError, DatabaseBranch, InstanceName, Params,
+        TcpKeepalive, Traces, Warnings, DEFAULT_CONNECT_TIMEOUT, DEFAULT_PORT,
+    },
     EnvVar, FileAccess, UserProfile,
 };
 use serde::{Deserialize, Serialize};
@@ -214,6 +217,85 @@ impl UserProfile for &ConnectionTestcase {
     }
 }
 
+/// The error that [`Config::to_env_vars`] is expected to return, if the
+/// config can't be exported.
+fn expected_export_error(config: &Config) -> Option<CredentialsError> {
+    let not_exportable = |what| Some(CredentialsError::NotExportable(what));
+    if config.connect_timeout != DEFAULT_CONNECT_TIMEOUT {
+        return not_exportable("connect timeout");
+    }
+    if config.max_concurrency.is_some() {
+        return not_exportable("maximum concurrency");
+    }
+    if config.tcp_keepalive != TcpKeepalive::Default {
+        return not_exportable("TCP keepalive");
+    }
+    let target = config.host.target_name().ok();
+    let Some((_, port)) = target.as_ref().and_then(|target| target.tcp()) else {
+        return Some(CredentialsError::NoTcpAddress);
+    };
+    let use_dsn =
+        matches!(config.db, DatabaseBranch::Ambiguous(_)) || !config.server_settings.is_empty();
+    if let Some(InstanceName::Cloud(..)) = config.instance_name {
+        if use_dsn || port != DEFAULT_PORT {
+            return not_exportable("cloud instance address");
+        }
+        return None;
+    }
+    if let DatabaseBranch::Ambiguous(name) = &config.db {
+        // DSN paths are not percent-decoded, so the name must not need encoding
+        let mut url = url::Url::parse("gel://").unwrap();
+        url.set_path(name);
+        if url.path().strip_prefix('/') != Some(name.as_str()) {
+            return not_exportable("database/branch name");
+        }
+    }
+    if config
+        .server_settings
+        .keys()
+        .any(|key| key.ends_with("_env") || key.ends_with("_file"))
+    {
+        return not_exportable("server setting");
+    }
+    None
+}
+
+/// Check that the config is rebuilt from its exported environment variables
+/// alone, without any files, or that it fails to export as expected.
+fn check_env_round_trip(config: &Config) -> Result<(), String> {
+    let expected_error = expected_export_error(config);
+    let vars = match (config.to_env_vars(), expected_error) {
+        (Ok(vars), None) => vars,
+        (Err(e), Some(expected)) if e == expected => return Ok(()),
+        (Ok(vars), Some(expected)) => {
+            return Err(format!("expected {expected:?}, exported {vars:?}"));
+        }
+        (Err(e), expected) => return Err(format!("expected {expected:?}, got {e:?}")),
+    };
+    let env = vars.iter().cloned().collect::<HashMap<_, _>>();
+    let rebuilt = Builder::default()
+        .without_system()
+        .with_env_impl(env)
+        .build_parse_error()
+        .map_err(|e| format!("{e} from {vars:?}"))?;
+
+    // Local instance names refer to stored credentials and aren't exported
+    let mut expected = config.clone();
+    if let Some(InstanceName::Local(_)) = expected.instance_name {
+        expected.instance_name = None;
+    }
+    if rebuilt != expected {
+        return Err(format!(
+            "{}",
+            pretty_assertions::StrComparison::new(
+                &format!("{expected:#?}"),
+                &format!("{rebuilt:#?}")
+            )
+        ));
+    }
+    Ok(())
+}
+
 fn main() {
     let testcases: Vec<ConnectionTestcase> = serde_json::from_str(JSON).unwrap();
     let mut failed = 0;
@@ -352,9 +434,18 @@ fn main() {
             fuzzy_match = true;
         }
 
-        if actual == expected || fuzzy_match {
+        let round_trip = match &result {
+            Ok(config) => check_env_round_trip(config),
+            Err(_) => Ok(()),
+        };
+
+        if (actual == expected || fuzzy_match) && round_trip.is_ok() {
             passed += 1;
             traces.trace(&format!("Passed: {}", testcase.name));
+        } else if let Err(e) = round_trip {
+            failed += 1;
+            println!("---------------------------------------------");
+            println!("Failed environment round trip: {}: {e}", testcase.name);
         } else {
             failed += 1;
             traces.trace(&format!("Failed: {}", testcase.name));
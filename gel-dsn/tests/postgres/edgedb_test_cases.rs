@@ -289,3 +289,16 @@ test_case!(
     error = "",
     expect_libpq_mismatch = "libpq parses hostnames with colons"
 );
+
+test_case!(target_session_attrs_env, "postgresql://host1,host2/db", env={
+    "PGUSER": "user",
+    "PGTARGETSESSIONATTRS": "standby",
+    "PGLOADBALANCEHOSTS": "random"
+}, output={
+    "user": "user",
+    "dbname": "db",
+    "host": "host1,host2",
+    "port": "5432,5432",
+    "target_session_attrs": "standby",
+    "load_balance_hosts": "random"
+});
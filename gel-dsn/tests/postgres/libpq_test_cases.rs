@@ -327,3 +327,112 @@ test_case!(sslmode_verify_full, "postgresql://host?sslmode=verify-full", output=
     "host": "host",
     "sslmode": "verify-full"
 }, no_env=no_env);
+
+// Keyword/value connection strings.
+
+test_case!(keywords_full, "host=host port=12345 dbname=db user=uri-user password=secret", output={
+    "user": "uri-user",
+    "password": "secret",
+    "dbname": "db",
+    "host": "host",
+    "port": "12345"
+}, no_env=no_env);
+
+test_case!(keywords_empty, "", output = {}, no_env = no_env);
+
+test_case!(keywords_whitespace, "  host = host\tport=\n12345  ", output={
+    "host": "host",
+    "port": "12345"
+}, no_env=no_env);
+
+test_case!(keywords_quoted, "dbname='my db' user='it\\'s' password='back\\\\slash'", output={
+    "dbname": "my db",
+    "user": "it's",
+    "password": "back\\slash"
+}, no_env=no_env);
+
+test_case!(keywords_escaped, "dbname=my\\ db user=a\\'b", output={
+    "dbname": "my db",
+    "user": "a'b"
+}, no_env=no_env);
+
+test_case!(keywords_empty_value, "host='' dbname=db", output={
+    "host": "",
+    "dbname": "db"
+}, no_env=no_env);
+
+test_case!(keywords_multi_host, "host=host1,host2 port=1111,2222", output={
+    "host": "host1,host2",
+    "port": "1111,2222"
+}, no_env=no_env);
+
+test_case!(keywords_socket_dir, "host=/path/to/socket/dir", output={
+    "host": "/path/to/socket/dir"
+}, no_env=no_env);
+
+test_case!(keywords_ipv6, "host=2001:db8::1234 port=5433", output={
+    "host": "2001:db8::1234",
+    "port": "5433"
+}, no_env=no_env);
+
+test_case!(keywords_sslmode, "host=host sslmode=verify-full", output={
+    "host": "host",
+    "sslmode": "verify-full"
+}, no_env=no_env);
+
+test_case!(
+    keywords_unknown,
+    "host=host uzer=x",
+    output = {
+        "uzer": "x",
+        "host": "host",
+    },
+    expect_libpq_mismatch = "Arbitrary keywords are supported",
+    no_env = no_env
+);
+
+test_case!(
+    keywords_missing_equals,
+    "host",
+    error = "missing \"=\" after \"host\" in connection info string",
+    no_env = no_env
+);
+
+test_case!(
+    keywords_unterminated_quote,
+    "host='host",
+    error = "unterminated quoted string in connection info string",
+    no_env = no_env
+);
+
+test_case!(target_session_attrs, "postgresql://host1,host2/db?target_session_attrs=prefer-standby&load_balance_hosts=random", output={
+    "host": "host1,host2",
+    "port": ",",
+    "dbname": "db",
+    "target_session_attrs": "prefer-standby",
+    "load_balance_hosts": "random"
+}, no_env=no_env);
+
+test_case!(
+    keywords_target_session_attrs,
+    "host=host1,host2 target_session_attrs=read-write",
+    output = {
+        "host": "host1,host2",
+        "target_session_attrs": "read-write"
+    },
+    no_env = no_env
+);
+
+test_case!(
+    target_session_attrs_invalid,
+    "postgresql://host?target_session_attrs=secondary",
+    error = "invalid target_session_attrs value: \"secondary\"",
+    no_env = no_env
+);
+
+test_case!(
+    load_balance_hosts_invalid,
+    "postgresql://host?load_balance_hosts=roundrobin",
+    error = "invalid load_balance_hosts value: \"roundrobin\"",
+    no_env = no_env
+);
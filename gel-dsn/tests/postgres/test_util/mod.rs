@@ -57,6 +57,19 @@ pub(crate) fn test(
         "Did not maintain fidelity through the roundtrip! ({url:?})"
     );
 
+    let conninfo = ours_no_env.to_keywords();
+    let roundtrip = match parse_postgres_dsn(&conninfo) {
+        Err(res) => {
+            panic!("Expected roundtripped connection string to pass {conninfo:?}, but instead failed:\n{res:#?}")
+        }
+        Ok(res) => res,
+    };
+    assert_eq_map!(
+        roundtrip,
+        ours_no_env,
+        "Did not maintain fidelity through the roundtrip! ({conninfo:?})"
+    );
+
     if no_env {
         assert_eq_map!(
             expected,
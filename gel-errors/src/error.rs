@@ -9,15 +9,15 @@ use crate::kinds::UserError;
 use crate::kinds::{error_name, tag_check};
 use crate::traits::{ErrorKind, Field};
 
-const FIELD_HINT: u16 = 0x_00_01;
-const FIELD_DETAILS: u16 = 0x_00_02;
+pub(crate) const FIELD_HINT: u16 = 0x_00_01;
+pub(crate) const FIELD_DETAILS: u16 = 0x_00_02;
 const FIELD_SERVER_TRACEBACK: u16 = 0x_01_01;
 
 // TODO(tailhook) these might be deprecated?
-const FIELD_POSITION_START: u16 = 0x_FF_F1;
-const FIELD_POSITION_END: u16 = 0x_FF_F2;
-const FIELD_LINE: u16 = 0x_FF_F3;
-const FIELD_COLUMN: u16 = 0x_FF_F4;
+pub(crate) const FIELD_POSITION_START: u16 = 0x_FF_F1;
+pub(crate) const FIELD_POSITION_END: u16 = 0x_FF_F2;
+pub(crate) const FIELD_LINE: u16 = 0x_FF_F3;
+pub(crate) const FIELD_COLUMN: u16 = 0x_FF_F4;
 
 /// Error type returned from Gel database calls.
 // This includes boxed error, because propagating through call chain is
@@ -84,6 +84,8 @@ pub mod kinds;
 
 #[cfg(feature = "miette")]
 pub mod miette;
+#[cfg(feature = "serde")]
+pub mod serialization;
 
 pub use error::{Error, Tag};
 pub use kinds::*;
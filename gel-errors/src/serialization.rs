@@ -0,0 +1,201 @@
+//! Serde support for Gel errors. Add "serde" feature flag to enable.
+//!
+//! Errors are serialized as a map of the code, messages and well-known
+//! headers, so they can be passed between services:
+//!
+//! ```json
+//! {
+//!   "code": 67174656,
+//!   "kind": "EdgeQLSyntaxError",
+//!   "message": "Unexpected 'selec'",
+//!   "contexts": [],
+//!   "hint": "did you mean 'select'?",
+//!   "position_start": 0,
+//!   "position_end": 5
+//! }
+//! ```
+//!
+//! The source of the error is kept as text only, and custom fields
+//! (except [QueryText]) are not serialized. The `kind` is informational
+//! and is ignored when deserializing.
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::{Error, Inner, Source};
+use crate::error::{FIELD_COLUMN, FIELD_DETAILS, FIELD_HINT, FIELD_LINE};
+use crate::error::{FIELD_POSITION_END, FIELD_POSITION_START};
+use crate::fields::QueryText;
+
+#[derive(Serialize, Deserialize)]
+struct Repr<'a> {
+    code: u32,
+    #[serde(default, skip_deserializing)]
+    kind: Cow<'a, str>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    message: Option<Cow<'a, str>>,
+    #[serde(default)]
+    contexts: Vec<Cow<'a, str>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hint: Option<Cow<'a, str>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    details: Option<Cow<'a, str>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position_start: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position_end: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    headers: HashMap<u16, Cow<'a, str>>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    annotations: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    query: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+}
+
+/// Source of a deserialized error, which only keeps the message.
+#[derive(Debug)]
+struct SourceMessage(String);
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr = Repr {
+            code: self.code(),
+            kind: self.kind_name().into(),
+            message: self.initial_message().map(Into::into),
+            contexts: self.contexts().map(Into::into).collect(),
+            hint: self.hint().map(Into::into),
+            details: self.details().map(Into::into),
+            position_start: self.position_start(),
+            position_end: self.position_end(),
+            line: self.line(),
+            column: self.column(),
+            headers: self
+                .unknown_headers()
+                .map(|(key, value)| (*key, String::from_utf8_lossy(value)))
+                .collect(),
+            annotations: self.annotations().clone(),
+            query: self.get::<QueryText>().cloned(),
+            source: std::error::Error::source(self).map(|e| e.to_string()),
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Error {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Error, D::Error> {
+        let repr = Repr::deserialize(deserializer)?;
+        let mut headers = repr
+            .headers
+            .into_iter()
+            .map(|(key, value)| (key, bytes::Bytes::from(value.into_owned())))
+            .collect::<HashMap<_, _>>();
+        let text = [(FIELD_HINT, repr.hint), (FIELD_DETAILS, repr.details)];
+        for (key, value) in text {
+            if let Some(value) = value {
+                headers.insert(key, bytes::Bytes::from(value.into_owned()));
+            }
+        }
+        let numbers = [
+            (FIELD_POSITION_START, repr.position_start),
+            (FIELD_POSITION_END, repr.position_end),
+            (FIELD_LINE, repr.line),
+            (FIELD_COLUMN, repr.column),
+        ];
+        for (key, value) in numbers {
+            if let Some(value) = value {
+                headers.insert(key, bytes::Bytes::from(value.to_string()));
+            }
+        }
+        let messages = repr
+            .message
+            .into_iter()
+            .chain(repr.contexts)
+            .map(|m| Cow::Owned(m.into_owned()))
+            .collect();
+        let error = Error(Box::new(Inner {
+            code: repr.code,
+            messages,
+            error: repr.source.map(|s| Source::Box(Box::new(SourceMessage(s)))),
+            headers,
+            annotations: repr.annotations,
+            fields: HashMap::new(),
+        }));
+        Ok(match repr.query {
+            Some(query) => error.set::<QueryText>(query),
+            None => error,
+        })
+    }
+}
+
+impl fmt::Display for SourceMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for SourceMessage {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::fields::QueryText;
+    use crate::{EdgeQLSyntaxError, Error, ErrorKind, UserError};
+
+    #[test]
+    fn round_trip() {
+        let error = EdgeQLSyntaxError::with_message("Unexpected 'selec'")
+            .context("while compiling")
+            .with_headers(HashMap::from([
+                (0x0001, "did you mean 'select'?".into()),
+                (0x0002, "the details".into()),
+                (0x0101, "Traceback".into()),
+                (0xFFF1, "0".into()),
+                (0xFFF2, "5".into()),
+                (0xFFF3, "1".into()),
+                (0xFFF4, "1".into()),
+            ]))
+            .with_annotations(HashMap::from([("key".into(), "value".into())]))
+            .set::<QueryText>("selec 1");
+        let json = serde_json::to_string(&error).unwrap();
+        let decoded: Error = serde_json::from_str(&json).unwrap();
+
+        assert!(decoded.is::<EdgeQLSyntaxError>());
+        assert_eq!(decoded.initial_message(), Some("Unexpected 'selec'"));
+        assert_eq!(
+            decoded.contexts().collect::<Vec<_>>(),
+            vec!["while compiling"]
+        );
+        assert_eq!(decoded.hint(), Some("did you mean 'select'?"));
+        assert_eq!(decoded.details(), Some("the details"));
+        assert_eq!(decoded.server_traceback(), Some("Traceback"));
+        assert_eq!(decoded.position_start(), Some(0));
+        assert_eq!(decoded.position_end(), Some(5));
+        assert_eq!(decoded.line(), Some(1));
+        assert_eq!(decoded.column(), Some(1));
+        assert_eq!(decoded.headers(), error.headers());
+        assert_eq!(decoded.annotations(), error.annotations());
+        assert_eq!(decoded.get::<QueryText>().unwrap(), "selec 1");
+        assert_eq!(decoded.to_string(), error.to_string());
+    }
+
+    #[test]
+    fn source() {
+        let error = UserError::with_source(std::io::Error::other("disk full"));
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["kind"], "UserError");
+        assert_eq!(json["source"], "disk full");
+
+        let decoded: Error = serde_json::from_value(json).unwrap();
+        assert!(decoded.is::<UserError>());
+        assert_eq!(format!("{decoded:#}"), format!("{error:#}"));
+    }
+}
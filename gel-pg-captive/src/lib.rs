@@ -585,6 +585,14 @@ pub struct PostgresCluster {
 }
 
 impl PostgresCluster {
+    pub fn primary(&self) -> &PostgresProcess {
+        &self.primary
+    }
+
+    pub fn standbys(&self) -> &[PostgresProcess] {
+        &self.standbys
+    }
+
     #[cfg(unix)]
     pub fn shutdown_timeout(
         self,
@@ -0,0 +1,295 @@
+//! Mapping between Gel errors and Postgres SQLSTATE codes.
+//!
+//! Used by Postgres-facing frontends to report Gel errors with a matching
+//! SQLSTATE, and to turn errors from a Postgres server into Gel errors.
+use std::collections::HashMap;
+
+use gel_errors::*;
+
+use crate::errors::{PgError, PgErrorSeverity, PgServerError, PgServerErrorField};
+
+// Gel error headers, see the `gel-errors` crate.
+const FIELD_HINT: u16 = 0x_00_01;
+const FIELD_DETAILS: u16 = 0x_00_02;
+const FIELD_POSITION_START: u16 = 0x_FF_F1;
+
+type IsKind = fn(&Error) -> bool;
+type BuildKind = fn() -> Error;
+
+/// SQLSTATE for Gel error kinds. More specific kinds come first, since
+/// [`Error::is`] matches any ancestor.
+const TO_PG: &[(IsKind, &[u8; 5])] = &[
+    (Error::is::<IdleSessionTimeoutError>, b"57P05"),
+    (Error::is::<IdleTransactionTimeoutError>, b"25P03"),
+    (Error::is::<TransactionTimeoutError>, b"25P04"),
+    (Error::is::<SessionTimeoutError>, b"57014"),
+    (Error::is::<UnsupportedFeatureError>, b"0A000"),
+    (Error::is::<UnsupportedCapabilityError>, b"0A000"),
+    (Error::is::<UnsupportedBackendFeatureError>, b"0A000"),
+    (Error::is::<ProtocolError>, b"08P01"),
+    (Error::is::<InvalidSyntaxError>, b"42601"),
+    (Error::is::<InvalidTypeError>, b"42804"),
+    (Error::is::<UnknownModuleError>, b"3F000"),
+    (Error::is::<UnknownDatabaseError>, b"3D000"),
+    (Error::is::<UnknownParameterError>, b"42P02"),
+    (Error::is::<UnknownLinkError>, b"42703"),
+    (Error::is::<UnknownPropertyError>, b"42703"),
+    (Error::is::<InvalidReferenceError>, b"42704"),
+    (Error::is::<DuplicateModuleDefinitionError>, b"42P06"),
+    (Error::is::<DuplicateLinkDefinitionError>, b"42701"),
+    (Error::is::<DuplicatePropertyDefinitionError>, b"42701"),
+    (Error::is::<DuplicateDatabaseDefinitionError>, b"42P04"),
+    (Error::is::<DuplicateFunctionDefinitionError>, b"42723"),
+    (Error::is::<DuplicateDefinitionError>, b"42710"),
+    (Error::is::<InvalidModuleDefinitionError>, b"42P15"),
+    (Error::is::<InvalidFunctionDefinitionError>, b"42P13"),
+    (Error::is::<SchemaDefinitionError>, b"42P17"),
+    (Error::is::<QueryError>, b"42000"),
+    (Error::is::<DivisionByZeroError>, b"22012"),
+    (Error::is::<NumericOutOfRangeError>, b"22003"),
+    (Error::is::<AccessPolicyError>, b"42501"),
+    (Error::is::<QueryAssertionError>, b"P0001"),
+    (Error::is::<InvalidValueError>, b"22023"),
+    (Error::is::<ConstraintViolationError>, b"23514"),
+    (Error::is::<CardinalityViolationError>, b"21000"),
+    (Error::is::<MissingRequiredError>, b"23502"),
+    (Error::is::<IntegrityError>, b"23000"),
+    (Error::is::<TransactionDeadlockError>, b"40P01"),
+    (Error::is::<TransactionConflictError>, b"40001"),
+    (Error::is::<TransactionError>, b"25000"),
+    (Error::is::<ConfigurationError>, b"F0000"),
+    (Error::is::<AuthenticationError>, b"28P01"),
+    (Error::is::<AccessError>, b"42501"),
+    (Error::is::<ServerOfflineError>, b"57P01"),
+    (Error::is::<AvailabilityError>, b"57P03"),
+    (Error::is::<WarningMessage>, b"01000"),
+    (Error::is::<LogMessage>, b"00000"),
+];
+
+/// Gel error kinds for SQLSTATE codes, matched by the full code first.
+const FROM_PG: &[(&[u8; 5], BuildKind)] = &[
+    (b"0A000", UnsupportedFeatureError::build),
+    (b"08P01", ProtocolError::build),
+    (b"42601", InvalidSyntaxError::build),
+    (b"42804", InvalidTypeError::build),
+    (b"3F000", UnknownModuleError::build),
+    (b"3D000", UnknownDatabaseError::build),
+    (b"42P02", UnknownParameterError::build),
+    (b"42703", UnknownPropertyError::build),
+    (b"42P01", InvalidReferenceError::build),
+    (b"42704", InvalidReferenceError::build),
+    (b"42883", InvalidReferenceError::build),
+    (b"42P06", DuplicateModuleDefinitionError::build),
+    (b"42701", DuplicatePropertyDefinitionError::build),
+    (b"42P04", DuplicateDatabaseDefinitionError::build),
+    (b"42723", DuplicateFunctionDefinitionError::build),
+    (b"42710", DuplicateDefinitionError::build),
+    (b"42P07", DuplicateDefinitionError::build),
+    (b"42P15", InvalidModuleDefinitionError::build),
+    (b"42P13", InvalidFunctionDefinitionError::build),
+    (b"42P17", SchemaDefinitionError::build),
+    (b"42501", AccessError::build),
+    (b"57P05", IdleSessionTimeoutError::build),
+    (b"57014", QueryTimeoutError::build),
+    (b"25P03", IdleTransactionTimeoutError::build),
+    (b"25P04", TransactionTimeoutError::build),
+    (b"22012", DivisionByZeroError::build),
+    (b"22003", NumericOutOfRangeError::build),
+    (b"P0001", QueryAssertionError::build),
+    (b"23502", MissingRequiredError::build),
+    (b"23503", ConstraintViolationError::build),
+    (b"23505", ConstraintViolationError::build),
+    (b"23514", ConstraintViolationError::build),
+    (b"23P01", ConstraintViolationError::build),
+    (b"40001", TransactionSerializationError::build),
+    (b"40P01", TransactionDeadlockError::build),
+    (b"57P01", ServerOfflineError::build),
+    (b"57P03", BackendUnavailableError::build),
+];
+
+/// Gel error kinds for SQLSTATE classes, used when the full code is unknown.
+const FROM_PG_CLASS: &[(&[u8; 2], BuildKind)] = &[
+    (b"01", WarningMessage::build),
+    (b"08", AvailabilityError::build),
+    (b"0A", UnsupportedFeatureError::build),
+    (b"21", CardinalityViolationError::build),
+    (b"22", InvalidValueError::build),
+    (b"23", IntegrityError::build),
+    (b"25", TransactionError::build),
+    (b"28", AuthenticationError::build),
+    (b"3D", UnknownDatabaseError::build),
+    (b"3F", UnknownModuleError::build),
+    (b"40", TransactionError::build),
+    (b"42", QueryError::build),
+    (b"57", AvailabilityError::build),
+    (b"F0", ConfigurationError::build),
+];
+
+impl PgError {
+    /// The SQLSTATE to report a Gel error with.
+    pub fn from_gel_error(error: &Error) -> PgError {
+        let code = TO_PG
+            .iter()
+            .find(|(is, _)| is(error))
+            .map(|(_, code)| **code)
+            .unwrap_or(*b"XX000");
+        PgError::from_code(code)
+    }
+
+    /// An empty Gel error of the kind matching this SQLSTATE.
+    pub fn to_gel_error(self) -> Error {
+        let code = self.to_code();
+        if let Some((_, build)) = FROM_PG.iter().find(|(c, _)| **c == code) {
+            return build();
+        }
+        if let Some((_, build)) = FROM_PG_CLASS
+            .iter()
+            .find(|(c, _)| **c == [code[0], code[1]])
+        {
+            return build();
+        }
+        InternalServerError::build()
+    }
+}
+
+impl From<&Error> for PgServerError {
+    fn from(error: &Error) -> PgServerError {
+        let mut extra = HashMap::new();
+        if let Some(details) = error.details() {
+            extra.insert(PgServerErrorField::Detail, details.to_string());
+        }
+        if let Some(hint) = error.hint() {
+            extra.insert(PgServerErrorField::Hint, hint.to_string());
+        }
+        if let Some(position) = error.position_start() {
+            // Postgres positions start at 1
+            extra.insert(PgServerErrorField::Position, (position + 1).to_string());
+        }
+        let contexts = error.contexts().collect::<Vec<_>>();
+        if !contexts.is_empty() {
+            extra.insert(PgServerErrorField::Where, contexts.join("\n"));
+        }
+        let message = error.initial_message().unwrap_or(error.kind_name());
+        let mut pg_error = PgServerError::new(PgError::from_gel_error(error), message, extra);
+        if error.is::<WarningMessage>() {
+            pg_error.severity = PgErrorSeverity::Warning;
+        } else if error.is::<LogMessage>() {
+            pg_error.severity = PgErrorSeverity::Log;
+        }
+        pg_error
+    }
+}
+
+impl From<PgServerError> for Error {
+    fn from(pg_error: PgServerError) -> Error {
+        let mut headers = HashMap::new();
+        let mut contexts = Vec::new();
+        for (field, value) in pg_error.extra {
+            match field {
+                PgServerErrorField::Detail => {
+                    headers.insert(FIELD_DETAILS, value.into());
+                }
+                PgServerErrorField::Hint => {
+                    headers.insert(FIELD_HINT, value.into());
+                }
+                PgServerErrorField::Position => {
+                    if let Some(position) =
+                        value.parse::<usize>().ok().and_then(|p| p.checked_sub(1))
+                    {
+                        headers.insert(FIELD_POSITION_START, position.to_string().into());
+                    }
+                }
+                PgServerErrorField::Where => {
+                    contexts.extend(value.lines().map(String::from));
+                }
+                _ => {}
+            }
+        }
+        let mut error = pg_error
+            .code
+            .to_gel_error()
+            .context(pg_error.message)
+            .with_headers(headers);
+        for context in contexts {
+            error = error.context(context);
+        }
+        error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gel_to_pg() {
+        let code = |error: Error| PgError::from_gel_error(&error).to_string();
+        assert_eq!(code(DivisionByZeroError::build()), "22012");
+        assert_eq!(code(InvalidValueError::build()), "22023");
+        assert_eq!(code(EdgeQLSyntaxError::build()), "42601");
+        assert_eq!(code(TransactionSerializationError::build()), "40001");
+        assert_eq!(code(QueryTimeoutError::build()), "57014");
+        assert_eq!(code(IdleSessionTimeoutError::build()), "57P05");
+        assert_eq!(code(ClientConnectionFailedError::build()), "XX000");
+    }
+
+    #[test]
+    fn pg_to_gel() {
+        let kind = |code: &[u8; 5]| PgError::from_code(*code).to_gel_error();
+        assert!(kind(b"23505").is::<ConstraintViolationError>());
+        assert!(kind(b"40P01").is::<TransactionDeadlockError>());
+        assert!(kind(b"22P02").is::<InvalidValueError>());
+        assert!(kind(b"42000").is::<QueryError>());
+        assert!(kind(b"XX001").is::<InternalServerError>());
+    }
+
+    #[test]
+    fn round_trip() {
+        let kinds = [
+            UnsupportedFeatureError::build(),
+            InvalidSyntaxError::build(),
+            UnknownDatabaseError::build(),
+            DuplicateDefinitionError::build(),
+            DivisionByZeroError::build(),
+            ConstraintViolationError::build(),
+            MissingRequiredError::build(),
+            TransactionDeadlockError::build(),
+            AuthenticationError::build(),
+            QueryTimeoutError::build(),
+        ];
+        for error in kinds {
+            let pg = PgError::from_gel_error(&error);
+            assert_eq!(pg.to_gel_error().code(), error.code(), "{pg}");
+        }
+    }
+
+    #[test]
+    fn server_error() {
+        let error = ConstraintViolationError::with_message("name violates exclusivity")
+            .context("while inserting")
+            .with_headers(HashMap::from([
+                (FIELD_HINT, "use a unique name".into()),
+                (FIELD_DETAILS, "value exists".into()),
+                (FIELD_POSITION_START, "7".into()),
+            ]));
+        let pg_error = PgServerError::from(&error);
+        assert_eq!(pg_error.code.to_string(), "23514");
+        assert_eq!(pg_error.message, "name violates exclusivity");
+        assert_eq!(pg_error.extra[&PgServerErrorField::Position], "8");
+        assert_eq!(
+            pg_error.extra[&PgServerErrorField::Where],
+            "while inserting"
+        );
+
+        let back = Error::from(pg_error);
+        assert!(back.is::<ConstraintViolationError>());
+        assert_eq!(back.initial_message(), error.initial_message());
+        assert_eq!(
+            back.contexts().collect::<Vec<_>>(),
+            error.contexts().collect::<Vec<_>>()
+        );
+        assert_eq!(back.hint(), Some("use a unique name"));
+        assert_eq!(back.details(), Some("value exists"));
+        assert_eq!(back.position_start(), Some(7));
+    }
+}
@@ -1,4 +1,5 @@
 pub mod errors;
+pub mod gel;
 pub mod protocol;
 
 pub use gel_protogen::prelude;
@@ -0,0 +1,600 @@
+/*!
+Conversion of query results into [Apache Arrow](https://arrow.apache.org)
+record batches.
+
+The output type descriptor is mapped to an arrow schema:
+
+* scalars map to the closest arrow primitive (`str`, `json`, enums, `bigint`
+  and `decimal` are rendered as `Utf8`);
+* sets and arrays map to `List`;
+* tuples, named tuples, objects and SQL rows map to `Struct`;
+* ranges map to a `Struct` of `lower`, `upper`, `inc_lower`, `inc_upper` and
+  `empty`, and multiranges to a `List` of those.
+
+If the query returns a struct-like type, its elements become the columns of
+the record batch, otherwise the batch contains a single `value` column.
+
+```rust,ignore
+let mut decoder = RecordBatchDecoder::new(desc.root_pos(), desc.descriptors(), 1024)?;
+for data in messages {
+    for batch in decoder.decode(&data)? {
+        // ...
+    }
+}
+if let Some(batch) = decoder.finish()? {
+    // ...
+}
+```
+*/
+
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::iter::FromIterator;
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, BinaryArray, BooleanArray, Date32Array, DurationMicrosecondArray,
+    FixedSizeBinaryArray, Float32Array, Float64Array, Int16Array, Int32Array, Int64Array,
+    IntervalMonthDayNanoArray, ListArray, RecordBatchOptions, StringArray, StructArray,
+    Time64MicrosecondArray, TimestampMicrosecondArray,
+};
+use arrow_buffer::{IntervalMonthDayNano, NullBuffer, OffsetBuffer};
+use arrow_schema::{DataType, Field, FieldRef, Fields, IntervalUnit, TimeUnit};
+use snafu::{OptionExt, ResultExt};
+
+use crate::codec::{self, build_codec, Codec};
+use crate::common::Cardinality;
+use crate::descriptors::{Descriptor, TypePos};
+use crate::errors::{self, ArrowError};
+use crate::model::LocalDate;
+use crate::server_message::Data;
+use crate::value::Value;
+
+pub use arrow_array::RecordBatch;
+pub use arrow_schema::{Schema, SchemaRef};
+
+/// Conversion plan for a single arrow column, mirroring its [DataType].
+#[derive(Debug)]
+enum Column {
+    Scalar(DataType),
+    Vector(FieldRef),
+    List(FieldRef, Box<Column>),
+    Struct(Fields, Vec<(usize, Column)>),
+    Range(Fields, Box<Column>),
+}
+
+struct SchemaBuilder<'a> {
+    descriptors: &'a [Descriptor],
+}
+
+/// Decodes `Data` messages into arrow record batches of at most
+/// `batch_size` rows.
+#[derive(Debug)]
+pub struct RecordBatchDecoder {
+    codec: Arc<dyn Codec>,
+    schema: SchemaRef,
+    root: Column,
+    batch_size: usize,
+    rows: Vec<Value>,
+}
+
+/// Build an arrow schema for the output type descriptor.
+pub fn build_schema(
+    root_pos: Option<TypePos>,
+    descriptors: &[Descriptor],
+) -> Result<SchemaRef, ArrowError> {
+    let (schema, _) = build_root(root_pos, descriptors)?;
+    Ok(schema)
+}
+
+fn build_root(
+    root_pos: Option<TypePos>,
+    descriptors: &[Descriptor],
+) -> Result<(SchemaRef, Column), ArrowError> {
+    let root_pos = root_pos.context(errors::NoOutput)?;
+    let column = SchemaBuilder { descriptors }.build(root_pos)?;
+    let schema = match &column {
+        Column::Struct(fields, _) => Schema::new(fields.clone()),
+        column => Schema::new(vec![Field::new("value", column.data_type(), false)]),
+    };
+    Ok((Arc::new(schema), column))
+}
+
+fn scalar_type(id: &uuid::Uuid) -> Option<Column> {
+    let data_type = match *id {
+        codec::STD_UUID => DataType::FixedSizeBinary(16),
+        codec::STD_STR | codec::STD_JSON | codec::STD_PG_JSON => DataType::Utf8,
+        codec::STD_DECIMAL | codec::STD_BIGINT => DataType::Utf8,
+        codec::STD_BYTES => DataType::Binary,
+        codec::STD_INT16 => DataType::Int16,
+        codec::STD_INT32 => DataType::Int32,
+        codec::STD_INT64 | codec::CFG_MEMORY => DataType::Int64,
+        codec::STD_FLOAT32 => DataType::Float32,
+        codec::STD_FLOAT64 => DataType::Float64,
+        codec::STD_BOOL => DataType::Boolean,
+        codec::STD_DATETIME | codec::STD_PG_TIMESTAMPTZ => {
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+        }
+        codec::CAL_LOCAL_DATETIME | codec::STD_PG_TIMESTAMP => {
+            DataType::Timestamp(TimeUnit::Microsecond, None)
+        }
+        codec::CAL_LOCAL_DATE | codec::STD_PG_DATE => DataType::Date32,
+        codec::CAL_LOCAL_TIME => DataType::Time64(TimeUnit::Microsecond),
+        codec::STD_DURATION => DataType::Duration(TimeUnit::Microsecond),
+        codec::CAL_RELATIVE_DURATION | codec::CAL_DATE_DURATION | codec::STD_PG_INTERVAL => {
+            DataType::Interval(IntervalUnit::MonthDayNano)
+        }
+        codec::POSTGIS_GEOMETRY
+        | codec::POSTGIS_GEOGRAPHY
+        | codec::POSTGIS_BOX_2D
+        | codec::POSTGIS_BOX_3D => DataType::Binary,
+        codec::PGVECTOR_VECTOR => {
+            return Some(Column::Vector(Arc::new(Field::new(
+                "item",
+                DataType::Float32,
+                false,
+            ))))
+        }
+        _ => return None,
+    };
+    Some(Column::Scalar(data_type))
+}
+
+impl Column {
+    fn data_type(&self) -> DataType {
+        match self {
+            Column::Scalar(data_type) => data_type.clone(),
+            Column::Vector(field) | Column::List(field, _) => DataType::List(field.clone()),
+            Column::Struct(fields, _) | Column::Range(fields, _) => {
+                DataType::Struct(fields.clone())
+            }
+        }
+    }
+
+    fn list(element: Column) -> Column {
+        let field = Field::new("item", element.data_type(), false);
+        Column::List(Arc::new(field), Box::new(element))
+    }
+
+    fn range(element: Column) -> Column {
+        let bound = element.data_type();
+        let fields = Fields::from(vec![
+            Field::new("lower", bound.clone(), true),
+            Field::new("upper", bound, true),
+            Field::new("inc_lower", DataType::Boolean, false),
+            Field::new("inc_upper", DataType::Boolean, false),
+            Field::new("empty", DataType::Boolean, false),
+        ]);
+        Column::Range(fields, Box::new(element))
+    }
+
+    fn from_fields(fields: Vec<(usize, Field, Column)>) -> Column {
+        let (fields, columns): (Vec<_>, Vec<_>) = fields
+            .into_iter()
+            .map(|(idx, field, column)| (field, (idx, column)))
+            .unzip();
+        Column::Struct(fields.into(), columns)
+    }
+}
+
+impl SchemaBuilder<'_> {
+    fn build(&self, pos: TypePos) -> Result<Column, ArrowError> {
+        use Descriptor as D;
+        let item = self
+            .descriptors
+            .get(pos.0 as usize)
+            .context(errors::UnsupportedType { position: pos.0 })?;
+        let unsupported = || errors::UnsupportedType { position: pos.0 };
+        match item {
+            D::BaseScalar(base) => scalar_type(&base.id).with_context(unsupported),
+            D::Scalar(d) => match d.base_type_pos {
+                Some(type_pos) => self.build(type_pos),
+                None => scalar_type(&d.id).with_context(unsupported),
+            },
+            D::Enumeration(_) => Ok(Column::Scalar(DataType::Utf8)),
+            D::Set(d) => Ok(Column::list(self.build(d.type_pos)?)),
+            D::Array(d) => Ok(Column::list(self.build(d.type_pos)?)),
+            D::Range(d) => Ok(Column::range(self.build(d.type_pos)?)),
+            D::MultiRange(d) => Ok(Column::list(Column::range(self.build(d.type_pos)?))),
+            D::ObjectShape(d) => {
+                let mut fields = Vec::with_capacity(d.elements.len());
+                for (idx, el) in d.elements.iter().enumerate() {
+                    if el.flag_implicit {
+                        continue;
+                    }
+                    let nullable = !matches!(
+                        el.cardinality,
+                        Some(Cardinality::One | Cardinality::Many | Cardinality::AtLeastOne)
+                    );
+                    let column = self.build(el.type_pos)?;
+                    let field = Field::new(&el.name, column.data_type(), nullable);
+                    fields.push((idx, field, column));
+                }
+                Ok(Column::from_fields(fields))
+            }
+            D::Tuple(d) => {
+                let mut fields = Vec::with_capacity(d.element_types.len());
+                for (idx, type_pos) in d.element_types.iter().enumerate() {
+                    let column = self.build(*type_pos)?;
+                    let field = Field::new(idx.to_string(), column.data_type(), false);
+                    fields.push((idx, field, column));
+                }
+                Ok(Column::from_fields(fields))
+            }
+            D::NamedTuple(d) => {
+                let mut fields = Vec::with_capacity(d.elements.len());
+                for (idx, el) in d.elements.iter().enumerate() {
+                    let column = self.build(el.type_pos)?;
+                    let field = Field::new(&el.name, column.data_type(), false);
+                    fields.push((idx, field, column));
+                }
+                Ok(Column::from_fields(fields))
+            }
+            D::SQLRow(d) => {
+                let mut fields = Vec::with_capacity(d.elements.len());
+                for (idx, el) in d.elements.iter().enumerate() {
+                    let column = self.build(el.type_pos)?;
+                    let field = Field::new(&el.name, column.data_type(), true);
+                    fields.push((idx, field, column));
+                }
+                Ok(Column::from_fields(fields))
+            }
+            D::Object(_) | D::Compound(_) | D::InputShape(_) | D::TypeAnnotation(_) => {
+                unsupported().fail()
+            }
+        }
+    }
+}
+
+impl RecordBatchDecoder {
+    /// Create a decoder for the output type descriptor, producing batches of
+    /// at most `batch_size` rows.
+    pub fn new(
+        root_pos: Option<TypePos>,
+        descriptors: &[Descriptor],
+        batch_size: usize,
+    ) -> Result<RecordBatchDecoder, ArrowError> {
+        let (schema, root) = build_root(root_pos, descriptors)?;
+        let codec = build_codec(root_pos, descriptors).context(errors::ArrowCodec)?;
+        let batch_size = batch_size.max(1);
+        Ok(RecordBatchDecoder {
+            codec,
+            schema,
+            root,
+            batch_size,
+            rows: Vec::with_capacity(batch_size),
+        })
+    }
+
+    /// The schema of the produced record batches.
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Decode a `Data` message, returning all batches that were filled.
+    pub fn decode(&mut self, data: &Data) -> Result<Vec<RecordBatch>, ArrowError> {
+        let mut batches = Vec::new();
+        for chunk in &data.data {
+            let value = self.codec.decode(chunk).context(errors::ArrowDecode)?;
+            batches.extend(self.push(value)?);
+        }
+        Ok(batches)
+    }
+
+    /// Add an already decoded row, returning a batch if it is full.
+    pub fn push(&mut self, value: Value) -> Result<Option<RecordBatch>, ArrowError> {
+        self.rows.push(value);
+        if self.rows.len() >= self.batch_size {
+            self.flush().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Convert the remaining rows into a final, possibly short, batch.
+    pub fn finish(&mut self) -> Result<Option<RecordBatch>, ArrowError> {
+        if self.rows.is_empty() {
+            Ok(None)
+        } else {
+            self.flush().map(Some)
+        }
+    }
+
+    fn flush(&mut self) -> Result<RecordBatch, ArrowError> {
+        let rows = std::mem::replace(&mut self.rows, Vec::with_capacity(self.batch_size));
+        let values = rows.iter().map(Some).collect::<Vec<_>>();
+        let columns = match &self.root {
+            Column::Struct(fields, elements) => struct_children(fields, elements, &values)?.0,
+            column => vec![to_array(column, &values)?],
+        };
+        let options = RecordBatchOptions::new().with_row_count(Some(rows.len()));
+        RecordBatch::try_new_with_options(self.schema.clone(), columns, &options)
+            .context(errors::Arrow)
+    }
+}
+
+fn mismatch(value: &Value, data_type: &DataType) -> ArrowError {
+    errors::ArrowValueMismatch {
+        value_type: value.kind(),
+        data_type: data_type.clone(),
+    }
+    .build()
+}
+
+fn null_buffer(valid: Vec<bool>) -> Option<NullBuffer> {
+    valid.contains(&false).then(|| NullBuffer::from(valid))
+}
+
+fn convert<'a, T>(
+    values: &[Option<&'a Value>],
+    data_type: &DataType,
+    f: impl Fn(&'a Value) -> Option<T>,
+) -> Result<Vec<Option<T>>, ArrowError> {
+    values
+        .iter()
+        .map(|v| match v {
+            None => Ok(None),
+            Some(v) => f(v).map(Some).ok_or_else(|| mismatch(v, data_type)),
+        })
+        .collect()
+}
+
+fn offsets(lengths: impl IntoIterator<Item = usize>) -> Result<OffsetBuffer<i32>, ArrowError> {
+    let mut offsets = vec![0i32];
+    let mut total = 0i32;
+    for len in lengths {
+        total = i32::try_from(len)
+            .ok()
+            .and_then(|len| total.checked_add(len))
+            .context(errors::ListTooLong)?;
+        offsets.push(total);
+    }
+    Ok(OffsetBuffer::new(offsets.into()))
+}
+
+fn struct_fields(value: &Value) -> Option<Vec<Option<&Value>>> {
+    match value {
+        Value::Object { fields, .. } | Value::SQLRow { fields, .. } => {
+            Some(fields.iter().map(Option::as_ref).collect())
+        }
+        Value::Tuple(fields) | Value::NamedTuple { fields, .. } => {
+            Some(fields.iter().map(Some).collect())
+        }
+        _ => None,
+    }
+}
+
+fn struct_children(
+    fields: &Fields,
+    elements: &[(usize, Column)],
+    values: &[Option<&Value>],
+) -> Result<(Vec<ArrayRef>, Option<NullBuffer>), ArrowError> {
+    let data_type = DataType::Struct(fields.clone());
+    let mut columns = vec![Vec::with_capacity(values.len()); elements.len()];
+    let mut valid = Vec::with_capacity(values.len());
+    for value in values {
+        match value {
+            None => {
+                columns.iter_mut().for_each(|c| c.push(None));
+                valid.push(false);
+            }
+            Some(value) => {
+                let row = struct_fields(value).ok_or_else(|| mismatch(value, &data_type))?;
+                for ((idx, _), column) in elements.iter().zip(&mut columns) {
+                    let item = row.get(*idx).ok_or_else(|| mismatch(value, &data_type))?;
+                    column.push(*item);
+                }
+                valid.push(true);
+            }
+        }
+    }
+    let children = elements
+        .iter()
+        .zip(&columns)
+        .map(|((_, column), values)| to_array(column, values))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((children, null_buffer(valid)))
+}
+
+fn to_array(column: &Column, values: &[Option<&Value>]) -> Result<ArrayRef, ArrowError> {
+    let array: ArrayRef = match column {
+        Column::Scalar(data_type) => scalar_array(data_type, values)?,
+        Column::Vector(field) => {
+            let data_type = column.data_type();
+            let vectors = convert(values, &data_type, |v| match v {
+                Value::Vector(items) => Some(&items[..]),
+                _ => None,
+            })?;
+            let lengths = vectors.iter().map(|v| v.map_or(0, |v| v.len()));
+            let offsets = offsets(lengths)?;
+            let items = Float32Array::from_iter_values(
+                vectors.iter().flatten().flat_map(|v| v.iter().copied()),
+            );
+            let valid = vectors.iter().map(Option::is_some).collect();
+            let list =
+                ListArray::try_new(field.clone(), offsets, Arc::new(items), null_buffer(valid))
+                    .context(errors::Arrow)?;
+            Arc::new(list)
+        }
+        Column::List(field, element) => {
+            let data_type = column.data_type();
+            let lists = convert(values, &data_type, |v| match v {
+                Value::Set(items) | Value::Array(items) => Some(items),
+                _ => None,
+            })?;
+            let offsets = offsets(lists.iter().map(|v| v.map_or(0, |v| v.len())))?;
+            let items = lists
+                .iter()
+                .flatten()
+                .flat_map(|items| items.iter().map(Some))
+                .collect::<Vec<_>>();
+            let items = to_array(element, &items)?;
+            let valid = lists.iter().map(Option::is_some).collect();
+            let list = ListArray::try_new(field.clone(), offsets, items, null_buffer(valid))
+                .context(errors::Arrow)?;
+            Arc::new(list)
+        }
+        Column::Struct(fields, elements) => {
+            let (children, nulls) = struct_children(fields, elements, values)?;
+            let array =
+                StructArray::try_new_with_length(fields.clone(), children, nulls, values.len())
+                    .context(errors::Arrow)?;
+            Arc::new(array)
+        }
+        Column::Range(fields, element) => {
+            let data_type = column.data_type();
+            let ranges = convert(values, &data_type, |v| match v {
+                Value::Range(range) => Some(range),
+                _ => None,
+            })?;
+            let lower = ranges
+                .iter()
+                .map(|r| r.and_then(|r| r.lower().map(|v| &**v)))
+                .collect::<Vec<_>>();
+            let upper = ranges
+                .iter()
+                .map(|r| r.and_then(|r| r.upper().map(|v| &**v)))
+                .collect::<Vec<_>>();
+            let flag = |f: fn(&crate::model::Range<Box<Value>>) -> bool| -> ArrayRef {
+                Arc::new(BooleanArray::from_iter(
+                    ranges.iter().map(|r| Some(r.is_some_and(f))),
+                ))
+            };
+            let children = vec![
+                to_array(element, &lower)?,
+                to_array(element, &upper)?,
+                flag(|r| r.inc_lower()),
+                flag(|r| r.inc_upper()),
+                flag(|r| r.is_empty()),
+            ];
+            let valid = ranges.iter().map(Option::is_some).collect();
+            let array = StructArray::try_new(fields.clone(), children, null_buffer(valid))
+                .context(errors::Arrow)?;
+            Arc::new(array)
+        }
+    };
+    Ok(array)
+}
+
+fn scalar_array(data_type: &DataType, values: &[Option<&Value>]) -> Result<ArrayRef, ArrowError> {
+    let array: ArrayRef = match data_type {
+        DataType::Utf8 => {
+            let strings = convert(values, data_type, |v| match v {
+                Value::Str(s) => Some(Cow::Borrowed(&s[..])),
+                Value::Json(j) => Some(Cow::Borrowed(&j[..])),
+                Value::Enum(e) => Some(Cow::Borrowed(&e[..])),
+                Value::BigInt(n) => Some(Cow::Owned(n.to_string())),
+                Value::Decimal(n) => Some(Cow::Owned(n.to_string())),
+                _ => None,
+            })?;
+            Arc::new(StringArray::from_iter(strings))
+        }
+        DataType::Binary => {
+            let bytes = convert(values, data_type, |v| match v {
+                Value::Bytes(b)
+                | Value::PostGisGeometry(b)
+                | Value::PostGisGeography(b)
+                | Value::PostGisBox2d(b)
+                | Value::PostGisBox3d(b) => Some(&b[..]),
+                _ => None,
+            })?;
+            Arc::new(BinaryArray::from(bytes))
+        }
+        DataType::FixedSizeBinary(size) => {
+            let uuids = convert(values, data_type, |v| match v {
+                Value::Uuid(u) => Some(u.as_bytes()),
+                _ => None,
+            })?;
+            let array =
+                FixedSizeBinaryArray::try_from_sparse_iter_with_size(uuids.into_iter(), *size)
+                    .context(errors::Arrow)?;
+            Arc::new(array)
+        }
+        DataType::Int16 => Arc::new(Int16Array::from(convert(values, data_type, |v| match v {
+            Value::Int16(n) => Some(*n),
+            _ => None,
+        })?)),
+        DataType::Int32 => Arc::new(Int32Array::from(convert(values, data_type, |v| match v {
+            Value::Int32(n) => Some(*n),
+            _ => None,
+        })?)),
+        DataType::Int64 => Arc::new(Int64Array::from(convert(values, data_type, |v| match v {
+            Value::Int64(n) => Some(*n),
+            Value::ConfigMemory(m) => Some(m.0),
+            _ => None,
+        })?)),
+        DataType::Float32 => Arc::new(Float32Array::from(convert(
+            values,
+            data_type,
+            |v| match v {
+                Value::Float32(n) => Some(*n),
+                _ => None,
+            },
+        )?)),
+        DataType::Float64 => Arc::new(Float64Array::from(convert(
+            values,
+            data_type,
+            |v| match v {
+                Value::Float64(n) => Some(*n),
+                _ => None,
+            },
+        )?)),
+        DataType::Boolean => Arc::new(BooleanArray::from(convert(
+            values,
+            data_type,
+            |v| match v {
+                Value::Bool(b) => Some(*b),
+                _ => None,
+            },
+        )?)),
+        DataType::Timestamp(TimeUnit::Microsecond, tz) => {
+            let micros = convert(values, data_type, |v| match v {
+                Value::Datetime(d) => Some(d.to_unix_micros()),
+                Value::LocalDatetime(d) => Some(d.to_utc().to_unix_micros()),
+                _ => None,
+            })?;
+            Arc::new(TimestampMicrosecondArray::from(micros).with_timezone_opt(tz.clone()))
+        }
+        DataType::Date32 => Arc::new(Date32Array::from(convert(
+            values,
+            data_type,
+            |v| match v {
+                Value::LocalDate(d) => Some(d.to_days() - LocalDate::UNIX_EPOCH.to_days()),
+                _ => None,
+            },
+        )?)),
+        DataType::Time64(TimeUnit::Microsecond) => {
+            let micros = convert(values, data_type, |v| match v {
+                Value::LocalTime(t) => i64::try_from(t.to_micros()).ok(),
+                _ => None,
+            })?;
+            Arc::new(Time64MicrosecondArray::from(micros))
+        }
+        DataType::Duration(TimeUnit::Microsecond) => {
+            let micros = convert(values, data_type, |v| match v {
+                Value::Duration(d) => Some(d.to_micros()),
+                _ => None,
+            })?;
+            Arc::new(DurationMicrosecondArray::from(micros))
+        }
+        DataType::Interval(IntervalUnit::MonthDayNano) => {
+            let intervals = convert(values, data_type, |v| match v {
+                Value::RelativeDuration(d) => d
+                    .micros
+                    .checked_mul(1000)
+                    .map(|nanos| IntervalMonthDayNano::new(d.months, d.days, nanos)),
+                Value::DateDuration(d) => Some(IntervalMonthDayNano::new(d.months, d.days, 0)),
+                _ => None,
+            })?;
+            Arc::new(IntervalMonthDayNanoArray::from(intervals))
+        }
+        // Every type returned by `scalar_type` should have a conversion here.
+        _ => {
+            return errors::UnsupportedArrowType {
+                data_type: data_type.clone(),
+            }
+            .fail()
+        }
+    };
+    Ok(array)
+}
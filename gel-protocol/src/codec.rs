@@ -44,6 +44,9 @@ pub const STD_JSON: UuidVal = UuidVal::from_u128(0x10f);
 pub const STD_BIGINT: UuidVal = UuidVal::from_u128(0x110);
 pub const CFG_MEMORY: UuidVal = UuidVal::from_u128(0x130);
 pub const PGVECTOR_VECTOR: UuidVal = UuidVal::from_u128(0x9565dd88_04f5_11ee_a691_0b6ebe179825);
+/// Extension scalars without a well-known id, recognized by name.
+pub const PGVECTOR_HALFVEC_NAME: &str = "ext::pgvector::halfvec";
+pub const PGVECTOR_SPARSEVEC_NAME: &str = "ext::pgvector::sparsevec";
 pub const STD_PG_JSON: UuidVal = UuidVal::from_u128(0x1000001);
 pub const STD_PG_TIMESTAMPTZ: UuidVal = UuidVal::from_u128(0x1000002);
 pub const STD_PG_TIMESTAMP: UuidVal = UuidVal::from_u128(0x1000003);
@@ -265,6 +268,12 @@ pub struct Array {
 #[derive(Debug)]
 pub struct Vector {}
 
+#[derive(Debug)]
+pub struct HalfVector {}
+
+#[derive(Debug)]
+pub struct SparseVector {}
+
 #[derive(Debug)]
 pub struct Range {
     element: Arc<dyn Codec>,
@@ -350,7 +359,11 @@ impl CodecBuilder<'_> {
                 D::Scalar(d) => Ok(Arc::new(Scalar {
                     inner: match d.base_type_pos {
                         Some(type_pos) => self.build(type_pos)?,
-                        None => scalar_codec(&d.id)?,
+                        None => match d.name.as_deref() {
+                            Some(PGVECTOR_HALFVEC_NAME) => Arc::new(HalfVector {}),
+                            Some(PGVECTOR_SPARSEVEC_NAME) => Arc::new(SparseVector {}),
+                            _ => scalar_codec(&d.id)?,
+                        },
                     },
                 })),
                 D::Tuple(d) => Ok(Arc::new(Tuple::build(d, self)?)),
@@ -1446,6 +1459,30 @@ impl Codec for Vector {
     }
 }
 
+impl Codec for HalfVector {
+    fn decode(&self, buf: &[u8]) -> Result<Value, DecodeError> {
+        RawCodec::decode(buf).map(Value::HalfVector)
+    }
+    fn encode(&self, buf: &mut BytesMut, val: &Value) -> Result<(), EncodeError> {
+        match val {
+            Value::HalfVector(val) => val.encode_raw(buf),
+            _ => Err(errors::invalid_value(type_name::<Self>(), val))?,
+        }
+    }
+}
+
+impl Codec for SparseVector {
+    fn decode(&self, buf: &[u8]) -> Result<Value, DecodeError> {
+        RawCodec::decode(buf).map(Value::SparseVector)
+    }
+    fn encode(&self, buf: &mut BytesMut, val: &Value) -> Result<(), EncodeError> {
+        match val {
+            Value::SparseVector(val) => val.encode_raw(buf),
+            _ => Err(errors::invalid_value(type_name::<Self>(), val))?,
+        }
+    }
+}
+
 impl Codec for Range {
     fn decode(&self, mut buf: &[u8]) -> Result<Value, DecodeError> {
         ensure!(buf.remaining() >= 1, errors::Underflow);
@@ -43,6 +43,7 @@ use snafu::{ensure, OptionExt};
 use uuid::Uuid;
 
 use crate::codec::{build_codec, uuid_to_known_name, Codec};
+use crate::codec::{PGVECTOR_HALFVEC_NAME, PGVECTOR_SPARSEVEC_NAME};
 use crate::common::{Cardinality, State};
 use crate::encoding::{Decode, Input};
 use crate::errors::{self, CodecError, DecodeError};
@@ -567,6 +568,20 @@ impl Descriptor {
     pub fn decode(buf: &mut Input) -> Result<Descriptor, DecodeError> {
         <Descriptor as Decode>::decode(buf)
     }
+    /// Name of the base scalar type, if the server sent it.
+    ///
+    /// This is used to recognize extension types that have no well-known id.
+    pub(crate) fn base_scalar_name<'a>(&'a self, descriptors: &'a [Descriptor]) -> Option<&'a str> {
+        match self {
+            Descriptor::Scalar(d) => match d.ancestors.last().or(d.base_type_pos.as_ref()) {
+                Some(pos) => descriptors
+                    .get(pos.0 as usize)?
+                    .base_scalar_name(descriptors),
+                None => d.name.as_deref(),
+            },
+            _ => None,
+        }
+    }
     pub fn normalize_to_base(
         &self,
         ctx: &query_arg::DescriptorContext,
@@ -1077,13 +1092,475 @@ impl Decode for TypeAnnotationDescriptor {
     }
 }
 
+impl Descriptor {
+    /// Render the type in EdgeQL syntax, e.g. `array<tuple<name: str, age: int64>>`.
+    ///
+    /// `descriptors` is the list this descriptor belongs to, and is used to
+    /// resolve the types it refers to.
+    pub fn edgeql_type(&self, descriptors: &[Descriptor]) -> String {
+        let mut out = String::new();
+        TypeRenderer {
+            descriptors,
+            rust: false,
+        }
+        .write(self, &mut out);
+        out
+    }
+    /// Render the Rust type that the value decodes into, e.g. `Vec<(String, i64)>`.
+    pub fn rust_type(&self, descriptors: &[Descriptor]) -> String {
+        let mut out = String::new();
+        TypeRenderer {
+            descriptors,
+            rust: true,
+        }
+        .write(self, &mut out);
+        out
+    }
+}
+
+impl Typedesc {
+    /// Render the type at `pos` in EdgeQL syntax.
+    pub fn edgeql_type(&self, pos: TypePos) -> String {
+        match self.array.get(pos.0 as usize) {
+            Some(desc) => desc.edgeql_type(&self.array),
+            None => INVALID_TYPE.into(),
+        }
+    }
+    /// Render the Rust type that the value at `pos` decodes into.
+    pub fn rust_type(&self, pos: TypePos) -> String {
+        match self.array.get(pos.0 as usize) {
+            Some(desc) => desc.rust_type(&self.array),
+            None => INVALID_TYPE.into(),
+        }
+    }
+}
+
+const INVALID_TYPE: &str = "<invalid>";
+
+struct TypeRenderer<'a> {
+    descriptors: &'a [Descriptor],
+    rust: bool,
+}
+
+impl TypeRenderer<'_> {
+    fn write_pos(&self, pos: TypePos, out: &mut String) {
+        match self.descriptors.get(pos.0 as usize) {
+            Some(desc) => self.write(desc, out),
+            None => out.push_str(INVALID_TYPE),
+        }
+    }
+
+    fn write_list<'b>(
+        &self,
+        items: impl Iterator<Item = (Option<&'b str>, TypePos)>,
+        out: &mut String,
+    ) {
+        for (idx, (name, pos)) in items.enumerate() {
+            if idx > 0 {
+                out.push_str(", ");
+            }
+            if let Some(name) = name {
+                out.push_str(name);
+                out.push_str(": ");
+            }
+            self.write_pos(pos, out);
+        }
+    }
+
+    fn write(&self, desc: &Descriptor, out: &mut String) {
+        use Descriptor as D;
+
+        match desc {
+            D::Set(d) => {
+                out.push_str(if self.rust { "Vec<" } else { "set<" });
+                self.write_pos(d.type_pos, out);
+                out.push('>');
+            }
+            D::BaseScalar(d) => self.write_scalar(&d.id, out),
+            D::Scalar(d) => match (&d.name, d.ancestors.last().or(d.base_type_pos.as_ref())) {
+                (Some(name), _) if !self.rust => out.push_str(short_name(name)),
+                (_, Some(base)) => self.write_pos(*base, out),
+                (Some(name), None) => match &name[..] {
+                    PGVECTOR_HALFVEC_NAME => out.push_str("HalfVector"),
+                    PGVECTOR_SPARSEVEC_NAME => out.push_str("SparseVector"),
+                    _ => self.write_scalar(&d.id, out),
+                },
+                (None, None) => self.write_scalar(&d.id, out),
+            },
+            D::Enumeration(d) => match &d.name {
+                _ if self.rust => out.push_str("String"),
+                Some(name) => out.push_str(name),
+                None => {
+                    out.push_str("enum<");
+                    out.push_str(&d.members.join(", "));
+                    out.push('>');
+                }
+            },
+            D::Tuple(d) => {
+                if self.rust {
+                    out.push('(');
+                    self.write_list(d.element_types.iter().map(|pos| (None, *pos)), out);
+                    if d.element_types.len() == 1 {
+                        out.push(',');
+                    }
+                    out.push(')');
+                } else {
+                    out.push_str("tuple<");
+                    self.write_list(d.element_types.iter().map(|pos| (None, *pos)), out);
+                    out.push('>');
+                }
+            }
+            D::NamedTuple(d) => {
+                if self.rust {
+                    out.push('(');
+                    self.write_list(d.elements.iter().map(|el| (None, el.type_pos)), out);
+                    if d.elements.len() == 1 {
+                        out.push(',');
+                    }
+                    out.push(')');
+                } else {
+                    out.push_str("tuple<");
+                    self.write_list(
+                        d.elements
+                            .iter()
+                            .map(|el| (Some(&el.name[..]), el.type_pos)),
+                        out,
+                    );
+                    out.push('>');
+                }
+            }
+            D::Array(d) => {
+                out.push_str(if self.rust { "Vec<" } else { "array<" });
+                self.write_pos(d.type_pos, out);
+                out.push('>');
+            }
+            D::Range(d) => {
+                out.push_str(if self.rust { "Range<" } else { "range<" });
+                self.write_pos(d.type_pos, out);
+                out.push('>');
+            }
+            D::MultiRange(d) => {
+                out.push_str(if self.rust {
+                    "Vec<Range<"
+                } else {
+                    "multirange<"
+                });
+                self.write_pos(d.type_pos, out);
+                out.push_str(if self.rust { ">>" } else { ">" });
+            }
+            D::ObjectShape(d) => {
+                if self.rust {
+                    out.push_str("struct");
+                } else {
+                    match d
+                        .type_pos
+                        .and_then(|pos| self.descriptors.get(pos.0 as usize))
+                    {
+                        Some(D::Object(ObjectTypeDescriptor {
+                            name: Some(name), ..
+                        })) => out.push_str(name),
+                        _ => out.push_str("std::FreeObject"),
+                    }
+                }
+                let elements = d
+                    .elements
+                    .iter()
+                    .filter(|el| !el.flag_implicit)
+                    .map(|el| (&el.name[..], el.cardinality, el.type_pos));
+                self.write_shape(elements, out);
+            }
+            D::InputShape(d) => {
+                let elements = d
+                    .elements
+                    .iter()
+                    .map(|el| (&el.name[..], el.cardinality, el.type_pos));
+                if self.rust {
+                    out.push_str("struct");
+                }
+                self.write_shape(elements, out);
+            }
+            D::SQLRow(d) => {
+                let elements = d
+                    .elements
+                    .iter()
+                    .map(|el| (&el.name[..], None, el.type_pos));
+                out.push_str(if self.rust { "struct" } else { "record" });
+                self.write_shape(elements, out);
+            }
+            D::Object(d) => match (&d.name, self.rust) {
+                (_, true) => out.push_str("()"),
+                (Some(name), false) => out.push_str(name),
+                (None, false) => out.push_str("std::BaseObject"),
+            },
+            D::Compound(d) => {
+                if self.rust {
+                    out.push_str("()");
+                    return;
+                }
+                let op = match d.op {
+                    TypeOperation::UNION => " | ",
+                    TypeOperation::INTERSECTION => " & ",
+                };
+                for (idx, pos) in d.components.iter().enumerate() {
+                    if idx > 0 {
+                        out.push_str(op);
+                    }
+                    self.write_pos(*pos, out);
+                }
+            }
+            D::TypeAnnotation(d) => out.push_str(&d.annotation),
+        }
+    }
+
+    fn write_shape<'b>(
+        &self,
+        elements: impl Iterator<Item = (&'b str, Option<Cardinality>, TypePos)>,
+        out: &mut String,
+    ) {
+        out.push_str(" {");
+        for (idx, (name, cardinality, pos)) in elements.enumerate() {
+            out.push_str(if idx > 0 { ", " } else { " " });
+            if self.rust {
+                out.push_str(name);
+                out.push_str(": ");
+                let (prefix, suffix) = match cardinality {
+                    Some(Cardinality::AtMostOne) => ("Option<", ">"),
+                    Some(Cardinality::Many | Cardinality::AtLeastOne) => ("Vec<", ">"),
+                    _ => ("", ""),
+                };
+                out.push_str(prefix);
+                self.write_pos(pos, out);
+                out.push_str(suffix);
+            } else {
+                out.push_str(match cardinality {
+                    Some(Cardinality::AtMostOne) => "optional ",
+                    Some(Cardinality::Many) => "multi ",
+                    Some(Cardinality::AtLeastOne) => "required multi ",
+                    _ => "",
+                });
+                out.push_str(name);
+                out.push_str(": ");
+                self.write_pos(pos, out);
+            }
+        }
+        out.push_str(" }");
+    }
+
+    fn write_scalar(&self, id: &Uuid, out: &mut String) {
+        let names = if self.rust {
+            scalar_rust_type(id)
+        } else {
+            scalar_edgeql_type(id)
+        };
+        match names {
+            Some(name) => out.push_str(name),
+            None => out.push_str(&id.to_string()),
+        }
+    }
+}
+
+/// Strip the `std::` module for the types that EdgeQL has in scope by default.
+fn short_name(name: &str) -> &str {
+    match name.strip_prefix("std::") {
+        Some(short) if !short.contains("::") => short,
+        _ => name,
+    }
+}
+
+fn scalar_edgeql_type(id: &Uuid) -> Option<&'static str> {
+    use crate::codec::*;
+
+    Some(match *id {
+        STD_UUID => "uuid",
+        STD_STR => "str",
+        STD_BYTES => "bytes",
+        STD_INT16 => "int16",
+        STD_INT32 => "int32",
+        STD_INT64 => "int64",
+        STD_FLOAT32 => "float32",
+        STD_FLOAT64 => "float64",
+        STD_DECIMAL => "decimal",
+        STD_BOOL => "bool",
+        STD_DATETIME => "datetime",
+        CAL_LOCAL_DATETIME => "cal::local_datetime",
+        CAL_LOCAL_DATE => "cal::local_date",
+        CAL_LOCAL_TIME => "cal::local_time",
+        STD_DURATION => "duration",
+        CAL_RELATIVE_DURATION => "cal::relative_duration",
+        CAL_DATE_DURATION => "cal::date_duration",
+        STD_JSON => "json",
+        STD_BIGINT => "bigint",
+        CFG_MEMORY => "cfg::memory",
+        PGVECTOR_VECTOR => "ext::pgvector::vector",
+        STD_PG_JSON => "std::pg::json",
+        STD_PG_TIMESTAMPTZ => "std::pg::timestamptz",
+        STD_PG_TIMESTAMP => "std::pg::timestamp",
+        STD_PG_DATE => "std::pg::date",
+        STD_PG_INTERVAL => "std::pg::interval",
+        POSTGIS_GEOMETRY => "ext::postgis::geometry",
+        POSTGIS_GEOGRAPHY => "ext::postgis::geography",
+        POSTGIS_BOX_2D => "ext::postgis::box2d",
+        POSTGIS_BOX_3D => "ext::postgis::box3d",
+        _ => return None,
+    })
+}
+
+fn scalar_rust_type(id: &Uuid) -> Option<&'static str> {
+    use crate::codec::*;
+
+    Some(match *id {
+        STD_UUID => "Uuid",
+        STD_STR => "String",
+        STD_BYTES => "Bytes",
+        STD_INT16 => "i16",
+        STD_INT32 => "i32",
+        STD_INT64 => "i64",
+        STD_FLOAT32 => "f32",
+        STD_FLOAT64 => "f64",
+        STD_DECIMAL => "Decimal",
+        STD_BOOL => "bool",
+        STD_DATETIME | STD_PG_TIMESTAMPTZ => "Datetime",
+        CAL_LOCAL_DATETIME | STD_PG_TIMESTAMP => "LocalDatetime",
+        CAL_LOCAL_DATE | STD_PG_DATE => "LocalDate",
+        CAL_LOCAL_TIME => "LocalTime",
+        STD_DURATION => "Duration",
+        CAL_RELATIVE_DURATION | STD_PG_INTERVAL => "RelativeDuration",
+        CAL_DATE_DURATION => "DateDuration",
+        STD_JSON | STD_PG_JSON => "Json",
+        STD_BIGINT => "BigInt",
+        CFG_MEMORY => "ConfigMemory",
+        PGVECTOR_VECTOR => "Vector",
+        POSTGIS_GEOMETRY | POSTGIS_GEOGRAPHY | POSTGIS_BOX_2D | POSTGIS_BOX_3D => "Bytes",
+        _ => return None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::common::Cardinality;
     use crate::descriptors::{
-        BaseScalarTypeDescriptor, Descriptor, DescriptorUuid, SetDescriptor, TypePos,
+        ArrayTypeDescriptor, BaseScalarTypeDescriptor, Descriptor, DescriptorUuid,
+        NamedTupleTypeDescriptor, ObjectShapeDescriptor, ObjectTypeDescriptor, RangeTypeDescriptor,
+        ScalarTypeDescriptor, SetDescriptor, ShapeElement, TupleElement, TypePos,
     };
+    use crate::queryable::{DescriptorContext, Queryable};
     use uuid::Uuid;
 
+    fn base_scalar(id: u128) -> Descriptor {
+        Descriptor::BaseScalar(BaseScalarTypeDescriptor {
+            id: Uuid::from_u128(id).into(),
+        })
+    }
+
+    fn shape_element(name: &str, cardinality: Cardinality, type_pos: u16) -> ShapeElement {
+        ShapeElement {
+            flag_implicit: false,
+            flag_link_property: false,
+            flag_link: false,
+            cardinality: Some(cardinality),
+            name: name.into(),
+            type_pos: TypePos(type_pos),
+            source_type_pos: None,
+        }
+    }
+
+    #[test]
+    fn render_types() {
+        let descriptors = vec![
+            base_scalar(0x101),
+            base_scalar(0x105),
+            Descriptor::NamedTuple(NamedTupleTypeDescriptor {
+                id: Uuid::from_u128(0x1000).into(),
+                elements: vec![
+                    TupleElement {
+                        name: "name".into(),
+                        type_pos: TypePos(0),
+                    },
+                    TupleElement {
+                        name: "age".into(),
+                        type_pos: TypePos(1),
+                    },
+                ],
+                name: None,
+                schema_defined: None,
+                ancestors: vec![],
+            }),
+            Descriptor::Array(ArrayTypeDescriptor {
+                id: Uuid::from_u128(0x1001).into(),
+                type_pos: TypePos(2),
+                dimensions: vec![None],
+                name: None,
+                schema_defined: None,
+                ancestors: vec![],
+            }),
+            base_scalar(0x10a),
+            Descriptor::Range(RangeTypeDescriptor {
+                id: Uuid::from_u128(0x1002).into(),
+                type_pos: TypePos(4),
+                name: None,
+                schema_defined: None,
+                ancestors: vec![],
+            }),
+            Descriptor::Scalar(ScalarTypeDescriptor {
+                id: Uuid::from_u128(0x1003).into(),
+                base_type_pos: None,
+                name: Some("default::title_t".into()),
+                schema_defined: Some(true),
+                ancestors: vec![TypePos(0)],
+            }),
+            Descriptor::Object(ObjectTypeDescriptor {
+                id: Uuid::from_u128(0x1004).into(),
+                name: Some("default::Movie".into()),
+                schema_defined: Some(true),
+            }),
+            Descriptor::ObjectShape(ObjectShapeDescriptor {
+                id: Uuid::from_u128(0x1005).into(),
+                ephemeral_free_shape: false,
+                type_pos: Some(TypePos(7)),
+                elements: vec![
+                    shape_element("title", Cardinality::One, 6),
+                    shape_element("year", Cardinality::AtMostOne, 1),
+                    shape_element("tags", Cardinality::Many, 0),
+                ],
+            }),
+        ];
+        let render = |pos: usize| {
+            (
+                descriptors[pos].edgeql_type(&descriptors),
+                descriptors[pos].rust_type(&descriptors),
+            )
+        };
+        assert_eq!(render(0), ("str".into(), "String".into()));
+        assert_eq!(
+            render(3),
+            (
+                "array<tuple<name: str, age: int64>>".into(),
+                "Vec<(String, i64)>".into()
+            )
+        );
+        assert_eq!(
+            render(5),
+            ("range<datetime>".into(), "Range<Datetime>".into())
+        );
+        assert_eq!(render(6), ("default::title_t".into(), "String".into()));
+        assert_eq!(
+            render(8),
+            (
+                "default::Movie { title: default::title_t, optional year: int64, multi tags: str }"
+                    .into(),
+                "struct { title: String, year: Option<i64>, tags: Vec<String> }".into()
+            )
+        );
+
+        let ctx = DescriptorContext::new(&descriptors);
+        let err = String::check_descriptor(&ctx, TypePos(3)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unexpected type array<tuple<name: str, age: int64>> \
+             (Rust type `Vec<(String, i64)>`), expected std::str"
+        );
+    }
+
     #[test]
     fn descriptor_uuid_debug_outputs() {
         let float_32: Uuid = "00000000-0000-0000-0000-000000000106".parse().unwrap();
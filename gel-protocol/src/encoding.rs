@@ -46,6 +46,10 @@ impl Input {
             bytes: self.bytes.slice(range),
         }
     }
+    /// Returns a [Bytes] sharing memory with the input for a subslice of it.
+    pub fn slice_ref(&self, subset: &[u8]) -> Bytes {
+        self.bytes.slice_ref(subset)
+    }
 }
 
 impl Buf for Input {
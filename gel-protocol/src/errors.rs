@@ -116,6 +116,8 @@ pub enum DecodeError {
     },
     #[snafu(display("invalid type operation value"))]
     InvalidTypeOperation { backtrace: Backtrace },
+    #[snafu(display("sparse vector indices are unordered or out of range"))]
+    InvalidSparseVector { backtrace: Backtrace },
 }
 
 #[derive(Snafu, Debug)]
@@ -195,6 +197,48 @@ pub enum CodecError {
     },
 }
 
+#[cfg(feature = "arrow")]
+#[derive(Snafu, Debug)]
+#[snafu(visibility(pub(crate)), context(suffix(false)))]
+#[non_exhaustive]
+pub enum ArrowError {
+    #[snafu(display("query does not return data"))]
+    NoOutput { backtrace: Backtrace },
+    #[snafu(display("type at position {} has no arrow representation", position))]
+    UnsupportedType { backtrace: Backtrace, position: u16 },
+    #[snafu(display("value of type {} does not fit arrow type {}", value_type, data_type))]
+    ArrowValueMismatch {
+        backtrace: Backtrace,
+        value_type: &'static str,
+        data_type: arrow_schema::DataType,
+    },
+    #[snafu(display("no conversion to arrow type {}", data_type))]
+    UnsupportedArrowType {
+        backtrace: Backtrace,
+        data_type: arrow_schema::DataType,
+    },
+    #[snafu(display("list has more than 2Gi elements in total"))]
+    ListTooLong { backtrace: Backtrace },
+    #[snafu(display("error building codec: {}", source))]
+    ArrowCodec {
+        backtrace: Backtrace,
+        #[snafu(source(from(CodecError, Box::new)))]
+        source: Box<CodecError>,
+    },
+    #[snafu(display("error decoding value: {}", source))]
+    ArrowDecode {
+        backtrace: Backtrace,
+        #[snafu(source(from(DecodeError, Box::new)))]
+        source: Box<DecodeError>,
+    },
+    #[snafu(display("error building record batch: {}", source))]
+    Arrow {
+        backtrace: Backtrace,
+        #[snafu(source(from(arrow_schema::ArrowError, Box::new)))]
+        source: Box<arrow_schema::ArrowError>,
+    },
+}
+
 pub fn invalid_value(codec: &'static str, value: &Value) -> EncodeError {
     InvalidValue {
         codec,
@@ -12,6 +12,7 @@ for working with the client such as:
 * [QueryArg](crate::query_arg::QueryArg): a single argument for a query
 * [QueryArgs](crate::query_arg::QueryArgs): a tuple of query arguments
 * [Queryable](crate::queryable::Queryable): for the Queryable derive macro
+* [Shape](crate::shape::Shape) and [Insertable](crate::shape::Insertable): query text generated by the derive macros
 * [QueryResult]: single result from a query (scalars and tuples)
 
 The Value enum:
@@ -70,14 +71,21 @@ pub mod features;
 pub mod queryable;
 pub mod serialization;
 pub mod server_message;
+pub mod shape;
 pub mod value;
 #[macro_use]
 pub mod value_opt;
 pub mod annotations;
+#[cfg(feature = "arrow")]
+pub mod arrow;
 pub mod model;
 pub mod query_arg;
 
 pub use query_result::QueryResult;
 
+// Used by the code generated in gel-derive
+#[doc(hidden)]
+pub use gel_errors;
+
 #[doc(hidden)]
 use gel_db_protocol::protocol as new_protocol;
@@ -1,6 +1,7 @@
 //! # Gel Types Used for Data Modelling
 
 mod bignum;
+mod bytes_str;
 mod json;
 mod memory;
 mod time;
@@ -9,14 +10,16 @@ mod vector;
 pub(crate) mod range;
 
 pub use self::bignum::{BigInt, Decimal};
+pub use self::bytes_str::BytesStr;
 pub use self::json::Json;
 pub use self::time::{DateDuration, RelativeDuration};
 pub use self::time::{Datetime, Duration, LocalDate, LocalDatetime, LocalTime};
+pub use half::f16;
 pub use memory::ConfigMemory;
 pub use range::Range;
 pub use uuid::Uuid;
-pub use vector::Vector;
 pub(crate) use vector::VectorRef;
+pub use vector::{HalfVector, SparseVector, Vector};
 
 use std::fmt;
 use std::num::ParseIntError;
@@ -0,0 +1,122 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::str::{self, Utf8Error};
+
+use bytes::Bytes;
+
+/// A string backed by [Bytes]
+///
+/// Decoding `std::str` into this type shares memory with the received data
+/// message instead of copying it into a new `String`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct BytesStr(Bytes);
+
+impl BytesStr {
+    /// Create a string from bytes, checking that they are valid UTF-8.
+    pub fn from_utf8(bytes: Bytes) -> Result<BytesStr, Utf8Error> {
+        str::from_utf8(&bytes)?;
+        Ok(BytesStr(bytes))
+    }
+
+    /// Create a string from a static `str` without copying.
+    pub const fn from_static(value: &'static str) -> BytesStr {
+        BytesStr(Bytes::from_static(value.as_bytes()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        // SAFETY: contents are checked on construction
+        unsafe { str::from_utf8_unchecked(&self.0) }
+    }
+
+    /// Return the underlying bytes.
+    pub fn into_bytes(self) -> Bytes {
+        self.0
+    }
+}
+
+impl std::ops::Deref for BytesStr {
+    type Target = str;
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for BytesStr {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<[u8]> for BytesStr {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Borrow<str> for BytesStr {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for BytesStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for BytesStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq<str> for BytesStr {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for BytesStr {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl From<String> for BytesStr {
+    fn from(value: String) -> BytesStr {
+        BytesStr(Bytes::from(value))
+    }
+}
+
+impl From<&'static str> for BytesStr {
+    fn from(value: &'static str) -> BytesStr {
+        BytesStr::from_static(value)
+    }
+}
+
+impl From<BytesStr> for String {
+    fn from(value: BytesStr) -> String {
+        value.as_str().to_owned()
+    }
+}
+
+impl From<BytesStr> for Bytes {
+    fn from(value: BytesStr) -> Bytes {
+        value.0
+    }
+}
+
+#[cfg(feature = "with-serde")]
+impl serde::Serialize for BytesStr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "with-serde")]
+impl<'de> serde::Deserialize<'de> for BytesStr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(BytesStr::from)
+    }
+}
@@ -1,13 +1,17 @@
+use std::convert::TryFrom;
 use std::ops::{Deref, DerefMut};
 
-use bytes::Buf;
+use bytes::{Buf, BufMut, BytesMut};
+use half::f16;
 
-use snafu::ensure;
+use snafu::{ensure, OptionExt};
 
 use crate::descriptors::TypePos;
-use crate::errors::{self, DecodeError};
+use crate::errors::{self, DecodeError, EncodeError};
+use crate::model::OutOfRangeError;
 use crate::queryable::{Decoder, Queryable};
-use crate::serialization::decode::queryable::scalars::check_scalar;
+use crate::serialization::decode::queryable::scalars::{check_named_scalar, check_scalar};
+use crate::serialization::decode::RawCodec;
 use crate::{codec, queryable};
 
 /// A structure that represents `ext::pgvector::vector`
@@ -55,3 +59,187 @@ impl Queryable for Vector {
 
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) struct VectorRef<'a>(pub &'a [f32]);
+
+/// A structure that represents `ext::pgvector::halfvec`
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HalfVector(pub Vec<f16>);
+
+impl Deref for HalfVector {
+    type Target = Vec<f16>;
+    fn deref(&self) -> &Vec<f16> {
+        &self.0
+    }
+}
+
+impl DerefMut for HalfVector {
+    fn deref_mut(&mut self) -> &mut Vec<f16> {
+        &mut self.0
+    }
+}
+
+impl HalfVector {
+    pub(crate) fn encode_raw(&self, buf: &mut BytesMut) -> Result<(), EncodeError> {
+        buf.reserve(4 + self.0.len() * 2);
+        buf.put_u16(
+            u16::try_from(self.0.len())
+                .ok()
+                .context(errors::ArrayTooLong)?,
+        );
+        buf.put_u16(0); // reserved
+        for item in &self.0 {
+            buf.put_u16(item.to_bits());
+        }
+        Ok(())
+    }
+}
+
+impl RawCodec<'_> for HalfVector {
+    fn decode(mut buf: &[u8]) -> Result<Self, DecodeError> {
+        ensure!(buf.remaining() >= 4, errors::Underflow);
+        let length = buf.get_u16() as usize;
+        let _reserved = buf.get_u16();
+        ensure!(buf.remaining() >= length * 2, errors::Underflow);
+        ensure!(buf.remaining() == length * 2, errors::ExtraData);
+        let vec = (0..length).map(|_| f16::from_bits(buf.get_u16())).collect();
+        Ok(HalfVector(vec))
+    }
+}
+
+impl Queryable for HalfVector {
+    type Args = ();
+
+    fn decode(_decoder: &Decoder, _args: &(), buf: &[u8]) -> Result<Self, DecodeError> {
+        RawCodec::decode(buf)
+    }
+
+    fn check_descriptor(
+        ctx: &queryable::DescriptorContext,
+        type_pos: TypePos,
+    ) -> Result<(), queryable::DescriptorMismatch> {
+        check_named_scalar(ctx, type_pos, codec::PGVECTOR_HALFVEC_NAME)
+    }
+}
+
+/// A structure that represents `ext::pgvector::sparsevec`
+///
+/// Only non-zero elements are stored, as `(index, value)` pairs sorted by
+/// index.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "with-serde", serde(try_from = "RawSparseVector"))]
+pub struct SparseVector {
+    dim: u32,
+    entries: Vec<(u32, f32)>,
+}
+
+/// Unchecked form of [SparseVector], which is validated on deserialization.
+#[cfg(feature = "with-serde")]
+#[derive(serde::Deserialize)]
+struct RawSparseVector {
+    dim: u32,
+    entries: Vec<(u32, f32)>,
+}
+
+#[cfg(feature = "with-serde")]
+impl TryFrom<RawSparseVector> for SparseVector {
+    type Error = OutOfRangeError;
+    fn try_from(raw: RawSparseVector) -> Result<SparseVector, OutOfRangeError> {
+        SparseVector::new(raw.dim, raw.entries)
+    }
+}
+
+impl SparseVector {
+    /// Create a vector with `dim` dimensions from `(index, value)` pairs.
+    ///
+    /// Pairs may come in any order and zero values are skipped. Returns an
+    /// error if an index is repeated or is not less than `dim`.
+    pub fn new(
+        dim: u32,
+        entries: impl IntoIterator<Item = (u32, f32)>,
+    ) -> Result<SparseVector, OutOfRangeError> {
+        let mut entries: Vec<_> = entries.into_iter().filter(|(_, v)| *v != 0.0).collect();
+        entries.sort_by_key(|(idx, _)| *idx);
+        for (pos, (idx, _)) in entries.iter().enumerate() {
+            if *idx >= dim || (pos > 0 && entries[pos - 1].0 == *idx) {
+                return Err(OutOfRangeError);
+            }
+        }
+        Ok(SparseVector { dim, entries })
+    }
+    /// Create a sparse vector from all of its elements.
+    pub fn from_dense(values: &[f32]) -> Result<SparseVector, OutOfRangeError> {
+        let dim = u32::try_from(values.len())?;
+        let entries = (0..dim).zip(values.iter().copied());
+        SparseVector::new(dim, entries)
+    }
+    /// Number of dimensions, including the zero elements.
+    pub fn dim(&self) -> u32 {
+        self.dim
+    }
+    /// Non-zero elements as `(index, value)` pairs, sorted by index.
+    pub fn entries(&self) -> &[(u32, f32)] {
+        &self.entries
+    }
+    /// Return all elements of the vector, including the zero ones.
+    pub fn to_dense(&self) -> Vec<f32> {
+        let mut result = vec![0.0; self.dim as usize];
+        for (idx, value) in &self.entries {
+            result[*idx as usize] = *value;
+        }
+        result
+    }
+
+    pub(crate) fn encode_raw(&self, buf: &mut BytesMut) -> Result<(), EncodeError> {
+        let nnz = i32::try_from(self.entries.len())
+            .ok()
+            .context(errors::ArrayTooLong)?;
+        buf.reserve(12 + self.entries.len() * 8);
+        buf.put_i32(i32::try_from(self.dim).ok().context(errors::ArrayTooLong)?);
+        buf.put_i32(nnz);
+        buf.put_i32(0); // reserved
+        for (idx, _) in &self.entries {
+            buf.put_u32(*idx);
+        }
+        for (_, value) in &self.entries {
+            buf.put_u32(value.to_bits());
+        }
+        Ok(())
+    }
+}
+
+impl RawCodec<'_> for SparseVector {
+    fn decode(mut buf: &[u8]) -> Result<Self, DecodeError> {
+        ensure!(buf.remaining() >= 12, errors::Underflow);
+        let dim = buf.get_u32();
+        let nnz = buf.get_u32() as usize;
+        let _reserved = buf.get_u32();
+        ensure!(buf.remaining() >= nnz * 8, errors::Underflow);
+        ensure!(buf.remaining() == nnz * 8, errors::ExtraData);
+        let (mut indices, mut values) = buf.split_at(nnz * 4);
+        let entries: Vec<_> = (0..nnz)
+            .map(|_| (indices.get_u32(), f32::from_bits(values.get_u32())))
+            .collect();
+        let ordered = entries.windows(2).all(|pair| pair[0].0 < pair[1].0);
+        ensure!(
+            ordered && entries.last().is_none_or(|(idx, _)| *idx < dim),
+            errors::InvalidSparseVector
+        );
+        Ok(SparseVector { dim, entries })
+    }
+}
+
+impl Queryable for SparseVector {
+    type Args = ();
+
+    fn decode(_decoder: &Decoder, _args: &(), buf: &[u8]) -> Result<Self, DecodeError> {
+        RawCodec::decode(buf)
+    }
+
+    fn check_descriptor(
+        ctx: &queryable::DescriptorContext,
+        type_pos: TypePos,
+    ) -> Result<(), queryable::DescriptorMismatch> {
+        check_named_scalar(ctx, type_pos, codec::PGVECTOR_SPARSEVEC_NAME)
+    }
+}
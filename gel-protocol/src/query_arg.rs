@@ -37,6 +37,13 @@ pub trait QueryArg: Send + Sync + Sized {
 pub trait ScalarArg: Send + Sync + Sized {
     fn encode(&self, encoder: &mut Encoder) -> Result<(), Error>;
     fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error>;
+    /// Check that this particular value fits the descriptor.
+    ///
+    /// Defaults to [ScalarArg::check_descriptor]. Types where only some
+    /// values fit a descriptor of the right kind, like enums, override it.
+    fn check_value(&self, ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        Self::check_descriptor(ctx, pos)
+    }
     fn to_value(&self) -> Result<Value, Error>;
 }
 
@@ -93,12 +100,21 @@ impl DescriptorContext<'_> {
     }
     pub fn wrong_type(&self, descriptor: &Descriptor, expected: &str) -> Error {
         DescriptorMismatch::with_message(format!(
-            "server returned unexpected type {descriptor:?} when client expected {expected}"
+            "server returned unexpected type {} (Rust type `{}`) when client expected {expected}",
+            descriptor.edgeql_type(self.descriptors),
+            descriptor.rust_type(self.descriptors),
         ))
     }
     pub fn field_number(&self, expected: usize, unexpected: usize) -> Error {
         DescriptorMismatch::with_message(format!("expected {expected} fields, got {unexpected}"))
     }
+    /// Check that `label` is a member of the enum type at `pos`.
+    pub fn check_enum_member(&self, pos: TypePos, label: &str) -> Result<(), Error> {
+        match self.get(pos)? {
+            Descriptor::Enumeration(desc) => check_enum(label, &desc.members),
+            desc => Err(self.wrong_type(desc, "enum")),
+        }
+    }
 }
 
 impl<T: ScalarArg> ScalarArg for &T {
@@ -109,6 +125,9 @@ impl<T: ScalarArg> ScalarArg for &T {
     fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
         T::check_descriptor(ctx, pos)
     }
+    fn check_value(&self, ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        (*self).check_value(ctx, pos)
+    }
 
     fn to_value(&self) -> Result<Value, Error> {
         (*self).to_value()
@@ -204,6 +223,8 @@ impl QueryArg for Value {
             Enum(v) => v.encode_slot(enc)?,
             Range(v) => v.encode_slot(enc)?,
             Vector(v) => crate::model::VectorRef(v).encode_slot(enc)?,
+            HalfVector(v) => v.encode_slot(enc)?,
+            SparseVector(v) => v.encode_slot(enc)?,
             PostGisGeometry(v) => v.encode_slot(enc)?,
             PostGisGeography(v) => v.encode_slot(enc)?,
             PostGisBox2d(v) => v.encode_slot(enc)?,
@@ -249,6 +270,12 @@ impl QueryArg for Value {
                 check_enum(val, &members)
             }
             (Vector(_), BaseScalar(d)) if d.id == codec::PGVECTOR_VECTOR => Ok(()),
+            (HalfVector(_), _) => {
+                <crate::model::HalfVector as ScalarArg>::check_descriptor(ctx, pos)
+            }
+            (SparseVector(_), _) => {
+                <crate::model::SparseVector as ScalarArg>::check_descriptor(ctx, pos)
+            }
             (PostGisGeometry(_), BaseScalar(d)) if d.id == codec::POSTGIS_GEOMETRY => Ok(()),
             (PostGisGeography(_), BaseScalar(d)) if d.id == codec::POSTGIS_GEOGRAPHY => Ok(()),
             (PostGisBox2d(_), BaseScalar(d)) if d.id == codec::POSTGIS_BOX_2D => Ok(()),
@@ -304,7 +331,7 @@ impl<T: ScalarArg> QueryArg for T {
         Ok(())
     }
     fn check_descriptor(&self, ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
-        T::check_descriptor(ctx, pos)
+        ScalarArg::check_value(self, ctx, pos)
     }
     fn to_value(&self) -> Result<Value, Error> {
         ScalarArg::to_value(self)
@@ -322,7 +349,10 @@ impl<T: ScalarArg> QueryArg for Option<T> {
         }
     }
     fn check_descriptor(&self, ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
-        T::check_descriptor(ctx, pos)
+        match self {
+            Some(val) => val.check_value(ctx, pos),
+            None => T::check_descriptor(ctx, pos),
+        }
     }
     fn to_value(&self) -> Result<Value, Error> {
         match self.as_ref() {
@@ -362,7 +392,11 @@ impl<T: ScalarArg> QueryArg for Vec<T> {
     fn check_descriptor(&self, ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
         let desc = ctx.get(pos)?;
         if let Descriptor::Array(arr) = desc {
-            T::check_descriptor(ctx, arr.type_pos)
+            T::check_descriptor(ctx, arr.type_pos)?;
+            for item in self {
+                item.check_value(ctx, arr.type_pos)?;
+            }
+            Ok(())
         } else {
             Err(ctx.wrong_type(desc, "array"))
         }
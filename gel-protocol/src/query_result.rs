@@ -39,11 +39,15 @@ impl<T: Queryable> QueryResult for T {
             has_implicit_id: ctx.has_implicit_id,
             has_implicit_tid: ctx.has_implicit_tid,
             has_implicit_tname: ctx.has_implicit_tname,
+            source: None,
         };
         Ok((decoder, args))
     }
     fn decode((decoder, args): &mut Self::State, msg: &Bytes) -> Result<Self, Error> {
-        Queryable::decode(decoder, args, msg).map_err(ProtocolEncodingError::with_source)
+        decoder.source = Some(msg.clone());
+        let result = Queryable::decode(decoder, args, msg);
+        decoder.source = None;
+        result.map_err(ProtocolEncodingError::with_source)
     }
 }
 
@@ -1,6 +1,7 @@
 /*!
 Contains the [Queryable] trait.
 */
+use bytes::Bytes;
 use snafu::{ensure, Snafu};
 use std::default::Default;
 use std::sync::Arc;
@@ -16,6 +17,33 @@ pub struct Decoder {
     pub has_implicit_id: bool,
     pub has_implicit_tid: bool,
     pub has_implicit_tname: bool,
+    /// Data message currently being decoded, if any
+    pub(crate) source: Option<Bytes>,
+}
+
+impl Decoder {
+    /// Set the data message that subsequent `decode` calls read from.
+    ///
+    /// This allows [Bytes]-backed types to share memory with the message
+    /// instead of copying.
+    pub fn set_source(&mut self, source: Option<Bytes>) {
+        self.source = source;
+    }
+
+    /// Return `buf` as [Bytes], sharing memory with the source message when
+    /// `buf` points into it and copying otherwise.
+    pub fn bytes(&self, buf: &[u8]) -> Bytes {
+        match &self.source {
+            Some(source) if contains(source, buf) => source.slice_ref(buf),
+            _ => Bytes::copy_from_slice(buf),
+        }
+    }
+}
+
+fn contains(outer: &[u8], inner: &[u8]) -> bool {
+    let outer = outer.as_ptr_range();
+    let inner = inner.as_ptr_range();
+    outer.start <= inner.start && inner.end <= outer.end
 }
 
 pub trait Queryable: Sized {
@@ -39,6 +67,30 @@ pub trait Queryable: Sized {
     ) -> Result<Self::Args, DescriptorMismatch>;
 }
 
+/// A type that can be decoded by borrowing from the data message, like `&str`
+/// and `&[u8]`.
+///
+/// The descriptor is checked by the [Owned](QueryableRef::Owned) type, so a
+/// query prepared for `String` can be decoded as `&str` too. Every
+/// [Queryable] type implements this trait as its own owned type.
+pub trait QueryableRef<'t>: Sized {
+    type Owned: Queryable;
+
+    fn decode_ref(
+        decoder: &Decoder,
+        args: &<Self::Owned as Queryable>::Args,
+        buf: &'t [u8],
+    ) -> Result<Self, DecodeError>;
+}
+
+impl<T: Queryable> QueryableRef<'_> for T {
+    type Owned = T;
+
+    fn decode_ref(decoder: &Decoder, args: &T::Args, buf: &[u8]) -> Result<Self, DecodeError> {
+        T::decode(decoder, args, buf)
+    }
+}
+
 #[derive(Snafu, Debug)]
 #[non_exhaustive]
 pub enum DescriptorMismatch {
@@ -79,6 +131,9 @@ impl DescriptorContext<'_> {
     pub fn build_codec(&self, root_pos: TypePos) -> Result<Arc<dyn Codec>, Error> {
         build_codec(Some(root_pos), self.descriptors).map_err(ProtocolEncodingError::with_source)
     }
+    pub(crate) fn descriptors(&self) -> &[Descriptor] {
+        self.descriptors
+    }
     pub fn get(&self, type_pos: TypePos) -> Result<&Descriptor, DescriptorMismatch> {
         self.descriptors
             .get(type_pos.0 as usize)
@@ -86,7 +141,11 @@ impl DescriptorContext<'_> {
     }
     pub fn wrong_type(&self, descriptor: &Descriptor, expected: &str) -> DescriptorMismatch {
         DescriptorMismatch::WrongType {
-            unexpected: format!("{descriptor:?}"),
+            unexpected: format!(
+                "{} (Rust type `{}`)",
+                descriptor.edgeql_type(self.descriptors),
+                descriptor.rust_type(self.descriptors),
+            ),
             expected: expected.into(),
         }
     }
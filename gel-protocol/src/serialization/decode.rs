@@ -8,4 +8,4 @@ mod chrono;
 pub(crate) use self::raw_composite::DecodeArrayLike;
 pub(crate) use self::raw_composite::DecodeRange;
 pub use self::raw_composite::DecodeTupleLike;
-pub(crate) use self::raw_scalar::RawCodec;
+pub use self::raw_scalar::RawCodec;
@@ -1,12 +1,12 @@
 use bytes::Bytes;
 
 use crate::queryable::DescriptorMismatch;
-use crate::queryable::{Decoder, DescriptorContext, Queryable};
+use crate::queryable::{Decoder, DescriptorContext, Queryable, QueryableRef};
 
 use crate::codec;
 use crate::descriptors::TypePos;
 use crate::errors::DecodeError;
-use crate::model::{BigInt, Decimal, Json, RelativeDuration, Uuid};
+use crate::model::{BigInt, BytesStr, Decimal, Json, RelativeDuration, Uuid};
 use crate::model::{ConfigMemory, DateDuration};
 use crate::model::{Datetime, Duration, LocalDate, LocalDatetime, LocalTime};
 use crate::serialization::decode::RawCodec;
@@ -35,6 +35,19 @@ pub(crate) fn check_scalar(
     Err(ctx.wrong_type(desc, name))
 }
 
+/// Check an extension scalar that has no well-known id by its type name.
+pub(crate) fn check_named_scalar(
+    ctx: &DescriptorContext,
+    type_pos: TypePos,
+    name: &str,
+) -> Result<(), DescriptorMismatch> {
+    let desc = ctx.get(type_pos)?;
+    if desc.base_scalar_name(ctx.descriptors()) == Some(name) {
+        return Ok(());
+    }
+    Err(ctx.wrong_type(desc, name))
+}
+
 pub trait DecodeScalar: for<'a> RawCodec<'a> + Sized {
     fn uuid() -> Uuid;
     fn typename() -> &'static str;
@@ -63,12 +76,48 @@ impl DecodeScalar for String {
     }
 }
 
-impl DecodeScalar for Bytes {
-    fn uuid() -> Uuid {
-        codec::STD_BYTES
+impl Queryable for Bytes {
+    type Args = ();
+
+    fn decode(decoder: &Decoder, _args: &(), buf: &[u8]) -> Result<Self, DecodeError> {
+        Ok(decoder.bytes(buf))
     }
-    fn typename() -> &'static str {
-        "std::bytes"
+    fn check_descriptor(
+        ctx: &DescriptorContext,
+        type_pos: TypePos,
+    ) -> Result<(), DescriptorMismatch> {
+        check_scalar(ctx, type_pos, codec::STD_BYTES, "std::bytes")
+    }
+}
+
+impl Queryable for BytesStr {
+    type Args = ();
+
+    fn decode(decoder: &Decoder, _args: &(), buf: &[u8]) -> Result<Self, DecodeError> {
+        <&str>::decode(buf)?;
+        Ok(BytesStr::from_utf8(decoder.bytes(buf)).expect("checked above"))
+    }
+    fn check_descriptor(
+        ctx: &DescriptorContext,
+        type_pos: TypePos,
+    ) -> Result<(), DescriptorMismatch> {
+        check_scalar(ctx, type_pos, codec::STD_STR, "std::str")
+    }
+}
+
+impl<'t> QueryableRef<'t> for &'t str {
+    type Owned = String;
+
+    fn decode_ref(_decoder: &Decoder, _args: &(), buf: &'t [u8]) -> Result<Self, DecodeError> {
+        RawCodec::decode(buf)
+    }
+}
+
+impl<'t> QueryableRef<'t> for &'t [u8] {
+    type Owned = Bytes;
+
+    fn decode_ref(_decoder: &Decoder, _args: &(), buf: &'t [u8]) -> Result<Self, DecodeError> {
+        RawCodec::decode(buf)
     }
 }
 
@@ -10,8 +10,8 @@ use snafu::{ensure, ResultExt};
 use crate::codec;
 use crate::descriptors::{Descriptor, TypePos};
 use crate::errors::{self, DecodeError};
-use crate::model::{range, Vector, VectorRef};
-use crate::model::{BigInt, Decimal};
+use crate::model::{range, HalfVector, SparseVector, Vector, VectorRef};
+use crate::model::{BigInt, BytesStr, Decimal};
 use crate::model::{ConfigMemory, Range};
 use crate::model::{DateDuration, RelativeDuration};
 use crate::model::{Datetime, Duration, LocalDate, LocalDatetime, LocalTime};
@@ -64,6 +64,14 @@ fn check_scalar(
     Err(ctx.wrong_type(desc, name))
 }
 
+fn check_named_scalar(ctx: &DescriptorContext, type_pos: TypePos, name: &str) -> Result<(), Error> {
+    let desc = ctx.get(type_pos)?;
+    if desc.base_scalar_name(ctx.descriptors) == Some(name) {
+        return Ok(());
+    }
+    Err(ctx.wrong_type(desc, name))
+}
+
 impl ScalarArg for String {
     fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
         encoder.buf.extend(self.as_bytes());
@@ -102,6 +110,25 @@ impl<'t> RawCodec<'t> for &'t str {
     }
 }
 
+impl RawCodec<'_> for BytesStr {
+    fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        <&str>::decode(buf).map(|s| BytesStr::from(s.to_owned()))
+    }
+}
+
+impl ScalarArg for BytesStr {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        encoder.buf.extend(self.as_bytes());
+        Ok(())
+    }
+    fn check_descriptor(ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        check_scalar(ctx, pos, codec::STD_STR, "std::str")
+    }
+    fn to_value(&self) -> Result<Value, Error> {
+        Ok(Value::Str(self.to_string()))
+    }
+}
+
 impl ScalarArg for Json {
     fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
         encoder.buf.reserve(self.len() + 1);
@@ -667,12 +694,13 @@ impl ScalarArg for EnumValue {
 
         let desc = ctx.get(pos)?;
         if let Enumeration(_) = desc {
-            // Should we check enum members?
-            // Should we override `QueryArg` check descriptor for that?
-            // Or maybe implement just `QueryArg` for enum?
+            return Ok(());
         }
         Err(ctx.wrong_type(desc, "enum"))
     }
+    fn check_value(&self, ctx: &DescriptorContext, pos: TypePos) -> Result<(), Error> {
+        ctx.check_enum_member(pos, self)
+    }
     fn to_value(&self) -> Result<Value, Error> {
         Ok(Value::Enum(self.clone()))
     }
@@ -773,3 +801,33 @@ impl ScalarArg for Vector {
         VectorRef(&self.0).to_value()
     }
 }
+
+impl ScalarArg for HalfVector {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        self.encode_raw(encoder.buf)
+            .map_err(ClientEncodingError::with_source)
+    }
+
+    fn check_descriptor(ctx: &DescriptorContext, type_pos: TypePos) -> Result<(), Error> {
+        check_named_scalar(ctx, type_pos, codec::PGVECTOR_HALFVEC_NAME)
+    }
+
+    fn to_value(&self) -> Result<Value, Error> {
+        Ok(Value::HalfVector(self.clone()))
+    }
+}
+
+impl ScalarArg for SparseVector {
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), Error> {
+        self.encode_raw(encoder.buf)
+            .map_err(ClientEncodingError::with_source)
+    }
+
+    fn check_descriptor(ctx: &DescriptorContext, type_pos: TypePos) -> Result<(), Error> {
+        check_named_scalar(ctx, type_pos, codec::PGVECTOR_SPARSEVEC_NAME)
+    }
+
+    fn to_value(&self) -> Result<Value, Error> {
+        Ok(Value::SparseVector(self.clone()))
+    }
+}
@@ -741,7 +741,7 @@ impl Decode for Data {
         let message = new_protocol::Data::new(buf)?;
         let mut data = Vec::new();
         for element in message.data() {
-            data.push(element.data().into_bytes().to_owned().into());
+            data.push(buf.slice_ref(element.data().into_bytes()));
         }
 
         let decoded = Data { data };
@@ -0,0 +1,102 @@
+/*!
+Contains the [Shape] and [Insertable] traits, implemented by the
+corresponding options of the `gel-derive` macros.
+*/
+use std::collections::HashMap;
+
+use gel_errors::Error;
+
+use crate::query_arg::QueryArg;
+use crate::value::Value;
+use crate::value_opt::ValueOpt;
+
+/// A type that knows the EdgeQL shape it is selected with.
+///
+/// Implemented by `#[derive(Queryable)]` with the `#[gel(shape)]` attribute:
+///
+/// ```rust,ignore
+/// #[derive(Queryable)]
+/// #[gel(shape)]
+/// struct User {
+///     first_name: String,
+///     age: i32,
+/// }
+///
+/// assert_eq!(User::SHAPE, "{ first_name, age }");
+/// let query = format!("select User {}", User::SHAPE);
+/// ```
+pub trait Shape {
+    /// Shape of the object, including the braces.
+    const SHAPE: &'static str;
+}
+
+impl<T: Shape> Shape for Option<T> {
+    const SHAPE: &'static str = T::SHAPE;
+}
+
+impl<T: Shape> Shape for Vec<T> {
+    const SHAPE: &'static str = T::SHAPE;
+}
+
+/// A type that can be inserted as an object.
+///
+/// Implemented by `#[derive(Insertable)]`:
+///
+/// ```rust,ignore
+/// #[derive(Insertable)]
+/// struct User {
+///     first_name: String,
+///     age: Option<i32>,
+/// }
+///
+/// assert_eq!(
+///     User::INSERT,
+///     "insert User { first_name := <str>$first_name, age := <optional int32>$age }",
+/// );
+/// client.execute(User::INSERT, &user.insert_args()?).await?;
+/// ```
+pub trait Insertable {
+    /// The `insert` statement, with a named argument for each field.
+    const INSERT: &'static str;
+    /// Named arguments for [Insertable::INSERT].
+    fn insert_args(&self) -> Result<HashMap<&'static str, ValueOpt>, Error>;
+}
+
+/// Convert an argument to a named argument, `None` becoming an empty set.
+#[doc(hidden)]
+pub fn named_arg<T: QueryArg>(value: &T) -> Result<ValueOpt, Error> {
+    match value.to_value()? {
+        Value::Nothing => Ok(ValueOpt::from(None::<Value>)),
+        value => Ok(ValueOpt::from(value)),
+    }
+}
+
+#[doc(hidden)]
+pub const fn concat_len(parts: &[&str]) -> usize {
+    let mut len = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        len += parts[i].len();
+        i += 1;
+    }
+    len
+}
+
+/// Concatenate strings at compile time, used for shapes with nested links.
+#[doc(hidden)]
+pub const fn concat<const N: usize>(parts: &[&str]) -> [u8; N] {
+    let mut buf = [0; N];
+    let mut pos = 0;
+    let mut i = 0;
+    while i < parts.len() {
+        let part = parts[i].as_bytes();
+        let mut j = 0;
+        while j < part.len() {
+            buf[pos] = part[j];
+            pos += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    buf
+}
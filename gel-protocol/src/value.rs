@@ -9,6 +9,7 @@ use crate::common::Cardinality;
 use crate::model::{BigInt, ConfigMemory, Decimal, Range, Uuid};
 use crate::model::{DateDuration, Json, RelativeDuration};
 use crate::model::{Datetime, Duration, LocalDate, LocalDatetime, LocalTime};
+use crate::model::{HalfVector, SparseVector};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
@@ -50,6 +51,8 @@ pub enum Value {
     },
     Array(Vec<Value>),
     Vector(Vec<f32>),
+    HalfVector(HalfVector),
+    SparseVector(SparseVector),
     Enum(EnumValue),
     Range(Range<Box<Value>>),
     PostGisGeometry(bytes::Bytes),
@@ -98,6 +101,8 @@ impl Value {
             Tuple(..) => "tuple",
             Uuid(..) => "uuid",
             Vector(..) => "ext::pgvector::vector",
+            HalfVector(..) => "ext::pgvector::halfvec",
+            SparseVector(..) => "ext::pgvector::sparsevec",
             PostGisGeometry(..) => "ext::postgis::geometry",
             PostGisGeography(..) => "ext::postgis::geography",
             PostGisBox2d(..) => "ext::postgis::box2d",
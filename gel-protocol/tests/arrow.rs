@@ -0,0 +1,221 @@
+#![cfg(feature = "arrow")]
+
+use std::error::Error;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Int32Type, Int64Type};
+use arrow_array::Array;
+use arrow_schema::DataType;
+use bytes::BytesMut;
+
+use gel_protocol::arrow::{build_schema, RecordBatchDecoder};
+use gel_protocol::codec::{build_codec, ObjectShape};
+use gel_protocol::common::Cardinality;
+use gel_protocol::descriptors::{ArrayTypeDescriptor, BaseScalarTypeDescriptor};
+use gel_protocol::descriptors::{Descriptor, TypePos};
+use gel_protocol::descriptors::{ObjectShapeDescriptor, ShapeElement};
+use gel_protocol::descriptors::{RangeTypeDescriptor, TupleTypeDescriptor};
+use gel_protocol::server_message::Data;
+use gel_protocol::value::Value;
+use uuid::Uuid;
+
+fn base_scalar(id: u128) -> Descriptor {
+    Descriptor::BaseScalar(BaseScalarTypeDescriptor {
+        id: Uuid::from_u128(id).into(),
+    })
+}
+
+fn element(name: &str, type_pos: u16, cardinality: Cardinality, implicit: bool) -> ShapeElement {
+    ShapeElement {
+        flag_implicit: implicit,
+        flag_link_property: false,
+        flag_link: false,
+        cardinality: Some(cardinality),
+        name: name.into(),
+        type_pos: TypePos(type_pos),
+        source_type_pos: None,
+    }
+}
+
+fn encode(
+    root_pos: TypePos,
+    descriptors: &[Descriptor],
+    values: &[Value],
+) -> Result<Data, Box<dyn Error>> {
+    let codec = build_codec(Some(root_pos), descriptors)?;
+    let mut data = Vec::new();
+    for value in values {
+        let mut buf = BytesMut::new();
+        codec.encode(&mut buf, value)?;
+        data.push(buf.freeze());
+    }
+    Ok(Data { data })
+}
+
+fn user_descriptors() -> (Vec<ShapeElement>, Vec<Descriptor>) {
+    let elements = vec![
+        element("id", 0, Cardinality::One, true),
+        element("name", 1, Cardinality::One, false),
+        element("age", 2, Cardinality::AtMostOne, false),
+        element("tags", 3, Cardinality::One, false),
+    ];
+    let descriptors = vec![
+        base_scalar(0x100),
+        base_scalar(0x101),
+        base_scalar(0x104),
+        Descriptor::Array(ArrayTypeDescriptor {
+            id: Uuid::from_u128(0xA1).into(),
+            type_pos: TypePos(1),
+            dimensions: vec![None],
+            name: None,
+            schema_defined: None,
+            ancestors: vec![],
+        }),
+        Descriptor::ObjectShape(ObjectShapeDescriptor {
+            id: Uuid::from_u128(0xA2).into(),
+            ephemeral_free_shape: false,
+            type_pos: None,
+            elements: elements.clone(),
+        }),
+    ];
+    (elements, descriptors)
+}
+
+fn user(shape: &ObjectShape, idx: u128, name: &str, age: Option<i32>, tags: &[&str]) -> Value {
+    Value::Object {
+        shape: shape.clone(),
+        fields: vec![
+            Some(Value::Uuid(Uuid::from_u128(idx))),
+            Some(Value::Str(name.into())),
+            age.map(Value::Int32),
+            Some(Value::Array(
+                tags.iter().map(|t| Value::Str((*t).into())).collect(),
+            )),
+        ],
+    }
+}
+
+#[test]
+fn object_schema() -> Result<(), Box<dyn Error>> {
+    let (_, descriptors) = user_descriptors();
+    let schema = build_schema(Some(TypePos(4)), &descriptors)?;
+    let names = schema
+        .fields()
+        .iter()
+        .map(|f| f.name().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["name", "age", "tags"]);
+    assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+    assert!(!schema.field(0).is_nullable());
+    assert_eq!(schema.field(1).data_type(), &DataType::Int32);
+    assert!(schema.field(1).is_nullable());
+    assert!(matches!(schema.field(2).data_type(), DataType::List(_)));
+    Ok(())
+}
+
+#[test]
+fn object_batches() -> Result<(), Box<dyn Error>> {
+    let (elements, descriptors) = user_descriptors();
+    let shape = ObjectShape::from(&elements[..]);
+    let data = encode(
+        TypePos(4),
+        &descriptors,
+        &[
+            user(&shape, 1, "alice", Some(31), &["a", "b"]),
+            user(&shape, 2, "bob", None, &[]),
+            user(&shape, 3, "carol", Some(27), &["c"]),
+        ],
+    )?;
+
+    let mut decoder = RecordBatchDecoder::new(Some(TypePos(4)), &descriptors, 2)?;
+    let batches = decoder.decode(&data)?;
+    assert_eq!(batches.len(), 1);
+    let batch = &batches[0];
+    assert_eq!(batch.num_rows(), 2);
+    let names = batch.column(0).as_string::<i32>();
+    assert_eq!(names.value(0), "alice");
+    assert_eq!(names.value(1), "bob");
+    let ages = batch.column(1).as_primitive::<Int32Type>();
+    assert_eq!(ages.value(0), 31);
+    assert!(ages.is_null(1));
+    let tags = batch.column(2).as_list::<i32>();
+    assert_eq!(tags.value_length(0), 2);
+    assert_eq!(tags.value_length(1), 0);
+
+    let last = decoder.finish()?.expect("remaining row");
+    assert_eq!(last.num_rows(), 1);
+    assert_eq!(last.column(0).as_string::<i32>().value(0), "carol");
+    assert!(decoder.finish()?.is_none());
+    Ok(())
+}
+
+#[test]
+fn scalar_and_tuple() -> Result<(), Box<dyn Error>> {
+    let descriptors = vec![
+        base_scalar(0x105),
+        Descriptor::Tuple(TupleTypeDescriptor {
+            id: Uuid::from_u128(0xA3).into(),
+            element_types: vec![TypePos(0), TypePos(0)],
+            name: None,
+            schema_defined: None,
+            ancestors: vec![],
+        }),
+    ];
+
+    let mut decoder = RecordBatchDecoder::new(Some(TypePos(0)), &descriptors, 10)?;
+    assert_eq!(decoder.schema().field(0).name(), "value");
+    assert!(decoder.push(Value::Int64(7))?.is_none());
+    let batch = decoder.finish()?.expect("one row");
+    assert_eq!(batch.column(0).as_primitive::<Int64Type>().value(0), 7);
+
+    let mut decoder = RecordBatchDecoder::new(Some(TypePos(1)), &descriptors, 10)?;
+    decoder.push(Value::Tuple(vec![Value::Int64(1), Value::Int64(2)]))?;
+    let batch = decoder.finish()?.expect("one row");
+    assert_eq!(batch.num_columns(), 2);
+    assert_eq!(batch.schema().field(1).name(), "1");
+    assert_eq!(batch.column(1).as_primitive::<Int64Type>().value(0), 2);
+    Ok(())
+}
+
+#[test]
+fn range() -> Result<(), Box<dyn Error>> {
+    let descriptors = vec![
+        base_scalar(0x105),
+        Descriptor::Range(RangeTypeDescriptor {
+            id: Uuid::from_u128(0xA4).into(),
+            type_pos: TypePos(0),
+            name: None,
+            schema_defined: None,
+            ancestors: vec![],
+        }),
+    ];
+    let data = encode(
+        TypePos(1),
+        &descriptors,
+        &[
+            Value::from(1i64..10i64),
+            gel_protocol::model::Range::<i64>::empty().into_value(),
+        ],
+    )?;
+
+    let mut decoder = RecordBatchDecoder::new(Some(TypePos(1)), &descriptors, 10)?;
+    assert!(decoder.decode(&data)?.is_empty());
+    let batch = decoder.finish()?.expect("two rows");
+    let ranges = batch.column(0).as_struct();
+    let lower = ranges.column_by_name("lower").unwrap();
+    assert_eq!(lower.as_primitive::<Int64Type>().value(0), 1);
+    assert!(lower.is_null(1));
+    let empty = ranges.column_by_name("empty").unwrap().as_boolean();
+    assert!(!empty.value(0));
+    assert!(empty.value(1));
+    Ok(())
+}
+
+#[test]
+fn value_mismatch() -> Result<(), Box<dyn Error>> {
+    let descriptors = vec![base_scalar(0x105)];
+    let mut decoder = RecordBatchDecoder::new(Some(TypePos(0)), &descriptors, 1)?;
+    assert!(decoder.push(Value::Str("x".into())).is_err());
+    assert!(RecordBatchDecoder::new(None, &descriptors, 1).is_err());
+    Ok(())
+}
@@ -20,6 +20,7 @@ use gel_protocol::descriptors::{MultiRangeTypeDescriptor, RangeTypeDescriptor};
 use gel_protocol::descriptors::{NamedTupleTypeDescriptor, TupleElement};
 use gel_protocol::descriptors::{ObjectShapeDescriptor, ShapeElement};
 use gel_protocol::features::ProtocolVersion;
+use gel_protocol::model::{f16, HalfVector, SparseVector};
 use gel_protocol::model::{Datetime, Json, RelativeDuration};
 use gel_protocol::model::{Duration, LocalDate, LocalTime};
 use gel_protocol::server_message::StateDataDescription;
@@ -817,6 +818,60 @@ fn vector() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+fn pgvector_codec(name: &str) -> Result<Arc<dyn Codec>, Box<dyn Error>> {
+    Ok(build_codec(
+        Some(TypePos(0)),
+        &[Descriptor::Scalar(ScalarTypeDescriptor {
+            id: "5bf2e6b2-1a0c-11ef-8d1d-2f1c5c7e5a10"
+                .parse::<Uuid>()?
+                .into(),
+            base_type_pos: None,
+            name: Some(name.into()),
+            schema_defined: Some(true),
+            ancestors: vec![],
+        })],
+    )?)
+}
+
+#[test]
+fn half_vector() -> Result<(), Box<dyn Error>> {
+    let codec = pgvector_codec("ext::pgvector::halfvec")?;
+    encoding_eq!(
+        &codec,
+        b"\0\x03\0\0\x3c\0\x40\0\x38\0",
+        Value::HalfVector(HalfVector(vec![
+            f16::from_f32(1.),
+            f16::from_f32(2.),
+            f16::from_f32(0.5),
+        ]))
+    );
+    assert!(codec.decode(b"\0\x03\0\0\x3c\0").is_err());
+    Ok(())
+}
+
+#[test]
+fn sparse_vector() -> Result<(), Box<dyn Error>> {
+    let codec = pgvector_codec("ext::pgvector::sparsevec")?;
+    encoding_eq!(
+        &codec,
+        bconcat!(b"\0\0\0\x05\0\0\0\x02\0\0\0\0"
+                 b"\0\0\0\x01\0\0\0\x03"
+                 b"?\x80\0\0@\0\0\0"),
+        Value::SparseVector(SparseVector::new(5, [(3, 2.), (1, 1.)])?)
+    );
+    // indices must be ordered and less than dimension
+    assert!(codec
+        .decode(bconcat!(b"\0\0\0\x05\0\0\0\x02\0\0\0\0"
+                         b"\0\0\0\x03\0\0\0\x01"
+                         b"?\x80\0\0@\0\0\0"))
+        .is_err());
+    assert!(codec
+        .decode(bconcat!(b"\0\0\0\x02\0\0\0\x01\0\0\0\0"
+                         b"\0\0\0\x02?\x80\0\0"))
+        .is_err());
+    Ok(())
+}
+
 #[test]
 fn local_time() -> Result<(), Box<dyn Error>> {
     let codec = build_codec(
@@ -1,5 +1,6 @@
-use gel_protocol::model::Vector;
-use gel_protocol::queryable::Queryable;
+use bytes::Bytes;
+use gel_protocol::model::{BytesStr, SparseVector, Vector};
+use gel_protocol::queryable::{Decoder, Queryable, QueryableRef};
 
 #[test]
 fn decode_vector() {
@@ -11,3 +12,70 @@ fn decode_vector() {
     .unwrap();
     assert_eq!(vec, Vector(vec![1., 2., 3.]));
 }
+
+#[test]
+fn sparse_vector_entries() {
+    let vec = SparseVector::new(4, [(2, 3.), (0, 1.), (1, 0.)]).unwrap();
+    assert_eq!(vec.dim(), 4);
+    assert_eq!(vec.entries(), &[(0, 1.), (2, 3.)]);
+    assert_eq!(vec.to_dense(), vec![1., 0., 3., 0.]);
+    assert_eq!(SparseVector::from_dense(&vec.to_dense()).unwrap(), vec);
+
+    assert!(SparseVector::new(4, [(4, 1.)]).is_err());
+    assert!(SparseVector::new(4, [(1, 1.), (1, 2.)]).is_err());
+}
+
+#[test]
+fn decode_bytes_shares_source() {
+    let msg = Bytes::from_static(b"hello world");
+    let mut decoder = Decoder::default();
+    decoder.set_source(Some(msg.clone()));
+
+    let bytes = Bytes::decode(&decoder, &(), &msg[6..]).unwrap();
+    assert_eq!(bytes, "world");
+    assert_eq!(bytes.as_ptr(), msg[6..].as_ptr());
+
+    let text = BytesStr::decode(&decoder, &(), &msg[..5]).unwrap();
+    assert_eq!(text, "hello");
+    assert_eq!(text.as_ptr(), msg.as_ptr());
+}
+
+#[test]
+fn decode_bytes_without_source() {
+    let buf = b"hello".to_vec();
+    let text = BytesStr::decode(&Decoder::default(), &(), &buf).unwrap();
+    assert_eq!(text, "hello");
+    assert_ne!(text.as_ptr(), buf.as_ptr());
+    assert!(BytesStr::decode(&Decoder::default(), &(), b"\xff").is_err());
+}
+
+#[test]
+fn decode_borrowed() {
+    let buf = b"hello".to_vec();
+    let decoder = Decoder::default();
+    let text = <&str>::decode_ref(&decoder, &(), &buf).unwrap();
+    assert_eq!(text, "hello");
+    assert_eq!(text.as_ptr(), buf.as_ptr());
+    let bytes = <&[u8]>::decode_ref(&decoder, &(), &buf).unwrap();
+    assert_eq!(bytes.as_ptr(), buf.as_ptr());
+    assert!(<&str>::decode_ref(&decoder, &(), b"\xff").is_err());
+
+    // Owned types decode as themselves.
+    let num = i32::decode_ref(&decoder, &(), b"\0\0\0\x07").unwrap();
+    assert_eq!(num, 7);
+}
+
+#[test]
+#[cfg(feature = "with-serde")]
+fn sparse_vector_deserialize() {
+    let vec: SparseVector =
+        serde_json::from_str(r#"{"dim":4,"entries":[[2,3.0],[0,1.0]]}"#).unwrap();
+    assert_eq!(vec, SparseVector::new(4, [(0, 1.), (2, 3.)]).unwrap());
+    let json = serde_json::to_string(&vec).unwrap();
+    assert_eq!(serde_json::from_str::<SparseVector>(&json).unwrap(), vec);
+
+    assert!(serde_json::from_str::<SparseVector>(r#"{"dim":1,"entries":[[5,1.0]]}"#).is_err());
+    assert!(
+        serde_json::from_str::<SparseVector>(r#"{"dim":4,"entries":[[1,1.0],[1,2.0]]}"#).is_err()
+    );
+}
@@ -1,9 +1,11 @@
 use std::marker::PhantomData;
 
+use super::happy_eyeballs::HappyEyeballs;
+use super::proxy::tunnel;
 use crate::common::resolver::Resolver;
 use crate::common::tokio_stream::TokioStream;
-use crate::Target;
 use crate::{ConnectionError, ResolvedTarget, Ssl, StreamUpgrade, TlsDriver, UpgradableStream};
+use crate::{Proxy, ProxyProtocol, Target};
 
 type Connection<S, D> = UpgradableStream<S, D>;
 
@@ -23,6 +25,8 @@ pub struct Connector<D: TlsDriver = Ssl> {
     #[debug(skip)]
     driver: PhantomData<D>,
     ignore_missing_close_notify: bool,
+    happy_eyeballs: HappyEyeballs,
+    proxy: Option<Proxy>,
     #[cfg(feature = "keepalive")]
     keepalive: Option<std::time::Duration>,
 }
@@ -52,6 +56,8 @@ impl<D: TlsDriver> Connector<D> {
             target: ConnectorInner::Unresolved(target, Resolver::new()?),
             driver: PhantomData,
             ignore_missing_close_notify: false,
+            happy_eyeballs: HappyEyeballs::default(),
+            proxy: None,
             #[cfg(feature = "keepalive")]
             keepalive: None,
         })
@@ -63,6 +69,8 @@ impl<D: TlsDriver> Connector<D> {
             target: ConnectorInner::Resolved(target),
             driver: PhantomData,
             ignore_missing_close_notify: false,
+            happy_eyeballs: HappyEyeballs::default(),
+            proxy: None,
             #[cfg(feature = "keepalive")]
             keepalive: None,
         }
@@ -74,6 +82,8 @@ impl<D: TlsDriver> Connector<D> {
             target: ConnectorInner::Unresolved(target, resolver),
             driver: PhantomData,
             ignore_missing_close_notify: false,
+            happy_eyeballs: HappyEyeballs::default(),
+            proxy: None,
             #[cfg(feature = "keepalive")]
             keepalive: None,
         }
@@ -86,6 +96,19 @@ impl<D: TlsDriver> Connector<D> {
         self.keepalive = keepalive;
     }
 
+    /// Configure how connection attempts are raced when the target resolves
+    /// to multiple addresses.
+    pub fn set_happy_eyeballs(&mut self, happy_eyeballs: HappyEyeballs) {
+        self.happy_eyeballs = happy_eyeballs;
+    }
+
+    /// Tunnel TCP connections through a SOCKS5 or HTTP `CONNECT` proxy. The
+    /// tunnel is established before any TLS or STARTTLS upgrade. Unix socket
+    /// connections are never proxied.
+    pub fn set_proxy(&mut self, proxy: Option<Proxy>) {
+        self.proxy = proxy;
+    }
+
     /// For TLS connections, ignore a hard close where the socket was closed
     /// before receiving CLOSE_NOTIFY.
     ///
@@ -99,17 +122,18 @@ impl<D: TlsDriver> Connector<D> {
 
     /// Connect to the target.
     pub async fn connect(&self) -> Result<Connection<TokioStream, D>, ConnectionError> {
-        let target = match &self.target {
-            ConnectorInner::Unresolved(target, resolver) => {
-                resolver.resolve_remote(target.maybe_resolved()).await?
-            }
-            ConnectorInner::Resolved(target) => target.clone(),
+        let is_tcp = match &self.target {
+            ConnectorInner::Unresolved(target, _) => target.is_tcp(),
+            ConnectorInner::Resolved(target) => target.is_tcp(),
+        };
+        let stream = match &self.proxy {
+            Some(proxy) if is_tcp => self.connect_proxy(proxy).await?,
+            _ => self.connect_direct().await?,
         };
-        let stream = target.connect().await?;
 
         #[cfg(feature = "keepalive")]
         if let Some(keepalive) = self.keepalive {
-            if target.is_tcp() {
+            if is_tcp {
                 stream.set_keepalive(Some(keepalive))?;
             }
         }
@@ -132,4 +156,55 @@ impl<D: TlsDriver> Connector<D> {
             Ok(UpgradableStream::new_client(stream, None))
         }
     }
+
+    async fn connect_direct(&self) -> std::io::Result<TokioStream> {
+        let targets = match &self.target {
+            ConnectorInner::Unresolved(target, resolver) => {
+                resolver.resolve_remote_all(target.maybe_resolved()).await?
+            }
+            ConnectorInner::Resolved(target) => vec![target.clone()],
+        };
+        let (stream, _) = self
+            .happy_eyeballs
+            .connect(targets, |target| async move { target.connect().await })
+            .await?;
+        Ok(stream)
+    }
+
+    async fn connect_proxy(&self, proxy: &Proxy) -> std::io::Result<TokioStream> {
+        let (host, port, resolver) = match &self.target {
+            ConnectorInner::Unresolved(target, resolver) => {
+                let (host, port) = target.tcp().expect("TCP target");
+                // With `socks5://`, the target is resolved locally and the
+                // proxy is asked to connect to an address.
+                let host = if proxy.protocol() == ProxyProtocol::Socks5 {
+                    let targets = resolver.resolve_remote_all(target.maybe_resolved()).await?;
+                    let addr = self
+                        .happy_eyeballs
+                        .sort_targets(targets)
+                        .into_iter()
+                        .find_map(|target| target.tcp())
+                        .ok_or_else(|| {
+                            std::io::Error::new(std::io::ErrorKind::NotFound, "No address found")
+                        })?;
+                    addr.ip().to_string()
+                } else {
+                    host.into_owned()
+                };
+                (host, port, resolver.clone())
+            }
+            ConnectorInner::Resolved(target) => {
+                let addr = target.tcp().expect("TCP target");
+                (addr.ip().to_string(), addr.port(), Resolver::new()?)
+            }
+        };
+
+        let proxies = resolver.resolve_all(&proxy.target_name()).await?;
+        let (mut stream, _) = self
+            .happy_eyeballs
+            .connect(proxies, |target| async move { target.connect().await })
+            .await?;
+        tunnel(&mut stream, proxy, &host, port).await?;
+        Ok(stream)
+    }
 }
@@ -0,0 +1,295 @@
+//! Connection racing across address families, as described in RFC 8305
+//! ("Happy Eyeballs Version 2").
+
+use std::{future::Future, net::SocketAddr, pin::pin, time::Duration};
+
+use futures::{
+    future::{select, Either},
+    stream::FuturesUnordered,
+    StreamExt,
+};
+
+use crate::ResolvedTarget;
+
+/// The default delay between connection attempts, as recommended by RFC 8305.
+pub const DEFAULT_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Which address family to attempt first, or to restrict attempts to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AddressFamilyPreference {
+    /// Attempt IPv6 addresses first, alternating with IPv4.
+    #[default]
+    PreferIpv6,
+    /// Attempt IPv4 addresses first, alternating with IPv6.
+    PreferIpv4,
+    /// Only attempt IPv6 addresses.
+    Ipv6Only,
+    /// Only attempt IPv4 addresses.
+    Ipv4Only,
+}
+
+/// Configuration for racing connection attempts when a target resolves to
+/// multiple addresses.
+///
+/// Addresses are attempted in order, alternating between address families.
+/// Each attempt starts after `attempt_delay` or as soon as the previous
+/// attempt fails, whichever is first. The first successful connection is
+/// used and the remaining attempts are cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HappyEyeballs {
+    /// The delay before starting the next connection attempt while earlier
+    /// attempts are still in progress.
+    pub attempt_delay: Duration,
+    /// The address family preference.
+    pub family: AddressFamilyPreference,
+}
+
+impl Default for HappyEyeballs {
+    fn default() -> Self {
+        Self {
+            attempt_delay: DEFAULT_ATTEMPT_DELAY,
+            family: AddressFamilyPreference::default(),
+        }
+    }
+}
+
+impl HappyEyeballs {
+    /// Order the targets for connection attempts: the preferred family first,
+    /// then alternating between families. The relative order of addresses
+    /// within a family is preserved. Non-IP targets are kept at the front.
+    pub(crate) fn sort_targets(&self, targets: Vec<ResolvedTarget>) -> Vec<ResolvedTarget> {
+        let (mut v6, mut v4, mut other) = (vec![], vec![], vec![]);
+        for target in targets {
+            match target.tcp() {
+                Some(SocketAddr::V6(_)) => v6.push(target),
+                Some(SocketAddr::V4(_)) => v4.push(target),
+                None => other.push(target),
+            }
+        }
+        let (first, second) = match self.family {
+            AddressFamilyPreference::PreferIpv6 => (v6, v4),
+            AddressFamilyPreference::PreferIpv4 => (v4, v6),
+            AddressFamilyPreference::Ipv6Only => (v6, vec![]),
+            AddressFamilyPreference::Ipv4Only => (v4, vec![]),
+        };
+        let mut first = first.into_iter();
+        let mut second = second.into_iter();
+        loop {
+            match (first.next(), second.next()) {
+                (None, None) => break,
+                (a, b) => other.extend(a.into_iter().chain(b)),
+            }
+        }
+        other
+    }
+
+    /// Race connection attempts to the targets, returning the first
+    /// successful connection and the target it was made to. If every attempt
+    /// fails, the error from the last failed attempt is returned.
+    pub(crate) async fn connect<T, F, Fut>(
+        &self,
+        targets: Vec<ResolvedTarget>,
+        connect: F,
+    ) -> std::io::Result<(T, ResolvedTarget)>
+    where
+        F: Fn(ResolvedTarget) -> Fut,
+        Fut: Future<Output = std::io::Result<T>>,
+    {
+        let mut targets = self.sort_targets(targets).into_iter();
+        let mut attempts = FuturesUnordered::new();
+        let mut last_error = None;
+        let mut next = targets.next();
+
+        loop {
+            if let Some(target) = next.take() {
+                let attempt = connect(target.clone());
+                attempts.push(async move { (attempt.await, target) });
+            }
+            if attempts.is_empty() {
+                break;
+            }
+
+            // Wait for an attempt to complete, or for the delay to expire if
+            // there are more targets to attempt.
+            let more_targets = targets.len() > 0;
+            let delay = pin!(async {
+                if more_targets {
+                    tokio::time::sleep(self.attempt_delay).await;
+                } else {
+                    futures::future::pending::<()>().await;
+                }
+            });
+            let completed = match select(attempts.next(), delay).await {
+                Either::Left((completed, _)) => completed,
+                Either::Right(_) => None,
+            };
+
+            match completed {
+                Some((Ok(stream), target)) => return Ok((stream, target)),
+                Some((Err(e), _)) => last_error = Some(e),
+                None => {}
+            }
+            next = targets.next();
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "No address found")
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        net::{Ipv4Addr, Ipv6Addr},
+        sync::Mutex,
+        time::Instant,
+    };
+    use tokio::net::TcpListener;
+
+    fn v4(n: u8) -> ResolvedTarget {
+        ResolvedTarget::SocketAddr(SocketAddr::new(Ipv4Addr::new(10, 0, 0, n).into(), 1))
+    }
+
+    fn v6(n: u16) -> ResolvedTarget {
+        ResolvedTarget::SocketAddr(SocketAddr::new(
+            Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, n).into(),
+            1,
+        ))
+    }
+
+    #[test]
+    fn test_sort_targets() {
+        let targets = vec![v4(1), v4(2), v4(3), v6(1), v6(2)];
+        let config = HappyEyeballs::default();
+        assert_eq!(
+            config.sort_targets(targets.clone()),
+            vec![v6(1), v4(1), v6(2), v4(2), v4(3)]
+        );
+
+        let config = HappyEyeballs {
+            family: AddressFamilyPreference::PreferIpv4,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.sort_targets(targets.clone()),
+            vec![v4(1), v6(1), v4(2), v6(2), v4(3)]
+        );
+
+        let config = HappyEyeballs {
+            family: AddressFamilyPreference::Ipv4Only,
+            ..Default::default()
+        };
+        assert_eq!(config.sort_targets(targets), vec![v4(1), v4(2), v4(3)]);
+    }
+
+    /// A stalled attempt doesn't prevent the next attempt from starting, and
+    /// the first successful attempt wins.
+    #[tokio::test]
+    async fn test_stalled_attempt() {
+        let config = HappyEyeballs {
+            attempt_delay: Duration::from_millis(20),
+            ..Default::default()
+        };
+        let started = Mutex::new(vec![]);
+        let start = Instant::now();
+        let (n, target) = config
+            .connect(vec![v4(1), v6(1), v6(2)], |target| {
+                started.lock().unwrap().push(target.clone());
+                async move {
+                    if target == v6(1) {
+                        // Stalls until cancelled
+                        futures::future::pending::<()>().await;
+                    }
+                    Ok(target.tcp().unwrap().ip())
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(target, v4(1));
+        assert_eq!(n, v4(1).tcp().unwrap().ip());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(*started.lock().unwrap(), vec![v6(1), v4(1)]);
+    }
+
+    /// A failed attempt starts the next attempt without waiting for the
+    /// delay.
+    #[tokio::test]
+    async fn test_failed_attempt() {
+        let config = HappyEyeballs {
+            attempt_delay: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let (_, target) = config
+            .connect(vec![v6(1), v4(1)], |target| async move {
+                if target == v6(1) {
+                    Err(std::io::ErrorKind::ConnectionRefused.into())
+                } else {
+                    Ok(())
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(target, v4(1));
+
+        let err = config
+            .connect(vec![v6(1), v4(1)], |_| async {
+                Err::<(), _>(std::io::Error::from(std::io::ErrorKind::ConnectionRefused))
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+
+        let err = config
+            .connect(vec![], |_| async { Ok(()) })
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    }
+
+    /// Race real connections to local listeners on `::1` and `127.0.0.1`.
+    #[tokio::test]
+    async fn test_local_listeners() {
+        let v4_listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let Ok(v6_listener) = TcpListener::bind((Ipv6Addr::LOCALHOST, 0)).await else {
+            eprintln!("IPv6 is not available, skipping test");
+            return;
+        };
+        let v4_addr = ResolvedTarget::SocketAddr(v4_listener.local_addr().unwrap());
+        let v6_addr = ResolvedTarget::SocketAddr(v6_listener.local_addr().unwrap());
+        let targets = vec![v4_addr.clone(), v6_addr.clone()];
+
+        let config = HappyEyeballs::default();
+        let (_, target) = config
+            .connect(
+                targets.clone(),
+                |target| async move { target.connect().await },
+            )
+            .await
+            .unwrap();
+        assert_eq!(target, v6_addr);
+
+        let config = HappyEyeballs {
+            family: AddressFamilyPreference::PreferIpv4,
+            ..Default::default()
+        };
+        let (_, target) = config
+            .connect(
+                targets.clone(),
+                |target| async move { target.connect().await },
+            )
+            .await
+            .unwrap();
+        assert_eq!(target, v4_addr);
+
+        // With nothing listening on IPv6, the IPv4 attempt wins
+        drop(v6_listener);
+        let config = HappyEyeballs::default();
+        let (_, target) = config
+            .connect(targets, |target| async move { target.connect().await })
+            .await
+            .unwrap();
+        assert_eq!(target, v4_addr);
+    }
+}
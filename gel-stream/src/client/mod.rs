@@ -1,3 +1,6 @@
 mod connection;
+mod happy_eyeballs;
+mod proxy;
 
 pub use connection::Connector;
+pub use happy_eyeballs::{AddressFamilyPreference, HappyEyeballs, DEFAULT_ATTEMPT_DELAY};
@@ -0,0 +1,365 @@
+//! In-memory connections between a [`Connector`](crate::Connector) and an
+//! [`Acceptor`](crate::Acceptor) in the same process.
+//!
+//! Listeners are registered by name in a process-local registry, and
+//! connecting to a name hands one end of a [`tokio::io::duplex`] pipe to the
+//! listener. No address, path or socket is involved, so in-memory streams
+//! support peeking and TLS, but not socket options or handles.
+
+use futures::{channel::mpsc, StreamExt};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    pin::Pin,
+    sync::{LazyLock, Mutex},
+    task::{ready, Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite, DuplexStream, ReadBuf};
+
+use crate::{
+    AsHandle, LocalAddress, PeekableStream, PeerCred, RemoteAddress, ResolvedTarget,
+    StreamMetadata, Transport,
+};
+
+/// The maximum number of bytes buffered in each direction of a connection.
+const MEMORY_BUFFER_SIZE: usize = 64 * 1024;
+
+type Listeners = HashMap<Cow<'static, str>, mpsc::UnboundedSender<MemoryStream>>;
+
+static LISTENERS: LazyLock<Mutex<Listeners>> = LazyLock::new(Default::default);
+
+/// One end of an in-memory connection.
+#[derive(derive_more::Debug)]
+#[debug("MemoryStream({name:?})")]
+pub struct MemoryStream {
+    inner: Mutex<MemoryStreamInner>,
+    pub(crate) name: Cow<'static, str>,
+}
+
+/// The duplex pipe and any bytes read ahead of the reader by a peek or
+/// readiness check.
+struct MemoryStreamInner {
+    stream: DuplexStream,
+    peeked: Vec<u8>,
+}
+
+impl MemoryStreamInner {
+    /// Wait until there are peeked bytes or the writer has closed the pipe.
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        if !self.peeked.is_empty() {
+            return Poll::Ready(Ok(()));
+        }
+        let mut buf = [0; 8192];
+        let mut buf = ReadBuf::new(&mut buf);
+        ready!(Pin::new(&mut self.stream).poll_read(cx, &mut buf))?;
+        self.peeked.extend_from_slice(buf.filled());
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_read(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        if self.peeked.is_empty() {
+            return Pin::new(&mut self.stream).poll_read(cx, buf);
+        }
+        let n = self.peeked.len().min(buf.remaining());
+        buf.put_slice(&self.peeked[..n]);
+        self.peeked.drain(..n);
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_peek(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<usize>> {
+        ready!(self.poll_fill(cx))?;
+        let n = self.peeked.len().min(buf.remaining());
+        buf.put_slice(&self.peeked[..n]);
+        Poll::Ready(Ok(n))
+    }
+}
+
+/// Run a poll function without registering for a wakeup, mapping
+/// `Poll::Pending` to `WouldBlock`.
+#[cfg(feature = "rustls")]
+fn try_poll<T>(f: impl FnOnce(&mut Context<'_>) -> Poll<std::io::Result<T>>) -> std::io::Result<T> {
+    match f(&mut Context::from_waker(std::task::Waker::noop())) {
+        Poll::Ready(res) => res,
+        Poll::Pending => Err(std::io::ErrorKind::WouldBlock.into()),
+    }
+}
+
+impl MemoryStream {
+    fn new(stream: DuplexStream, name: Cow<'static, str>) -> Self {
+        Self {
+            inner: Mutex::new(MemoryStreamInner {
+                stream,
+                peeked: Vec::new(),
+            }),
+            name,
+        }
+    }
+
+    /// Connect to the in-memory listener registered as `name`.
+    pub(crate) fn connect(name: Cow<'static, str>) -> std::io::Result<Self> {
+        let refused = || {
+            std::io::Error::new(
+                std::io::ErrorKind::ConnectionRefused,
+                format!("No in-memory listener named {name:?}"),
+            )
+        };
+        let Some(sender) = LISTENERS.lock().unwrap().get(&name).cloned() else {
+            return Err(refused());
+        };
+        let (client, server) = tokio::io::duplex(MEMORY_BUFFER_SIZE);
+        sender
+            .unbounded_send(MemoryStream::new(server, name.clone()))
+            .map_err(|_| refused())?;
+        Ok(MemoryStream::new(client, name))
+    }
+
+    /// The name of the listener this stream is connected to.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The non-blocking, `&self` API that rustls expects of an underlying stream,
+/// mirroring the one on tokio's sockets.
+#[cfg(feature = "rustls")]
+impl MemoryStream {
+    pub(crate) fn poll_read_ready(&self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.inner.lock().unwrap().poll_fill(cx)
+    }
+
+    pub(crate) fn poll_write_ready(&self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        // An empty write only succeeds if there is room in the pipe.
+        let mut inner = self.inner.lock().unwrap();
+        Pin::new(&mut inner.stream)
+            .poll_write(cx, &[])
+            .map_ok(|_| ())
+    }
+
+    pub(crate) async fn readable(&self) -> std::io::Result<()> {
+        std::future::poll_fn(|cx| self.poll_read_ready(cx)).await
+    }
+
+    pub(crate) async fn writable(&self) -> std::io::Result<()> {
+        std::future::poll_fn(|cx| self.poll_write_ready(cx)).await
+    }
+
+    pub(crate) fn try_read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut inner = self.inner.lock().unwrap();
+        let mut buf = ReadBuf::new(buf);
+        try_poll(|cx| inner.poll_read(cx, &mut buf))?;
+        Ok(buf.filled().len())
+    }
+
+    pub(crate) fn try_write(&self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut inner = self.inner.lock().unwrap();
+        try_poll(|cx| Pin::new(&mut inner.stream).poll_write(cx, buf))
+    }
+
+    pub(crate) fn shutdown(&self, how: std::net::Shutdown) -> std::io::Result<()> {
+        if how == std::net::Shutdown::Read {
+            return Ok(());
+        }
+        let mut inner = self.inner.lock().unwrap();
+        try_poll(|cx| Pin::new(&mut inner.stream).poll_shutdown(cx))
+    }
+}
+
+impl AsyncRead for MemoryStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        self.get_mut().inner.get_mut().unwrap().poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for MemoryStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner.get_mut().unwrap().stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner.get_mut().unwrap().stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner.get_mut().unwrap().stream).poll_shutdown(cx)
+    }
+}
+
+impl PeekableStream for MemoryStream {
+    fn poll_peek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<usize>> {
+        self.get_mut().inner.get_mut().unwrap().poll_peek(cx, buf)
+    }
+}
+
+impl LocalAddress for MemoryStream {
+    fn local_address(&self) -> std::io::Result<ResolvedTarget> {
+        Ok(ResolvedTarget::Memory(self.name.clone()))
+    }
+}
+
+impl RemoteAddress for MemoryStream {
+    fn remote_address(&self) -> std::io::Result<ResolvedTarget> {
+        Ok(ResolvedTarget::Memory(self.name.clone()))
+    }
+}
+
+impl PeerCred for MemoryStream {
+    #[cfg(unix)]
+    fn peer_cred(&self) -> std::io::Result<tokio::net::unix::UCred> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "In-memory streams do not support peer credentials",
+        ))
+    }
+}
+
+impl StreamMetadata for MemoryStream {
+    fn transport(&self) -> Transport {
+        Transport::Memory
+    }
+}
+
+impl AsHandle for MemoryStream {
+    #[cfg(windows)]
+    fn as_handle(&self) -> Option<std::os::windows::io::BorrowedSocket> {
+        None
+    }
+
+    #[cfg(unix)]
+    fn as_fd(&self) -> Option<std::os::fd::BorrowedFd> {
+        None
+    }
+}
+
+/// A named in-memory listener. The name is released when the listener is
+/// dropped.
+#[derive(Debug)]
+pub(crate) struct MemoryListener {
+    name: Cow<'static, str>,
+    receiver: mpsc::UnboundedReceiver<MemoryStream>,
+}
+
+impl MemoryListener {
+    /// Register a listener as `name`.
+    pub(crate) fn bind(name: Cow<'static, str>) -> std::io::Result<Self> {
+        let mut listeners = LISTENERS.lock().unwrap();
+        if listeners
+            .get(&name)
+            .is_some_and(|sender| !sender.is_closed())
+        {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AddrInUse,
+                format!("An in-memory listener named {name:?} already exists"),
+            ));
+        }
+        let (sender, receiver) = mpsc::unbounded();
+        listeners.insert(name.clone(), sender);
+        Ok(Self { name, receiver })
+    }
+
+    pub(crate) fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+
+    pub(crate) fn poll_accept(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<std::io::Result<MemoryStream>> {
+        match self.receiver.poll_next_unpin(cx) {
+            Poll::Ready(Some(stream)) => Poll::Ready(Ok(stream)),
+            // The sender is only dropped after the receiver is closed.
+            Poll::Ready(None) => Poll::Ready(Err(std::io::ErrorKind::NotConnected.into())),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl Drop for MemoryListener {
+    fn drop(&mut self) {
+        self.receiver.close();
+        let mut listeners = LISTENERS.lock().unwrap();
+        // A new listener may have already taken the name.
+        if listeners
+            .get(&self.name)
+            .is_some_and(|sender| sender.is_closed())
+        {
+            listeners.remove(&self.name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_memory_listener() {
+        let name = Cow::Borrowed("test_memory_listener");
+        let err = MemoryStream::connect(name.clone()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+
+        let mut listener = MemoryListener::bind(name.clone()).unwrap();
+        let err = MemoryListener::bind(name.clone()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+
+        let mut client = MemoryStream::connect(name.clone()).unwrap();
+        let mut server = std::future::poll_fn(|cx| listener.poll_accept(cx))
+            .await
+            .unwrap();
+        assert_eq!(server.name(), "test_memory_listener");
+        client.write_all(b"hello").await.unwrap();
+        let mut buf = [0; 5];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+
+        // The name is released when the listener is dropped.
+        drop(listener);
+        let err = MemoryStream::connect(name.clone()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+        let _listener = MemoryListener::bind(name.clone()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_memory_peek() {
+        let name = Cow::Borrowed("test_memory_peek");
+        let mut listener = MemoryListener::bind(name.clone()).unwrap();
+        let mut client = MemoryStream::connect(name).unwrap();
+        let mut server = std::future::poll_fn(|cx| listener.poll_accept(cx))
+            .await
+            .unwrap();
+
+        let mut buf = [0; 5];
+        client.write_all(b"hello world").await.unwrap();
+        assert_eq!(Pin::new(&mut server).peek(&mut buf).await.unwrap(), 5);
+        assert_eq!(&buf, b"hello");
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+
+        AsyncWriteExt::shutdown(&mut client).await.unwrap();
+        let mut rest = String::new();
+        server.read_to_string(&mut rest).await.unwrap();
+        assert_eq!(rest, " world");
+        #[cfg(unix)]
+        assert!(server.as_fd().is_none());
+    }
+}
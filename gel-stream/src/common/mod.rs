@@ -1,3 +1,7 @@
+#[cfg(feature = "tokio")]
+pub mod memory;
+pub mod proxy;
+pub mod proxy_protocol;
 pub mod resolver;
 pub mod stream;
 pub mod target;
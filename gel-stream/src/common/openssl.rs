@@ -14,9 +14,10 @@ use std::{
 };
 
 use crate::{
-    AsHandle, LocalAddress, PeekableStream, PeerCred, RemoteAddress, ResolvedTarget, SslError,
-    SslVersion, Stream, StreamMetadata, TlsCert, TlsClientCertVerify, TlsDriver, TlsHandshake,
-    TlsParameters, TlsServerCertVerify, TlsServerParameterProvider, TlsServerParameters, Transport,
+    AsHandle, CertificatePinMismatch, LocalAddress, PeekableStream, PeerCred, RemoteAddress,
+    ResolvedTarget, SslError, SslVersion, Stream, StreamMetadata, TlsCert, TlsClientCertVerify,
+    TlsDriver, TlsHandshake, TlsParameters, TlsServerCertVerify, TlsServerParameterProvider,
+    TlsServerParameters, Transport,
 };
 
 use super::tokio_stream::TokioStream;
@@ -45,6 +46,16 @@ fn get_ssl_ex_data_index() -> openssl::ex_data::Index<Ssl, Arc<Mutex<HandshakeDa
         .get_or_init(|| Ssl::new_ex_index().expect("Failed to create SSL ex_data index"))
 }
 
+static PINNED_EX_DATA_INDEX: OnceLock<openssl::ex_data::Index<Ssl, Vec<[u8; 32]>>> =
+    OnceLock::new();
+
+/// The pinned fingerprints for a client connection using
+/// [`TlsServerCertVerify::Pinned`].
+fn get_pinned_ex_data_index() -> openssl::ex_data::Index<Ssl, Vec<[u8; 32]>> {
+    *PINNED_EX_DATA_INDEX
+        .get_or_init(|| Ssl::new_ex_index().expect("Failed to create SSL ex_data index"))
+}
+
 #[derive(Default)]
 
 pub struct OpensslDriver;
@@ -160,6 +171,11 @@ impl TlsDriver for OpensslDriver {
             TlsServerCertVerify::Insecure => {
                 ssl.set_verify(SslVerifyMode::NONE);
             }
+            TlsServerCertVerify::Pinned(_) => {
+                // The pins are checked in `upgrade_client` once the handshake
+                // completes, before the stream is handed to the caller.
+                ssl.set_verify(SslVerifyMode::NONE);
+            }
             TlsServerCertVerify::IgnoreHostname => {
                 ssl.set_verify(SslVerifyMode::PEER);
             }
@@ -228,6 +244,9 @@ impl TlsDriver for OpensslDriver {
 
         let mut ssl = openssl::ssl::Ssl::new(&ssl.build())?;
         ssl.set_connect_state();
+        if let TlsServerCertVerify::Pinned(pins) = server_cert_verify {
+            ssl.set_ex_data(get_pinned_ex_data_index(), pins.clone());
+        }
 
         // Set hostname if it's not an IP address
         if let Some(hostname) = sni_override {
@@ -257,7 +276,11 @@ impl TlsDriver for OpensslDriver {
         let cert = openssl::x509::X509::from_der(server_certificate.cert.as_ref())?;
         let key = openssl::pkey::PKey::private_key_from_der(server_certificate.key.secret_der())?;
         ssl.set_certificate(&cert)?;
+        for cert in &server_certificate.chain {
+            ssl.add_extra_chain_cert(openssl::x509::X509::from_der(cert.as_ref())?)?;
+        }
         ssl.set_private_key(&key)?;
+        ssl.check_private_key()?;
         ssl.set_min_proto_version(min_protocol_version.map(|s| s.into()))?;
         ssl.set_max_proto_version(max_protocol_version.map(|s| s.into()))?;
         match client_cert_verify {
@@ -303,11 +326,22 @@ impl TlsDriver for OpensslDriver {
             .map(|p| Cow::Owned(p.to_vec()));
 
         res.map_err(SslError::OpenSslError)?;
-        let cert = stream
-            .ssl()
-            .peer_certificate()
-            .map(|cert| cert.to_der())
-            .transpose()?;
+        let peer_cert = stream.ssl().peer_certificate();
+        if let Some(pins) = stream.ssl().ex_data(get_pinned_ex_data_index()) {
+            let Some(peer_cert) = &peer_cert else {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "server did not present a certificate to check against the pinned fingerprints",
+                )
+                .into());
+            };
+            CertificatePinMismatch::check(
+                pins,
+                openssl::sha::sha256(&peer_cert.to_der()?),
+                openssl::sha::sha256(&peer_cert.public_key()?.public_key_to_der()?),
+            )?;
+        }
+        let cert = peer_cert.map(|cert| cert.to_der()).transpose()?;
         let cert = cert.map(CertificateDer::from);
         let version = match stream.ssl().version2() {
             Some(openssl::ssl::SslVersion::TLS1) => Some(SslVersion::Tls1),
@@ -477,12 +511,12 @@ impl From<SslVersion> for openssl::ssl::SslVersion {
 
 impl AsHandle for TlsStream {
     #[cfg(windows)]
-    fn as_handle(&self) -> std::os::windows::io::BorrowedSocket {
+    fn as_handle(&self) -> Option<std::os::windows::io::BorrowedSocket> {
         self.0.get_ref().as_handle()
     }
 
     #[cfg(unix)]
-    fn as_fd(&self) -> std::os::fd::BorrowedFd {
+    fn as_fd(&self) -> Option<std::os::fd::BorrowedFd> {
         self.0.get_ref().as_fd()
     }
 }
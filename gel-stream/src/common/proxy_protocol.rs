@@ -0,0 +1,470 @@
+//! The [PROXY protocol](https://www.haproxy.org/download/3.1/doc/proxy-protocol.txt),
+//! used by load balancers such as HAProxy and AWS NLB to pass the original
+//! client address to the server.
+
+use std::{
+    borrow::Cow,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    str::FromStr,
+    time::Duration,
+};
+
+/// The signature that starts a PROXY protocol v2 header.
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+/// The maximum length of a PROXY protocol v1 header, including the CRLF.
+const V1_MAX_LENGTH: usize = 107;
+
+/// The version of a PROXY protocol header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProxyProtocolVersion {
+    /// The text format.
+    V1,
+    /// The binary format.
+    V2,
+}
+
+/// A PROXY protocol header received at the start of a connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyProtocolHeader {
+    pub version: ProxyProtocolVersion,
+    /// The address of the original client. This is `None` when the proxy
+    /// connected on its own behalf (such as for health checks), or when the
+    /// address family is unknown or not an IP address.
+    pub source: Option<SocketAddr>,
+    /// The address the original client connected to.
+    pub destination: Option<SocketAddr>,
+    /// The type-length-value fields of a v2 header, in order.
+    pub tlvs: Vec<(u8, Vec<u8>)>,
+}
+
+impl ProxyProtocolHeader {
+    /// The value of the first TLV of the given type, such as `0x02` for the
+    /// authority (the SNI sent by the client to the proxy).
+    pub fn tlv(&self, kind: u8) -> Option<&[u8]> {
+        self.tlvs
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, v)| v.as_slice())
+    }
+}
+
+/// An error parsing an IP network in CIDR notation.
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display, derive_more::Error)]
+#[display("Invalid IP network: {_0}")]
+pub struct InvalidIpNetwork(#[error(not(source))] Cow<'static, str>);
+
+/// An IP network in CIDR notation, such as `10.0.0.0/8` or `fd00::/8`. A bare
+/// address matches only itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNetwork {
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Result<Self, InvalidIpNetwork> {
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        if prefix_len > max {
+            return Err(InvalidIpNetwork("prefix length is too long".into()));
+        }
+        Ok(Self { addr, prefix_len })
+    }
+
+    /// Whether the address is in this network. IPv4-mapped IPv6 addresses
+    /// are matched as IPv4 addresses.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        let addr = addr.to_canonical();
+        match (self.addr, addr) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                prefix_matches(&net.octets(), &addr.octets(), self.prefix_len)
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                prefix_matches(&net.octets(), &addr.octets(), self.prefix_len)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn prefix_matches(net: &[u8], addr: &[u8], prefix_len: u8) -> bool {
+    let bytes = prefix_len as usize / 8;
+    let bits = prefix_len % 8;
+    if net[..bytes] != addr[..bytes] {
+        return false;
+    }
+    bits == 0 || {
+        let mask = 0xff << (8 - bits);
+        net[bytes] & mask == addr[bytes] & mask
+    }
+}
+
+impl From<IpAddr> for IpNetwork {
+    fn from(addr: IpAddr) -> Self {
+        let prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+        Self { addr, prefix_len }
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = InvalidIpNetwork;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => (
+                addr,
+                Some(
+                    prefix_len
+                        .parse::<u8>()
+                        .map_err(|_| InvalidIpNetwork("invalid prefix length".into()))?,
+                ),
+            ),
+            None => (s, None),
+        };
+        let addr = addr
+            .parse::<IpAddr>()
+            .map_err(|_| InvalidIpNetwork(format!("invalid address {addr:?}").into()))?;
+        match prefix_len {
+            Some(prefix_len) => Self::new(addr, prefix_len),
+            None => Ok(addr.into()),
+        }
+    }
+}
+
+/// Configuration for accepting PROXY protocol headers on an
+/// [`Acceptor`](crate::Acceptor).
+#[derive(Debug, Clone)]
+pub struct ProxyProtocolConfiguration {
+    /// The proxies that are trusted to send a PROXY protocol header. TCP
+    /// connections from other addresses are accepted as direct connections,
+    /// and no header is read from them. Connections over Unix sockets and
+    /// in-memory connections are always trusted.
+    pub trusted_sources: Vec<IpNetwork>,
+    /// The maximum duration to wait for the header. Recommended value is 10
+    /// seconds.
+    pub timeout: Duration,
+}
+
+impl ProxyProtocolConfiguration {
+    /// Trust PROXY protocol headers from the given sources.
+    pub fn new(trusted_sources: impl IntoIterator<Item = IpNetwork>) -> Self {
+        Self {
+            trusted_sources: trusted_sources.into_iter().collect(),
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    pub(crate) fn is_trusted(&self, source: &crate::ResolvedTarget) -> bool {
+        match source.tcp() {
+            Some(addr) => self
+                .trusted_sources
+                .iter()
+                .any(|network| network.contains(addr.ip())),
+            None => true,
+        }
+    }
+}
+
+fn invalid(message: &'static str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("Invalid PROXY protocol header: {message}"),
+    )
+}
+
+/// Read a v1 or v2 PROXY protocol header from the start of the stream,
+/// without reading past the end of it.
+#[cfg(feature = "tokio")]
+pub(crate) async fn read_header(
+    stream: &mut (impl tokio::io::AsyncRead + Unpin),
+) -> std::io::Result<ProxyProtocolHeader> {
+    use tokio::io::AsyncReadExt;
+
+    let mut header = [0; 16];
+    stream.read_exact(&mut header[..6]).await?;
+    if &header[..6] == b"PROXY " {
+        // The v1 header is a single line: read a byte at a time to avoid
+        // consuming any data that follows it.
+        let mut line = header[..6].to_vec();
+        while !line.ends_with(b"\r\n") {
+            if line.len() == V1_MAX_LENGTH {
+                return Err(invalid("line too long"));
+            }
+            line.push(stream.read_u8().await?);
+        }
+        parse_v1(&line)
+    } else if header[..6] == V2_SIGNATURE[..6] {
+        stream.read_exact(&mut header[6..]).await?;
+        let len = u16::from_be_bytes([header[14], header[15]]) as usize;
+        let mut body = vec![0; len];
+        stream.read_exact(&mut body).await?;
+        parse_v2(&header, &body)
+    } else {
+        Err(invalid("missing header"))
+    }
+}
+
+/// Parse a v1 header line, including the trailing CRLF.
+fn parse_v1(line: &[u8]) -> std::io::Result<ProxyProtocolHeader> {
+    let line = line
+        .strip_suffix(b"\r\n")
+        .and_then(|line| std::str::from_utf8(line).ok())
+        .ok_or_else(|| invalid("malformed line"))?;
+    let mut parts = line.split(' ');
+    if parts.next() != Some("PROXY") {
+        return Err(invalid("malformed line"));
+    }
+    let unknown = ProxyProtocolHeader {
+        version: ProxyProtocolVersion::V1,
+        source: None,
+        destination: None,
+        tlvs: vec![],
+    };
+    let parse_ip = |s: Option<&str>, v4: bool| -> std::io::Result<IpAddr> {
+        let s = s.ok_or_else(|| invalid("missing address"))?;
+        let ip = if v4 {
+            s.parse::<Ipv4Addr>().map(IpAddr::V4)
+        } else {
+            s.parse::<Ipv6Addr>().map(IpAddr::V6)
+        };
+        ip.map_err(|_| invalid("invalid address"))
+    };
+    let parse_port = |s: Option<&str>| -> std::io::Result<u16> {
+        s.and_then(|s| s.parse().ok())
+            .ok_or_else(|| invalid("invalid port"))
+    };
+    let v4 = match parts.next() {
+        // The rest of the line is ignored for unknown protocols.
+        Some("UNKNOWN") => return Ok(unknown),
+        Some("TCP4") => true,
+        Some("TCP6") => false,
+        _ => return Err(invalid("unsupported protocol")),
+    };
+    let source = parse_ip(parts.next(), v4)?;
+    let destination = parse_ip(parts.next(), v4)?;
+    let source_port = parse_port(parts.next())?;
+    let destination_port = parse_port(parts.next())?;
+    if parts.next().is_some() {
+        return Err(invalid("trailing data"));
+    }
+    Ok(ProxyProtocolHeader {
+        source: Some(SocketAddr::new(source, source_port)),
+        destination: Some(SocketAddr::new(destination, destination_port)),
+        ..unknown
+    })
+}
+
+/// Parse a v2 header from its fixed 16-byte prefix and the variable-length
+/// body that follows.
+fn parse_v2(header: &[u8; 16], body: &[u8]) -> std::io::Result<ProxyProtocolHeader> {
+    if header[..12] != V2_SIGNATURE {
+        return Err(invalid("invalid signature"));
+    }
+    if header[12] >> 4 != 2 {
+        return Err(invalid("unsupported version"));
+    }
+    let local = match header[12] & 0xf {
+        0 => true,
+        1 => false,
+        _ => return Err(invalid("unsupported command")),
+    };
+
+    // The address family and transport protocol.
+    let (addr_len, v4) = match header[13] >> 4 {
+        0 => (0, false),
+        1 => (12, true),
+        2 => (36, false),
+        3 => (216, false),
+        _ => return Err(invalid("unsupported address family")),
+    };
+    if body.len() < addr_len {
+        return Err(invalid("truncated addresses"));
+    }
+    let (addrs, mut tlv_bytes) = body.split_at(addr_len);
+    // Only report the addresses of proxied TCP connections over IP.
+    let stream = header[13] & 0xf == 1;
+    let (source, destination) = match addr_len {
+        12 | 36 if stream && !local => {
+            let ip_len = addr_len / 2 - 2;
+            let ip = |bytes: &[u8]| -> IpAddr {
+                if v4 {
+                    IpAddr::V4(<[u8; 4]>::try_from(bytes).unwrap().into())
+                } else {
+                    IpAddr::V6(<[u8; 16]>::try_from(bytes).unwrap().into())
+                }
+            };
+            let ports = &addrs[ip_len * 2..];
+            (
+                Some(SocketAddr::new(
+                    ip(&addrs[..ip_len]),
+                    u16::from_be_bytes([ports[0], ports[1]]),
+                )),
+                Some(SocketAddr::new(
+                    ip(&addrs[ip_len..ip_len * 2]),
+                    u16::from_be_bytes([ports[2], ports[3]]),
+                )),
+            )
+        }
+        _ => (None, None),
+    };
+
+    let mut tlvs = vec![];
+    while !tlv_bytes.is_empty() {
+        let [kind, len_hi, len_lo, rest @ ..] = tlv_bytes else {
+            return Err(invalid("truncated TLV"));
+        };
+        let len = u16::from_be_bytes([*len_hi, *len_lo]) as usize;
+        if rest.len() < len {
+            return Err(invalid("truncated TLV"));
+        }
+        let (value, rest) = rest.split_at(len);
+        tlvs.push((*kind, value.to_vec()));
+        tlv_bytes = rest;
+    }
+
+    Ok(ProxyProtocolHeader {
+        version: ProxyProtocolVersion::V2,
+        source,
+        destination,
+        tlvs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ip_network() {
+        let network: IpNetwork = "10.1.0.0/16".parse().unwrap();
+        assert!(network.contains("10.1.2.3".parse().unwrap()));
+        assert!(network.contains("::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!network.contains("10.2.0.1".parse().unwrap()));
+        assert!(!network.contains("::1".parse().unwrap()));
+
+        let network: IpNetwork = "fd00::/7".parse().unwrap();
+        assert!(network.contains("fdff::1".parse().unwrap()));
+        assert!(!network.contains("fe00::1".parse().unwrap()));
+
+        let network: IpNetwork = "192.168.0.1".parse().unwrap();
+        assert!(network.contains("192.168.0.1".parse().unwrap()));
+        assert!(!network.contains("192.168.0.2".parse().unwrap()));
+
+        let network: IpNetwork = "0.0.0.0/0".parse().unwrap();
+        assert!(network.contains("1.2.3.4".parse().unwrap()));
+
+        for invalid in ["10.0.0.0/33", "::/129", "10.0.0.0/x", "host/8", ""] {
+            assert!(invalid.parse::<IpNetwork>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_parse_v1() {
+        let header = parse_v1(b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\n").unwrap();
+        assert_eq!(header.version, ProxyProtocolVersion::V1);
+        assert_eq!(header.source, Some("192.0.2.1:56324".parse().unwrap()));
+        assert_eq!(
+            header.destination,
+            Some("198.51.100.1:443".parse().unwrap())
+        );
+
+        let header = parse_v1(b"PROXY TCP6 2001:db8::1 ::1 56324 443\r\n").unwrap();
+        assert_eq!(header.source, Some("[2001:db8::1]:56324".parse().unwrap()));
+
+        let header = parse_v1(b"PROXY UNKNOWN ignored\r\n").unwrap();
+        assert_eq!(header.source, None);
+
+        for invalid in [
+            &b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\n"[..],
+            b"PROXY TCP4 2001:db8::1 ::1 56324 443\r\n",
+            b"PROXY TCP6 192.0.2.1 198.51.100.1 56324 443\r\n",
+            b"PROXY TCP4 192.0.2.1 198.51.100.1 56324\r\n",
+            b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 65536\r\n",
+            b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443 extra\r\n",
+            b"PROXY UDP4 192.0.2.1 198.51.100.1 56324 443\r\n",
+        ] {
+            assert!(parse_v1(invalid).is_err(), "{invalid:?}");
+        }
+    }
+
+    fn v2(ver_cmd: u8, fam: u8, body: &[u8]) -> ([u8; 16], Vec<u8>) {
+        let mut header = [0; 16];
+        header[..12].copy_from_slice(&V2_SIGNATURE);
+        header[12] = ver_cmd;
+        header[13] = fam;
+        header[14..].copy_from_slice(&(body.len() as u16).to_be_bytes());
+        (header, body.to_vec())
+    }
+
+    #[test]
+    fn test_parse_v2() {
+        let mut body = vec![192, 0, 2, 1, 198, 51, 100, 1];
+        body.extend(56324_u16.to_be_bytes());
+        body.extend(443_u16.to_be_bytes());
+        // Authority TLV, followed by an empty NOOP TLV
+        body.extend([0x02, 0, 9]);
+        body.extend(b"localhost");
+        body.extend([0x04, 0, 0]);
+        let (header, body) = v2(0x21, 0x11, &body);
+        let header = parse_v2(&header, &body).unwrap();
+        assert_eq!(header.version, ProxyProtocolVersion::V2);
+        assert_eq!(header.source, Some("192.0.2.1:56324".parse().unwrap()));
+        assert_eq!(
+            header.destination,
+            Some("198.51.100.1:443".parse().unwrap())
+        );
+        assert_eq!(header.tlv(0x02), Some(&b"localhost"[..]));
+        assert_eq!(header.tlv(0x04), Some(&b""[..]));
+        assert_eq!(header.tlv(0x05), None);
+
+        let mut body = vec![0; 36];
+        body[15] = 1;
+        body[31] = 2;
+        body[32..34].copy_from_slice(&1234_u16.to_be_bytes());
+        let (header, body) = v2(0x21, 0x21, &body);
+        let header = parse_v2(&header, &body).unwrap();
+        assert_eq!(header.source, Some("[::1]:1234".parse().unwrap()));
+        assert_eq!(header.destination, Some("[::2]:0".parse().unwrap()));
+
+        // LOCAL connections from the proxy itself have no client address.
+        let (header, body) = v2(0x20, 0x11, &[0; 12]);
+        assert_eq!(parse_v2(&header, &body).unwrap().source, None);
+        let (header, body) = v2(0x20, 0x00, &[]);
+        assert_eq!(parse_v2(&header, &body).unwrap().source, None);
+
+        for (ver_cmd, fam, body) in [
+            (0x11, 0x11, &[0; 12][..]),
+            (0x22, 0x11, &[0; 12]),
+            (0x21, 0x41, &[0; 12]),
+            (0x21, 0x11, &[0; 11]),
+            (0x21, 0x21, &[0; 12]),
+            (0x21, 0x11, &[0; 14]),
+            (0x21, 0x11, &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1]),
+        ] {
+            let (header, body) = v2(ver_cmd, fam, body);
+            assert!(
+                parse_v2(&header, &body).is_err(),
+                "{ver_cmd:x} {fam:x} {body:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_header() {
+        let mut data = &b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\nHELLO"[..];
+        let header = read_header(&mut data).await.unwrap();
+        assert_eq!(header.source, Some("192.0.2.1:56324".parse().unwrap()));
+        assert_eq!(data, b"HELLO");
+
+        let (header, body) = v2(0x20, 0x00, &[0x04, 0, 1, 0]);
+        let mut data = [&header[..], &body, b"HELLO"].concat();
+        let header = read_header(&mut data.as_slice()).await.unwrap();
+        assert_eq!(header.tlvs, vec![(0x04, vec![0])]);
+        data.truncate(16);
+        assert!(read_header(&mut data.as_slice()).await.is_err());
+
+        let mut data = &b"GET / HTTP/1.1\r\n"[..];
+        assert!(read_header(&mut data).await.is_err());
+        let long = format!("PROXY UNKNOWN {}\r\n", "x".repeat(100));
+        assert!(read_header(&mut long.as_bytes()).await.is_err());
+    }
+}
@@ -13,17 +13,25 @@ pub struct Resolver {
 
 #[cfg(feature = "tokio")]
 #[allow(unused)]
-async fn resolve_host_to_socket_addrs(host: String) -> std::io::Result<ResolvedTarget> {
-    let res = tokio::task::spawn_blocking(move || format!("{host}:0").to_socket_addrs())
+async fn resolve_host_to_socket_addrs(
+    host: String,
+    port: u16,
+) -> std::io::Result<Vec<ResolvedTarget>> {
+    let res = tokio::task::spawn_blocking(move || (host, port).to_socket_addrs())
         .await
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::Interrupted, e.to_string()))??;
-    res.into_iter()
-        .next()
-        .ok_or(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            "No address found",
-        ))
+    let addrs = res
+        .into_iter()
         .map(ResolvedTarget::SocketAddr)
+        .collect::<Vec<_>>();
+    if addrs.is_empty() {
+        return Err(no_address_found());
+    }
+    Ok(addrs)
+}
+
+fn no_address_found() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::NotFound, "No address found")
 }
 
 impl Resolver {
@@ -31,23 +39,45 @@ impl Resolver {
     pub fn new() -> Result<Self, std::io::Error> {
         Ok(Self {
             #[cfg(feature = "hickory")]
-            resolver: hickory_resolver::Resolver::builder_tokio()?.build().into(),
+            resolver: {
+                let mut builder = hickory_resolver::Resolver::builder_tokio()?;
+                // Look up both address families so that connections can be
+                // raced between them.
+                builder.options_mut().ip_strategy =
+                    hickory_resolver::config::LookupIpStrategy::Ipv4AndIpv6;
+                builder.build().into()
+            },
         })
     }
 
+    /// Resolve a target to all of its addresses, in the order returned by the
+    /// system or DNS resolver.
+    pub fn resolve_all(&self, target: &TargetName) -> ResolveResult<Vec<ResolvedTarget>> {
+        self.resolve_remote_all(target.maybe_resolved())
+    }
+
     pub(crate) fn resolve_remote(
         &self,
         host: &MaybeResolvedTarget,
     ) -> ResolveResult<ResolvedTarget> {
+        // `resolve_remote_all` never returns an empty list.
+        self.resolve_remote_all(host)
+            .map(|addrs| addrs.into_iter().next().expect("at least one address"))
+    }
+
+    pub(crate) fn resolve_remote_all(
+        &self,
+        host: &MaybeResolvedTarget,
+    ) -> ResolveResult<Vec<ResolvedTarget>> {
         match host {
             MaybeResolvedTarget::Resolved(resolved) => {
-                ResolveResult::new_sync(Ok(resolved.clone()))
+                ResolveResult::new_sync(Ok(vec![resolved.clone()]))
             }
             MaybeResolvedTarget::Unresolved(host, port, _) => {
                 if let Ok(ip) = IpAddr::from_str(host) {
-                    ResolveResult::new_sync(Ok(ResolvedTarget::SocketAddr(SocketAddr::from((
-                        ip, *port,
-                    )))))
+                    ResolveResult::new_sync(Ok(vec![ResolvedTarget::SocketAddr(SocketAddr::from(
+                        (ip, *port),
+                    ))]))
                 } else {
                     #[cfg(feature = "hickory")]
                     {
@@ -55,19 +85,24 @@ impl Resolver {
                         let host = host.to_string();
                         let port = *port;
                         ResolveResult::new_async(async move {
-                            let f = resolver.lookup_ip(host);
-                            let Some(addr) = f.await?.iter().next() else {
-                                return Err(std::io::Error::new(
-                                    std::io::ErrorKind::NotFound,
-                                    "No address found",
-                                ));
-                            };
-                            Ok(ResolvedTarget::SocketAddr(SocketAddr::new(addr, port)))
+                            let addrs = resolver
+                                .lookup_ip(host)
+                                .await?
+                                .iter()
+                                .map(|addr| ResolvedTarget::SocketAddr(SocketAddr::new(addr, port)))
+                                .collect::<Vec<_>>();
+                            if addrs.is_empty() {
+                                return Err(no_address_found());
+                            }
+                            Ok(addrs)
                         })
                     }
                     #[cfg(all(feature = "tokio", not(feature = "hickory")))]
                     {
-                        ResolveResult::new_async(resolve_host_to_socket_addrs(host.to_string()))
+                        ResolveResult::new_async(resolve_host_to_socket_addrs(
+                            host.to_string(),
+                            *port,
+                        ))
                     }
                     #[cfg(not(any(feature = "tokio", feature = "hickory")))]
                     {
@@ -1,6 +1,7 @@
 use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
 use rustls::client::WebPkiServerVerifier;
-use rustls::server::{Acceptor, WebPkiClientVerifier};
+use rustls::crypto::WebPkiSupportedAlgorithms;
+use rustls::server::{Acceptor, ParsedCertificate, WebPkiClientVerifier};
 use rustls::{
     ClientConfig, ClientConnection, DigitallySignedStruct, RootCertStore, ServerConfig,
     SignatureScheme,
@@ -18,7 +19,7 @@ use crate::{
     SslVersion, Stream, StreamMetadata, TlsClientCertVerify, TlsDriver, TlsHandshake,
     TlsServerParameterProvider, TlsServerParameters, Transport,
 };
-use crate::{TlsCert, TlsParameters, TlsServerCertVerify};
+use crate::{CertificatePinMismatch, TlsCert, TlsParameters, TlsServerCertVerify};
 use std::borrow::Cow;
 use std::mem::MaybeUninit;
 use std::net::{IpAddr, Ipv4Addr};
@@ -117,8 +118,9 @@ impl TlsDriver for RustlsDriver {
             }
         };
 
+        // This also checks that the key matches the certificate.
         let mut config = builder.with_single_cert(
-            vec![params.server_certificate.cert.clone()],
+            params.server_certificate.cert_chain(),
             params.server_certificate.key.clone_key(),
         )?;
 
@@ -164,7 +166,9 @@ impl TlsDriver for RustlsDriver {
                 let kind = e.kind();
                 if let Some(e2) = e.into_inner() {
                     match e2.downcast::<::rustls::Error>() {
-                        Ok(e) => Err(crate::SslError::RustlsError(*e)),
+                        Ok(e) => Err(pin_mismatch(&e)
+                            .map(crate::SslError::CertificatePinMismatch)
+                            .unwrap_or(crate::SslError::RustlsError(*e))),
                         Err(e) => Err(std::io::Error::new(kind, e).into()),
                     }
                 } else {
@@ -312,6 +316,10 @@ fn make_verifier(
         return Ok(Arc::new(NullVerifier));
     }
 
+    if let TlsServerCertVerify::Pinned(pins) = server_cert_verify {
+        return Ok(Arc::new(PinnedVerifier::new(pins.clone())));
+    }
+
     if matches!(
         root_cert,
         TlsCert::Webpki | TlsCert::WebpkiPlus(_) | TlsCert::Custom(_)
@@ -415,6 +423,85 @@ impl ServerCertVerifier for IgnoreHostnameVerifier {
     }
 }
 
+/// Accepts a certificate if it matches one of the pinned fingerprints,
+/// regardless of its issuer or name. Handshake signatures are still verified
+/// against the certificate's key.
+#[derive(Debug)]
+struct PinnedVerifier {
+    pins: Vec<[u8; 32]>,
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl PinnedVerifier {
+    fn new(pins: Vec<[u8; 32]>) -> Self {
+        Self {
+            pins,
+            algorithms: rustls::crypto::ring::default_provider().signature_verification_algorithms,
+        }
+    }
+}
+
+/// Extract the pin mismatch reported by [`PinnedVerifier`], which rustls
+/// would otherwise only render with `Debug`.
+fn pin_mismatch(e: &rustls::Error) -> Option<CertificatePinMismatch> {
+    match e {
+        rustls::Error::InvalidCertificate(rustls::CertificateError::Other(other)) => {
+            other.0.downcast_ref::<CertificatePinMismatch>().cloned()
+        }
+        _ => None,
+    }
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    ring::digest::digest(&ring::digest::SHA256, data)
+        .as_ref()
+        .try_into()
+        .expect("SHA-256 digests are 32 bytes")
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let spki = ParsedCertificate::try_from(end_entity)?.subject_public_key_info();
+        CertificatePinMismatch::check(&self.pins, sha256(end_entity), sha256(&spki)).map_err(
+            |e| {
+                rustls::Error::InvalidCertificate(rustls::CertificateError::Other(
+                    rustls::OtherError(Arc::new(e)),
+                ))
+            },
+        )?;
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
 #[derive(Debug)]
 struct ChainingVerifier {
     verifier1: Arc<dyn ServerCertVerifier>,
@@ -666,19 +753,21 @@ impl PeerCred for TlsStream<TokioStream> {
 
 impl StreamMetadata for TlsStream<TokioStream> {
     fn transport(&self) -> Transport {
-        Transport::Tcp
+        self.underlying_stream()
+            .map(|stream| stream.transport())
+            .unwrap_or(Transport::Tcp)
     }
 }
 
 impl AsHandle for TlsStream<TokioStream> {
     #[cfg(windows)]
-    fn as_handle(&self) -> std::os::windows::io::BorrowedSocket {
-        std::os::windows::io::AsSocket::as_socket(self.underlying_stream().unwrap())
+    fn as_handle(&self) -> Option<std::os::windows::io::BorrowedSocket> {
+        self.underlying_stream()?.as_handle()
     }
 
     #[cfg(unix)]
-    fn as_fd(&self) -> std::os::fd::BorrowedFd {
-        std::os::fd::AsFd::as_fd(self.underlying_stream().unwrap())
+    fn as_fd(&self) -> Option<std::os::fd::BorrowedFd> {
+        self.underlying_stream()?.as_fd()
     }
 }
 
@@ -690,6 +779,7 @@ impl UnderlyingStream for TokioStream {
             TokioStream::Tcp(stream) => stream.readable().await,
             #[cfg(unix)]
             TokioStream::Unix(stream) => stream.readable().await,
+            TokioStream::Memory(stream) => stream.readable().await,
         }
     }
     async fn writable(&self) -> std::io::Result<()> {
@@ -697,6 +787,7 @@ impl UnderlyingStream for TokioStream {
             TokioStream::Tcp(stream) => stream.writable().await,
             #[cfg(unix)]
             TokioStream::Unix(stream) => stream.writable().await,
+            TokioStream::Memory(stream) => stream.writable().await,
         }
     }
     fn poll_read_ready(&self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
@@ -704,6 +795,7 @@ impl UnderlyingStream for TokioStream {
             TokioStream::Tcp(stream) => stream.poll_read_ready(cx),
             #[cfg(unix)]
             TokioStream::Unix(stream) => stream.poll_read_ready(cx),
+            TokioStream::Memory(stream) => stream.poll_read_ready(cx),
         }
     }
     fn poll_write_ready(&self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
@@ -711,6 +803,7 @@ impl UnderlyingStream for TokioStream {
             TokioStream::Tcp(stream) => stream.poll_write_ready(cx),
             #[cfg(unix)]
             TokioStream::Unix(stream) => stream.poll_write_ready(cx),
+            TokioStream::Memory(stream) => stream.poll_write_ready(cx),
         }
     }
     fn try_read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
@@ -718,6 +811,7 @@ impl UnderlyingStream for TokioStream {
             TokioStream::Tcp(stream) => stream.try_read(buf),
             #[cfg(unix)]
             TokioStream::Unix(stream) => stream.try_read(buf),
+            TokioStream::Memory(stream) => stream.try_read(buf),
         }
     }
     fn try_write(&self, buf: &[u8]) -> std::io::Result<usize> {
@@ -725,6 +819,7 @@ impl UnderlyingStream for TokioStream {
             TokioStream::Tcp(stream) => stream.try_write(buf),
             #[cfg(unix)]
             TokioStream::Unix(stream) => stream.try_write(buf),
+            TokioStream::Memory(stream) => stream.try_write(buf),
         }
     }
     fn shutdown(&self, how: std::net::Shutdown) -> std::io::Result<()> {
@@ -732,6 +827,7 @@ impl UnderlyingStream for TokioStream {
             TokioStream::Tcp(stream) => stream.shutdown(how),
             #[cfg(unix)]
             TokioStream::Unix(stream) => stream.shutdown(how),
+            TokioStream::Memory(stream) => stream.shutdown(how),
         }
     }
 
@@ -744,6 +840,8 @@ impl UnderlyingStream for TokioStream {
             TokioStream::Tcp(stream) => UnderlyingStream::downcast(stream).map_err(Self::Tcp),
             #[cfg(unix)]
             TokioStream::Unix(stream) => UnderlyingStream::downcast(stream).map_err(Self::Unix),
+            // In-memory streams have no underlying socket to downcast to.
+            TokioStream::Memory(stream) => Err(Self::Memory(stream)),
         }
     }
 }
@@ -10,20 +10,25 @@ use std::{
 use std::{future::Future, num::NonZeroUsize, ops::Deref};
 
 use crate::{
-    LocalAddress, PeerCred, RemoteAddress, ResolvedTarget, Ssl, SslError, StreamMetadata,
-    TlsDriver, TlsHandshake, TlsServerParameterProvider, Transport, DEFAULT_PREVIEW_BUFFER_SIZE,
+    LocalAddress, PeerCred, ProxyProtocolHeader, RemoteAddress, ResolvedTarget, Ssl, SslError,
+    StreamMetadata, TlsDriver, TlsHandshake, TlsServerParameterProvider, Transport,
+    DEFAULT_PREVIEW_BUFFER_SIZE,
 };
 
 /// A trait for streams that can be converted to a handle or file descriptor.
+/// Streams that are not backed by a socket, such as in-memory streams,
+/// return `None`.
 #[cfg(unix)]
 pub trait AsHandle {
-    fn as_fd(&self) -> std::os::fd::BorrowedFd;
+    fn as_fd(&self) -> Option<std::os::fd::BorrowedFd>;
 }
 
 /// A trait for streams that can be converted to a handle or file descriptor.
+/// Streams that are not backed by a socket, such as in-memory streams,
+/// return `None`.
 #[cfg(windows)]
 pub trait AsHandle {
-    fn as_handle(&self) -> std::os::windows::io::BorrowedSocket;
+    fn as_handle(&self) -> Option<std::os::windows::io::BorrowedSocket>;
 }
 
 /// A convenience trait for streams from this crate.
@@ -130,12 +135,12 @@ impl PeerCred for () {}
 #[cfg(not(feature = "tokio"))]
 impl AsHandle for () {
     #[cfg(unix)]
-    fn as_fd(&self) -> std::os::fd::BorrowedFd {
-        unreachable!()
+    fn as_fd(&self) -> Option<std::os::fd::BorrowedFd> {
+        None
     }
     #[cfg(windows)]
-    fn as_handle(&self) -> std::os::windows::io::BorrowedSocket {
-        unreachable!()
+    fn as_handle(&self) -> Option<std::os::windows::io::BorrowedSocket> {
+        None
     }
 }
 
@@ -183,10 +188,16 @@ fn with_socket2<S: AsHandle + Sized>(
     f: &mut dyn for<'a> FnMut(socket2::SockRef<'a>) -> Result<(), std::io::Error>,
 ) -> Result<(), std::io::Error> {
     #[cfg(unix)]
-    let res = f(socket2::SockRef::from(&stream.as_fd()));
+    let handle = stream.as_fd();
     #[cfg(windows)]
-    let res = f(socket2::SockRef::from(&stream.as_handle()));
-    res
+    let handle = stream.as_handle();
+    let Some(handle) = handle else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "Stream does not have a socket handle",
+        ));
+    };
+    f(socket2::SockRef::from(&handle))
 }
 
 #[cfg(feature = "optimization")]
@@ -200,8 +211,7 @@ pub trait StreamOptimizationExt: Stream + Sized {
             };
         );
 
-        #[cfg(unix)]
-        if self.transport() == Transport::Unix {
+        if self.transport() != Transport::Tcp {
             return Ok(());
         }
 
@@ -355,6 +365,7 @@ impl Default for PreviewConfiguration {
 #[derive(Default, Debug)]
 struct UpgradableStreamOptions {
     ignore_missing_close_notify: bool,
+    proxy_header: Option<Box<ProxyProtocolHeader>>,
 }
 
 #[allow(private_bounds)]
@@ -418,6 +429,16 @@ impl<S: Stream, D: TlsDriver> UpgradableStream<S, D> {
         self.options.ignore_missing_close_notify = true;
     }
 
+    /// The PROXY protocol header sent by a trusted proxy at the start of the
+    /// connection, if any.
+    pub fn proxy_header(&self) -> Option<&ProxyProtocolHeader> {
+        self.options.proxy_header.as_deref()
+    }
+
+    pub(crate) fn set_proxy_header(&mut self, header: ProxyProtocolHeader) {
+        self.options.proxy_header = Some(Box::new(header));
+    }
+
     /// Uncleanly shut down the stream. This may cause errors on the peer side
     /// when using TLS.
     pub fn unclean_shutdown(self) -> Result<(), Self> {
@@ -576,6 +597,10 @@ impl<S: Stream, D: TlsDriver> LocalAddress for UpgradableStream<S, D> {
 
 impl<S: Stream, D: TlsDriver> RemoteAddress for UpgradableStream<S, D> {
     fn remote_address(&self) -> std::io::Result<ResolvedTarget> {
+        // Report the original client's address when connected via a proxy.
+        if let Some(source) = self.proxy_header().and_then(|header| header.source) {
+            return Ok(ResolvedTarget::SocketAddr(source));
+        }
         self.inner
             .with_inner_metadata(|inner| inner.remote_address())
     }
@@ -658,12 +683,12 @@ impl<S: Stream, D: TlsDriver> UpgradableStreamInner<S, D> {
 
 impl<S: Stream, D: TlsDriver> AsHandle for UpgradableStream<S, D> {
     #[cfg(windows)]
-    fn as_handle(&self) -> std::os::windows::io::BorrowedSocket {
+    fn as_handle(&self) -> Option<std::os::windows::io::BorrowedSocket> {
         self.inner.as_inner_handle().as_handle()
     }
 
     #[cfg(unix)]
-    fn as_fd(&self) -> std::os::fd::BorrowedFd {
+    fn as_fd(&self) -> Option<std::os::fd::BorrowedFd> {
         self.inner.as_inner_handle().as_fd()
     }
 }
@@ -769,12 +794,12 @@ impl<S: PeekableStream> PeekableStream for RewindStream<S> {
 
 impl<S: Stream + AsHandle> AsHandle for RewindStream<S> {
     #[cfg(windows)]
-    fn as_handle(&self) -> std::os::windows::io::BorrowedSocket {
+    fn as_handle(&self) -> Option<std::os::windows::io::BorrowedSocket> {
         self.inner.as_handle()
     }
 
     #[cfg(unix)]
-    fn as_fd(&self) -> std::os::fd::BorrowedFd {
+    fn as_fd(&self) -> Option<std::os::fd::BorrowedFd> {
         self.inner.as_fd()
     }
 }
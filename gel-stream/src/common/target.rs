@@ -62,6 +62,14 @@ impl TargetName {
         }
     }
 
+    /// Create a new target for an in-memory listener registered in this
+    /// process.
+    pub fn new_memory(name: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            inner: MaybeResolvedTarget::Resolved(ResolvedTarget::Memory(name.into())),
+        }
+    }
+
     /// Create a new target for a TCP socket.
     #[allow(private_bounds)]
     pub fn new_tcp(host: impl TcpResolve) -> Self {
@@ -230,6 +238,22 @@ impl Target {
         }
     }
 
+    /// Create a new target for an in-memory listener registered in this
+    /// process.
+    pub fn new_memory(name: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            inner: TargetInner::NoTls(ResolvedTarget::Memory(name.into()).into()),
+        }
+    }
+
+    /// Create a new target for an in-memory listener registered in this
+    /// process, with TLS.
+    pub fn new_memory_tls(name: impl Into<Cow<'static, str>>, params: TlsParameters) -> Self {
+        Self {
+            inner: TargetInner::Tls(ResolvedTarget::Memory(name.into()).into(), params.into()),
+        }
+    }
+
     /// Create a new target for a TCP socket.
     pub fn new_tcp(host: impl TcpResolve) -> Self {
         Self {
@@ -410,6 +434,9 @@ impl std::fmt::Debug for MaybeResolvedTarget {
                 }
                 Ok(())
             }
+            MaybeResolvedTarget::Resolved(ResolvedTarget::Memory(name)) => {
+                write!(f, "memory:{name}")
+            }
             MaybeResolvedTarget::Unresolved(host, port, interface) => {
                 write!(f, "{host}:{port}")?;
                 if let Some(interface) = interface {
@@ -430,7 +457,6 @@ impl MaybeResolvedTarget {
             MaybeResolvedTarget::Unresolved(host, _, _) => {
                 Some(ServerName::DnsName(host.to_string().try_into().ok()?))
             }
-            #[cfg(unix)]
             _ => None,
         }
     }
@@ -441,7 +467,6 @@ impl MaybeResolvedTarget {
                 Some((Cow::Owned(addr.ip().to_string()), addr.port()))
             }
             MaybeResolvedTarget::Unresolved(host, port, _) => Some((Cow::Borrowed(host), *port)),
-            #[cfg(unix)]
             _ => None,
         }
     }
@@ -462,7 +487,6 @@ impl MaybeResolvedTarget {
                 Some(Cow::Owned(addr.ip().to_string()))
             }
             MaybeResolvedTarget::Unresolved(host, _, _) => Some(Cow::Borrowed(host)),
-            #[cfg(unix)]
             _ => None,
         }
     }
@@ -471,7 +495,6 @@ impl MaybeResolvedTarget {
         match self {
             MaybeResolvedTarget::Resolved(ResolvedTarget::SocketAddr(addr)) => Some(addr.port()),
             MaybeResolvedTarget::Unresolved(_, port, _) => Some(*port),
-            #[cfg(unix)]
             _ => None,
         }
     }
@@ -488,7 +511,6 @@ impl MaybeResolvedTarget {
                 *port = new_port;
                 Some(old_port)
             }
-            #[cfg(unix)]
             _ => None,
         }
     }
@@ -509,6 +531,9 @@ pub enum ResolvedTarget {
     SocketAddr(std::net::SocketAddr),
     #[cfg(unix)]
     UnixSocketAddr(std::os::unix::net::SocketAddr),
+    /// An in-memory listener registered in this process, by name.
+    #[from(skip)]
+    Memory(Cow<'static, str>),
 }
 
 /// Because `std::os::unix::net::SocketAddr` does not implement many helper
@@ -521,10 +546,7 @@ enum ResolvedTargetInner<'a> {
     UnixSocketPath(&'a std::path::Path),
     #[cfg(any(target_os = "linux", target_os = "android"))]
     UnixSocketAbstract(&'a [u8]),
-    /// Windows doesn't need the lifetime, so we create a fake enum variant
-    /// to use it.
-    #[allow(dead_code)]
-    Phantom(std::marker::PhantomData<&'a ()>),
+    Memory(&'a str),
 }
 
 #[cfg(unix)]
@@ -570,11 +592,20 @@ impl ResolvedTarget {
         self.tcp().is_some()
     }
 
+    /// The name of the in-memory listener, if this is an in-memory target.
+    pub fn memory(&self) -> Option<&str> {
+        match self {
+            ResolvedTarget::Memory(name) => Some(name),
+            _ => None,
+        }
+    }
+
     pub fn transport(&self) -> Transport {
         match self {
             ResolvedTarget::SocketAddr(_) => Transport::Tcp,
             #[cfg(unix)]
             ResolvedTarget::UnixSocketAddr(_) => Transport::Unix,
+            ResolvedTarget::Memory(_) => Transport::Memory,
         }
     }
 
@@ -598,6 +629,7 @@ impl ResolvedTarget {
                 }
                 unreachable!()
             }
+            ResolvedTarget::Memory(name) => ResolvedTargetInner::Memory(name),
         }
     }
 }
@@ -622,6 +654,8 @@ pub trait PeerCred {
 pub enum Transport {
     Tcp,
     Unix,
+    /// An in-memory connection within this process.
+    Memory,
 }
 
 /// A trait for stream metadata.
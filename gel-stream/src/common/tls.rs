@@ -103,10 +103,14 @@ impl TlsDriver for NullTlsDriver {
 /// | verify-ca | no_host_verification | `IgnoreHostname`        |
 /// | verify-full | strict | `VerifyFull`      |
 ///
+/// `Pinned` has no equivalent in either: it trusts a certificate by its
+/// fingerprint rather than by its CA, for servers with self-signed
+/// certificates.
+///
 /// Note that both EdgeDB/Gel and Postgres may alter certificate validation levels
 /// when custom root certificates are provided. This must be done in the
 /// `TlsParameters` struct by the caller.
-#[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Default, Clone, derive_more::Debug, PartialEq, Eq)]
 pub enum TlsServerCertVerify {
     /// Do not verify the server's certificate. Only confirm that the server is
     /// using TLS.
@@ -116,6 +120,86 @@ pub enum TlsServerCertVerify {
     /// Verify the server's certificate using the CA and hostname.
     #[default]
     VerifyFull,
+    /// Verify that the server's certificate matches one of the given SHA-256
+    /// fingerprints, of either the DER-encoded certificate or its
+    /// SubjectPublicKeyInfo (SPKI). The CA and hostname are not checked.
+    #[debug("Pinned([{} fingerprint(s)])", _0.len())]
+    Pinned(Vec<[u8; 32]>),
+}
+
+/// Format a SHA-256 fingerprint as colon-separated uppercase hex, as
+/// `openssl x509 -fingerprint -sha256` does.
+pub fn format_fingerprint(fingerprint: &[u8; 32]) -> String {
+    fingerprint
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Parse a SHA-256 fingerprint from hex, with or without colons.
+pub fn parse_fingerprint(s: &str) -> Option<[u8; 32]> {
+    let hex = s.trim().replace(':', "");
+    if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut fingerprint = [0; 32];
+    for (i, b) in fingerprint.iter_mut().enumerate() {
+        *b = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(fingerprint)
+}
+
+/// The server's certificate did not match any of the pinned fingerprints in
+/// [`TlsServerCertVerify::Pinned`].
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Error)]
+pub struct CertificatePinMismatch {
+    /// The pinned fingerprints.
+    pub expected: Vec<[u8; 32]>,
+    /// The SHA-256 fingerprint of the server's certificate.
+    pub certificate: [u8; 32],
+    /// The SHA-256 fingerprint of the server's SubjectPublicKeyInfo.
+    pub spki: [u8; 32],
+}
+
+impl CertificatePinMismatch {
+    /// Check a certificate's fingerprints against the pins.
+    pub(crate) fn check(
+        expected: &[[u8; 32]],
+        certificate: [u8; 32],
+        spki: [u8; 32],
+    ) -> Result<(), Self> {
+        if expected
+            .iter()
+            .any(|pin| *pin == certificate || *pin == spki)
+        {
+            Ok(())
+        } else {
+            Err(Self {
+                expected: expected.to_vec(),
+                certificate,
+                spki,
+            })
+        }
+    }
+}
+
+impl std::fmt::Display for CertificatePinMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let expected = self
+            .expected
+            .iter()
+            .map(format_fingerprint)
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(
+            f,
+            "server certificate does not match any pinned fingerprint: expected {expected}, \
+            but the certificate's SHA-256 fingerprint is {} and its SPKI SHA-256 fingerprint is {}",
+            format_fingerprint(&self.certificate),
+            format_fingerprint(&self.spki)
+        )
+    }
 }
 
 #[derive(Clone, derive_more::Debug, Default, PartialEq, Eq)]
@@ -227,29 +311,53 @@ pub enum TlsClientCertVerify {
     Validate(Vec<CertificateDer<'static>>),
 }
 
-#[derive(derive_more::Debug, derive_more::Constructor)]
+#[derive(derive_more::Debug)]
 pub struct TlsKey {
     #[debug("key(...)")]
     pub(crate) key: PrivateKeyDer<'static>,
     #[debug("cert(...)")]
     pub(crate) cert: CertificateDer<'static>,
+    /// Intermediate certificates sent after `cert`.
+    #[debug("chain({})", chain.len())]
+    pub(crate) chain: Vec<CertificateDer<'static>>,
 }
 
 impl TlsKey {
-    /// Create a new `TlsKey` from a PEM-encoded certificate and key.
+    pub fn new(key: PrivateKeyDer<'static>, cert: CertificateDer<'static>) -> Self {
+        Self {
+            key,
+            cert,
+            chain: vec![],
+        }
+    }
+
+    /// Send these intermediate certificates after the certificate, in order.
+    pub fn with_chain(mut self, chain: Vec<CertificateDer<'static>>) -> Self {
+        self.chain = chain;
+        self
+    }
+
+    /// Create a new `TlsKey` from a PEM-encoded certificate and key. Any
+    /// certificates after the first are used as the certificate chain.
     #[cfg(feature = "pem")]
     pub fn new_pem(mut key: &[u8], mut cert: &[u8]) -> Result<Self, std::io::Error> {
-        let cert = rustls_pemfile::certs(&mut cert)
-            .next()
-            .ok_or(std::io::Error::new(
+        let mut certs = rustls_pemfile::certs(&mut cert).collect::<Result<Vec<_>, _>>()?;
+        if certs.is_empty() {
+            return Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
                 "No certificate found",
-            ))??;
+            ));
+        }
+        let cert = certs.remove(0);
         let key = rustls_pemfile::private_key(&mut key)?.ok_or(std::io::Error::new(
             std::io::ErrorKind::InvalidData,
             "No key found",
         ))?;
-        Ok(Self { cert, key })
+        Ok(Self {
+            cert,
+            key,
+            chain: certs,
+        })
     }
 
     /// Create a clone of this private key and certificate.
@@ -257,8 +365,22 @@ impl TlsKey {
         Self {
             key: self.key.clone_key(),
             cert: self.cert.clone(),
+            chain: self.chain.clone(),
         }
     }
+
+    /// The intermediate certificates sent after the certificate.
+    pub fn chain(&self) -> &[CertificateDer<'static>] {
+        &self.chain
+    }
+
+    /// The certificate followed by its chain.
+    #[cfg(feature = "rustls")]
+    pub(crate) fn cert_chain(&self) -> Vec<CertificateDer<'static>> {
+        std::iter::once(self.cert.clone())
+            .chain(self.chain.iter().cloned())
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -473,6 +595,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fingerprint() {
+        let fingerprint: [u8; 32] = std::array::from_fn(|i| i as u8 * 8);
+        let formatted = format_fingerprint(&fingerprint);
+        assert_eq!(&formatted[..11], "00:08:10:18");
+        assert_eq!(parse_fingerprint(&formatted), Some(fingerprint));
+        assert_eq!(
+            parse_fingerprint(&formatted.replace(':', "").to_lowercase()),
+            Some(fingerprint)
+        );
+        assert_eq!(parse_fingerprint("00:08"), None);
+        assert_eq!(parse_fingerprint(&"zz".repeat(32)), None);
+        // `from_str_radix` alone would accept a sign.
+        assert_eq!(parse_fingerprint(&"+f".repeat(32)), None);
+
+        let params = TlsParameters {
+            server_cert_verify: TlsServerCertVerify::Pinned(vec![fingerprint]),
+            ..Default::default()
+        };
+        assert!(format!("{params:?}").contains("Pinned([1 fingerprint(s)])"));
+
+        assert!(CertificatePinMismatch::check(&[fingerprint], [0; 32], fingerprint).is_ok());
+        let err = CertificatePinMismatch::check(&[fingerprint], [0; 32], [1; 32]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "server certificate does not match any pinned fingerprint: expected {formatted}, \
+                but the certificate's SHA-256 fingerprint is {} and its SPKI SHA-256 fingerprint is {}",
+                format_fingerprint(&[0; 32]),
+                format_fingerprint(&[1; 32])
+            )
+        );
+    }
+
     #[test]
     fn test_tls_alpn() {
         let alpn = TlsAlpn::new_str(&["h2", "http/1.1"]);
@@ -1,4 +1,5 @@
-//! This module provides functionality to connect to Tokio TCP and Unix sockets.
+//! This module provides functionality to connect to Tokio TCP and Unix sockets,
+//! and to in-memory listeners.
 
 use std::pin::Pin;
 use std::task::{ready, Context, Poll};
@@ -6,6 +7,8 @@ use tokio::net::{TcpListener, TcpStream};
 #[cfg(unix)]
 use tokio::net::{UnixListener, UnixStream};
 
+use super::memory::{MemoryListener, MemoryStream};
+
 use crate::{AsHandle, PeekableStream, PeerCred, RemoteAddress, StreamMetadata, Transport};
 
 use super::target::{LocalAddress, ResolvedTarget};
@@ -26,6 +29,9 @@ impl ResolvedTarget {
                 let stream = UnixStream::from_std(stm)?;
                 Ok(TokioStream::Unix(stream))
             }
+            ResolvedTarget::Memory(name) => {
+                Ok(TokioStream::Memory(MemoryStream::connect(name.clone())?))
+            }
         }
     }
 
@@ -48,7 +54,7 @@ impl ResolvedTarget {
         if !self.is_tcp() {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
-                "Unix sockets do not support a connectionbacklog",
+                "Only TCP sockets support a connection backlog",
             ));
         }
         let backlog = u32::try_from(backlog)
@@ -97,6 +103,9 @@ impl ResolvedTarget {
                 let listener = tokio::net::UnixListener::from_std(listener)?;
                 Ok(TokioListenerStream::Unix(listener))
             }
+            ResolvedTarget::Memory(name) => Ok(TokioListenerStream::Memory(MemoryListener::bind(
+                name.clone(),
+            )?)),
         }
     }
 }
@@ -105,6 +114,7 @@ pub(crate) enum TokioListenerStream {
     Tcp(TcpListener),
     #[cfg(unix)]
     Unix(UnixListener),
+    Memory(MemoryListener),
 }
 
 impl LocalAddress for TokioListenerStream {
@@ -117,6 +127,9 @@ impl LocalAddress for TokioListenerStream {
             TokioListenerStream::Unix(listener) => listener
                 .local_addr()
                 .map(|addr| ResolvedTarget::UnixSocketAddr(addr.into())),
+            TokioListenerStream::Memory(listener) => {
+                Ok(ResolvedTarget::Memory(listener.name().clone()))
+            }
         }
     }
 }
@@ -139,21 +152,23 @@ impl futures::Stream for TokioListenerStream {
                 let target = ResolvedTarget::UnixSocketAddr(addr.into());
                 Poll::Ready(Some(Ok((stream, target))))
             }
+            TokioListenerStream::Memory(listener) => {
+                let stream = ready!(listener.poll_accept(cx))?;
+                let target = ResolvedTarget::Memory(stream.name.clone());
+                Poll::Ready(Some(Ok((TokioStream::Memory(stream), target))))
+            }
         }
     }
 }
 
-/// Represents a connected Tokio stream, either TCP or Unix
-#[derive(
-    derive_io::AsyncRead, derive_io::AsyncWrite, derive_io::AsSocketDescriptor, derive_more::Debug,
-)]
+/// Represents a connected Tokio stream, either TCP, Unix or in-memory
+#[derive(derive_io::AsyncRead, derive_io::AsyncWrite, derive_more::Debug)]
 pub enum TokioStream {
     /// TCP stream
     #[debug("{_0:?}")]
     Tcp(
         #[read]
         #[write]
-        #[descriptor]
         TcpStream,
     ),
     /// Unix stream (only available on Unix systems)
@@ -162,9 +177,15 @@ pub enum TokioStream {
     Unix(
         #[read]
         #[write]
-        #[descriptor]
         UnixStream,
     ),
+    /// In-memory stream
+    #[debug("{_0:?}")]
+    Memory(
+        #[read]
+        #[write]
+        MemoryStream,
+    ),
 }
 
 impl TokioStream {
@@ -189,19 +210,30 @@ impl TokioStream {
                 std::io::ErrorKind::Unsupported,
                 "Unix sockets do not support keepalive",
             )),
+            TokioStream::Memory(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "In-memory streams do not support keepalive",
+            )),
         }
     }
 }
 
 impl AsHandle for TokioStream {
     #[cfg(windows)]
-    fn as_handle(&self) -> std::os::windows::io::BorrowedSocket {
-        <Self as std::os::windows::io::AsSocket>::as_socket(self)
+    fn as_handle(&self) -> Option<std::os::windows::io::BorrowedSocket> {
+        match self {
+            TokioStream::Tcp(stream) => stream.as_handle(),
+            TokioStream::Memory(stream) => stream.as_handle(),
+        }
     }
 
     #[cfg(unix)]
-    fn as_fd(&self) -> std::os::fd::BorrowedFd {
-        <Self as std::os::fd::AsFd>::as_fd(self)
+    fn as_fd(&self) -> Option<std::os::fd::BorrowedFd> {
+        match self {
+            TokioStream::Tcp(stream) => stream.as_fd(),
+            TokioStream::Unix(stream) => stream.as_fd(),
+            TokioStream::Memory(stream) => stream.as_fd(),
+        }
     }
 }
 
@@ -214,27 +246,38 @@ impl PeekableStream for TokioStream {
         match self.get_mut() {
             TokioStream::Tcp(stream) => Pin::new(stream).poll_peek(cx, buf),
             #[cfg(unix)]
-            TokioStream::Unix(stream) => loop {
-                ready!(stream.poll_read_ready(cx))?;
-                let sock = socket2::SockRef::from(&*stream);
-                break match sock.recv_with_flags(unsafe { buf.unfilled_mut() }, libc::MSG_PEEK) {
-                    Ok(n) => Poll::Ready(Ok(n)),
-                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                        continue;
-                    }
-                    Err(e) => Poll::Ready(Err(e)),
-                };
-            },
+            TokioStream::Unix(stream) => poll_peek_unix(stream, cx, buf),
+            TokioStream::Memory(stream) => Pin::new(stream).poll_peek(cx, buf),
         }
     }
 }
 
+#[cfg(unix)]
+fn poll_peek_unix(
+    stream: &UnixStream,
+    cx: &mut Context<'_>,
+    buf: &mut tokio::io::ReadBuf<'_>,
+) -> Poll<std::io::Result<usize>> {
+    loop {
+        ready!(stream.poll_read_ready(cx))?;
+        let sock = socket2::SockRef::from(stream);
+        break match sock.recv_with_flags(unsafe { buf.unfilled_mut() }, libc::MSG_PEEK) {
+            Ok(n) => Poll::Ready(Ok(n)),
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                continue;
+            }
+            Err(e) => Poll::Ready(Err(e)),
+        };
+    }
+}
+
 impl LocalAddress for TokioStream {
     fn local_address(&self) -> std::io::Result<ResolvedTarget> {
         match self {
             TokioStream::Tcp(stream) => <TcpStream as LocalAddress>::local_address(stream),
             #[cfg(unix)]
             TokioStream::Unix(stream) => <UnixStream as LocalAddress>::local_address(stream),
+            TokioStream::Memory(stream) => stream.local_address(),
         }
     }
 }
@@ -245,6 +288,7 @@ impl RemoteAddress for TokioStream {
             TokioStream::Tcp(stream) => <TcpStream as RemoteAddress>::remote_address(stream),
             #[cfg(unix)]
             TokioStream::Unix(stream) => <UnixStream as RemoteAddress>::remote_address(stream),
+            TokioStream::Memory(stream) => stream.remote_address(),
         }
     }
 }
@@ -254,6 +298,7 @@ impl PeerCred for TokioStream {
     fn peer_cred(&self) -> std::io::Result<tokio::net::unix::UCred> {
         match self {
             TokioStream::Unix(unix) => unix.peer_cred(),
+            TokioStream::Memory(memory) => memory.peer_cred(),
             TokioStream::Tcp(_) => Err(std::io::Error::new(
                 std::io::ErrorKind::Unsupported,
                 "TCP sockets do not support peer credentials",
@@ -268,6 +313,7 @@ impl StreamMetadata for TokioStream {
             TokioStream::Tcp(_) => Transport::Tcp,
             #[cfg(unix)]
             TokioStream::Unix(_) => Transport::Unix,
+            TokioStream::Memory(_) => Transport::Memory,
         }
     }
 }
@@ -302,13 +348,13 @@ impl StreamMetadata for TcpStream {
 
 impl AsHandle for TcpStream {
     #[cfg(windows)]
-    fn as_handle(&self) -> std::os::windows::io::BorrowedSocket {
-        <Self as std::os::windows::io::AsSocket>::as_socket(self)
+    fn as_handle(&self) -> Option<std::os::windows::io::BorrowedSocket> {
+        Some(<Self as std::os::windows::io::AsSocket>::as_socket(self))
     }
 
     #[cfg(unix)]
-    fn as_fd(&self) -> std::os::fd::BorrowedFd {
-        <Self as std::os::fd::AsFd>::as_fd(self)
+    fn as_fd(&self) -> Option<std::os::fd::BorrowedFd> {
+        Some(<Self as std::os::fd::AsFd>::as_fd(self))
     }
 }
 
@@ -344,7 +390,7 @@ impl StreamMetadata for UnixStream {
 
 #[cfg(unix)]
 impl AsHandle for UnixStream {
-    fn as_fd(&self) -> std::os::fd::BorrowedFd {
-        <Self as std::os::fd::AsFd>::as_fd(self)
+    fn as_fd(&self) -> Option<std::os::fd::BorrowedFd> {
+        Some(<Self as std::os::fd::AsFd>::as_fd(self))
     }
 }
@@ -15,17 +15,21 @@ mod client;
 mod server;
 
 #[cfg(feature = "client")]
-pub use client::Connector;
+pub use client::{AddressFamilyPreference, Connector, HappyEyeballs, DEFAULT_ATTEMPT_DELAY};
 
 #[cfg(feature = "server")]
 pub use server::Acceptor;
+#[cfg(all(feature = "server", feature = "pem"))]
+pub use server::{TlsKeyReloader, TlsReloadError, TlsReloadEvent};
 
 mod common;
 #[cfg(feature = "openssl")]
 pub use common::openssl::OpensslDriver;
 #[cfg(feature = "rustls")]
 pub use common::rustls::RustlsDriver;
-pub use common::{resolver::*, stream::*, target::*, tls::*, BaseStream};
+pub use common::{
+    proxy::*, proxy_protocol::*, resolver::*, stream::*, target::*, tls::*, BaseStream,
+};
 pub use rustls_pki_types as pki_types;
 
 pub type RawStream = UpgradableStream<BaseStream>;
@@ -98,6 +102,9 @@ pub enum SslError {
     #[display("Invalid DNS name: {_0}")]
     InvalidDnsNameError(#[from] ::rustls_pki_types::InvalidDnsNameError),
 
+    #[display("{_0}")]
+    CertificatePinMismatch(#[from] CertificatePinMismatch),
+
     #[display("SSL I/O error: {_0}")]
     SslIoError(#[from] std::io::Error),
 }
@@ -174,6 +181,7 @@ impl SslError {
                 }
                 _ => None,
             },
+            SslError::CertificatePinMismatch(_) => Some(CommonError::CertificatePinMismatch),
             _ => None,
         }
     }
@@ -200,6 +208,8 @@ pub enum CommonError {
     CertificateExpired,
     #[display("The certificate was issued by an untrusted authority")]
     InvalidIssuer,
+    #[display("The certificate does not match any pinned fingerprint")]
+    CertificatePinMismatch,
     #[display("TLS protocol error")]
     InvalidTlsProtocolData,
 }
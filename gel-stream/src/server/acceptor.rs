@@ -1,12 +1,16 @@
 use crate::{
-    common::tokio_stream::TokioListenerStream, ConnectionError, LocalAddress, Preview,
-    PreviewConfiguration, ResolvedTarget, RewindStream, Ssl, StreamUpgrade, TlsDriver,
-    TlsServerParameterProvider, UpgradableStream, DEFAULT_TLS_BACKLOG,
+    common::{proxy_protocol, tokio_stream::TokioListenerStream},
+    BaseStream, ConnectionError, LocalAddress, PeerCred, Preview, PreviewConfiguration,
+    ProxyProtocolConfiguration, ProxyProtocolHeader, RemoteAddress, ResolvedTarget, RewindStream,
+    Ssl, StreamMetadata, StreamUpgrade, TlsDriver, TlsServerParameterProvider, Transport,
+    UpgradableStream, DEFAULT_TLS_BACKLOG,
 };
 use futures::{stream::FuturesUnordered, StreamExt};
 use std::{
+    borrow::Cow,
     future::Future,
     pin::Pin,
+    sync::Arc,
     task::{ready, Poll},
 };
 use std::{net::SocketAddr, path::Path};
@@ -21,7 +25,7 @@ pub struct Acceptor<const PREVIEW: bool = false> {
     options: StreamOptions<PREVIEW>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 struct StreamOptions<const PREVIEW: bool> {
     ignore_missing_tls_close_notify: bool,
     reuse_port: bool,
@@ -29,6 +33,7 @@ struct StreamOptions<const PREVIEW: bool> {
     preview_configuration: Option<PreviewConfiguration>,
     tcp_backlog: Option<u32>,
     tls_backlog: Option<u32>,
+    proxy_protocol: Option<Arc<ProxyProtocolConfiguration>>,
 }
 
 impl<const PREVIEW: bool> Default for StreamOptions<PREVIEW> {
@@ -40,6 +45,7 @@ impl<const PREVIEW: bool> Default for StreamOptions<PREVIEW> {
             preview_configuration: None,
             tcp_backlog: None,
             tls_backlog: None,
+            proxy_protocol: None,
         }
     }
 }
@@ -108,6 +114,24 @@ impl<const PREVIEW: bool> Acceptor<PREVIEW> {
             ..self
         }
     }
+
+    /// Read a PROXY protocol v1 or v2 header from the start of each
+    /// connection from a trusted proxy, before any preview or TLS handshake.
+    ///
+    /// The connection's [`RemoteAddress`], including the stream metadata
+    /// passed to a [`TlsServerParameterProvider`] lookup, then reports the
+    /// original client's address, and the header is available from
+    /// [`UpgradableStream::proxy_header`]. Connections with a missing or
+    /// malformed header are dropped and returned as errors.
+    pub fn with_proxy_protocol(self, configuration: ProxyProtocolConfiguration) -> Self {
+        Self {
+            options: StreamOptions {
+                proxy_protocol: Some(Arc::new(configuration)),
+                ..self.options
+            },
+            ..self
+        }
+    }
 }
 
 impl Acceptor<false> {
@@ -208,6 +232,18 @@ impl Acceptor<false> {
         }
     }
 
+    /// Create a new acceptor for an in-memory listener registered in this
+    /// process as `name`. Clients connect to it with
+    /// [`Target::new_memory`](crate::Target::new_memory).
+    pub fn new_memory(name: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            resolved_target: ResolvedTarget::Memory(name.into()),
+            tls_provider: None,
+            should_upgrade: false,
+            options: Default::default(),
+        }
+    }
+
     pub async fn bind(
         self,
     ) -> Result<
@@ -231,6 +267,7 @@ impl Acceptor<false> {
                 self.options.tls_backlog.unwrap_or(DEFAULT_TLS_BACKLOG) as _,
             ),
             preview_configuration: None,
+            proxy_protocol: self.options.proxy_protocol,
             _phantom: None,
         })
     }
@@ -259,6 +296,7 @@ impl Acceptor<false> {
                 self.options.tls_backlog.unwrap_or(DEFAULT_TLS_BACKLOG) as _,
             ),
             preview_configuration: None,
+            proxy_protocol: self.options.proxy_protocol,
             _phantom: None,
         })
     }
@@ -350,6 +388,7 @@ impl Acceptor<true> {
             tls_provider: self.tls_provider,
             tls_backlog: TlsAcceptBacklog::new(self.options.tls_backlog.unwrap_or(128) as _),
             preview_configuration: self.options.preview_configuration,
+            proxy_protocol: self.options.proxy_protocol,
             _phantom: None,
         })
     }
@@ -378,6 +417,7 @@ impl Acceptor<true> {
                 self.options.tls_backlog.unwrap_or(DEFAULT_TLS_BACKLOG) as _,
             ),
             preview_configuration: self.options.preview_configuration,
+            proxy_protocol: self.options.proxy_protocol,
             _phantom: None,
         })
     }
@@ -401,6 +441,7 @@ struct AcceptedStream<S, D: TlsDriver = Ssl> {
     tls_provider: Option<TlsServerParameterProvider>,
     tls_backlog: TlsAcceptBacklog<S>,
     preview_configuration: Option<PreviewConfiguration>,
+    proxy_protocol: Option<Arc<ProxyProtocolConfiguration>>,
     // Avoid using PhantomData because it fails to implement certain auto-traits
     _phantom: Option<&'static D>,
 }
@@ -427,9 +468,9 @@ impl<D: TlsDriver> futures::Stream for AcceptedStream<Connection<D>, D> {
             stream
         };
 
-        // If we're not upgrading, we can just return the stream as is and skip
-        // the second-level backlog.
-        if !self.should_upgrade {
+        // If we're not upgrading or reading a PROXY protocol header, we can
+        // just return the stream as is and skip the second-level backlog.
+        if !self.should_upgrade && self.proxy_protocol.is_none() {
             return self.as_mut().stream.poll_next_unpin(cx).map(|c| {
                 c.map(|c| Ok(c.map(|(c, _t)| make_stream(self.tls_provider.clone(), c))?))
             });
@@ -444,7 +485,7 @@ impl<D: TlsDriver> futures::Stream for AcceptedStream<Connection<D>, D> {
                 break;
             };
 
-            let Some((stream, _t)) = r.transpose()? else {
+            let Some((mut stream, source)) = r.transpose()? else {
                 if self.tls_backlog.is_empty() {
                     return Poll::Ready(None);
                 }
@@ -452,9 +493,19 @@ impl<D: TlsDriver> futures::Stream for AcceptedStream<Connection<D>, D> {
             };
 
             let tls_provider = self.tls_provider.clone();
+            let should_upgrade = self.should_upgrade;
+            let proxy_protocol = self.proxy_protocol.clone();
             self.tls_backlog.push(async move {
-                let stream = make_stream(tls_provider, stream);
-                let stream = stream.secure_upgrade().await?;
+                let header =
+                    read_proxy_header(&mut stream, &source, proxy_protocol.as_deref()).await?;
+                let tls_provider = with_proxy_source(tls_provider, header.as_ref());
+                let mut stream = make_stream(tls_provider, stream);
+                if let Some(header) = header {
+                    stream.set_proxy_header(header);
+                }
+                if should_upgrade {
+                    stream = stream.secure_upgrade().await?;
+                }
                 Ok(stream)
             })
         }
@@ -481,7 +532,7 @@ impl<D: TlsDriver> futures::Stream for AcceptedStream<(Preview, Connection<D>),
                 break;
             };
 
-            let Some((mut stream, _t)) = r.transpose()? else {
+            let Some((mut stream, source)) = r.transpose()? else {
                 if self.tls_backlog.is_empty() {
                     return Poll::Ready(None);
                 }
@@ -491,7 +542,10 @@ impl<D: TlsDriver> futures::Stream for AcceptedStream<(Preview, Connection<D>),
             let tls_provider = self.tls_provider.clone();
             let preview_configuration = self.preview_configuration.unwrap();
             let ignore_missing_tls_close_notify = self.ignore_missing_tls_close_notify;
+            let proxy_protocol = self.proxy_protocol.clone();
             self.tls_backlog.push(async move {
+                let header =
+                    read_proxy_header(&mut stream, &source, proxy_protocol.as_deref()).await?;
                 let mut buf = smallvec::SmallVec::with_capacity(
                     preview_configuration.max_preview_bytes.get(),
                 );
@@ -500,10 +554,14 @@ impl<D: TlsDriver> futures::Stream for AcceptedStream<(Preview, Connection<D>),
                 let mut stream = RewindStream::new(stream);
                 stream.rewind(&buf);
                 let preview = Preview::new(buf);
+                let tls_provider = with_proxy_source(tls_provider, header.as_ref());
                 let mut stream = UpgradableStream::<_, D>::new_server_preview(stream, tls_provider);
                 if ignore_missing_tls_close_notify {
                     stream.ignore_missing_close_notify();
                 }
+                if let Some(header) = header {
+                    stream.set_proxy_header(header);
+                }
 
                 Ok((preview, stream))
             })
@@ -516,6 +574,96 @@ impl<D: TlsDriver> futures::Stream for AcceptedStream<(Preview, Connection<D>),
     }
 }
 
+/// Read the PROXY protocol header from a newly-accepted connection, if
+/// configured and the connection is from a trusted source.
+async fn read_proxy_header(
+    stream: &mut BaseStream,
+    source: &ResolvedTarget,
+    configuration: Option<&ProxyProtocolConfiguration>,
+) -> Result<Option<ProxyProtocolHeader>, ConnectionError> {
+    let Some(configuration) = configuration.filter(|c| c.is_trusted(source)) else {
+        return Ok(None);
+    };
+    let header = tokio::time::timeout(configuration.timeout, proxy_protocol::read_header(stream))
+        .await
+        .map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "Timed out waiting for PROXY protocol header",
+            )
+        })??;
+    Ok(Some(header))
+}
+
+/// Report the PROXY protocol header's source address to the TLS parameter
+/// lookup in place of the proxy's address.
+fn with_proxy_source(
+    provider: Option<TlsServerParameterProvider>,
+    header: Option<&ProxyProtocolHeader>,
+) -> Option<TlsServerParameterProvider> {
+    let provider = provider?;
+    let Some(source) = header.and_then(|header| header.source) else {
+        return Some(provider);
+    };
+    Some(TlsServerParameterProvider::with_lookup(
+        move |name, stream| provider.lookup(name, &ProxiedStreamMetadata::new(stream, source)),
+    ))
+}
+
+/// The metadata of a stream from a proxy, reporting the original client's
+/// address as the remote address.
+struct ProxiedStreamMetadata {
+    source: SocketAddr,
+    local_address: std::io::Result<ResolvedTarget>,
+    #[cfg(all(unix, feature = "tokio"))]
+    peer_cred: std::io::Result<tokio::net::unix::UCred>,
+    transport: Transport,
+}
+
+impl ProxiedStreamMetadata {
+    fn new(stream: &dyn StreamMetadata, source: SocketAddr) -> Self {
+        Self {
+            source,
+            local_address: stream.local_address(),
+            #[cfg(all(unix, feature = "tokio"))]
+            peer_cred: stream.peer_cred(),
+            transport: stream.transport(),
+        }
+    }
+}
+
+fn clone_result<T: Clone>(result: &std::io::Result<T>) -> std::io::Result<T> {
+    match result {
+        Ok(value) => Ok(value.clone()),
+        Err(e) => Err(std::io::Error::new(e.kind(), e.to_string())),
+    }
+}
+
+impl LocalAddress for ProxiedStreamMetadata {
+    fn local_address(&self) -> std::io::Result<ResolvedTarget> {
+        clone_result(&self.local_address)
+    }
+}
+
+impl RemoteAddress for ProxiedStreamMetadata {
+    fn remote_address(&self) -> std::io::Result<ResolvedTarget> {
+        Ok(ResolvedTarget::SocketAddr(self.source))
+    }
+}
+
+impl PeerCred for ProxiedStreamMetadata {
+    #[cfg(all(unix, feature = "tokio"))]
+    fn peer_cred(&self) -> std::io::Result<tokio::net::unix::UCred> {
+        clone_result(&self.peer_cred)
+    }
+}
+
+impl StreamMetadata for ProxiedStreamMetadata {
+    fn transport(&self) -> Transport {
+        self.transport
+    }
+}
+
 struct TlsAcceptBacklog<C> {
     capacity: usize,
     #[allow(clippy::type_complexity)]
@@ -557,7 +705,8 @@ impl<C> TlsAcceptBacklog<C> {
 mod tests {
     use super::*;
     use crate::{
-        Connector, OpensslDriver, RustlsDriver, Target, TlsParameters, TlsServerParameters,
+        Connector, OpensslDriver, RemoteAddress, RustlsDriver, Target, TlsParameters,
+        TlsServerParameters,
     };
     use std::net::*;
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -618,4 +767,193 @@ mod tests {
     async fn test_acceptor_new_tcp_previewing_rustls() -> Result<(), ConnectionError> {
         test_acceptor_new_tcp_previewing::<RustlsDriver>().await
     }
+
+    async fn test_acceptor_memory_previewing<D: TlsDriver>(
+        name: &'static str,
+    ) -> Result<(), ConnectionError> {
+        use crate::{StreamMetadata, Transport};
+
+        let acceptor = Acceptor::new_tls_previewing(
+            ResolvedTarget::Memory(name.into()),
+            PreviewConfiguration::default(),
+            TlsServerParameterProvider::new(TlsServerParameters::new_with_certificate(
+                crate::test_keys::SERVER_KEY.clone_key(),
+            )),
+        );
+
+        let mut conns = acceptor.bind_explicit::<D>().await?;
+        assert_eq!(conns.local_address()?, ResolvedTarget::Memory(name.into()));
+
+        tokio::task::spawn(async move {
+            let mut conn = Connector::new(Target::new_memory(name))?.connect().await?;
+            conn.write_all(b"HELLO WORLD").await
+        });
+
+        let (preview, mut conn) = conns.next().await.unwrap()?;
+        assert_eq!(preview, b"HELLO WO");
+        assert_eq!(conn.transport(), Transport::Memory);
+        assert_eq!(conn.remote_address()?, ResolvedTarget::Memory(name.into()));
+        let mut string = String::new();
+        conn.read_to_string(&mut string).await?;
+        assert_eq!(string, "HELLO WORLD");
+
+        tokio::task::spawn(async move {
+            let target = Target::new_memory_tls(name, TlsParameters::insecure());
+            let mut conn = Connector::new(target)?.connect().await?;
+            conn.write_all(b"HELLO WORLD").await?;
+            conn.shutdown().await?;
+            // Dropping an in-memory stream closes both directions, so keep it
+            // open while the server finishes the handshake.
+            conn.read_to_end(&mut vec![]).await
+        });
+
+        let (preview, conn) = conns.next().await.unwrap()?;
+        assert!(matches!(preview.as_ref(), [0x16, 3, 1, ..]));
+        let (preview, mut conn) = conn
+            .secure_upgrade_preview(PreviewConfiguration::default())
+            .await?;
+        assert_eq!(preview, b"HELLO WO");
+        assert_eq!(conn.transport(), Transport::Memory);
+        assert_eq!(conn.remote_address()?, ResolvedTarget::Memory(name.into()));
+
+        let mut string = String::new();
+        conn.read_to_string(&mut string).await?;
+        assert_eq!(string, "HELLO WORLD");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_acceptor_memory_previewing_openssl() -> Result<(), ConnectionError> {
+        test_acceptor_memory_previewing::<OpensslDriver>("previewing_openssl").await
+    }
+
+    #[tokio::test]
+    async fn test_acceptor_memory_previewing_rustls() -> Result<(), ConnectionError> {
+        test_acceptor_memory_previewing::<RustlsDriver>("previewing_rustls").await
+    }
+
+    /// Connect and send `header` before optionally upgrading to TLS and
+    /// writing `data`.
+    async fn send_proxied<D: TlsDriver>(
+        addr: ResolvedTarget,
+        header: Vec<u8>,
+        tls: bool,
+        data: &'static [u8],
+    ) -> Result<(), ConnectionError> {
+        let mut stream = addr.connect().await?;
+        stream.write_all(&header).await?;
+        let params = D::init_client(&TlsParameters::insecure(), None)?;
+        let mut stream = UpgradableStream::<_, D>::new_client(stream, Some(params));
+        if tls {
+            stream = stream.secure_upgrade().await?;
+        }
+        stream.write_all(data).await?;
+        stream.shutdown().await?;
+        Ok(())
+    }
+
+    async fn test_acceptor_proxy_protocol<D: TlsDriver>() -> Result<(), ConnectionError> {
+        const V1: &[u8] = b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\n";
+        let trusted = || ProxyProtocolConfiguration::new(["127.0.0.0/8".parse().unwrap()]);
+        let client = "192.0.2.1:56324".parse::<SocketAddr>().unwrap();
+
+        // Plaintext, with the header read from a trusted proxy.
+        let mut conns = Acceptor::new_tcp(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+            .with_proxy_protocol(trusted())
+            .bind_explicit::<D>()
+            .await?;
+        let addr = conns.local_address()?;
+        tokio::task::spawn(send_proxied::<D>(addr, V1.to_vec(), false, b"HELLO"));
+        let mut conn = conns.next().await.unwrap()?;
+        assert_eq!(conn.remote_address()?, ResolvedTarget::SocketAddr(client));
+        assert_eq!(
+            conn.proxy_header().unwrap().version,
+            crate::ProxyProtocolVersion::V1
+        );
+        let mut string = String::new();
+        conn.read_to_string(&mut string).await?;
+        assert_eq!(string, "HELLO");
+
+        // A malformed header drops the connection.
+        let addr = conns.local_address()?;
+        tokio::task::spawn(send_proxied::<D>(addr, b"GARBAGE\r\n".to_vec(), false, b""));
+        assert!(conns.next().await.unwrap().is_err());
+
+        // TLS, with a v2 header.
+        let mut v2 = b"\r\n\r\n\0\r\nQUIT\n\x21\x11\x00\x0c".to_vec();
+        v2.extend([192, 0, 2, 1, 198, 51, 100, 1, 0xdc, 0x04, 0x01, 0xbb]);
+        // The TLS parameter lookup sees the original client's address.
+        let looked_up = Arc::new(std::sync::Mutex::new(None));
+        let params = Arc::new(TlsServerParameters::new_with_certificate(
+            crate::test_keys::SERVER_KEY.clone_key(),
+        ));
+        let mut conns = Acceptor::new_tcp_tls(
+            SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+            TlsServerParameterProvider::with_lookup({
+                let looked_up = looked_up.clone();
+                move |_, stream| {
+                    *looked_up.lock().unwrap() = stream.remote_address().ok();
+                    params.clone()
+                }
+            }),
+        )
+        .with_proxy_protocol(trusted())
+        .bind_explicit::<D>()
+        .await?;
+        let addr = conns.local_address()?;
+        tokio::task::spawn(send_proxied::<D>(addr, v2, true, b"HELLO"));
+        let mut conn = conns.next().await.unwrap()?;
+        assert!(conn.handshake().is_some());
+        assert_eq!(conn.remote_address()?, ResolvedTarget::SocketAddr(client));
+        assert_eq!(
+            *looked_up.lock().unwrap(),
+            Some(ResolvedTarget::SocketAddr(client))
+        );
+        let mut string = String::new();
+        conn.read_to_string(&mut string).await?;
+        assert_eq!(string, "HELLO");
+
+        // Previewing, with the header read before the preview.
+        let mut conns = Acceptor::new_previewing(
+            ResolvedTarget::SocketAddr(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))),
+            PreviewConfiguration::default(),
+        )
+        .with_proxy_protocol(trusted())
+        .bind_explicit::<D>()
+        .await?;
+        let addr = conns.local_address()?;
+        tokio::task::spawn(send_proxied::<D>(addr, V1.to_vec(), false, b"HELLO WORLD"));
+        let (preview, conn) = conns.next().await.unwrap()?;
+        assert_eq!(preview, b"HELLO WO");
+        assert_eq!(conn.remote_address()?, ResolvedTarget::SocketAddr(client));
+
+        // Headers are not read from untrusted sources.
+        let mut conns = Acceptor::new_tcp(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+            .with_proxy_protocol(ProxyProtocolConfiguration::new(["10.0.0.0/8"
+                .parse()
+                .unwrap()]))
+            .bind_explicit::<D>()
+            .await?;
+        let addr = conns.local_address()?;
+        tokio::task::spawn(send_proxied::<D>(addr, V1.to_vec(), false, b"HELLO"));
+        let mut conn = conns.next().await.unwrap()?;
+        assert!(conn.proxy_header().is_none());
+        assert_ne!(conn.remote_address()?, ResolvedTarget::SocketAddr(client));
+        let mut string = String::new();
+        conn.read_to_string(&mut string).await?;
+        assert!(string.starts_with("PROXY TCP4") && string.ends_with("HELLO"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_acceptor_proxy_protocol_openssl() -> Result<(), ConnectionError> {
+        test_acceptor_proxy_protocol::<OpensslDriver>().await
+    }
+
+    #[tokio::test]
+    async fn test_acceptor_proxy_protocol_rustls() -> Result<(), ConnectionError> {
+        test_acceptor_proxy_protocol::<RustlsDriver>().await
+    }
 }
@@ -1,2 +1,6 @@
 mod acceptor;
+#[cfg(feature = "pem")]
+mod reload;
 pub use acceptor::Acceptor;
+#[cfg(feature = "pem")]
+pub use reload::{TlsKeyReloader, TlsReloadError, TlsReloadEvent};
@@ -0,0 +1,235 @@
+use crate::{Ssl, SslError, TlsDriver, TlsKey, TlsServerParameterProvider, TlsServerParameters};
+use std::{
+    future::Future,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, SystemTime},
+};
+
+/// An error reloading the server's key and certificate chain.
+#[derive(Debug, derive_more::Display, derive_more::Error)]
+pub enum TlsReloadError {
+    #[display("Failed to read {}: {_1}", _0.display())]
+    Read(
+        #[error(not(source))] PathBuf,
+        #[error(source)] std::io::Error,
+    ),
+    #[display("Invalid PEM key or certificate: {_0}")]
+    Pem(std::io::Error),
+    #[display("Invalid key or certificate: {_0}")]
+    Invalid(SslError),
+}
+
+/// An event reported after each reload attempt.
+#[derive(Debug)]
+pub enum TlsReloadEvent<'a> {
+    /// The new key and certificate chain are being served.
+    Reloaded,
+    /// The new key or certificate chain could not be loaded, and the previous
+    /// ones are still being served.
+    Failed(&'a TlsReloadError),
+}
+
+type MakeParamsFn = dyn Fn(TlsKey) -> TlsServerParameters + Send + Sync + 'static;
+type OnReloadFn = dyn Fn(&TlsReloadEvent) + Send + Sync + 'static;
+
+/// The modification time and length of a file, used to detect changes.
+type FileStamp = Option<(SystemTime, u64)>;
+
+/// Serves a PEM-encoded key and certificate chain from files, reloading them
+/// when the files change or when [`TlsKeyReloader::reload`] is called.
+///
+/// A new key pair is validated before it replaces the current one. If it is
+/// invalid, the previous key pair continues to be served. Clones share the
+/// same state, so a clone can be kept as a handle to trigger reloads.
+///
+/// The key pair is validated with the TLS driver `D`, which should be the
+/// driver the acceptor is bound with.
+///
+/// ```no_run
+/// # use gel_stream::*;
+/// # use std::time::Duration;
+/// # async fn f() -> Result<(), TlsReloadError> {
+/// let reloader = TlsKeyReloader::new("server.key.pem", "server.cert.pem")?;
+/// reloader.set_on_reload(|event| eprintln!("TLS reload: {event:?}"));
+/// tokio::spawn(reloader.watch(Duration::from_secs(10)));
+/// let acceptor = Acceptor::new_tcp_tls(
+///     "127.0.0.1:5656".parse().unwrap(),
+///     reloader.provider(),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[allow(private_bounds)]
+pub struct TlsKeyReloader<D: TlsDriver = Ssl> {
+    inner: Arc<Inner>,
+    driver: PhantomData<D>,
+}
+
+struct Inner {
+    key_path: PathBuf,
+    cert_path: PathBuf,
+    make_params: Box<MakeParamsFn>,
+    current: RwLock<Arc<TlsServerParameters>>,
+    stamps: Mutex<(FileStamp, FileStamp)>,
+    on_reload: RwLock<Option<Box<OnReloadFn>>>,
+}
+
+impl<D: TlsDriver> Clone for TlsKeyReloader<D> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            driver: PhantomData,
+        }
+    }
+}
+
+impl<D: TlsDriver> std::fmt::Debug for TlsKeyReloader<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsKeyReloader")
+            .field("key_path", &self.inner.key_path)
+            .field("cert_path", &self.inner.cert_path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TlsKeyReloader<Ssl> {
+    /// Load the key and certificate chain, using the default server
+    /// parameters.
+    pub fn new(
+        key_path: impl AsRef<Path>,
+        cert_path: impl AsRef<Path>,
+    ) -> Result<Self, TlsReloadError> {
+        Self::new_explicit(key_path, cert_path)
+    }
+
+    /// Load the key and certificate chain, building the server parameters
+    /// for each loaded key with `make_params`.
+    pub fn with_parameters(
+        key_path: impl AsRef<Path>,
+        cert_path: impl AsRef<Path>,
+        make_params: impl Fn(TlsKey) -> TlsServerParameters + Send + Sync + 'static,
+    ) -> Result<Self, TlsReloadError> {
+        Self::with_parameters_explicit(key_path, cert_path, make_params)
+    }
+}
+
+#[allow(private_bounds)]
+impl<D: TlsDriver> TlsKeyReloader<D> {
+    /// Load the key and certificate chain with the given TLS driver, using
+    /// the default server parameters.
+    pub fn new_explicit(
+        key_path: impl AsRef<Path>,
+        cert_path: impl AsRef<Path>,
+    ) -> Result<Self, TlsReloadError> {
+        Self::with_parameters_explicit(
+            key_path,
+            cert_path,
+            TlsServerParameters::new_with_certificate,
+        )
+    }
+
+    /// Load the key and certificate chain with the given TLS driver,
+    /// building the server parameters for each loaded key with
+    /// `make_params`.
+    pub fn with_parameters_explicit(
+        key_path: impl AsRef<Path>,
+        cert_path: impl AsRef<Path>,
+        make_params: impl Fn(TlsKey) -> TlsServerParameters + Send + Sync + 'static,
+    ) -> Result<Self, TlsReloadError> {
+        let key_path = key_path.as_ref().to_path_buf();
+        let cert_path = cert_path.as_ref().to_path_buf();
+        let stamps = (file_stamp(&key_path), file_stamp(&cert_path));
+        let params = load::<D>(&key_path, &cert_path, &make_params)?;
+        Ok(Self {
+            inner: Arc::new(Inner {
+                key_path,
+                cert_path,
+                make_params: Box::new(make_params),
+                current: RwLock::new(Arc::new(params)),
+                stamps: Mutex::new(stamps),
+                on_reload: RwLock::new(None),
+            }),
+            driver: PhantomData,
+        })
+    }
+
+    /// Call `f` after each reload attempt.
+    pub fn set_on_reload(&self, f: impl Fn(&TlsReloadEvent) + Send + Sync + 'static) {
+        *self.inner.on_reload.write().unwrap() = Some(Box::new(f));
+    }
+
+    /// A provider that always returns the current server parameters.
+    pub fn provider(&self) -> TlsServerParameterProvider {
+        let inner = self.inner.clone();
+        TlsServerParameterProvider::with_lookup(move |_, _| inner.current.read().unwrap().clone())
+    }
+
+    /// The server parameters currently being served.
+    pub fn current(&self) -> Arc<TlsServerParameters> {
+        self.inner.current.read().unwrap().clone()
+    }
+
+    /// Reload the key and certificate chain from their files now.
+    pub fn reload(&self) -> Result<(), TlsReloadError> {
+        let inner = &self.inner;
+        *inner.stamps.lock().unwrap() = (file_stamp(&inner.key_path), file_stamp(&inner.cert_path));
+        let res = load::<D>(&inner.key_path, &inner.cert_path, &inner.make_params)
+            .map(|params| *inner.current.write().unwrap() = Arc::new(params));
+        if let Some(on_reload) = &*inner.on_reload.read().unwrap() {
+            match &res {
+                Ok(()) => on_reload(&TlsReloadEvent::Reloaded),
+                Err(e) => on_reload(&TlsReloadEvent::Failed(e)),
+            }
+        }
+        res
+    }
+
+    /// Check the files for changes every `interval`, reloading them when
+    /// their modification time or size changes. The returned future never
+    /// completes: spawn it and abort the task to stop watching.
+    pub fn watch(&self, interval: Duration) -> impl Future<Output = ()> + Send + 'static {
+        let this = self.clone();
+        async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if this.changed() {
+                    // Failures are reported to the callback, and the
+                    // previous key pair continues to be served.
+                    _ = this.reload();
+                }
+            }
+        }
+    }
+
+    fn changed(&self) -> bool {
+        let inner = &self.inner;
+        let stamps = (file_stamp(&inner.key_path), file_stamp(&inner.cert_path));
+        *inner.stamps.lock().unwrap() != stamps
+    }
+}
+
+fn file_stamp(path: &Path) -> FileStamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+fn load<D: TlsDriver>(
+    key_path: &Path,
+    cert_path: &Path,
+    make_params: &MakeParamsFn,
+) -> Result<TlsServerParameters, TlsReloadError> {
+    let read =
+        |path: &Path| std::fs::read(path).map_err(|e| TlsReloadError::Read(path.to_path_buf(), e));
+    let key = read(key_path)?;
+    let cert = read(cert_path)?;
+    let key = TlsKey::new_pem(&key, &cert).map_err(TlsReloadError::Pem)?;
+    let params = make_params(key);
+    // Building the server configuration checks that the key matches the
+    // certificate.
+    D::init_server(&params).map_err(TlsReloadError::Invalid)?;
+    Ok(params)
+}
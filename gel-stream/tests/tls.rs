@@ -402,6 +402,82 @@ tls_test! {
         Ok(())
     }
 
+    /// The certificate is pinned by its fingerprint or its SPKI fingerprint,
+    /// so the connection should succeed without a CA or matching hostname.
+    #[tokio::test]
+    #[ntest::timeout(30_000)]
+    async fn test_target_tcp_tls_pinned_ok<C: TlsDriver, S: TlsDriver>() -> Result<(), ConnectionError> {
+        let cert = load_test_cert();
+        let pins = [sha256(&cert), sha256(parse_cert(&cert).tbs_certificate.subject_pki.raw)];
+        for pin in pins {
+            let (addr, accept_task) =
+                spawn_tls_server::<S>(None, TlsAlpn::default(), None, TlsClientCertVerify::Ignore).await?;
+
+            let connect_task = tokio::spawn(async move {
+                let target = Target::new_resolved_tls(
+                    addr, // Raw IP
+                    TlsParameters {
+                        server_cert_verify: TlsServerCertVerify::Pinned(vec![[0; 32], pin]),
+                        ..Default::default()
+                    },
+                );
+                let mut stm = Connector::<C>::new_explicit(target).unwrap().connect().await?;
+                stm.write_all(b"Hello, world!").await?;
+                stm.shutdown().await?;
+                Ok::<_, ConnectionError>(())
+            });
+
+            accept_task.await.unwrap().unwrap();
+            connect_task.await.unwrap().unwrap();
+        }
+
+        Ok(())
+    }
+
+    /// The certificate doesn't match the pin, even though it is signed by a
+    /// trusted CA, so the connection should fail.
+    #[tokio::test]
+    #[ntest::timeout(30_000)]
+    async fn test_target_tcp_tls_pinned_mismatch<C: TlsDriver, S: TlsDriver>() -> Result<(), ConnectionError> {
+        let (addr, accept_task) = spawn_tls_server::<S>(
+            Some("localhost"),
+            TlsAlpn::default(),
+            None,
+            TlsClientCertVerify::Ignore,
+        )
+        .await?;
+
+        let connect_task = tokio::spawn(async move {
+            let target = Target::new_tcp_tls(
+                ("localhost", addr.tcp().unwrap().port()),
+                TlsParameters {
+                    root_cert: TlsCert::Custom(vec![load_test_ca()]),
+                    server_cert_verify: TlsServerCertVerify::Pinned(vec![[0; 32]]),
+                    ..Default::default()
+                },
+            );
+            let stm = Connector::<C>::new_explicit(target).unwrap().connect().await;
+            let Err(ConnectionError::SslError(ssl)) = &stm else {
+                panic!("{stm:?}");
+            };
+            assert_eq!(ssl.common_error(), Some(CommonError::CertificatePinMismatch));
+            let message = ssl.to_string();
+            assert!(message.contains(&format_fingerprint(&[0; 32])), "{message}");
+            assert!(
+                message.contains(&format_fingerprint(&sha256(&load_test_cert()))),
+                "{message}"
+            );
+            Ok::<_, std::io::Error>(())
+        });
+
+        connect_task.await.unwrap().unwrap();
+        // The server fails either during the handshake or when the client
+        // drops the connection, depending on the client's driver.
+        _ = accept_task.await;
+
+        Ok(())
+    }
+
     #[tokio::test]
     #[ntest::timeout(30_000)]
     async fn test_target_server_unclean_shutdown<C: TlsDriver, S: TlsDriver>() -> Result<(), ConnectionError> {
@@ -805,6 +881,85 @@ tls_test! {
 
         Ok(())
     }
+
+    #[tokio::test]
+    #[ntest::timeout(30_000)]
+    async fn test_cert_reload<C: TlsDriver, S: TlsDriver>() -> Result<(), ConnectionError> {
+        async fn served_subject<C: TlsDriver>(addr: &ResolvedTarget) -> String {
+            let connector = Connector::<C>::new_explicit(Target::new_resolved_tls(
+                addr.clone(),
+                TlsParameters::insecure(),
+            ))
+            .unwrap();
+            let mut stm = connector.connect().await.unwrap();
+            let cert = stm.handshake().unwrap().cert.clone().unwrap();
+            stm.read_u8().await.unwrap();
+            parse_cert(&cert).subject().to_string()
+        }
+
+        let dir = tempfile::tempdir()?;
+        let key_path = dir.path().join("server.key.pem");
+        let cert_path = dir.path().join("server.cert.pem");
+        // Include the CA so that the chain is sent too.
+        std::fs::write(&key_path, test_keys::raw::SERVER_KEY)?;
+        std::fs::write(
+            &cert_path,
+            format!("{}{}", test_keys::raw::SERVER_CERT, test_keys::raw::CA_CERT),
+        )?;
+
+        let reloader = TlsKeyReloader::<S>::new_explicit(&key_path, &cert_path).unwrap();
+        assert_eq!(reloader.current().server_certificate.chain().len(), 1);
+        let events = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        reloader.set_on_reload({
+            let events = events.clone();
+            move |event| {
+                events
+                    .lock()
+                    .unwrap()
+                    .push(matches!(event, TlsReloadEvent::Reloaded))
+            }
+        });
+
+        let mut acceptor = Acceptor::new_tcp_tls(
+            SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0),
+            reloader.provider(),
+        )
+        .bind_explicit::<S>()
+        .await?;
+        let addr = acceptor.local_address()?;
+        tokio::task::spawn(async move {
+            while let Some(stm) = acceptor.next().await {
+                let mut stm = stm.unwrap();
+                stm.write_all(b"x").await.unwrap();
+                stm.shutdown().await.unwrap();
+            }
+        });
+        assert!(served_subject::<C>(&addr).await.contains("CN=localhost,"));
+
+        // A certificate that doesn't match the key is rejected, and the
+        // previous certificate is still served.
+        std::fs::write(&cert_path, test_keys::raw::SERVER_ALT_CERT)?;
+        let err = reloader.reload().unwrap_err();
+        assert!(matches!(err, TlsReloadError::Invalid(_)), "{err:?}");
+        assert!(served_subject::<C>(&addr).await.contains("CN=localhost,"));
+
+        std::fs::write(&key_path, test_keys::raw::SERVER_ALT_KEY)?;
+        reloader.reload().unwrap();
+        assert!(served_subject::<C>(&addr).await.contains("CN=localhost-alt,"));
+        assert_eq!(*events.lock().unwrap(), [false, true]);
+
+        // The watcher picks up changes to the files.
+        let watcher = tokio::task::spawn(reloader.watch(std::time::Duration::from_millis(10)));
+        std::fs::write(&key_path, test_keys::raw::SERVER_KEY)?;
+        std::fs::write(&cert_path, test_keys::raw::SERVER_CERT)?;
+        while !served_subject::<C>(&addr).await.contains("CN=localhost,") {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        watcher.abort();
+        assert_eq!(events.lock().unwrap().last(), Some(&true));
+
+        Ok(())
+    }
 }
 
 macro_rules! tls_client_test (
@@ -951,6 +1106,13 @@ tls_client_test! {
     }
 }
 
+fn sha256(data: &[u8]) -> [u8; 32] {
+    ring::digest::digest(&ring::digest::SHA256, data)
+        .as_ref()
+        .try_into()
+        .unwrap()
+}
+
 fn parse_cert<'a>(
     cert: &'a rustls_pki_types::CertificateDer<'a>,
 ) -> x509_parser::prelude::X509Certificate<'a> {
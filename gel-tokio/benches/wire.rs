@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use bytes::{Bytes, BytesMut};
+use gel_protocol::client_message::{ClientMessage, Execute1};
+use gel_protocol::common::{Capabilities, Cardinality, CompilationFlags, RawTypedesc, State};
+use gel_protocol::common::{InputLanguage, IoFormat};
+use gel_protocol::encoding::{Input, Output};
+use gel_protocol::features::ProtocolVersion;
+use gel_protocol::model::Uuid;
+use gel_protocol::server_message::{CommandComplete1, CommandDataDescription1, Data};
+use gel_protocol::server_message::{ReadyForCommand, ServerMessage, TransactionState};
+use gel_tokio::raw::zero_copy;
+
+const ROWS: &[usize] = &[1, 100, 10_000];
+
+/// A query response: descriptors, `rows` data messages, completion.
+fn response(rows: usize) -> Vec<Bytes> {
+    let proto = ProtocolVersion::current();
+    let typedesc = Bytes::from(vec![0x42; 256]);
+    let mut messages = vec![ServerMessage::CommandDataDescription1(
+        CommandDataDescription1 {
+            annotations: HashMap::new(),
+            capabilities: Capabilities::empty(),
+            result_cardinality: Cardinality::Many,
+            input: RawTypedesc {
+                proto: proto.clone(),
+                id: Uuid::from_u128(1),
+                data: typedesc.clone(),
+            },
+            output: RawTypedesc {
+                proto: proto.clone(),
+                id: Uuid::from_u128(2),
+                data: typedesc,
+            },
+        },
+    )];
+    for _ in 0..rows {
+        messages.push(ServerMessage::Data(Data {
+            data: vec![Bytes::from(vec![0x17; 64])],
+        }));
+    }
+    messages.push(ServerMessage::CommandComplete1(CommandComplete1 {
+        annotations: HashMap::new(),
+        capabilities: Capabilities::empty(),
+        status: "SELECT".into(),
+        state: Some(State {
+            typedesc_id: Uuid::from_u128(3),
+            data: Bytes::from(vec![0x01; 128]),
+        }),
+    }));
+    messages.push(ServerMessage::ReadyForCommand(ReadyForCommand {
+        annotations: HashMap::new(),
+        transaction_state: TransactionState::NotInTransaction,
+    }));
+    messages
+        .iter()
+        .map(|msg| {
+            let mut buf = BytesMut::new();
+            msg.encode(&mut Output::new(&proto, &mut buf)).unwrap();
+            buf.freeze()
+        })
+        .collect()
+}
+
+fn execute() -> ClientMessage {
+    ClientMessage::Execute1(Execute1 {
+        annotations: None,
+        allowed_capabilities: Capabilities::ALL,
+        compilation_flags: CompilationFlags::INJECT_OUTPUT_OBJECT_IDS,
+        implicit_limit: None,
+        output_format: IoFormat::Binary,
+        expected_cardinality: Cardinality::Many,
+        command_text: "select User { name, email } filter .id = <uuid>$0".into(),
+        state: State {
+            typedesc_id: Uuid::from_u128(3),
+            data: Bytes::from(vec![0x01; 128]),
+        },
+        input_typedesc_id: Uuid::from_u128(1),
+        output_typedesc_id: Uuid::from_u128(2),
+        arguments: Bytes::from(vec![0x00; 32]),
+        input_language: InputLanguage::EdgeQL,
+    })
+}
+
+#[divan::bench(args = ROWS)]
+fn decode_owned(b: divan::Bencher, rows: usize) {
+    let proto = ProtocolVersion::current();
+    let frames = response(rows);
+    b.bench_local(|| {
+        for frame in &frames {
+            divan::black_box(
+                ServerMessage::decode(&mut Input::new(proto.clone(), frame.clone())).unwrap(),
+            );
+        }
+    });
+}
+
+#[divan::bench(args = ROWS)]
+fn decode_zero_copy(b: divan::Bencher, rows: usize) {
+    let proto = ProtocolVersion::current();
+    let frames = response(rows);
+    b.bench_local(|| {
+        for frame in &frames {
+            divan::black_box(zero_copy::decode_message(&proto, frame.clone()).unwrap());
+        }
+    });
+}
+
+#[divan::bench]
+fn encode_owned(b: divan::Bencher) {
+    let proto = ProtocolVersion::current();
+    let messages = [execute(), ClientMessage::Sync];
+    let mut buf = BytesMut::with_capacity(8192);
+    b.bench_local(|| {
+        buf.truncate(0);
+        for msg in &messages {
+            msg.encode(&mut Output::new(&proto, &mut buf)).unwrap();
+        }
+    });
+}
+
+#[divan::bench]
+fn encode_zero_copy(b: divan::Bencher) {
+    let proto = ProtocolVersion::current();
+    let messages = [execute(), ClientMessage::Sync];
+    let mut buf = BytesMut::with_capacity(8192);
+    b.bench_local(|| {
+        buf.truncate(0);
+        for msg in &messages {
+            zero_copy::encode_message(&mut buf, &proto, msg).unwrap();
+        }
+    });
+}
+
+fn main() {
+    // Run registered benchmarks.
+    divan::main();
+}
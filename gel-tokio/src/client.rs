@@ -19,6 +19,24 @@ use crate::state::{AliasesModifier, ConfigModifier, Fn, GlobalsModifier};
 use crate::transaction;
 use crate::ResultVerbose;
 
+#[cfg(feature = "arrow")]
+use bytes::BytesMut;
+#[cfg(feature = "arrow")]
+use futures_util::Stream;
+#[cfg(feature = "arrow")]
+use gel_protocol::arrow::{RecordBatch, RecordBatchDecoder};
+#[cfg(feature = "arrow")]
+use gel_protocol::common::{CompilationOptions, InputLanguage};
+#[cfg(feature = "arrow")]
+use gel_protocol::query_arg::Encoder;
+#[cfg(feature = "arrow")]
+use gel_protocol::value::Value;
+#[cfg(feature = "arrow")]
+use tokio::sync::mpsc;
+
+#[cfg(feature = "arrow")]
+use crate::errors::ProtocolEncodingError;
+
 /// Gel database client.
 ///
 /// Internally it contains a connection pool.
@@ -58,6 +76,26 @@ impl Client {
         Ok(())
     }
 
+    /// Whether a failed query can be safely retried.
+    fn allow_retry(&self, e: &Error) -> bool {
+        // Without these capabilities the server could not have modified
+        // anything, so running the query again is always safe
+        if !self
+            .options
+            .allow_capabilities
+            .intersects(Capabilities::MODIFICATIONS | Capabilities::DDL)
+        {
+            return true;
+        }
+        match e.get::<QueryCapabilities>() {
+            // Error from a weird source, or just a bug
+            // Let's keep on the safe side
+            None => false,
+            Some(QueryCapabilities::Unparsed) => true,
+            Some(QueryCapabilities::Parsed(c)) => c.is_empty(),
+        }
+    }
+
     /// Query with retry.
     async fn query_helper<R, A>(
         &self,
@@ -76,7 +114,7 @@ impl Client {
 
             let conn = conn.inner();
             let state = &self.options.state;
-            let caps = Capabilities::MODIFICATIONS | Capabilities::DDL;
+            let caps = self.options.allow_capabilities;
             match conn
                 .query(
                     query.as_ref(),
@@ -91,14 +129,7 @@ impl Client {
             {
                 Ok(resp) => return Ok(resp),
                 Err(e) => {
-                    let allow_retry = match e.get::<QueryCapabilities>() {
-                        // Error from a weird source, or just a bug
-                        // Let's keep on the safe side
-                        None => false,
-                        Some(QueryCapabilities::Unparsed) => true,
-                        Some(QueryCapabilities::Parsed(c)) => c.is_empty(),
-                    };
-                    if allow_retry && e.has_tag(SHOULD_RETRY) {
+                    if self.allow_retry(&e) && e.has_tag(SHOULD_RETRY) {
                         let rule = self.options.retry.get_rule(&e);
                         iteration += 1;
                         if iteration < rule.attempts {
@@ -323,6 +354,141 @@ impl Client {
             .ok_or_else(|| NoDataError::with_message("query row returned zero results"))
     }
 
+    /// Execute a query and return the results as a stream of Arrow record
+    /// batches of at most `batch_size` rows.
+    ///
+    /// Objects, tuples and SQL rows are split into columns, other types
+    /// produce a single `value` column. See [`gel_protocol::arrow`] for the
+    /// type mapping.
+    ///
+    /// ```rust,ignore
+    /// let mut batches = client
+    ///     .query_arrow("select User { name, age }", &(), 1024)
+    ///     .await?;
+    /// while let Some(batch) = batches.next().await {
+    ///     let batch = batch?;
+    ///     // ...
+    /// }
+    /// ```
+    ///
+    /// Unlike the other query methods this one is not retried, since part of
+    /// the result may have been consumed already. Errors occurring after the
+    /// query has started are reported as items of the stream.
+    #[cfg(feature = "arrow")]
+    pub async fn query_arrow<A>(
+        &self,
+        query: impl AsRef<str>,
+        arguments: &A,
+        batch_size: usize,
+    ) -> Result<impl Stream<Item = Result<RecordBatch, Error>> + Send + 'static, Error>
+    where
+        A: QueryArgs,
+    {
+        let mut conn = self.pool.acquire().await?;
+        let flags = CompilationOptions {
+            implicit_limit: None,
+            implicit_typenames: false,
+            implicit_typeids: false,
+            explicit_objectids: true,
+            allow_capabilities: self.options.allow_capabilities,
+            io_format: IoFormat::Binary,
+            input_language: InputLanguage::EdgeQL,
+            expected_cardinality: Cardinality::Many,
+        };
+        let query = query.as_ref().to_owned();
+        let desc = conn
+            .parse(
+                &flags,
+                &query,
+                &self.options.state,
+                &self.options.annotations,
+            )
+            .await?;
+        let inp_desc = desc.input().map_err(ProtocolEncodingError::with_source)?;
+        let mut arg_buf = BytesMut::with_capacity(8);
+        arguments.encode(&mut Encoder::new(
+            &inp_desc.as_query_arg_context(),
+            &mut arg_buf,
+        ))?;
+        let out_desc = desc.output().map_err(ProtocolEncodingError::with_source)?;
+        let mut decoder =
+            RecordBatchDecoder::new(out_desc.root_pos(), out_desc.descriptors(), batch_size)
+                .map_err(ProtocolEncodingError::with_source)?;
+
+        let (tx, rx) = mpsc::channel(1);
+        let options = self.options.clone();
+        tokio::task::spawn(async move {
+            let result = async {
+                let mut stream = conn
+                    .inner()
+                    ._execute_stream::<Value>(
+                        &flags,
+                        &query,
+                        &options.state,
+                        &options.annotations,
+                        &desc,
+                        arg_buf.freeze(),
+                    )
+                    .await?;
+                while let Some(row) = stream.next_element().await {
+                    let batch = decoder
+                        .push(row)
+                        .map_err(ProtocolEncodingError::with_source)?;
+                    if let Some(batch) = batch {
+                        if tx.send(Ok(batch)).await.is_err() {
+                            // receiver is gone, the connection is dropped as dirty
+                            return Ok(());
+                        }
+                    }
+                }
+                stream.process_complete().await?;
+                if let Some(batch) = decoder
+                    .finish()
+                    .map_err(ProtocolEncodingError::with_source)?
+                {
+                    tx.send(Ok(batch)).await.ok();
+                }
+                Ok::<_, Error>(())
+            }
+            .await;
+            if let Err(e) = result {
+                tx.send(Err(e)).await.ok();
+            }
+        });
+        Ok(futures_util::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|item| (item, rx))
+        }))
+    }
+
+    /// Execute a query on the JSON endpoint of the HTTP transport.
+    ///
+    /// Unlike [`query_json`](Client::query_json), the query takes named
+    /// arguments as a JSON object and the query is not described or encoded
+    /// by the client. Globals and config set on the client are not sent.
+    ///
+    /// Requires the client to be configured with
+    /// [`Transport::Http`](gel_dsn::gel::Transport::Http).
+    ///
+    /// ```rust,ignore
+    /// let json = client
+    ///     .query_json_http(
+    ///         "select User { name } filter .name = <str>$name",
+    ///         &serde_json::json!({"name": "Alice"}),
+    ///     )
+    ///     .await?;
+    /// ```
+    #[cfg(feature = "http")]
+    pub async fn query_json_http(
+        &self,
+        query: impl AsRef<str>,
+        variables: &serde_json::Value,
+    ) -> Result<Json, Error> {
+        let mut conn = self.pool.acquire().await?;
+        conn.inner()
+            .query_json_http(query.as_ref(), variables)
+            .await
+    }
+
     /// Execute a query and don't expect result
     ///
     /// This method can be used with both static arguments, like a tuple of
@@ -338,7 +504,7 @@ impl Client {
 
             let conn = conn.inner();
             let state = &self.options.state;
-            let caps = Capabilities::MODIFICATIONS | Capabilities::DDL;
+            let caps = self.options.allow_capabilities;
             match conn
                 .execute(
                     query.as_ref(),
@@ -351,14 +517,7 @@ impl Client {
             {
                 Ok(_) => return Ok(()),
                 Err(e) => {
-                    let allow_retry = match e.get::<QueryCapabilities>() {
-                        // Error from a weird source, or just a bug
-                        // Let's keep on the safe side
-                        None => false,
-                        Some(QueryCapabilities::Unparsed) => true,
-                        Some(QueryCapabilities::Parsed(c)) => c.is_empty(),
-                    };
-                    if allow_retry && e.has_tag(SHOULD_RETRY) {
+                    if self.allow_retry(&e) && e.has_tag(SHOULD_RETRY) {
                         let rule = self.options.retry.get_rule(&e);
                         iteration += 1;
                         if iteration < rule.attempts {
@@ -495,6 +654,7 @@ impl Client {
                 retry: self.options.retry.clone(),
                 state: self.options.state.clone(),
                 annotations: self.options.annotations.clone(),
+                allow_capabilities: self.options.allow_capabilities,
             }),
             pool: self.pool.clone(),
         }
@@ -514,11 +674,45 @@ impl Client {
                 retry: options,
                 state: self.options.state.clone(),
                 annotations: self.options.annotations.clone(),
+                allow_capabilities: self.options.allow_capabilities,
+            }),
+            pool: self.pool.clone(),
+        }
+    }
+
+    /// Returns client that only allows queries with the given capabilities.
+    ///
+    /// This method returns a "shallow copy" of the current client
+    /// with modified capabilities.
+    ///
+    /// The server rejects queries that need capabilities outside of this
+    /// set, so it can be used to limit what a client is able to do. By
+    /// default, `MODIFICATIONS` and `DDL` are allowed. Queries executed
+    /// in transactions are additionally limited to `MODIFICATIONS`.
+    ///
+    /// If neither `MODIFICATIONS` nor `DDL` are allowed, failed queries are
+    /// always considered safe to retry.
+    pub fn with_allowed_capabilities(&self, capabilities: Capabilities) -> Self {
+        Client {
+            options: Arc::new(Options {
+                transaction: self.options.transaction.clone(),
+                retry: self.options.retry.clone(),
+                state: self.options.state.clone(),
+                annotations: self.options.annotations.clone(),
+                allow_capabilities: capabilities,
             }),
             pool: self.pool.clone(),
         }
     }
 
+    /// Returns client that rejects any modifications and DDL.
+    ///
+    /// Shortcut for
+    /// [`with_allowed_capabilities(Capabilities::empty())`](Client::with_allowed_capabilities).
+    pub fn read_only(&self) -> Self {
+        self.with_allowed_capabilities(Capabilities::empty())
+    }
+
     fn with_state(&self, f: impl FnOnce(&PoolState) -> PoolState) -> Self {
         Client {
             options: Arc::new(Options {
@@ -526,6 +720,7 @@ impl Client {
                 retry: self.options.retry.clone(),
                 state: Arc::new(f(&self.options.state)),
                 annotations: self.options.annotations.clone(),
+                allow_capabilities: self.options.allow_capabilities,
             }),
             pool: self.pool.clone(),
         }
@@ -668,6 +863,7 @@ impl Client {
                 retry: self.options.retry.clone(),
                 state: self.options.state.clone(),
                 annotations,
+                allow_capabilities: self.options.allow_capabilities,
             }),
             pool: self.pool.clone(),
         })
@@ -29,6 +29,15 @@ async fn main() -> anyhow::Result<()> {
 ```
 More [examples on github](https://github.com/edgedb/edgedb-rust/tree/master/gel-tokio/examples)
 
+# HTTP Transport
+
+Where connections can't be kept open between requests (e.g. in serverless
+functions), queries can be sent over HTTP instead of a persistent connection
+by enabling the `http` feature and setting `GEL_CLIENT_TRANSPORT=http` (or
+[`Transport::Http`](dsn::Transport::Http) on the [`Builder`]). The same
+query methods work over both transports, but transactions, dumps and
+restores require the binary transport.
+
 # Nice Error Reporting
 
 We use [miette] crate for including snippets in your error reporting code.
@@ -149,7 +158,7 @@ mod transaction;
 pub mod tutorial;
 
 #[cfg(feature = "derive")]
-pub use gel_derive::{ConfigDelta, GlobalsDelta, Queryable};
+pub use gel_derive::{ConfigDelta, GlobalsDelta, Insertable, QueryArg, Queryable};
 
 pub use client::Client;
 pub use errors::Error;
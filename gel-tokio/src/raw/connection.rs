@@ -13,11 +13,10 @@ use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 use tokio::time::{sleep, timeout_at, Instant};
 
-use gel_dsn::gel::{ClientSecurity, Config};
+use gel_dsn::gel::{ClientSecurity, Config, Transport};
 use gel_auth::{handshake::{ClientAuthDrive, ClientAuthResponse}, AuthType, CredentialData};
 use gel_stream::{CommonError, ConnectionError, Connector, Target};
 use gel_protocol::client_message::{ClientHandshake, ClientMessage, SaslInitialResponse, SaslResponse};
-use gel_protocol::encoding::{Input, Output};
 use gel_protocol::features::ProtocolVersion;
 use gel_protocol::value::Value;
 use gel_protocol::server_message::{
@@ -26,15 +25,39 @@ use gel_protocol::server_message::{
 
 use crate::builder::CertCheck;
 use crate::errors::{
-    AuthenticationError, ClientConnectionEosError, ClientConnectionError,
-    ClientConnectionFailedError, ClientConnectionFailedTemporarilyError, ClientEncodingError,
+    AuthenticationError, ClientConnectionEosError, ClientError, ClientConnectionError,
+    ClientConnectionFailedError, ClientConnectionFailedTemporarilyError,
     Error, ErrorKind, IdleSessionTimeoutError, PasswordRequired,
     ProtocolEncodingError, ProtocolError,
 };
 use crate::raw::queries::Guard;
+#[cfg(feature = "zero-copy")]
+use crate::raw::zero_copy::{decode_message as decode_frame, encode_message};
 use crate::raw::{Connection, PingInterval};
 use crate::server_params::{ServerParam, ServerParams, SystemConfig};
 
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)] // the raw stream is the common case
+pub(crate) enum Stream {
+    Raw(gel_stream::RawStream),
+    #[cfg(feature = "http")]
+    Http(super::http::HttpTunnel),
+}
+
+impl Stream {
+    /// The underlying socket, for the parts of the protocol that need to
+    /// read and write concurrently.
+    pub(crate) fn raw(&mut self) -> Result<&mut gel_stream::RawStream, Error> {
+        match self {
+            Stream::Raw(stream) => Ok(stream),
+            #[cfg(feature = "http")]
+            Stream::Http(_) => Err(ClientError::with_message(
+                "operation is not supported over the HTTP transport",
+            )),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub(crate) enum Mode {
     Normal { idle_since: Instant },
@@ -46,9 +69,19 @@ impl Connection {
     pub fn is_consistent(&self) -> bool {
         matches!(self.mode, Mode::Normal { .. })
     }
+    /// Whether the connection uses a stateless transport, where each request
+    /// may be served by a different server session.
+    pub fn is_stateless(&self) -> bool {
+        !matches!(self.stream, Stream::Raw(_))
+    }
     pub async fn is_connection_reset(&mut self) -> bool {
+        let stream = match &mut self.stream {
+            Stream::Raw(stream) => stream,
+            #[cfg(feature = "http")]
+            Stream::Http(tunnel) => return tunnel.is_closed(),
+        };
         tokio::select! { biased;
-            msg = wait_message(&mut self.stream, &mut self.in_buf, &self.proto)
+            msg = wait_message(stream, &mut self.in_buf, &self.proto)
             => {
                 match msg {
                     Ok(ServerMessage::ErrorResponse(e)) => {
@@ -103,10 +136,28 @@ impl Connection {
         &mut self,
         msgs: impl IntoIterator<Item = &'x ClientMessage>,
     ) -> Result<(), Error> {
-        send_messages(&mut self.stream, &mut self.out_buf, &self.proto, msgs).await
+        match &mut self.stream {
+            Stream::Raw(stream) => {
+                send_messages(stream, &mut self.out_buf, &self.proto, msgs).await
+            }
+            #[cfg(feature = "http")]
+            Stream::Http(tunnel) => {
+                encode_messages(&mut self.out_buf, &self.proto, msgs)?;
+                let response = tunnel.round_trip(self.out_buf.split().freeze()).await?;
+                self.in_buf.extend_from_slice(&response);
+                Ok(())
+            }
+        }
     }
     pub async fn message(&mut self) -> Result<ServerMessage, Error> {
-        wait_message(&mut self.stream, &mut self.in_buf, &self.proto).await
+        match &mut self.stream {
+            Stream::Raw(stream) => wait_message(stream, &mut self.in_buf, &self.proto).await,
+            // The whole response is already buffered
+            #[cfg(feature = "http")]
+            Stream::Http(_) => {
+                wait_message(&mut tokio::io::empty(), &mut self.in_buf, &self.proto).await
+            }
+        }
     }
     pub fn get_server_param<T: ServerParam>(&self) -> Option<&T::Value> {
         self.server_params.get::<T>()
@@ -229,6 +280,9 @@ impl Connection {
         }
     }
     pub async fn terminate(mut self) -> Result<(), Error> {
+        if self.is_stateless() {
+            return Ok(());
+        }
         let _ = self.begin_request()?; // not need to cleanup after that
         self.send_messages(&[ClientMessage::Terminate]).await?;
         match self.message().await {
@@ -260,7 +314,22 @@ async fn connect(cfg: &Config, cert_check: Option<CertCheck>) -> Result<Connecti
     let warned = &mut false;
     let mut retry = 0;
     let conn = loop {
-        match connect_timeout(cfg, connect2(cfg, target.clone(), warned, cert_check.clone())).await {
+        let res = match cfg.transport {
+            Transport::Binary => {
+                connect_timeout(cfg, connect2(cfg, target.clone(), warned, cert_check.clone())).await
+            }
+            #[cfg(feature = "http")]
+            Transport::Http => {
+                connect_timeout(cfg, super::http::connect(cfg, warned, cert_check.clone())).await
+            }
+            #[cfg(not(feature = "http"))]
+            Transport::Http => {
+                return Err(ClientError::with_message(
+                    "HTTP transport requires the `http` feature of gel-tokio",
+                ));
+            }
+        };
+        match res {
             Err(e) if is_temporary(&e) => {
                 log::debug!("Temporary connection error: {e:#}");
                 if wait > start.elapsed() {
@@ -291,6 +360,7 @@ async fn connect2(
 ) -> Result<Connection, Error> {
     let mut connector = Connector::new(target.clone()).map_err(ClientConnectionError::with_source)?;
     connector.set_keepalive(cfg.tcp_keepalive.as_keepalive());
+    connector.set_proxy(cfg.proxy.clone());
     // Ignore missing close notify on Windows. This is unfortunately reasonably
     // common on that platform.
     #[cfg(windows)]
@@ -308,6 +378,7 @@ async fn connect2(
                     *warned = true;
                     let mut connector = Connector::new(target.clone()).map_err(ClientConnectionError::with_source)?;
                     connector.set_keepalive(cfg.tcp_keepalive.as_keepalive());
+                    connector.set_proxy(cfg.proxy.clone());
                     res = connector.connect().await;
                 } else {
                     return Err(ClientConnectionError::with_source(e).context(format!(
@@ -326,6 +397,13 @@ async fn connect2(
                     `--tls-security no-host-verification` to bypass this check.",
                 target.host().unwrap_or_default())));
             }
+            Some(CommonError::CertificatePinMismatch) => {
+                return Err(ClientConnectionError::with_source(e).context(format!(
+                    "The server's certificate does not match the pinned fingerprint(s) \
+                    while connecting to ({target:?}). Check `GEL_TLS_CERT_FINGERPRINT` or \
+                    the `tls_cert_fingerprint` option."
+                )));
+            }
             Some(e) => {
                 return Err(ClientConnectionError::with_source(e).context(format!(
                     "TLS handshake failed while connecting to ({target:?}) ({e:?}). \
@@ -559,7 +637,7 @@ async fn connect4(cfg: &Config, mut stream: gel_stream::RawStream, cert_check: O
         state_desc,
         in_buf,
         out_buf,
-        stream,
+        stream: Stream::Raw(stream),
         ping_interval: PingInterval::Unknown,
     })
 }
@@ -610,21 +688,46 @@ pub(crate) async fn send_messages<'x>(
     buf: &mut BytesMut,
     proto: &ProtocolVersion,
     messages: impl IntoIterator<Item = &'x ClientMessage>,
+) -> Result<(), Error> {
+    encode_messages(buf, proto, messages)?;
+    stream
+        .write_all_buf(buf)
+        .await
+        .map_err(ClientConnectionError::with_source)?;
+    Ok(())
+}
+
+fn encode_messages<'x>(
+    buf: &mut BytesMut,
+    proto: &ProtocolVersion,
+    messages: impl IntoIterator<Item = &'x ClientMessage>,
 ) -> Result<(), Error> {
     buf.truncate(0);
     for msg in messages {
         log::debug!(target: "edgedb::outgoing::frame",
                     "Frame Contents: {msg:#?}");
-        msg.encode(&mut Output::new(proto, buf))
-            .map_err(ClientEncodingError::with_source)?;
+        encode_message(buf, proto, msg)?;
     }
-    stream
-        .write_all_buf(buf)
-        .await
-        .map_err(ClientConnectionError::with_source)?;
     Ok(())
 }
 
+#[cfg(not(feature = "zero-copy"))]
+fn encode_message(buf: &mut BytesMut, proto: &ProtocolVersion, msg: &ClientMessage) -> Result<(), Error> {
+    use crate::errors::ClientEncodingError;
+    use gel_protocol::encoding::Output;
+
+    msg.encode(&mut Output::new(proto, buf))
+        .map_err(ClientEncodingError::with_source)
+}
+
+#[cfg(not(feature = "zero-copy"))]
+fn decode_frame(proto: &ProtocolVersion, frame: Bytes) -> Result<ServerMessage, Error> {
+    use gel_protocol::encoding::Input;
+
+    ServerMessage::decode(&mut Input::new(proto.clone(), frame))
+        .map_err(ProtocolEncodingError::with_source)
+}
+
 fn conn_err(err: io::Error) -> Error {
     ClientConnectionError::with_source(err)
 }
@@ -702,8 +805,7 @@ async fn _wait_message(
         }
     }
     let frame = buf.split_to(frame_len).freeze();
-    let result = ServerMessage::decode(&mut Input::new(proto.clone(), frame))
-        .map_err(ProtocolEncodingError::with_source)?;
+    let result = decode_frame(proto, frame)?;
 
     log::debug!(target: "edgedb::incoming::frame",
                 "Frame Contents: {result:#?}");
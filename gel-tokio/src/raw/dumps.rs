@@ -36,6 +36,7 @@ impl Connection {
         header: Bytes,
         mut stream: impl Stream<Item = Result<Bytes, Error>> + Unpin,
     ) -> Result<Response<()>, Error> {
+        self.stream.raw()?;
         let guard = self.begin_request()?;
         let start_headers = Instant::now();
         self.send_messages(&[ClientMessage::Restore(Restore {
@@ -70,7 +71,7 @@ impl Connection {
         while let Some(data) = stream.next().await.transpose()? {
             num_blocks += 1;
             total_len += data.len();
-            let (mut rd, mut wr) = tokio::io::split(&mut self.stream);
+            let (mut rd, mut wr) = tokio::io::split(self.stream.raw()?);
             let block = [ClientMessage::RestoreBlock(RestoreBlock { data })];
             tokio::select! {
                 msg = wait_message(&mut rd, &mut self.in_buf, &self.proto)
@@ -140,6 +141,7 @@ impl Connection {
         self.dump_with_secrets(false).await
     }
     pub async fn dump_with_secrets(&mut self, with_secrets: bool) -> Result<DumpStream<'_>, Error> {
+        self.stream.raw()?;
         let guard = self.begin_request()?;
 
         if self.proto.is_3() {
@@ -0,0 +1,274 @@
+//! Binary protocol tunnelled over HTTP(S)
+//!
+//! Every batch of client messages (always terminated by `Sync`) is sent as a
+//! single `POST` request to `/branch/<name>` and the response body contains
+//! the server messages up to `ReadyForCommand`. There is no handshake and no
+//! session on the server side, so connections only carry the HTTP keep-alive
+//! connection and the last state descriptor seen.
+use bytes::{Bytes, BytesMut};
+use http_body_util::{BodyExt, Full};
+use hyper::client::conn::http1::SendRequest;
+use hyper::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE, HOST};
+use hyper::{Method, Request, StatusCode};
+use hyper_util::rt::TokioIo;
+use log::{debug, warn};
+use tokio::time::Instant;
+use url::Url;
+
+use gel_dsn::gel::{Authentication, ClientSecurity, Config};
+use gel_protocol::common::RawTypedesc;
+use gel_protocol::features::ProtocolVersion;
+use gel_protocol::model::Json;
+use gel_protocol::server_message::TransactionState;
+use gel_stream::{CommonError, ConnectionError, Connector, Target, TlsAlpn};
+
+use crate::builder::CertCheck;
+use crate::errors::{AuthenticationError, ClientConnectionError, Error, ErrorKind};
+use crate::errors::{InterfaceError, ProtocolEncodingError, ProtocolError};
+use crate::raw::connection::{Mode, Stream};
+use crate::raw::{Connection, PingInterval};
+use crate::server_params::ServerParams;
+
+const USER_HEADER: &str = "x-edgedb-user";
+
+#[derive(Debug)]
+pub(crate) struct HttpTunnel {
+    sender: SendRequest<Full<Bytes>>,
+    host: HeaderValue,
+    authorization: Option<HeaderValue>,
+    user: HeaderValue,
+    binary_path: String,
+    edgeql_path: String,
+    content_type: HeaderValue,
+}
+
+pub(crate) async fn connect(
+    cfg: &Config,
+    warned: &mut bool,
+    cert_check: Option<CertCheck>,
+) -> Result<Connection, Error> {
+    let target = cfg
+        .host
+        .target_name()
+        .map_err(ClientConnectionError::with_source)?;
+    if !target.is_tcp() {
+        return Err(ClientConnectionError::with_message(
+            "HTTP transport requires a TCP address",
+        ));
+    }
+    let mut tls = cfg.to_tls();
+    tls.alpn = TlsAlpn::new_str(&["http/1.1"]);
+    let mut target = Target::new_tls(target, tls);
+    debug!("Connecting to {target:?} over HTTP...");
+
+    let mut connector =
+        Connector::new(target.clone()).map_err(ClientConnectionError::with_source)?;
+    connector.set_proxy(cfg.proxy.clone());
+    let mut res = connector.connect().await;
+    // Same plaintext fallback as the binary transport.
+    if let Err(ConnectionError::SslError(e)) = &res {
+        if e.common_error() == Some(CommonError::InvalidTlsProtocolData)
+            && cfg.client_security == ClientSecurity::InsecureDevMode
+        {
+            target.try_remove_tls();
+            warn!("TLS handshake failed, trying again without TLS");
+            *warned = true;
+            let mut connector =
+                Connector::new(target.clone()).map_err(ClientConnectionError::with_source)?;
+            connector.set_proxy(cfg.proxy.clone());
+            res = connector.connect().await;
+        }
+    }
+    let stream = res.map_err(ClientConnectionError::with_source)?;
+    let tls = stream.handshake().is_some();
+    if let Some(cert_check) = &cert_check {
+        if let Some(cert) = stream.handshake().and_then(|h| h.cert.as_ref()) {
+            cert_check.call(cert).await?;
+        }
+    }
+
+    let (sender, conn) = hyper::client::conn::http1::handshake(TokioIo::new(stream))
+        .await
+        .map_err(ClientConnectionError::with_source)?;
+    tokio::spawn(async move {
+        if let Err(e) = conn.await {
+            debug!("HTTP connection error: {e:#}");
+        }
+    });
+
+    let proto = ProtocolVersion::current();
+    let tunnel = HttpTunnel::new(cfg, sender, tls, &proto)?;
+    Ok(Connection {
+        proto,
+        server_params: ServerParams::new(),
+        mode: Mode::Normal {
+            idle_since: Instant::now(),
+        },
+        transaction_state: TransactionState::NotInTransaction,
+        state_desc: RawTypedesc::uninitialized(),
+        in_buf: BytesMut::with_capacity(8192),
+        out_buf: BytesMut::with_capacity(8192),
+        stream: Stream::Http(tunnel),
+        ping_interval: PingInterval::Disabled,
+    })
+}
+
+fn header(value: impl AsRef<str>) -> Result<HeaderValue, Error> {
+    HeaderValue::from_str(value.as_ref()).map_err(ClientConnectionError::with_source)
+}
+
+impl HttpTunnel {
+    fn new(
+        cfg: &Config,
+        sender: SendRequest<Full<Bytes>>,
+        tls: bool,
+        proto: &ProtocolVersion,
+    ) -> Result<HttpTunnel, Error> {
+        let url = cfg
+            .http_url(tls)
+            .ok_or_else(|| ClientConnectionError::with_message("no HTTP address"))?;
+        let mut url = Url::parse(&url).map_err(ClientConnectionError::with_source)?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => header(format!("{host}:{port}"))?,
+            (Some(host), None) => header(host)?,
+            _ => return Err(ClientConnectionError::with_message("no HTTP host")),
+        };
+        let branch = cfg.db.branch_for_connect().unwrap_or_default();
+        url.path_segments_mut()
+            .map_err(|()| ClientConnectionError::with_message("invalid HTTP address"))?
+            .clear()
+            .push("branch")
+            .push(branch);
+        let binary_path = url.path().to_string();
+        url.path_segments_mut()
+            .map_err(|()| ClientConnectionError::with_message("invalid HTTP address"))?
+            .push("edgeql");
+        let edgeql_path = url.path().to_string();
+
+        let authorization = match &cfg.authentication {
+            Authentication::SecretKey(key) => Some(header(format!("Bearer {key}"))?),
+            Authentication::Password(password) => {
+                use base64::Engine;
+                let credentials = base64::engine::general_purpose::STANDARD
+                    .encode(format!("{}:{}", cfg.user, password));
+                Some(header(format!("Basic {credentials}"))?)
+            }
+            Authentication::None => None,
+        };
+        let (major, minor) = proto.version_tuple();
+        Ok(HttpTunnel {
+            sender,
+            host,
+            authorization,
+            user: header(&cfg.user)?,
+            binary_path,
+            edgeql_path,
+            content_type: header(format!("application/x.edgedb.v_{major}_{minor}.binary"))?,
+        })
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    async fn request(
+        &mut self,
+        path: &str,
+        content_type: HeaderValue,
+        body: Bytes,
+    ) -> Result<Bytes, Error> {
+        let mut req = Request::new(Full::new(body));
+        *req.method_mut() = Method::POST;
+        *req.uri_mut() = path.parse().map_err(ClientConnectionError::with_source)?;
+        let headers = req.headers_mut();
+        headers.insert(HOST, self.host.clone());
+        headers.insert(CONTENT_TYPE, content_type);
+        headers.insert(USER_HEADER, self.user.clone());
+        if let Some(authorization) = &self.authorization {
+            headers.insert(AUTHORIZATION, authorization.clone());
+        }
+
+        self.sender
+            .ready()
+            .await
+            .map_err(ClientConnectionError::with_source)?;
+        let response = self
+            .sender
+            .send_request(req)
+            .await
+            .map_err(ClientConnectionError::with_source)?;
+        let status = response.status();
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .map_err(ClientConnectionError::with_source)?
+            .to_bytes();
+        match status {
+            StatusCode::OK => Ok(body),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                Err(AuthenticationError::with_message(format!(
+                    "HTTP request was rejected with status {status}: {}",
+                    String::from_utf8_lossy(&body).trim()
+                )))
+            }
+            _ => Err(ProtocolError::with_message(format!(
+                "HTTP request to {path} failed with status {status}: {}",
+                String::from_utf8_lossy(&body).trim()
+            ))),
+        }
+    }
+
+    /// Send encoded client messages and return the encoded server messages.
+    pub async fn round_trip(&mut self, messages: Bytes) -> Result<Bytes, Error> {
+        let path = self.binary_path.clone();
+        let content_type = self.content_type.clone();
+        self.request(&path, content_type, messages).await
+    }
+
+    /// Run a query on the `edgeql` endpoint, which accepts named variables
+    /// as JSON and returns the result as JSON.
+    pub async fn query_json(
+        &mut self,
+        query: &str,
+        variables: &serde_json::Value,
+    ) -> Result<Json, Error> {
+        let body = serde_json::to_vec(&serde_json::json!({
+            "query": query,
+            "variables": variables,
+        }))
+        .map_err(ProtocolEncodingError::with_source)?;
+        let path = self.edgeql_path.clone();
+        let content_type = HeaderValue::from_static("application/json");
+        let body = self.request(&path, content_type, body.into()).await?;
+        let mut response: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_slice(&body).map_err(ProtocolEncodingError::with_source)?;
+        if let Some(error) = response.remove("error") {
+            let code = error.get("code").and_then(|c| c.as_u64()).unwrap_or(0);
+            let message = error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or_default();
+            return Err(Error::from_code(code as u32).context(message.to_string()));
+        }
+        let data = response
+            .remove("data")
+            .ok_or_else(|| ProtocolError::with_message("HTTP response has no data"))?;
+        Ok(Json::new_unchecked(data.to_string()))
+    }
+}
+
+impl Connection {
+    pub(crate) async fn query_json_http(
+        &mut self,
+        query: &str,
+        variables: &serde_json::Value,
+    ) -> Result<Json, Error> {
+        match &mut self.stream {
+            Stream::Http(tunnel) => tunnel.query_json(query, variables).await,
+            Stream::Raw(_) => Err(InterfaceError::with_message(
+                "JSON queries over HTTP require the HTTP transport",
+            )),
+        }
+    }
+}
@@ -3,10 +3,14 @@
 mod connection;
 #[cfg(feature = "unstable")]
 mod dumps;
+#[cfg(feature = "http")]
+mod http;
 mod options;
 mod queries;
 mod response;
 pub mod state;
+#[cfg(feature = "zero-copy")]
+pub mod zero_copy;
 
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex as BlockingMutex};
@@ -65,7 +69,7 @@ pub struct Connection {
     state_desc: RawTypedesc,
     in_buf: BytesMut,
     out_buf: BytesMut,
-    stream: gel_stream::RawStream,
+    stream: connection::Stream,
     ping_interval: PingInterval,
 }
 
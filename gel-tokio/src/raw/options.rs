@@ -1,14 +1,28 @@
 use std::sync::Arc;
 
+use gel_protocol::common::Capabilities;
 use gel_protocol::encoding::Annotations;
 
 use crate::options::{RetryOptions, TransactionOptions};
 use crate::raw::state::PoolState;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct Options {
     pub(crate) transaction: TransactionOptions,
     pub(crate) retry: RetryOptions,
     pub(crate) state: Arc<PoolState>,
     pub(crate) annotations: Arc<Annotations>,
+    pub(crate) allow_capabilities: Capabilities,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            transaction: Default::default(),
+            retry: Default::default(),
+            state: Default::default(),
+            annotations: Default::default(),
+            allow_capabilities: Capabilities::MODIFICATIONS | Capabilities::DDL,
+        }
+    }
 }
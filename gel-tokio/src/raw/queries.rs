@@ -6,6 +6,7 @@ use tokio::time::Instant;
 use gel_errors::fields::QueryText;
 use gel_protocol::client_message::{ClientMessage, Parse};
 use gel_protocol::client_message::Execute1;
+use gel_protocol::client_message::State as EncodedState;
 use gel_protocol::common::CompilationOptions;
 use gel_protocol::common::{Capabilities, Cardinality, InputLanguage, IoFormat};
 use gel_protocol::descriptors::Typedesc;
@@ -38,6 +39,13 @@ impl Connection {
             Mode::AwaitingPing => Err(ClientInconsistentError::with_message("interrupted ping")),
         }
     }
+    fn encode_state(&self, state: &dyn State) -> Result<EncodedState, Error> {
+        if self.is_stateless() && self.state_desc.id == Uuid::from_u128(0) {
+            // No state descriptor is received before the first query
+            return state.encode_without_descriptor();
+        }
+        state.encode(&self.state_desc)
+    }
     pub(crate) fn end_request(&mut self, _guard: Guard) {
         self.mode = Mode::Normal {
             idle_since: Instant::now(),
@@ -91,7 +99,7 @@ impl Connection {
             ClientMessage::Parse(Parse::new(
                 flags,
                 query,
-                state.encode(&self.state_desc)?,
+                self.encode_state(state)?,
                 self.proto.is_3().then(|| annotations.clone()),
             )),
             ClientMessage::Sync,
@@ -158,7 +166,7 @@ impl Connection {
                 output_format: opts.io_format,
                 expected_cardinality: opts.expected_cardinality,
                 command_text: query.into(),
-                state: state.encode(&self.state_desc)?,
+                state: self.encode_state(state)?,
                 input_typedesc_id: desc.input.id,
                 output_typedesc_id: desc.output.id,
                 arguments: arguments.clone(),
@@ -235,6 +243,23 @@ impl Connection {
             &mut arg_buf,
         ))?;
 
+        self._execute_stream(opts, query, state, annotations, desc, arg_buf.freeze())
+            .await
+    }
+
+    pub(crate) async fn _execute_stream<R>(
+        &mut self,
+        opts: &CompilationOptions,
+        query: &str,
+        state: &dyn State,
+        annotations: &Arc<Annotations>,
+        desc: &CommandDataDescription1,
+        arguments: Bytes,
+    ) -> Result<ResponseStream<R>, Error>
+    where
+        R: QueryResult,
+        R::State: Unpin,
+    {
         let guard = self.begin_request()?;
         self.send_messages(&[
             ClientMessage::Execute1(Execute1 {
@@ -246,10 +271,10 @@ impl Connection {
                 output_format: opts.io_format,
                 expected_cardinality: opts.expected_cardinality,
                 command_text: query.into(),
-                state: state.encode(&self.state_desc)?,
+                state: self.encode_state(state)?,
                 input_typedesc_id: desc.input.id,
                 output_typedesc_id: desc.output.id,
-                arguments: arg_buf.freeze(),
+                arguments,
             }),
             ClientMessage::Sync,
         ])
@@ -292,7 +317,7 @@ impl Connection {
                 output_format: opts.io_format,
                 expected_cardinality: opts.expected_cardinality,
                 command_text: query.into(),
-                state: state.encode(&self.state_desc)?,
+                state: self.encode_state(state)?,
                 input_typedesc_id: *input.id(),
                 output_typedesc_id: *input.id(),
                 arguments: arg_buf.freeze(),
@@ -331,7 +356,7 @@ impl Connection {
                 output_format: opts.io_format,
                 expected_cardinality: opts.expected_cardinality,
                 command_text: query.into(),
-                state: state.encode(&self.state_desc)?,
+                state: self.encode_state(state)?,
                 input_typedesc_id: Uuid::from_u128(0),
                 output_typedesc_id: Uuid::from_u128(0),
                 arguments: Bytes::new(),
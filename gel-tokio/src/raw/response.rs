@@ -8,6 +8,7 @@ use gel_errors::ProtocolOutOfOrderError;
 use gel_protocol::annotations::Warning;
 use gel_protocol::common::State;
 use gel_protocol::descriptors::Typedesc;
+use gel_protocol::queryable::{Queryable, QueryableRef};
 use gel_protocol::server_message::CommandDataDescription1;
 use gel_protocol::server_message::{ErrorResponse, ServerMessage};
 use gel_protocol::{annotations, QueryResult};
@@ -23,6 +24,8 @@ enum Buffer {
     },
     ErrorResponse(ErrorResponse),
     Error(Error),
+    /// Decoding an element failed and the rest of the data is not read yet
+    DecodeError(Error),
     Reset,
 }
 
@@ -36,6 +39,8 @@ where
     guard: Option<Guard>,
     description: Option<CommandDataDescription1>,
     warnings: Vec<Warning>,
+    /// Data message the last borrowed element was decoded from
+    current: Option<Bytes>,
 }
 
 impl<'a, T: QueryResult> ResponseStream<'a, T>
@@ -114,6 +119,7 @@ where
                 guard,
                 description,
                 warnings,
+                current: None,
             })
         } else {
             Ok(ResponseStream {
@@ -123,6 +129,7 @@ where
                 guard,
                 description,
                 warnings,
+                current: None,
             })
         }
     }
@@ -177,7 +184,7 @@ where
             }
         }
     }
-    pub async fn next_element(&mut self) -> Option<T> {
+    async fn next_data(&mut self) -> Option<Bytes> {
         use Buffer::*;
 
         let Reading(ref mut buffer) = self.buffer else {
@@ -185,18 +192,7 @@ where
         };
         loop {
             if let Some(element) = buffer.pop_front() {
-                let state = self
-                    .state
-                    .as_mut()
-                    .expect("data packets are ignored if state is None");
-                match T::decode(state, &element) {
-                    Ok(value) => return Some(value),
-                    Err(e) => {
-                        self.ignore_data().await;
-                        self.buffer = Error(e);
-                        return None;
-                    }
-                }
+                return Some(element);
             }
             match self.connection.message().await {
                 Ok(ServerMessage::StateDataDescription(d)) => {
@@ -238,6 +234,21 @@ where
             }
         }
     }
+    pub async fn next_element(&mut self) -> Option<T> {
+        let element = self.next_data().await?;
+        let state = self
+            .state
+            .as_mut()
+            .expect("data packets are ignored if state is None");
+        match T::decode(state, &element) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.ignore_data().await;
+                self.buffer = Buffer::Error(e);
+                None
+            }
+        }
+    }
     pub fn warnings(&self) -> &[Warning] {
         &self.warnings
     }
@@ -246,12 +257,21 @@ where
     }
     pub async fn process_complete(&mut self) -> Result<Response<()>, Error> {
         use Buffer::*;
+        if matches!(self.buffer, DecodeError(_)) {
+            let DecodeError(e) = mem::replace(&mut self.buffer, Reading(VecDeque::new())) else {
+                unreachable!()
+            };
+            self.ignore_data().await;
+            self.buffer = Error(e);
+        } else if matches!(self.buffer, Reset) {
+            panic!("process_complete() called twice");
+        }
         while matches!(self.buffer, Reading(_)) {
             self.ignore_data().await
         }
 
         match mem::replace(&mut self.buffer, Buffer::Reset) {
-            Reading(_) => unreachable!(),
+            Reading(_) | DecodeError(_) => unreachable!(),
             Complete {
                 status,
                 new_state,
@@ -277,3 +297,140 @@ where
         }
     }
 }
+
+impl<T: Queryable> ResponseStream<'_, T>
+where
+    T::Args: Unpin,
+{
+    /// Receive the next element and decode it as `R` by borrowing from the
+    /// data message, e.g. as `&str` for a `String` query.
+    ///
+    /// The element must be dropped before receiving the next one.
+    pub async fn next_element_ref<'s, R>(&'s mut self) -> Option<R>
+    where
+        R: QueryableRef<'s, Owned = T>,
+    {
+        let element = self.next_data().await?;
+        let element = self.current.insert(element);
+        let (decoder, args) = self
+            .state
+            .as_ref()
+            .expect("data packets are ignored if state is None");
+        match R::decode_ref(decoder, args, element) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                // The rest of the data is skipped by `process_complete`, as
+                // `current` stays borrowed by the caller.
+                self.buffer = Buffer::DecodeError(ProtocolEncodingError::with_source(e));
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+    use gel_protocol::common::{Capabilities, Cardinality, RawTypedesc};
+    use gel_protocol::descriptors::Typedesc;
+    use gel_protocol::encoding::Output;
+    use gel_protocol::features::ProtocolVersion;
+    use gel_protocol::model::Uuid;
+    use gel_protocol::server_message::{CommandComplete1, CommandDataDescription1, Data};
+    use gel_protocol::server_message::{ReadyForCommand, ServerMessage, TransactionState};
+    use gel_stream::{Connector, ResolvedTarget};
+    use tokio::time::Instant;
+
+    use super::ResponseStream;
+    use crate::raw::connection::{Mode, Stream};
+    use crate::raw::{Connection, PingInterval};
+    use crate::server_params::ServerParams;
+
+    /// A connection that has already received `data` followed by the end of
+    /// a `std::str` query.
+    async fn connection(data: &[&'static [u8]]) -> (Connection, tokio::net::TcpStream) {
+        let proto = ProtocolVersion::new(1, 0);
+        let str_id = Uuid::from_u128(0x101);
+        let output = RawTypedesc {
+            proto: proto.clone(),
+            id: str_id,
+            data: [&[2][..], str_id.as_bytes()].concat().into(),
+        };
+        let messages = [
+            ServerMessage::CommandDataDescription1(CommandDataDescription1 {
+                annotations: Default::default(),
+                capabilities: Capabilities::empty(),
+                result_cardinality: Cardinality::Many,
+                input: RawTypedesc::uninitialized(),
+                output,
+            }),
+            ServerMessage::Data(Data {
+                data: data.iter().map(|d| bytes::Bytes::from_static(d)).collect(),
+            }),
+            ServerMessage::CommandComplete1(CommandComplete1 {
+                annotations: Default::default(),
+                capabilities: Capabilities::empty(),
+                status: "SELECT".into(),
+                state: None,
+            }),
+            ServerMessage::ReadyForCommand(ReadyForCommand {
+                annotations: Default::default(),
+                transaction_state: TransactionState::NotInTransaction,
+            }),
+        ];
+        let mut in_buf = BytesMut::new();
+        for message in &messages {
+            message
+                .encode(&mut Output::new(&proto, &mut in_buf))
+                .unwrap();
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = ResolvedTarget::SocketAddr(listener.local_addr().unwrap());
+        let stream = Connector::new_resolved(target).connect().await.unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        let connection = Connection {
+            proto,
+            server_params: ServerParams::new(),
+            mode: Mode::Normal {
+                idle_since: Instant::now(),
+            },
+            transaction_state: TransactionState::NotInTransaction,
+            state_desc: RawTypedesc::uninitialized(),
+            in_buf,
+            out_buf: BytesMut::new(),
+            stream: Stream::Raw(stream),
+            ping_interval: PingInterval::Disabled,
+        };
+        (connection, server)
+    }
+
+    #[tokio::test]
+    async fn complete_unread_data() {
+        let (mut connection, _server) = connection(&[b"hello", b"world"]).await;
+        let proto = connection.proto.clone();
+        let guard = connection.begin_request().unwrap();
+        let stream =
+            ResponseStream::<String>::new(&mut connection, &Typedesc::nothing(&proto), guard)
+                .await
+                .unwrap();
+        let response = stream.complete().await.unwrap();
+        assert_eq!(response.status, "SELECT");
+        assert!(connection.is_consistent());
+    }
+
+    #[tokio::test]
+    async fn complete_after_borrowed_decode_error() {
+        let (mut connection, _server) = connection(&[b"hello", b"\xff", b"world"]).await;
+        let proto = connection.proto.clone();
+        let guard = connection.begin_request().unwrap();
+        let mut stream =
+            ResponseStream::<String>::new(&mut connection, &Typedesc::nothing(&proto), guard)
+                .await
+                .unwrap();
+        assert_eq!(stream.next_element_ref::<&str>().await, Some("hello"));
+        assert_eq!(stream.next_element_ref::<&str>().await, None);
+        assert!(stream.complete().await.is_err());
+        assert!(connection.is_consistent());
+    }
+}
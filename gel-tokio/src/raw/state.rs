@@ -67,6 +67,15 @@ struct CommonState {
     config: BTreeMap<String, Value>,
 }
 
+impl RawState {
+    fn is_default(&self) -> bool {
+        self.common.module.is_none()
+            && self.common.aliases.is_empty()
+            && self.common.config.is_empty()
+            && self.globals.is_empty()
+    }
+}
+
 /// Utility object used to modify globals
 ///
 /// This object is passed to [`Fn`] closure and [`GlobalsDelta::apply`].
@@ -114,6 +123,9 @@ pub trait AliasesDelta {
 
 pub trait SealedState {
     fn encode(&self, desc: &RawTypedesc) -> Result<EncodedState, Error>;
+    /// Encode the state before any state descriptor was received, which
+    /// happens on stateless transports that have no handshake.
+    fn encode_without_descriptor(&self) -> Result<EncodedState, Error>;
 }
 
 /// Provides state of the session in the binary form
@@ -395,18 +407,38 @@ impl PoolState {
         self.cache.store(Some(Arc::new(result.clone())));
         Ok(result)
     }
+    fn encode_without_descriptor(&self) -> Result<EncodedState, Error> {
+        // The default state can be sent without a descriptor, otherwise we
+        // send an unknown id so that the server replies with its descriptor
+        // and a retryable `StateMismatchError`.
+        let typedesc_id = if self.raw_state.is_default() {
+            Uuid::from_u128(0)
+        } else {
+            Uuid::from_u128(u128::MAX)
+        };
+        Ok(EncodedState {
+            typedesc_id,
+            data: Default::default(),
+        })
+    }
 }
 
 impl SealedState for &PoolState {
     fn encode(&self, desc: &RawTypedesc) -> Result<EncodedState, Error> {
         PoolState::encode(self, desc)
     }
+    fn encode_without_descriptor(&self) -> Result<EncodedState, Error> {
+        PoolState::encode_without_descriptor(self)
+    }
 }
 impl State for &PoolState {}
 impl SealedState for Arc<PoolState> {
     fn encode(&self, desc: &RawTypedesc) -> Result<EncodedState, Error> {
         PoolState::encode(self, desc)
     }
+    fn encode_without_descriptor(&self) -> Result<EncodedState, Error> {
+        PoolState::encode_without_descriptor(self)
+    }
 }
 impl State for Arc<PoolState> {}
 
@@ -419,12 +451,18 @@ impl SealedState for EncodedState {
             "state doesn't match state descriptor",
         ))
     }
+    fn encode_without_descriptor(&self) -> Result<EncodedState, Error> {
+        Ok((*self).clone())
+    }
 }
 impl State for EncodedState {}
 impl SealedState for Arc<EncodedState> {
     fn encode(&self, desc: &RawTypedesc) -> Result<EncodedState, Error> {
         (**self).encode(desc)
     }
+    fn encode_without_descriptor(&self) -> Result<EncodedState, Error> {
+        (**self).encode_without_descriptor()
+    }
 }
 impl State for Arc<EncodedState> {}
 
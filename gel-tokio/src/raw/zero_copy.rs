@@ -0,0 +1,389 @@
+//! Message handling on top of the borrowed `gel-db-protocol` types
+//!
+//! Server frames are parsed in place: the hot messages (`Data`,
+//! `CommandComplete`, `CommandDataDescription`, `StateDataDescription` and
+//! `ReadyForCommand`) are read straight from the received frame and every
+//! byte payload is a slice of that frame rather than a copy. Query messages
+//! are encoded with the protocol builders directly into the output buffer.
+//!
+//! Everything else still goes through the owned `gel-protocol` codec until
+//! this path reaches parity.
+use std::collections::HashMap;
+
+use bytes::{Bytes, BytesMut};
+use gel_db_protocol::prelude::*;
+use gel_db_protocol::protocol as wire;
+use gel_db_protocol::protocol::Message;
+
+use gel_protocol::client_message::{ClientMessage, Execute1, Parse};
+use gel_protocol::common::{Capabilities, RawTypedesc, State};
+use gel_protocol::encoding::{Annotations, Input, Output};
+use gel_protocol::features::ProtocolVersion;
+use gel_protocol::model::Uuid;
+use gel_protocol::server_message::StateDataDescription;
+use gel_protocol::server_message::{CommandComplete1, CommandDataDescription1};
+use gel_protocol::server_message::{Data, ReadyForCommand, ServerMessage};
+
+use crate::errors::{ClientEncodingError, Error, ErrorKind, ProtocolEncodingError};
+
+/// Encode a single client message at the end of `buf`.
+pub fn encode_message(
+    buf: &mut BytesMut,
+    proto: &ProtocolVersion,
+    msg: &ClientMessage,
+) -> Result<(), Error> {
+    match msg {
+        ClientMessage::Parse(parse) if proto.is_multilingual() => {
+            encode_builder(buf, parse_builder(parse))
+        }
+        ClientMessage::Parse(parse) => encode_builder(buf, parse2_builder(parse)),
+        ClientMessage::Execute1(execute) if proto.is_multilingual() => {
+            encode_builder(buf, execute_builder(execute))
+        }
+        ClientMessage::Execute1(execute) => encode_builder(buf, execute2_builder(execute)),
+        ClientMessage::Sync => encode_builder(buf, wire::SyncBuilder::default()),
+        msg => msg
+            .encode(&mut Output::new(proto, buf))
+            .map_err(ClientEncodingError::with_source),
+    }
+}
+
+/// Decode one complete server frame.
+///
+/// Byte payloads of the returned message share the frame's allocation.
+pub fn decode_message(proto: &ProtocolVersion, frame: Bytes) -> Result<ServerMessage, Error> {
+    let parsed = match_message!(Message::new(&frame), EdgeDBBackend {
+        (wire::Data as data) => {
+            let data = data
+                .data()
+                .into_iter()
+                .map(|element| frame.slice_ref(element.data().into_bytes()))
+                .collect();
+            Ok(Some(ServerMessage::Data(Data { data })))
+        },
+        (wire::CommandComplete as complete) => {
+            let state = if complete.state_typedesc_id() == Uuid::from_u128(0) {
+                None
+            } else {
+                Some(State {
+                    typedesc_id: complete.state_typedesc_id(),
+                    data: frame.slice_ref(complete.state_data().into_bytes()),
+                })
+            };
+            Ok(Some(ServerMessage::CommandComplete1(CommandComplete1 {
+                annotations: annotations(complete.annotations()),
+                capabilities: Capabilities::from_bits_retain(complete.capabilities()),
+                status: complete.status().to_string_lossy().into_owned(),
+                state,
+            })))
+        },
+        (wire::CommandDataDescription as desc) => {
+            let result_cardinality = desc
+                .result_cardinality()
+                .try_into()
+                .map_err(ProtocolEncodingError::with_source)?;
+            Ok(Some(ServerMessage::CommandDataDescription1(CommandDataDescription1 {
+                annotations: annotations(desc.annotations()),
+                capabilities: Capabilities::from_bits_retain(desc.capabilities()),
+                result_cardinality,
+                input: RawTypedesc {
+                    proto: proto.clone(),
+                    id: desc.input_typedesc_id(),
+                    data: frame.slice_ref(desc.input_typedesc().into_bytes()),
+                },
+                output: RawTypedesc {
+                    proto: proto.clone(),
+                    id: desc.output_typedesc_id(),
+                    data: frame.slice_ref(desc.output_typedesc().into_bytes()),
+                },
+            })))
+        },
+        (wire::StateDataDescription as desc) => {
+            Ok(Some(ServerMessage::StateDataDescription(StateDataDescription {
+                typedesc: RawTypedesc {
+                    proto: proto.clone(),
+                    id: desc.typedesc_id(),
+                    data: frame.slice_ref(desc.typedesc().into_bytes()),
+                },
+            })))
+        },
+        (wire::ReadyForCommand as ready) => {
+            Ok(Some(ServerMessage::ReadyForCommand(ReadyForCommand {
+                annotations: annotations(ready.annotations()),
+                transaction_state: ready.transaction_state(),
+            })))
+        },
+        unknown => {
+            match unknown {
+                Ok(_) => Ok(None),
+                Err(e) => Err(ProtocolEncodingError::with_source(e)),
+            }
+        }
+    });
+    match parsed? {
+        Some(msg) => Ok(msg),
+        None => ServerMessage::decode(&mut Input::new(proto.clone(), frame))
+            .map_err(ProtocolEncodingError::with_source),
+    }
+}
+
+fn annotations<'a>(annotations: Array<'a, i16, wire::Annotation<'a>>) -> Annotations {
+    // Most messages carry no annotations, which leaves the map unallocated.
+    let mut map = HashMap::new();
+    for ann in annotations {
+        map.insert(
+            ann.name().to_string_lossy().into_owned(),
+            ann.value().to_string_lossy().into_owned(),
+        );
+    }
+    map
+}
+
+fn encode_builder<T: 'static>(
+    buf: &mut BytesMut,
+    builder: impl EncoderFor<T>,
+) -> Result<(), Error> {
+    let len = builder.measure();
+    buf.reserve(len);
+    let written = builder
+        .encode_buffer_uninit(buf.spare_capacity_mut())
+        .map_err(|_| ClientEncodingError::with_message("message does not fit the buffer"))?
+        .len();
+    // SAFETY: `written` bytes of the spare capacity were just initialized.
+    unsafe { buf.set_len(buf.len() + written) };
+    Ok(())
+}
+
+macro_rules! annotation_builders {
+    ($annotations:expr) => {
+        || {
+            $annotations
+                .as_deref()
+                .map(|annotations| annotations.iter())
+                .unwrap_or_default()
+                .map(|(name, value)| wire::AnnotationBuilder { name, value })
+        }
+    };
+}
+
+fn parse_builder(parse: &Parse) -> impl EncoderFor<wire::Parse<'static>> + '_ {
+    wire::ParseBuilder {
+        annotations: annotation_builders!(parse.annotations),
+        allowed_capabilities: parse.allowed_capabilities.bits(),
+        compilation_flags: parse.compilation_flags.bits(),
+        implicit_limit: parse.implicit_limit.unwrap_or(0),
+        input_language: parse.input_language,
+        output_format: parse.output_format,
+        expected_cardinality: parse.expected_cardinality as u8,
+        command_text: &parse.command_text,
+        state_typedesc_id: parse.state.typedesc_id,
+        state_data: parse.state.data.as_ref(),
+    }
+}
+
+fn parse2_builder(parse: &Parse) -> impl EncoderFor<wire::Parse2<'static>> + '_ {
+    wire::Parse2Builder {
+        annotations: annotation_builders!(parse.annotations),
+        allowed_capabilities: parse.allowed_capabilities.bits(),
+        compilation_flags: parse.compilation_flags.bits(),
+        implicit_limit: parse.implicit_limit.unwrap_or(0),
+        output_format: parse.output_format,
+        expected_cardinality: parse.expected_cardinality as u8,
+        command_text: &parse.command_text,
+        state_typedesc_id: parse.state.typedesc_id,
+        state_data: parse.state.data.as_ref(),
+    }
+}
+
+fn execute_builder(execute: &Execute1) -> impl EncoderFor<wire::Execute<'static>> + '_ {
+    wire::ExecuteBuilder {
+        annotations: annotation_builders!(execute.annotations),
+        allowed_capabilities: execute.allowed_capabilities.bits(),
+        compilation_flags: execute.compilation_flags.bits(),
+        implicit_limit: execute.implicit_limit.unwrap_or(0),
+        input_language: execute.input_language,
+        output_format: execute.output_format,
+        expected_cardinality: execute.expected_cardinality as u8,
+        command_text: &execute.command_text,
+        state_typedesc_id: execute.state.typedesc_id,
+        state_data: execute.state.data.as_ref(),
+        input_typedesc_id: execute.input_typedesc_id,
+        output_typedesc_id: execute.output_typedesc_id,
+        arguments: execute.arguments.as_ref(),
+    }
+}
+
+fn execute2_builder(execute: &Execute1) -> impl EncoderFor<wire::Execute2<'static>> + '_ {
+    wire::Execute2Builder {
+        annotations: annotation_builders!(execute.annotations),
+        allowed_capabilities: execute.allowed_capabilities.bits(),
+        compilation_flags: execute.compilation_flags.bits(),
+        implicit_limit: execute.implicit_limit.unwrap_or(0),
+        output_format: execute.output_format,
+        expected_cardinality: execute.expected_cardinality as u8,
+        command_text: &execute.command_text,
+        state_typedesc_id: execute.state.typedesc_id,
+        state_data: execute.state.data.as_ref(),
+        input_typedesc_id: execute.input_typedesc_id,
+        output_typedesc_id: execute.output_typedesc_id,
+        arguments: execute.arguments.as_ref(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use bytes::{Bytes, BytesMut};
+    use gel_protocol::client_message::{ClientMessage, Execute1, Parse};
+    use gel_protocol::common::{Capabilities, Cardinality, CompilationFlags, RawTypedesc, State};
+    use gel_protocol::common::{InputLanguage, IoFormat};
+    use gel_protocol::encoding::{Input, Output};
+    use gel_protocol::features::ProtocolVersion;
+    use gel_protocol::model::Uuid;
+    use gel_protocol::server_message::{CommandComplete1, CommandDataDescription1, Data};
+    use gel_protocol::server_message::{ErrorResponse, ErrorSeverity, ReadyForCommand};
+    use gel_protocol::server_message::{ServerMessage, StateDataDescription, TransactionState};
+
+    use super::{decode_message, encode_message};
+
+    fn protocols() -> [ProtocolVersion; 2] {
+        [ProtocolVersion::new(2, 0), ProtocolVersion::current()]
+    }
+
+    fn server_messages(proto: &ProtocolVersion) -> Vec<ServerMessage> {
+        let typedesc = |id, data: &'static [u8]| RawTypedesc {
+            proto: proto.clone(),
+            id: Uuid::from_u128(id),
+            data: Bytes::from_static(data),
+        };
+        vec![
+            ServerMessage::CommandDataDescription1(CommandDataDescription1 {
+                annotations: HashMap::from([("warnings".into(), "[]".into())]),
+                capabilities: Capabilities::MODIFICATIONS,
+                result_cardinality: Cardinality::AtMostOne,
+                input: typedesc(1, b"\x04\x00\x00"),
+                output: typedesc(2, b"\x02\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00"),
+            }),
+            ServerMessage::StateDataDescription(StateDataDescription {
+                typedesc: typedesc(3, b"state"),
+            }),
+            ServerMessage::Data(Data {
+                data: vec![Bytes::from_static(b"first"), Bytes::from_static(b"")],
+            }),
+            ServerMessage::CommandComplete1(CommandComplete1 {
+                annotations: HashMap::new(),
+                capabilities: Capabilities::empty(),
+                status: "SELECT".into(),
+                state: None,
+            }),
+            ServerMessage::CommandComplete1(CommandComplete1 {
+                annotations: HashMap::new(),
+                capabilities: Capabilities::DDL,
+                status: "CREATE TYPE".into(),
+                state: Some(State {
+                    typedesc_id: Uuid::from_u128(3),
+                    data: Bytes::from_static(b"\x00\x01"),
+                }),
+            }),
+            ServerMessage::ReadyForCommand(ReadyForCommand {
+                annotations: HashMap::new(),
+                transaction_state: TransactionState::InFailedTransaction,
+            }),
+            ServerMessage::ErrorResponse(ErrorResponse {
+                severity: ErrorSeverity::Error,
+                code: 0x04_00_00_00,
+                message: "syntax error".into(),
+                attributes: HashMap::new(),
+            }),
+        ]
+    }
+
+    fn client_messages() -> Vec<ClientMessage> {
+        let state = State {
+            typedesc_id: Uuid::from_u128(3),
+            data: Bytes::from_static(b"\x00\x01"),
+        };
+        let annotations = Some(Arc::new(HashMap::from([("tag".into(), "test".into())])));
+        vec![
+            ClientMessage::Parse(Parse {
+                annotations: None,
+                allowed_capabilities: Capabilities::ALL,
+                compilation_flags: CompilationFlags::INJECT_OUTPUT_TYPE_NAMES,
+                implicit_limit: Some(10),
+                output_format: IoFormat::Json,
+                expected_cardinality: Cardinality::Many,
+                command_text: "select 1".into(),
+                state: state.clone(),
+                input_language: InputLanguage::SQL,
+            }),
+            ClientMessage::Execute1(Execute1 {
+                annotations,
+                allowed_capabilities: Capabilities::MODIFICATIONS,
+                compilation_flags: CompilationFlags::empty(),
+                implicit_limit: None,
+                output_format: IoFormat::Binary,
+                expected_cardinality: Cardinality::One,
+                command_text: "select <str>$0".into(),
+                state,
+                input_typedesc_id: Uuid::from_u128(1),
+                output_typedesc_id: Uuid::from_u128(2),
+                arguments: Bytes::from_static(b"\x00\x00\x00\x01"),
+                input_language: InputLanguage::EdgeQL,
+            }),
+            ClientMessage::Sync,
+            ClientMessage::Terminate,
+        ]
+    }
+
+    #[test]
+    fn decode_matches_owned() {
+        for proto in protocols() {
+            for msg in server_messages(&proto) {
+                let mut buf = BytesMut::new();
+                msg.encode(&mut Output::new(&proto, &mut buf)).unwrap();
+                let frame = buf.freeze();
+                let owned = ServerMessage::decode(&mut Input::new(proto.clone(), frame.clone()));
+                assert_eq!(decode_message(&proto, frame).unwrap(), owned.unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn decode_borrows_frame() {
+        let proto = ProtocolVersion::current();
+        let mut buf = BytesMut::new();
+        ServerMessage::Data(Data {
+            data: vec![Bytes::from_static(b"payload")],
+        })
+        .encode(&mut Output::new(&proto, &mut buf))
+        .unwrap();
+        let frame = buf.freeze();
+        let ServerMessage::Data(data) = decode_message(&proto, frame.clone()).unwrap() else {
+            panic!("expected a data message");
+        };
+        let frame_range = frame.as_ptr_range();
+        assert!(frame_range.contains(&data.data[0].as_ptr()));
+    }
+
+    #[test]
+    fn decode_invalid() {
+        let proto = ProtocolVersion::current();
+        let frame = Bytes::from_static(b"D\x00\x00\x00\x10\x00\x01");
+        assert!(decode_message(&proto, frame).is_err());
+    }
+
+    #[test]
+    fn encode_matches_owned() {
+        for proto in protocols() {
+            let mut owned = BytesMut::new();
+            let mut zero_copy = BytesMut::new();
+            for msg in client_messages() {
+                msg.encode(&mut Output::new(&proto, &mut owned)).unwrap();
+                encode_message(&mut zero_copy, &proto, &msg).unwrap();
+            }
+            assert_eq!(zero_copy, owned, "protocol {proto:?}");
+        }
+    }
+}
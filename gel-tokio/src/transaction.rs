@@ -11,7 +11,7 @@ use tokio::sync::oneshot;
 use tokio::time::sleep;
 
 use crate::errors::{Error, ErrorKind, SHOULD_RETRY};
-use crate::errors::{NoDataError, ProtocolEncodingError};
+use crate::errors::{InterfaceError, NoDataError, ProtocolEncodingError};
 use crate::raw::{Options, Pool, PoolConnection, Response};
 use crate::ResultVerbose;
 
@@ -225,6 +225,11 @@ impl Transaction {
 
     async fn ensure_started(&mut self) -> anyhow::Result<(), Error> {
         if !self.started {
+            if self.conn.inner().is_stateless() {
+                return Err(InterfaceError::with_message(
+                    "transactions are not supported over the HTTP transport",
+                ));
+            }
             let options = &self.options;
             self.conn
                 .statement("START TRANSACTION", &options.state, &options.annotations)
@@ -282,7 +287,7 @@ impl Transaction {
                 arguments,
                 &self.options.state,
                 &self.options.annotations,
-                Capabilities::MODIFICATIONS,
+                self.options.allow_capabilities & Capabilities::MODIFICATIONS,
                 io_format,
                 cardinality,
             )
@@ -495,7 +500,7 @@ impl Transaction {
             implicit_typenames: false,
             implicit_typeids: false,
             explicit_objectids: true,
-            allow_capabilities: Capabilities::MODIFICATIONS,
+            allow_capabilities: self.options.allow_capabilities & Capabilities::MODIFICATIONS,
             input_language: InputLanguage::EdgeQL,
             io_format: IoFormat::Binary,
             expected_cardinality: Cardinality::Many,
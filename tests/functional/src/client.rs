@@ -1,9 +1,9 @@
 use std::str::FromStr;
 
 use futures_util::stream::{self, StreamExt};
-use gel_errors::NoDataError;
+use gel_errors::{DisabledCapabilityError, NoDataError};
 use gel_protocol::codec::{ObjectShape, ShapeElement};
-use gel_protocol::common::Cardinality;
+use gel_protocol::common::{Capabilities, Cardinality};
 use gel_protocol::model::{Json, Uuid};
 use gel_protocol::named_args;
 use gel_protocol::value::{EnumValue, Value};
@@ -486,3 +486,71 @@ async fn props_in_wrong_order() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn arrow() -> anyhow::Result<()> {
+    let client = Client::new(&SERVER.config);
+    client.ensure_connected().await?;
+
+    let batches = client
+        .query_arrow(
+            "select { x := range_unpack(range(0, 25)), y := 'a' }",
+            &(),
+            10,
+        )
+        .await?
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+    let rows = batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+    assert_eq!(rows, vec![10, 10, 5]);
+    let schema = batches[0].schema();
+    let names = schema
+        .fields()
+        .iter()
+        .map(|f| f.name().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["x", "y"]);
+
+    let batches = client
+        .query_arrow("select <int64>{}", &(), 10)
+        .await?
+        .collect::<Vec<_>>()
+        .await;
+    assert!(batches.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn read_only() -> anyhow::Result<()> {
+    let client = Client::new(&SERVER.config).read_only();
+    client.ensure_connected().await?;
+
+    let value = client
+        .query_required_single::<i64, _>("SELECT 1", &())
+        .await?;
+    assert_eq!(value, 1);
+
+    let err = client
+        .execute("CREATE TYPE ReadOnlyTest", &())
+        .await
+        .unwrap_err();
+    assert!(err.is::<DisabledCapabilityError>());
+
+    let err = client
+        .query::<Value, _>("INSERT test::Counter { name := 'read_only' }", &())
+        .await
+        .unwrap_err();
+    assert!(err.is::<DisabledCapabilityError>());
+
+    let client = client.with_allowed_capabilities(Capabilities::MODIFICATIONS);
+    let err = client
+        .execute("CREATE TYPE ReadOnlyTest", &())
+        .await
+        .unwrap_err();
+    assert!(err.is::<DisabledCapabilityError>());
+
+    Ok(())
+}
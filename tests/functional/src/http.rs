@@ -0,0 +1,64 @@
+use gel_errors::InterfaceError;
+use gel_protocol::model::Json;
+use gel_tokio::dsn::Transport;
+use gel_tokio::Client;
+
+use crate::server::SERVER;
+
+fn http_client() -> Client {
+    let mut config = SERVER.config.clone();
+    config.transport = Transport::Http;
+    Client::new(&config)
+}
+
+#[tokio::test]
+async fn simple() -> anyhow::Result<()> {
+    let client = http_client();
+    client.ensure_connected().await?;
+
+    let value = client.query::<i64, _>("SELECT 7*93", &()).await?;
+    assert_eq!(value, vec![651]);
+
+    let value = client
+        .query_single::<String, _>("SELECT <str>$0", &("hello",))
+        .await?;
+    assert_eq!(value.as_deref(), Some("hello"));
+
+    let json = client.query_json("SELECT {1, 2}", &()).await?;
+    assert_eq!(json, Json::new_unchecked("[1, 2]".into()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn state() -> anyhow::Result<()> {
+    // The first query receives the state descriptor and is retried
+    let client = http_client().with_default_module(Some("test"));
+    let value = client
+        .query_required_single::<String, _>("SELECT <str>1", &())
+        .await?;
+    assert_eq!(value, "1");
+    Ok(())
+}
+
+#[tokio::test]
+async fn json_endpoint() -> anyhow::Result<()> {
+    let client = http_client();
+    let json = client
+        .query_json_http("SELECT <int64>$x * 2", &serde_json::json!({"x": 21}))
+        .await?;
+    assert_eq!(json, Json::new_unchecked("[42]".into()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn transaction_unsupported() -> anyhow::Result<()> {
+    let client = http_client();
+    let err = client
+        .transaction(
+            |mut tx| async move { tx.query_required_single::<i64, _>("SELECT 1", &()).await },
+        )
+        .await
+        .unwrap_err();
+    assert!(err.is::<InterfaceError>());
+    Ok(())
+}
@@ -11,3 +11,5 @@ mod transactions;
 mod globals;
 
 mod derive;
+
+mod http;